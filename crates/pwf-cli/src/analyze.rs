@@ -0,0 +1,1903 @@
+//! `pwf analyze` subcommands: training analytics over history exports
+
+use crate::{date_range, parse_history_input, to_yaml_without_nulls, DocumentFormat, OutputFormat};
+use chrono::NaiveDate;
+use clap::{Subcommand, ValueEnum};
+use colored::*;
+use pwf_core::analytics::{
+    self, AcwrFlag, AcwrParams, DateRange, DistributionModel, E1rmFormula, ExecutionOptions,
+    FtpSource, IntensityDistribution, IntensityOptions, IntensityStream, IntervalMetric,
+    IntervalOptions, LoadParams, LoadThresholds, MetricExecution, PhaseAlignment,
+    RecordDisagreement, RecordOptions, SegmentKind, SetRange, StrengthOptions, SummaryTolerances,
+    VolumeStatus, ZoneBoundaries,
+};
+use pwf_core::history::{PoolLengthUnit, RecordType, StrokeType, WpsHistory};
+use pwf_core::plan::AthleteProfile;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Subcommand)]
+pub(crate) enum AnalyzeCommand {
+    /// Mean-maximal power curve, critical power model and estimated FTP
+    PowerCurve {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// First workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Daily training stress with chronic/acute load (CTL/ATL) and form (TSB)
+    Load {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// First date to report (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last date to report (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Functional threshold power in watts, for workouts that do not record one
+        #[arg(long)]
+        ftp: Option<u32>,
+
+        /// Lactate threshold heart rate in bpm, for hrTSS
+        #[arg(long)]
+        threshold_hr: Option<u32>,
+
+        /// Threshold pace in seconds per km, for rTSS
+        #[arg(long)]
+        threshold_pace: Option<u32>,
+
+        /// CTL time constant in days
+        #[arg(long, default_value_t = analytics::DEFAULT_CTL_DAYS)]
+        ctl_days: f64,
+
+        /// ATL time constant in days
+        #[arg(long, default_value_t = analytics::DEFAULT_ATL_DAYS)]
+        atl_days: f64,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Estimated 1RM and personal records recomputed from the sets
+    Records {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// Formula for estimated one-rep max
+        #[arg(long, value_enum, default_value = "epley")]
+        formula: Formula,
+
+        /// Write a copy of the history with regenerated `personal_records`
+        /// and `is_pr` flags (JSON for .json paths, YAML otherwise)
+        #[arg(long, value_name = "OUTPUT")]
+        fix: Option<PathBuf>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Splits, best efforts, grade-adjusted pace and aerobic decoupling for runs
+    Running {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// First workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Show mile splits and paces per mile instead of per km
+        #[arg(long)]
+        miles: bool,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Weekly sets, hard sets, reps and tonnage per exercise and muscle group
+    Strength {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// Plan whose exercise library maps exercises to muscle groups
+        #[arg(long)]
+        plan: Option<PathBuf>,
+
+        /// First workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Fewest weekly hard sets per muscle group before flagging
+        #[arg(long, default_value_t = analytics::DEFAULT_WEEKLY_SETS.min)]
+        min_sets: u32,
+
+        /// Most weekly hard sets per muscle group before flagging
+        #[arg(long, default_value_t = analytics::DEFAULT_WEEKLY_SETS.max)]
+        max_sets: u32,
+
+        /// Weekly hard-set range for one muscle group, e.g. `chest=12-20`
+        /// (repeatable)
+        #[arg(long = "range", value_name = "GROUP=MIN-MAX", value_parser = parse_muscle_range)]
+        ranges: Vec<(String, SetRange)>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Pool swim pace, stroke breakdown and critical swim speed
+    Swimming {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// First workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Write a copy of the history with per-length records rebuilt from
+        /// time series (JSON for .json paths, YAML otherwise)
+        #[arg(long, value_name = "OUTPUT")]
+        fill_lengths: Option<PathBuf>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Work/recovery intervals or distance laps found in single-lap recordings
+    Intervals {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// Stream to detect intervals on (default: power, then speed, then heart rate)
+        #[arg(long, value_enum)]
+        metric: Option<Metric>,
+
+        /// Cut a lap every this many meters instead of detecting intervals
+        #[arg(long, value_name = "METERS")]
+        auto_lap: Option<f64>,
+
+        /// Shortest work or recovery segment in seconds
+        #[arg(long, default_value_t = analytics::DEFAULT_MIN_SEGMENT_SEC)]
+        min_segment: f64,
+
+        /// Plan whose interval phases the segments are matched against
+        #[arg(long)]
+        plan: Option<PathBuf>,
+
+        /// Write a copy of the history with each split set replaced by one
+        /// set per segment (JSON for .json paths, YAML otherwise)
+        #[arg(long, value_name = "OUTPUT")]
+        split: Option<PathBuf>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// How closely planned interval phases or zones were hit
+    Execution {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// Plan whose interval phases the workouts carried out
+        #[arg(long)]
+        plan: PathBuf,
+
+        /// First workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// How phases are lined up with the recording
+        #[arg(long, value_enum, default_value = "auto")]
+        align: Align,
+
+        /// Start of the first phase in seconds, for offset alignment
+        #[arg(long, default_value_t = 0.0)]
+        offset: f64,
+
+        /// Allowed distance from a target, as a percentage
+        #[arg(long, default_value_t = 5.0)]
+        tolerance: f64,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Summary telemetry checked against the time series and GPS routes
+    Summaries {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// Allowed difference as a percentage of the recomputed value
+        #[arg(long, default_value_t = 5.0)]
+        tolerance: f64,
+
+        /// Write a copy of the history with the mismatched summaries
+        /// recomputed (JSON for .json paths, YAML otherwise)
+        #[arg(long, value_name = "OUTPUT")]
+        fix: Option<PathBuf>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Session-RPE load, weekly monotony/strain and acute:chronic workload ratio
+    Srpe {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// First date to report (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last date to report (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Acute window in days
+        #[arg(long, default_value_t = 7)]
+        acute_days: u32,
+
+        /// Chronic window in days
+        #[arg(long, default_value_t = 28)]
+        chronic_days: u32,
+
+        /// Flag ratios above this as spikes
+        #[arg(long, default_value_t = 1.5)]
+        spike: f64,
+
+        /// Flag ratios below this as underloading
+        #[arg(long, default_value_t = 0.8)]
+        low: f64,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Weekly low/moderate/high intensity distribution and polarization index
+    Intensity {
+        /// History file to analyze
+        file: PathBuf,
+
+        /// First workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last workout date to include (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Zone times to use (default: power, then pace, then heart rate)
+        #[arg(long, value_enum)]
+        stream: Option<Stream>,
+
+        /// Functional threshold power in watts, for zones of workouts
+        /// without recorded time in zones
+        #[arg(long)]
+        ftp: Option<u32>,
+
+        /// Lactate threshold heart rate in bpm
+        #[arg(long)]
+        threshold_hr: Option<u32>,
+
+        /// Maximum heart rate in bpm (preferred over --threshold-hr)
+        #[arg(long)]
+        max_hr: Option<u32>,
+
+        /// Threshold pace in seconds per km
+        #[arg(long)]
+        threshold_pace: Option<u32>,
+
+        /// Plan whose athlete profile provides thresholds not given above
+        #[arg(long)]
+        plan: Option<PathBuf>,
+
+        /// Number of zones counted as low intensity (default depends on the
+        /// zone count: 1 of 3, 2 of 5, 2 of 6 or 7)
+        #[arg(long, value_name = "N")]
+        low_zones: Option<usize>,
+
+        /// Number of zones counted as moderate intensity
+        #[arg(long, value_name = "N")]
+        moderate_zones: Option<usize>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+}
+
+/// E1RM formulas selectable on the command line
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Formula {
+    Epley,
+    Brzycki,
+    /// RPE percentage table; needs RPE or RIR on the sets
+    RpeTable,
+}
+
+impl From<Formula> for E1rmFormula {
+    fn from(formula: Formula) -> Self {
+        match formula {
+            Formula::Epley => E1rmFormula::Epley,
+            Formula::Brzycki => E1rmFormula::Brzycki,
+            Formula::RpeTable => E1rmFormula::RpeTable,
+        }
+    }
+}
+
+/// Streams selectable for interval detection
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Metric {
+    Power,
+    Speed,
+    HeartRate,
+}
+
+impl From<Metric> for IntervalMetric {
+    fn from(metric: Metric) -> Self {
+        match metric {
+            Metric::Power => IntervalMetric::Power,
+            Metric::Speed => IntervalMetric::Speed,
+            Metric::HeartRate => IntervalMetric::HeartRate,
+        }
+    }
+}
+
+/// Zone streams selectable for intensity distribution
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Stream {
+    Power,
+    Pace,
+    HeartRate,
+}
+
+impl From<Stream> for IntensityStream {
+    fn from(stream: Stream) -> Self {
+        match stream {
+            Stream::Power => IntensityStream::Power,
+            Stream::Pace => IntensityStream::Pace,
+            Stream::HeartRate => IntensityStream::HeartRate,
+        }
+    }
+}
+
+/// Phase alignments selectable on the command line
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Align {
+    /// Detected intervals, falling back to offsets
+    Auto,
+    Offset,
+    Intervals,
+}
+
+impl From<Align> for PhaseAlignment {
+    fn from(align: Align) -> Self {
+        match align {
+            Align::Auto => PhaseAlignment::Auto,
+            Align::Offset => PhaseAlignment::Offset,
+            Align::Intervals => PhaseAlignment::Intervals,
+        }
+    }
+}
+
+/// Parse `GROUP=MIN-MAX`
+fn parse_muscle_range(value: &str) -> Result<(String, SetRange), String> {
+    let invalid = || format!("expected GROUP=MIN-MAX, got `{}`", value);
+    let (group, range) = value.split_once('=').ok_or_else(invalid)?;
+    let (min, max) = range.split_once('-').ok_or_else(invalid)?;
+    let parse = |n: &str| n.trim().parse::<u32>().map_err(|_| invalid());
+    let (min, max) = (parse(min)?, parse(max)?);
+    if group.trim().is_empty() || min > max {
+        return Err(invalid());
+    }
+    Ok((group.trim().to_string(), SetRange { min, max }))
+}
+
+pub(crate) fn run(command: AnalyzeCommand) -> ExitCode {
+    match command {
+        AnalyzeCommand::PowerCurve {
+            file,
+            from,
+            to,
+            format,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            power_curve(&file, range, format)
+        }
+        AnalyzeCommand::Load {
+            file,
+            from,
+            to,
+            ftp,
+            threshold_hr,
+            threshold_pace,
+            ctl_days,
+            atl_days,
+            format,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            let thresholds = LoadThresholds {
+                ftp_watts: ftp,
+                threshold_hr_bpm: threshold_hr,
+                threshold_pace_sec_per_km: threshold_pace,
+            };
+            let params = LoadParams {
+                ctl_days,
+                atl_days,
+                ..Default::default()
+            };
+            load(&file, range, &thresholds, &params, format)
+        }
+        AnalyzeCommand::Records {
+            file,
+            formula,
+            fix,
+            format,
+        } => {
+            let options = RecordOptions {
+                formula: formula.into(),
+            };
+            records(&file, &options, fix.as_deref(), format)
+        }
+        AnalyzeCommand::Running {
+            file,
+            from,
+            to,
+            miles,
+            format,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            running(&file, range, miles, format)
+        }
+        AnalyzeCommand::Strength {
+            file,
+            plan,
+            from,
+            to,
+            min_sets,
+            max_sets,
+            ranges,
+            format,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            if min_sets > max_sets {
+                eprintln!(
+                    "{}: --min-sets {} is above --max-sets {}",
+                    "error".red(),
+                    min_sets,
+                    max_sets
+                );
+                return ExitCode::FAILURE;
+            }
+            let options = StrengthOptions {
+                weekly_sets: SetRange {
+                    min: min_sets,
+                    max: max_sets,
+                },
+                muscle_ranges: ranges.into_iter().collect(),
+            };
+            strength(&file, plan.as_deref(), range, &options, format)
+        }
+        AnalyzeCommand::Swimming {
+            file,
+            from,
+            to,
+            fill_lengths,
+            format,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            swimming(&file, range, fill_lengths.as_deref(), format)
+        }
+        AnalyzeCommand::Intervals {
+            file,
+            metric,
+            auto_lap,
+            min_segment,
+            plan,
+            split,
+            format,
+        } => {
+            let options = IntervalOptions {
+                metric: metric.map(Into::into),
+                min_segment_sec: min_segment,
+                auto_lap_m: auto_lap,
+                ..Default::default()
+            };
+            intervals(&file, &options, plan.as_deref(), split.as_deref(), format)
+        }
+        AnalyzeCommand::Execution {
+            file,
+            plan,
+            from,
+            to,
+            align,
+            offset,
+            tolerance,
+            format,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            let options = ExecutionOptions {
+                alignment: align.into(),
+                offset_sec: offset,
+                tolerance: tolerance / 100.0,
+            };
+            execution(&file, &plan, range, &options, format)
+        }
+        AnalyzeCommand::Summaries {
+            file,
+            tolerance,
+            fix,
+            format,
+        } => {
+            let tolerances = SummaryTolerances {
+                relative: tolerance / 100.0,
+                ..Default::default()
+            };
+            summaries(&file, &tolerances, fix.as_deref(), format)
+        }
+        AnalyzeCommand::Srpe {
+            file,
+            from,
+            to,
+            acute_days,
+            chronic_days,
+            spike,
+            low,
+            format,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            let params = AcwrParams {
+                acute_days,
+                chronic_days,
+                spike_threshold: spike,
+                low_threshold: low,
+            };
+            srpe(&file, range, &params, format)
+        }
+        AnalyzeCommand::Intensity {
+            file,
+            from,
+            to,
+            stream,
+            ftp,
+            threshold_hr,
+            max_hr,
+            threshold_pace,
+            plan,
+            low_zones,
+            moderate_zones,
+            format,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            let profile = AthleteProfile {
+                ftp_watts: ftp,
+                threshold_hr_bpm: threshold_hr,
+                max_hr_bpm: max_hr,
+                threshold_pace_sec_per_km: threshold_pace,
+                weight_kg: None,
+            };
+            let mut boundaries = ZoneBoundaries::from_profile(&profile);
+            if let Some(plan) = plan {
+                let Some(plan) = crate::compliance::load_plan(&plan) else {
+                    return ExitCode::FAILURE;
+                };
+                if let Some(profile) = plan.meta.as_ref().and_then(|m| m.athlete_profile.as_ref()) {
+                    boundaries = boundaries.or(&ZoneBoundaries::from_profile(profile));
+                }
+            }
+            let options = IntensityOptions {
+                stream: stream.map(Into::into),
+                boundaries,
+                low_zones,
+                moderate_zones,
+            };
+            intensity(&file, range, &options, format)
+        }
+    }
+}
+
+/// Read and parse a history file, reporting failures on stderr
+pub(crate) fn load_history(path: &Path) -> Option<WpsHistory> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}: {}", path.display().to_string().red(), e);
+            return None;
+        }
+    };
+    match parse_history_input(path, &content) {
+        Ok(history) => Some(history),
+        Err(e) => {
+            eprintln!("{}: {}", "error".red(), e);
+            None
+        }
+    }
+}
+
+fn power_curve(path: &Path, range: DateRange, format: OutputFormat) -> ExitCode {
+    let Some(history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let analysis = analytics::analyze_power_curve(analytics::history_power_curve(&history, &range));
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "from": range.from,
+                "to": range.to,
+                "curve": analysis.curve.points,
+                "critical_power": analysis.critical_power,
+                "estimated_ftp": analysis.estimated_ftp,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            let mut parts = vec![format!("{} points", analysis.curve.points.len())];
+            if let Some(model) = &analysis.critical_power {
+                parts.push(format!(
+                    "CP {:.0} W, W′ {:.1} kJ",
+                    model.cp_watts,
+                    model.w_prime_joules / 1000.0
+                ));
+            }
+            if let Some(ftp) = &analysis.estimated_ftp {
+                parts.push(format!("FTP {} W", ftp.watts));
+            }
+            println!("{}: {}", path.display(), parts.join(", "));
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Power curve:".bold(), path.display());
+            if analysis.curve.is_empty() {
+                println!("  {}", "No power data in the selected workouts".dimmed());
+                return ExitCode::SUCCESS;
+            }
+            println!();
+            for point in &analysis.curve.points {
+                println!(
+                    "  {:>8}  {:>6} W  {}",
+                    format_duration(point.duration_sec),
+                    format!("{:.0}", point.watts).cyan(),
+                    point.date.as_deref().unwrap_or("").dimmed()
+                );
+            }
+            println!();
+            match &analysis.critical_power {
+                Some(model) => println!(
+                    "  Critical power: {} W, W′ {:.1} kJ (R² {:.3})",
+                    format!("{:.0}", model.cp_watts).green(),
+                    model.w_prime_joules / 1000.0,
+                    model.r_squared
+                ),
+                None => println!(
+                    "  Critical power: {}",
+                    "not enough 2–20 min efforts to fit".dimmed()
+                ),
+            }
+            if let Some(ftp) = &analysis.estimated_ftp {
+                let source = match ftp.source {
+                    FtpSource::CriticalPower => "from critical power",
+                    FtpSource::TwentyMinutePower => "95% of 20 min power",
+                };
+                println!(
+                    "  Estimated FTP:  {} W ({})",
+                    ftp.watts.to_string().green(),
+                    source
+                );
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn load(
+    path: &Path,
+    range: DateRange,
+    thresholds: &LoadThresholds,
+    params: &LoadParams,
+    format: OutputFormat,
+) -> ExitCode {
+    let Some(history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let days = analytics::training_load(&history, thresholds, params, &range);
+    let unscored = range
+        .workouts(&history)
+        .filter(|w| analytics::workout_stress(w, thresholds).is_none())
+        .count();
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "ctl_days": params.ctl_days,
+                "atl_days": params.atl_days,
+                "unscored_workouts": unscored,
+                "days": days,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => match days.last() {
+            Some(day) => println!(
+                "{}: {} CTL {:.1}, ATL {:.1}, TSB {:.1}",
+                path.display(),
+                day.date,
+                day.ctl,
+                day.atl,
+                day.ctl - day.atl
+            ),
+            None => println!("{}: no scored workouts", path.display()),
+        },
+        OutputFormat::Pretty => {
+            println!("{} {}", "Training load:".bold(), path.display());
+            if days.is_empty() {
+                println!("  {}", "No scored workouts in the selected range".dimmed());
+            } else {
+                println!();
+                println!(
+                    "  {:<10}  {:>6}  {:>6}  {:>6}  {:>6}",
+                    "Date", "Stress", "CTL", "ATL", "TSB"
+                );
+                for day in &days {
+                    let tsb = format!("{:>6.1}", day.tsb);
+                    println!(
+                        "  {:<10}  {:>6.1}  {:>6.1}  {:>6.1}  {}",
+                        day.date,
+                        day.stress,
+                        day.ctl,
+                        day.atl,
+                        if day.tsb < 0.0 {
+                            tsb.red()
+                        } else {
+                            tsb.green()
+                        }
+                    );
+                }
+            }
+            if unscored > 0 {
+                println!();
+                println!(
+                    "  {}",
+                    format!(
+                        "{} workout(s) had no power, heart rate, pace or RPE to score; \
+                         try --ftp, --threshold-hr or --threshold-pace",
+                        unscored
+                    )
+                    .yellow()
+                );
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn records(
+    path: &Path,
+    options: &RecordOptions,
+    fix: Option<&Path>,
+    format: OutputFormat,
+) -> ExitCode {
+    let Some(mut history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let analysis = match fix {
+        Some(_) => analytics::regenerate_records(&mut history, options),
+        None => analytics::analyze_records(&history, options),
+    };
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "formula": options.formula,
+                "records": analysis.records,
+                "events": analysis.events,
+                "disagreements": analysis.disagreements,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => println!(
+            "{}: {} records, {} PRs, {} disagreements",
+            path.display(),
+            analysis.records.len(),
+            analysis.events.len(),
+            analysis.disagreements.len()
+        ),
+        OutputFormat::Pretty => {
+            println!("{} {}", "Personal records:".bold(), path.display());
+            if analysis.records.is_empty() {
+                println!(
+                    "  {}",
+                    "No sets with weight, reps, duration or distance".dimmed()
+                );
+            }
+            let mut current = None;
+            for record in &analysis.records {
+                if current != Some(&record.exercise_name) {
+                    println!();
+                    println!("  {}", record.exercise_name.bold());
+                    current = Some(&record.exercise_name);
+                }
+                println!(
+                    "    {:<16} {:>10}  {}",
+                    record_label(record.record_type),
+                    format!(
+                        "{} {}",
+                        record.value,
+                        record.unit.as_deref().unwrap_or("reps")
+                    )
+                    .cyan(),
+                    record.achieved_at.dimmed()
+                );
+            }
+
+            if !analysis.disagreements.is_empty() {
+                println!();
+                println!(
+                    "  {} {}",
+                    "Disagreements with stored values:".yellow(),
+                    analysis.disagreements.len()
+                );
+                for disagreement in &analysis.disagreements {
+                    println!("    {}", describe_disagreement(&history, disagreement));
+                }
+            }
+        }
+    }
+
+    match fix {
+        Some(output) => write_fixed(&history, output, format),
+        None => ExitCode::SUCCESS,
+    }
+}
+
+/// Write a corrected history (JSON for .json paths, YAML otherwise)
+fn write_fixed(history: &WpsHistory, output: &Path, format: OutputFormat) -> ExitCode {
+    let encoded = match DocumentFormat::from_path(output) {
+        DocumentFormat::Json => pwf_core::history::to_json(history).map_err(|e| e.to_string()),
+        DocumentFormat::Yaml => to_yaml_without_nulls(history).map_err(|e| e.to_string()),
+    };
+    let written = encoded.and_then(|content| fs::write(output, content).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        eprintln!("{}: {}", output.display().to_string().red(), e);
+        return ExitCode::FAILURE;
+    }
+    if format == OutputFormat::Pretty {
+        println!();
+        println!("  {} {}", "Wrote".green(), output.display());
+    }
+    ExitCode::SUCCESS
+}
+
+fn summaries(
+    path: &Path,
+    tolerances: &SummaryTolerances,
+    fix: Option<&Path>,
+    format: OutputFormat,
+) -> ExitCode {
+    let Some(mut history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let discrepancies = match fix {
+        Some(_) => analytics::fix_summaries(&mut history, tolerances),
+        None => analytics::check_summaries(&history, tolerances),
+    };
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "discrepancies": discrepancies,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            println!("{}: {} discrepancies", path.display(), discrepancies.len())
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Telemetry summaries:".bold(), path.display());
+            if discrepancies.is_empty() {
+                println!(
+                    "  {}",
+                    "All summaries match their time series and GPS routes".green()
+                );
+            }
+            for discrepancy in &discrepancies {
+                println!("  {}", discrepancy.path.yellow());
+                println!("    {}", discrepancy.message());
+            }
+        }
+    }
+
+    match fix {
+        Some(output) => write_fixed(&history, output, format),
+        None => ExitCode::SUCCESS,
+    }
+}
+
+fn running(path: &Path, range: DateRange, miles: bool, format: OutputFormat) -> ExitCode {
+    let Some(history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let runs = analytics::analyze_runs(&history, &range);
+    let (unit, unit_m) = if miles {
+        ("mi", pwf_core::METERS_PER_MILE)
+    } else {
+        ("km", 1000.0)
+    };
+    // Paces are per km in the analysis
+    let pace = |sec_per_km: f64| format_clock(sec_per_km * unit_m / 1000.0);
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "runs": runs,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            for run in &runs {
+                println!(
+                    "{}: {} {:.2} {}, {}/{}{}",
+                    path.display(),
+                    run.date,
+                    run.distance_m / unit_m,
+                    unit,
+                    pace(run.pace_sec_per_km),
+                    unit,
+                    run.grade_adjusted_pace_sec_per_km
+                        .map(|gap| format!(" (GAP {}/{})", pace(gap), unit))
+                        .unwrap_or_default()
+                );
+            }
+            if runs.is_empty() {
+                println!("{}: no runs with distance data", path.display());
+            }
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Running:".bold(), path.display());
+            if runs.is_empty() {
+                println!(
+                    "  {}",
+                    "No runs with distance data in the selected range".dimmed()
+                );
+            }
+            for run in &runs {
+                println!();
+                println!(
+                    "  {}  {:.2} {} in {}, {}/{}",
+                    run.date.bold(),
+                    run.distance_m / unit_m,
+                    unit,
+                    format_duration(run.moving_time_sec.round() as u32),
+                    pace(run.pace_sec_per_km).cyan(),
+                    unit
+                );
+                if let Some(gap) = run.grade_adjusted_pace_sec_per_km {
+                    println!("    Grade-adjusted pace: {}/{}", pace(gap).cyan(), unit);
+                }
+
+                let splits = if miles {
+                    &run.splits_mile
+                } else {
+                    &run.splits_km
+                };
+                println!();
+                println!(
+                    "    {:>5}  {:>7}  {:>6}  {:>6}  {:>6}  {:>5}",
+                    unit, "Time", "Pace", "GAP", "Elev", "HR"
+                );
+                for split in splits {
+                    let label = if split.distance_m < unit_m - 0.5 {
+                        format!(
+                            "{:.2}",
+                            (split.number - 1) as f64 + split.distance_m / unit_m
+                        )
+                    } else {
+                        split.number.to_string()
+                    };
+                    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+                    println!(
+                        "    {:>5}  {:>7}  {:>6}  {:>6}  {:>6}  {:>5}",
+                        label,
+                        format_clock(split.duration_sec),
+                        pace(split.pace_sec_per_km),
+                        optional(split.grade_adjusted_pace_sec_per_km.map(pace)),
+                        optional(split.elevation_change_m.map(|e| format!("{:+.0}m", e))),
+                        optional(split.heart_rate_avg.map(|hr| format!("{:.0}", hr)))
+                    );
+                }
+
+                if !run.best_efforts.is_empty() {
+                    println!();
+                    println!("    {}", "Best efforts".bold());
+                    for effort in &run.best_efforts {
+                        println!(
+                            "    {:<14} {:>8}  {}/{}",
+                            effort.name,
+                            format_clock(effort.duration_sec),
+                            pace(effort.pace_sec_per_km),
+                            unit
+                        );
+                    }
+                }
+
+                if let Some(decoupling) = &run.decoupling {
+                    let percent = format!("{:.1}%", decoupling.decoupling_percent);
+                    println!();
+                    println!(
+                        "    Aerobic decoupling: {} ({:.0} → {:.0} bpm)",
+                        if decoupling.decoupling_percent > 5.0 {
+                            percent.yellow()
+                        } else {
+                            percent.green()
+                        },
+                        decoupling.first_half_heart_rate,
+                        decoupling.second_half_heart_rate
+                    );
+                }
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn strength(
+    path: &Path,
+    plan_path: Option<&Path>,
+    range: DateRange,
+    options: &StrengthOptions,
+    format: OutputFormat,
+) -> ExitCode {
+    let Some(history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let plan = match plan_path {
+        Some(plan_path) => match crate::compliance::load_plan(plan_path) {
+            Some(plan) => Some(plan),
+            None => return ExitCode::FAILURE,
+        },
+        None => None,
+    };
+    let report = analytics::strength_volume(&history, plan.as_ref(), &range, options);
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "weeks": report.weeks,
+                "unmapped_exercises": report.unmapped_exercises,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            for week in &report.weeks {
+                let flagged: Vec<String> = week
+                    .muscle_groups
+                    .iter()
+                    .filter(|m| m.status != VolumeStatus::Within)
+                    .map(|m| format!("{} {}", m.muscle_group, status_label(m.status)))
+                    .collect();
+                println!(
+                    "{}: week of {} {} sets, {} hard, {:.0} kg{}",
+                    path.display(),
+                    week.week_start,
+                    week.total.sets,
+                    week.total.hard_sets,
+                    week.total.tonnage_kg,
+                    if flagged.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", flagged.join(", "))
+                    }
+                );
+            }
+            if report.weeks.is_empty() {
+                println!("{}: no strength sets", path.display());
+            }
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Strength volume:".bold(), path.display());
+            if report.weeks.is_empty() {
+                println!("  {}", "No strength sets in the selected range".dimmed());
+            }
+            for week in &report.weeks {
+                println!();
+                println!(
+                    "  {}  {} sets, {} hard, {} reps, {:.0} kg",
+                    format!("Week of {}", week.week_start).bold(),
+                    week.total.sets,
+                    week.total.hard_sets,
+                    week.total.reps,
+                    week.total.tonnage_kg
+                );
+                println!(
+                    "    {:<24} {:>4} {:>5} {:>8}  {:>5} {:>5} {:>5}",
+                    "Exercise", "Sets", "Hard", "kg", "1-5", "6-12", "13+"
+                );
+                for exercise in &week.exercises {
+                    let volume = &exercise.volume;
+                    println!(
+                        "    {:<24} {:>4} {:>5} {:>8.0}  {:>5} {:>5} {:>5}",
+                        exercise.exercise,
+                        volume.sets,
+                        volume.hard_sets,
+                        volume.tonnage_kg,
+                        volume.rep_distribution.low,
+                        volume.rep_distribution.moderate,
+                        volume.rep_distribution.high
+                    );
+                }
+                if !week.muscle_groups.is_empty() {
+                    println!();
+                    for muscle in &week.muscle_groups {
+                        let status = match muscle.status {
+                            VolumeStatus::Below => status_label(muscle.status).yellow(),
+                            VolumeStatus::Within => status_label(muscle.status).green(),
+                            VolumeStatus::Above => status_label(muscle.status).red(),
+                        };
+                        println!(
+                            "    {:<24} {:>3} hard sets ({}-{})  {}",
+                            muscle.muscle_group,
+                            muscle.volume.hard_sets,
+                            muscle.range.min,
+                            muscle.range.max,
+                            status
+                        );
+                    }
+                }
+            }
+            if !report.unmapped_exercises.is_empty() {
+                println!();
+                println!(
+                    "  {} {}",
+                    "No muscle groups for:".yellow(),
+                    report.unmapped_exercises.join(", ")
+                );
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn status_label(status: VolumeStatus) -> &'static str {
+    match status {
+        VolumeStatus::Below => "below range",
+        VolumeStatus::Within => "within range",
+        VolumeStatus::Above => "above range",
+    }
+}
+
+fn swimming(path: &Path, range: DateRange, fill: Option<&Path>, format: OutputFormat) -> ExitCode {
+    let Some(mut history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let report = analytics::analyze_swims(&history, &range);
+    let unit = |unit: PoolLengthUnit| match unit {
+        PoolLengthUnit::Meters => "m",
+        PoolLengthUnit::Yards => "yd",
+    };
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "swims": report.swims,
+                "css": report.css,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            for swim in &report.swims {
+                println!(
+                    "{}: {} {:.0} {}{}",
+                    path.display(),
+                    swim.date,
+                    swim.distance,
+                    unit(swim.unit),
+                    swim.pace_per_100_sec
+                        .map(|pace| format!(", {}/100{}", format_clock(pace), unit(swim.unit)))
+                        .unwrap_or_default()
+                );
+            }
+            if let Some(css) = &report.css {
+                println!(
+                    "{}: CSS {}/100{}",
+                    path.display(),
+                    format_clock(css.pace_per_100_sec),
+                    unit(css.unit)
+                );
+            }
+            if report.swims.is_empty() {
+                println!("{}: no swims", path.display());
+            }
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Swimming:".bold(), path.display());
+            if report.swims.is_empty() {
+                println!("  {}", "No swims in the selected range".dimmed());
+            }
+            for swim in &report.swims {
+                let u = unit(swim.unit);
+                println!();
+                let mut line = format!(
+                    "  {}  {:.0} {} in {}",
+                    swim.date.bold(),
+                    swim.distance,
+                    u,
+                    format_duration(swim.active_time_sec.round() as u32)
+                );
+                if let Some(pace) = swim.pace_per_100_sec {
+                    line.push_str(&format!(", {}/100{}", format_clock(pace).cyan(), u));
+                }
+                println!("{}", line);
+                if swim.active_lengths > 0 {
+                    let mut lengths = format!("    {} lengths", swim.active_lengths);
+                    if let Some(pool) = swim.pool_length {
+                        lengths.push_str(&format!(" of {}{}", pool, u));
+                    }
+                    if let Some(swolf) = swim.swolf_avg {
+                        lengths.push_str(&format!(", SWOLF {:.0}", swolf));
+                    }
+                    if swim.rest_time_sec > 0.0 {
+                        lengths.push_str(&format!(
+                            ", {} rest",
+                            format_duration(swim.rest_time_sec.round() as u32)
+                        ));
+                    }
+                    if swim.reconstructed {
+                        lengths.push_str(&format!(" {}", "(rebuilt from time series)".dimmed()));
+                    }
+                    println!("{}", lengths);
+                }
+                for stroke in &swim.strokes {
+                    println!(
+                        "    {:<14} {:>5.0} {}  {:>6}/100{}  {}",
+                        stroke_label(stroke.stroke_type),
+                        stroke.distance,
+                        u,
+                        stroke
+                            .pace_per_100_sec
+                            .map(format_clock)
+                            .unwrap_or_else(|| "-".to_string()),
+                        u,
+                        stroke
+                            .strokes_per_length
+                            .map(|s| format!("{:.1} strokes/length", s))
+                            .unwrap_or_default()
+                    );
+                }
+            }
+            if let Some(css) = &report.css {
+                println!();
+                println!(
+                    "  {} {}/100{} (400 in {}, 200 in {} on {})",
+                    "Critical swim speed:".bold(),
+                    format_clock(css.pace_per_100_sec).cyan(),
+                    unit(css.unit),
+                    format_clock(css.long_time_sec),
+                    format_clock(css.short_time_sec),
+                    css.date
+                );
+            }
+        }
+    }
+
+    match fill {
+        Some(output) => {
+            analytics::fill_swim_lengths(&mut history);
+            write_fixed(&history, output, format)
+        }
+        None => ExitCode::SUCCESS,
+    }
+}
+
+fn intervals(
+    path: &Path,
+    options: &IntervalOptions,
+    plan: Option<&Path>,
+    split: Option<&Path>,
+    format: OutputFormat,
+) -> ExitCode {
+    let Some(mut history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let plan = match plan {
+        Some(plan_path) => match crate::compliance::load_plan(plan_path) {
+            Some(plan) => Some(plan),
+            None => return ExitCode::FAILURE,
+        },
+        None => None,
+    };
+    let found = analytics::analyze_intervals(&history, options, plan.as_ref());
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "sets": found,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            for set in &found {
+                println!(
+                    "{}: {} {} segments, {} work",
+                    path.display(),
+                    set.path,
+                    set.segments.len(),
+                    set.work().count()
+                );
+            }
+            if found.is_empty() {
+                println!("{}: no intervals found", path.display());
+            }
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Intervals:".bold(), path.display());
+            if found.is_empty() {
+                println!(
+                    "  {}",
+                    "No sets with intervals in their time series".dimmed()
+                );
+            }
+            for set in &found {
+                println!();
+                println!(
+                    "  {}  {}  {}",
+                    set.date.bold(),
+                    set.exercise,
+                    set.path.dimmed()
+                );
+                for segment in &set.segments {
+                    let label = match segment.kind {
+                        SegmentKind::Work => "work".red(),
+                        SegmentKind::Recovery => "recovery".green(),
+                        SegmentKind::Lap => "lap".cyan(),
+                    };
+                    let mut line = format!(
+                        "    {:>8}  {:<8}  {:>7}",
+                        format_clock(segment.start_sec),
+                        label,
+                        format_clock(segment.duration_sec)
+                    );
+                    if let Some(distance) = segment.distance_m {
+                        line.push_str(&format!("  {:>6.0} m", distance));
+                    }
+                    if let Some(average) = segment.average {
+                        line.push_str(&format!("  avg {:.0}", average));
+                    }
+                    if let Some(phase) = &segment.phase {
+                        line.push_str(&format!(
+                            "  {} #{} ({:+.0}s)",
+                            phase.name, phase.repetition, phase.duration_diff_sec
+                        ));
+                    }
+                    println!("{}", line);
+                }
+                if !set.unmatched_phases.is_empty() {
+                    println!(
+                        "    {} {}",
+                        "Not found:".yellow(),
+                        set.unmatched_phases.join(", ")
+                    );
+                }
+            }
+        }
+    }
+
+    match split {
+        Some(output) => {
+            analytics::split_intervals(&mut history, options, plan.as_ref());
+            write_fixed(&history, output, format)
+        }
+        None => ExitCode::SUCCESS,
+    }
+}
+
+fn execution(
+    path: &Path,
+    plan_path: &Path,
+    range: DateRange,
+    options: &ExecutionOptions,
+    format: OutputFormat,
+) -> ExitCode {
+    let Some(history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let Some(plan) = crate::compliance::load_plan(plan_path) else {
+        return ExitCode::FAILURE;
+    };
+    let executions = analytics::score_executions(&plan, &history, &range, options);
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "plan": plan_path.display().to_string(),
+                "executions": executions,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            for execution in &executions {
+                println!(
+                    "{}: {} {} score {:.0}",
+                    path.display(),
+                    execution.date,
+                    execution.exercise,
+                    execution.score
+                );
+            }
+            if executions.is_empty() {
+                println!("{}: no planned intervals with time series", path.display());
+            }
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Interval execution:".bold(), path.display());
+            if executions.is_empty() {
+                println!(
+                    "  {}",
+                    "No workouts with time series for planned interval phases".dimmed()
+                );
+            }
+            for execution in &executions {
+                println!();
+                println!(
+                    "  {}  {}  score {}",
+                    execution.date.bold(),
+                    execution.exercise,
+                    colored_score(execution.score)
+                );
+                for phase in &execution.phases {
+                    let planned = &phase.planned;
+                    let label = format!("{} #{}", planned.name, planned.repetition);
+                    let Some(duration) = phase.actual_duration_sec else {
+                        println!("    {:<14} {}", label, "not reached".yellow());
+                        continue;
+                    };
+                    let mut line = format!(
+                        "    {:<14} {:>7}/{:<7}",
+                        label,
+                        format_clock(duration),
+                        format_clock(planned.duration_sec as f64)
+                    );
+                    let streams = [
+                        ("W", &phase.power, false),
+                        ("bpm", &phase.heart_rate, false),
+                        ("/km", &phase.pace, true),
+                        ("rpm", &phase.cadence, false),
+                    ];
+                    for (unit, metric, pace) in streams {
+                        if let Some(metric) = metric {
+                            line.push_str(&format!("  {}", describe_metric(metric, unit, pace)));
+                        }
+                    }
+                    println!("{}  {}", line, colored_score(phase.score));
+                }
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// `actual unit (in-range%)`, or `-` without readings
+fn describe_metric(metric: &MetricExecution, unit: &str, pace: bool) -> String {
+    let value = |v: f64| {
+        if pace {
+            format_clock(v)
+        } else {
+            format!("{:.0}", v)
+        }
+    };
+    match (metric.actual, metric.percent_in_range) {
+        (Some(actual), Some(percent)) => {
+            format!("{}{} ({:.0}% in range)", value(actual), unit, percent)
+        }
+        _ => format!("-{}", unit),
+    }
+}
+
+fn colored_score(score: f64) -> ColoredString {
+    let text = format!("{:.0}", score);
+    if score >= 80.0 {
+        text.green()
+    } else if score >= 50.0 {
+        text.yellow()
+    } else {
+        text.red()
+    }
+}
+
+fn stroke_label(stroke: StrokeType) -> &'static str {
+    match stroke {
+        StrokeType::Freestyle => "Freestyle",
+        StrokeType::Backstroke => "Backstroke",
+        StrokeType::Breaststroke => "Breaststroke",
+        StrokeType::Butterfly => "Butterfly",
+        StrokeType::Drill => "Drill",
+        StrokeType::Mixed => "Mixed",
+        StrokeType::IndividualMedley => "IM",
+    }
+}
+
+fn record_label(record_type: RecordType) -> &'static str {
+    match record_type {
+        RecordType::OneRepMax => "Estimated 1RM",
+        RecordType::MaxWeight3rm => "3RM",
+        RecordType::MaxWeight5rm => "5RM",
+        RecordType::MaxWeight8rm => "8RM",
+        RecordType::MaxWeight10rm => "10RM",
+        RecordType::MaxWeight => "Max weight",
+        RecordType::MaxReps => "Max reps",
+        RecordType::MaxVolume => "Session volume",
+        RecordType::MaxDuration => "Longest",
+        RecordType::MaxDistance => "Farthest",
+        RecordType::FastestTime => "Fastest",
+    }
+}
+
+fn describe_disagreement(history: &WpsHistory, disagreement: &RecordDisagreement) -> String {
+    match disagreement {
+        RecordDisagreement::ValueMismatch {
+            exercise_name,
+            record_type,
+            stored,
+            computed,
+        } => match computed {
+            Some(computed) => format!(
+                "{} {}: stored {}, data shows {}",
+                exercise_name,
+                record_label(*record_type),
+                stored,
+                computed
+            ),
+            None => format!(
+                "{} {}: stored {}, no matching sets",
+                exercise_name,
+                record_label(*record_type),
+                stored
+            ),
+        },
+        RecordDisagreement::Missing {
+            exercise_name,
+            record_type,
+            computed,
+        } => format!(
+            "{} {}: {} not in personal_records",
+            exercise_name,
+            record_label(*record_type),
+            computed
+        ),
+        RecordDisagreement::IsPrMismatch {
+            workout_index,
+            exercise_index,
+            set_index,
+            stored,
+            computed,
+        } => {
+            let workout = &history.workouts[*workout_index];
+            format!(
+                "{} {} set {}: is_pr {} but {}",
+                workout.date,
+                workout.exercises[*exercise_index].name,
+                set_index + 1,
+                stored,
+                if *computed {
+                    "it set a record"
+                } else {
+                    "it set no record"
+                }
+            )
+        }
+    }
+}
+
+fn srpe(path: &Path, range: DateRange, params: &AcwrParams, format: OutputFormat) -> ExitCode {
+    let Some(history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let report = analytics::srpe_report(&history, &range, params);
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "acute_days": params.acute_days,
+                "chronic_days": params.chronic_days,
+                "sessions": report.sessions,
+                "weeks": report.weeks,
+                "days": report.days,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            let ratio = report
+                .days
+                .last()
+                .and_then(|d| d.ratio)
+                .map_or("n/a".to_string(), |r| format!("{:.2}", r));
+            println!(
+                "{}: {} sessions, {:.0} AU, ACWR {}, {} flagged days",
+                path.display(),
+                report.sessions.len(),
+                report.sessions.iter().map(|s| s.load).sum::<f64>(),
+                ratio,
+                report.flagged().count()
+            );
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Session-RPE load:".bold(), path.display());
+            if report.sessions.is_empty() && report.days.is_empty() {
+                println!(
+                    "  {}",
+                    "No workouts with RPE and duration in the selected range".dimmed()
+                );
+                return ExitCode::SUCCESS;
+            }
+
+            println!();
+            println!("  {}", "Weeks".bold());
+            println!(
+                "  {:<10}  {:>8}  {:>4}  {:>8}  {:>8}",
+                "Week of", "Load AU", "Days", "Monotony", "Strain"
+            );
+            for week in &report.weeks {
+                let optional = |v: Option<f64>, precision: usize| {
+                    v.map_or("-".to_string(), |v| format!("{:.*}", precision, v))
+                };
+                println!(
+                    "  {:<10}  {:>8.0}  {:>4}  {:>8}  {:>8}",
+                    week.week_start,
+                    week.total_load,
+                    week.training_days,
+                    optional(week.monotony, 2),
+                    optional(week.strain, 0)
+                );
+            }
+
+            let flagged: Vec<_> = report.flagged().collect();
+            println!();
+            if flagged.is_empty() {
+                println!(
+                    "  {}",
+                    format!(
+                        "No acute:chronic ratios outside {:.2}–{:.2}",
+                        params.low_threshold, params.spike_threshold
+                    )
+                    .green()
+                );
+            } else {
+                println!("  {}", "Flagged days".bold());
+                for day in flagged {
+                    let ratio = format!("{:.2}", day.ratio.unwrap_or_default());
+                    let label = match day.flag {
+                        Some(AcwrFlag::Spike) => "spike".red(),
+                        _ => "low".yellow(),
+                    };
+                    println!("  {}  ACWR {}  {}", day.date, ratio, label);
+                }
+            }
+            if let Some(latest) = report.days.last() {
+                println!();
+                match latest.ratio {
+                    Some(ratio) => println!(
+                        "  Latest ACWR ({}): {}",
+                        latest.date,
+                        format!("{:.2}", ratio).cyan()
+                    ),
+                    None => println!(
+                        "  Latest ACWR ({}): {}",
+                        latest.date,
+                        format!("needs {} days of history", params.chronic_days).dimmed()
+                    ),
+                }
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn intensity(
+    path: &Path,
+    range: DateRange,
+    options: &IntensityOptions,
+    format: OutputFormat,
+) -> ExitCode {
+    let Some(history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let report = analytics::intensity_distribution(&history, &range, options);
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "overall": report.overall,
+                "weeks": report.weeks,
+                "trend": report.trend,
+                "by_sport": report.by_sport,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => match &report.overall {
+            Some(overall) => println!(
+                "{}: {} weeks, {}, {}",
+                path.display(),
+                report.weeks.len(),
+                describe_distribution(overall),
+                model_label(overall.model)
+            ),
+            None => println!("{}: no zone data", path.display()),
+        },
+        OutputFormat::Pretty => {
+            println!("{} {}", "Intensity distribution:".bold(), path.display());
+            let Some(overall) = &report.overall else {
+                println!(
+                    "  {}",
+                    "No workouts with zone times in the selected range".dimmed()
+                );
+                return ExitCode::SUCCESS;
+            };
+            println!(
+                "  {}  {}",
+                describe_distribution(overall),
+                model_label(overall.model).cyan()
+            );
+
+            println!();
+            println!(
+                "  {:<10}  {:>8}  {:>5}  {:>5}  {:>5}  {:>5}  Model",
+                "Week of", "Sessions", "Low", "Mod", "High", "PI"
+            );
+            for week in &report.weeks {
+                let d = &week.distribution;
+                println!(
+                    "  {:<10}  {:>8}  {:>4.0}%  {:>4.0}%  {:>4.0}%  {:>5}  {}",
+                    week.week_start,
+                    week.sessions,
+                    d.low_percent,
+                    d.moderate_percent,
+                    d.high_percent,
+                    d.polarization_index
+                        .map_or("-".to_string(), |pi| format!("{:.2}", pi)),
+                    model_label(d.model)
+                );
+            }
+            if let Some(trend) = &report.trend {
+                println!();
+                println!(
+                    "  Trend per week: low {:+.1} pts, moderate {:+.1} pts, high {:+.1} pts",
+                    trend.low_percent_per_week,
+                    trend.moderate_percent_per_week,
+                    trend.high_percent_per_week
+                );
+            }
+
+            if report.by_sport.len() > 1 {
+                println!();
+                println!("  {}", "By sport".bold());
+                for sport in &report.by_sport {
+                    println!(
+                        "  {:<12}  {}  {}",
+                        sport.sport,
+                        describe_distribution(&sport.overall),
+                        model_label(sport.overall.model)
+                    );
+                }
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn describe_distribution(distribution: &IntensityDistribution) -> String {
+    let mut out = format!(
+        "{:.0}/{:.0}/{:.0}% low/moderate/high",
+        distribution.low_percent, distribution.moderate_percent, distribution.high_percent
+    );
+    if let Some(pi) = distribution.polarization_index {
+        out.push_str(&format!(", PI {:.2}", pi));
+    }
+    out
+}
+
+fn model_label(model: DistributionModel) -> &'static str {
+    match model {
+        DistributionModel::Polarized => "polarized",
+        DistributionModel::Pyramidal => "pyramidal",
+        DistributionModel::Threshold => "threshold",
+        DistributionModel::HighIntensity => "high intensity",
+        DistributionModel::Unclassified => "unclassified",
+    }
+}
+
+/// Format seconds as `m:ss`, or `h:mm:ss` from an hour up
+fn format_clock(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    let (h, m, s) = (total / 3600, total % 3600 / 60, total % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+/// Format a duration as `5s`, `1m30s`, `20m` or `1h`
+fn format_duration(seconds: u32) -> String {
+    let (h, m, s) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    let mut out = String::new();
+    if h > 0 {
+        out.push_str(&format!("{}h", h));
+    }
+    if m > 0 {
+        out.push_str(&format!("{}m", m));
+    }
+    if s > 0 || out.is_empty() {
+        out.push_str(&format!("{}s", s));
+    }
+    out
+}
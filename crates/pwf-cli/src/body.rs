@@ -0,0 +1,159 @@
+//! `pwf body`: trends in body measurements
+
+use crate::analyze::load_history;
+use crate::OutputFormat;
+use clap::ValueEnum;
+use colored::*;
+use pwf_core::analytics::{self, BodyOptions, DateRange, Limb};
+use pwf_core::WeightUnit;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Limb differences above this are highlighted
+const ASYMMETRY_WARNING_PERCENT: f64 = 10.0;
+
+/// Weight units selectable on the command line
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Unit {
+    Kg,
+    Lb,
+}
+
+impl From<Unit> for WeightUnit {
+    fn from(unit: Unit) -> Self {
+        match unit {
+            Unit::Kg => WeightUnit::Kg,
+            Unit::Lb => WeightUnit::Lb,
+        }
+    }
+}
+
+pub(crate) fn run(
+    path: &Path,
+    range: DateRange,
+    options: &BodyOptions,
+    format: OutputFormat,
+) -> ExitCode {
+    let Some(history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let report = analytics::body_trends(&history, &range, options);
+    let unit = report.unit;
+    let optional = |value: Option<f64>, precision: usize| {
+        value.map_or("-".to_string(), |v| format!("{:.*}", precision, v))
+    };
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "report": report,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            let latest = report.entries.iter().rev().find_map(|e| e.trend_weight);
+            println!(
+                "{}: {} entries, trend {} {}, {} {}/week",
+                path.display(),
+                report.entries.len(),
+                optional(latest, 1),
+                unit,
+                optional(report.change_per_week, 2),
+                unit
+            );
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Body trends:".bold(), path.display());
+            println!("  {}", format!("weights in {}", unit).dimmed());
+            if report.entries.is_empty() {
+                println!(
+                    "  {}",
+                    "No body measurements in the selected range".dimmed()
+                );
+                return ExitCode::SUCCESS;
+            }
+
+            println!();
+            println!(
+                "  {:<10}  {:>7}  {:>7}  {:>6}  {:>6}  {:>6}  {:>5}",
+                "Date", "Weight", "Trend", "Fat %", "Fat", "Lean", "WHR"
+            );
+            for entry in &report.entries {
+                println!(
+                    "  {:<10}  {:>7}  {:>7}  {:>6}  {:>6}  {:>6}  {:>5}",
+                    entry.date,
+                    optional(entry.weight, 1),
+                    optional(entry.trend_weight, 1),
+                    optional(entry.body_fat_percent, 1),
+                    optional(entry.fat_mass, 1),
+                    optional(entry.lean_mass, 1),
+                    optional(entry.waist_to_hip, 2)
+                );
+            }
+
+            if !report.weeks.is_empty() {
+                println!();
+                println!("  {:<10}  {:>7}  {:>10}", "Week of", "Trend", "Change/wk");
+                for week in &report.weeks {
+                    let change = week.change_per_week.map_or("-".normal(), |c| {
+                        let text = format!("{:+.2}", c);
+                        if c < 0.0 {
+                            text.cyan()
+                        } else {
+                            text.normal()
+                        }
+                    });
+                    println!(
+                        "  {:<10}  {:>7.1}  {:>10}",
+                        week.week_start, week.trend_weight, change
+                    );
+                }
+            }
+
+            if let Some(entry) = report
+                .entries
+                .iter()
+                .rev()
+                .find(|e| !e.asymmetry.is_empty())
+            {
+                println!();
+                println!("  {} ({})", "Left/right asymmetry".bold(), entry.date);
+                for limb in &entry.asymmetry {
+                    let percent = format!("{:.1}%", limb.percent);
+                    println!(
+                        "    {:<8} L {:.1} cm  R {:.1} cm  {}",
+                        limb_label(limb.limb),
+                        limb.left_cm,
+                        limb.right_cm,
+                        if limb.percent > ASYMMETRY_WARNING_PERCENT {
+                            percent.yellow()
+                        } else {
+                            percent.normal()
+                        }
+                    );
+                }
+            }
+
+            if let Some(change) = report.change_per_week {
+                println!();
+                println!(
+                    "  Trend: {} {}/week",
+                    format!("{:+.2}", change).cyan(),
+                    unit
+                );
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn limb_label(limb: Limb) -> &'static str {
+    match limb {
+        Limb::Bicep => "Bicep",
+        Limb::Forearm => "Forearm",
+        Limb::Thigh => "Thigh",
+        Limb::Calf => "Calf",
+    }
+}
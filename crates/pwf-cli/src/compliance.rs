@@ -0,0 +1,173 @@
+//! `pwf compliance`: plan adherence of a history
+
+use crate::analyze::load_history;
+use crate::{parse_plan_input, OutputFormat};
+use chrono::NaiveDate;
+use colored::*;
+use pwf_core::compliance::{
+    plan_compliance, ComplianceOptions, ExerciseStatus, SessionCompliance, SessionStatus,
+};
+use pwf_core::plan::WpsPlan;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+pub(crate) fn run(
+    plan_path: &Path,
+    history_path: &Path,
+    as_of: Option<NaiveDate>,
+    format: OutputFormat,
+) -> ExitCode {
+    let Some(plan) = load_plan(plan_path) else {
+        return ExitCode::FAILURE;
+    };
+    let Some(history) = load_history(history_path) else {
+        return ExitCode::FAILURE;
+    };
+    let report = plan_compliance(&plan, &history, &ComplianceOptions { as_of });
+
+    match format {
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "plan": plan_path.display().to_string(),
+                "history": history_path.display().to_string(),
+                "report": report,
+            });
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        OutputFormat::Compact => {
+            let count = |status| {
+                report
+                    .sessions
+                    .iter()
+                    .filter(|s| s.status == status)
+                    .count()
+            };
+            println!(
+                "{}: {} completed, {} partial, {} missed, {} upcoming, {}",
+                plan_path.display(),
+                count(SessionStatus::Completed),
+                count(SessionStatus::Partial),
+                count(SessionStatus::Missed),
+                count(SessionStatus::Upcoming),
+                format_percent(report.completion_percent)
+            );
+        }
+        OutputFormat::Pretty => {
+            println!("{} {}", "Plan compliance:".bold(), plan_path.display());
+            if let Some(as_of) = report.as_of {
+                println!("  {}", format!("as of {}", as_of).dimmed());
+            }
+            if report.sessions.is_empty() {
+                println!("  {}", "The plan prescribes no sessions".dimmed());
+                return ExitCode::SUCCESS;
+            }
+
+            for week in &report.weeks {
+                println!();
+                println!(
+                    "  {} {}  {}/{} completed, {} missed{}",
+                    "Week".bold(),
+                    week.week.to_string().bold(),
+                    week.completed,
+                    week.planned,
+                    week.missed,
+                    if week.upcoming > 0 {
+                        format!(", {} upcoming", week.upcoming)
+                    } else {
+                        String::new()
+                    }
+                );
+                for session in report.sessions.iter().filter(|s| s.week == week.week) {
+                    print_session(session);
+                }
+            }
+
+            println!();
+            if !report.unplanned_workouts.is_empty() {
+                println!(
+                    "  {} workout(s) linked to the plan match no session",
+                    report.unplanned_workouts.len().to_string().yellow()
+                );
+            }
+            println!(
+                "  Overall: {}",
+                format_percent(report.completion_percent).green()
+            );
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Read and parse a plan file, reporting failures on stderr
+pub(crate) fn load_plan(path: &Path) -> Option<WpsPlan> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}: {}", path.display().to_string().red(), e);
+            return None;
+        }
+    };
+    match parse_plan_input(path, &content) {
+        Ok(plan) => Some(plan),
+        Err(e) => {
+            eprintln!("{}: {}", "error".red(), e);
+            None
+        }
+    }
+}
+
+fn print_session(session: &SessionCompliance) {
+    let label = session
+        .focus
+        .as_deref()
+        .or(session.day_id.as_deref())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Day {}", session.day_index + 1));
+    let status = match session.status {
+        SessionStatus::Completed => "completed".green(),
+        SessionStatus::Partial => "partial".yellow(),
+        SessionStatus::Missed => "missed".red(),
+        SessionStatus::Upcoming => "upcoming".dimmed(),
+    };
+    let date = session
+        .workout_date
+        .clone()
+        .or_else(|| session.scheduled_date.map(|d| d.to_string()))
+        .unwrap_or_default();
+    println!(
+        "    {:<24} {:<10} {:>5}  {}",
+        label,
+        status,
+        format_percent(session.completion_percent),
+        date.dimmed()
+    );
+
+    for exercise in &session.exercises {
+        match exercise.status {
+            ExerciseStatus::Done => {}
+            ExerciseStatus::Substituted => println!(
+                "      {} {} → {}",
+                "substituted".yellow(),
+                exercise.planned.as_deref().unwrap_or(""),
+                exercise.actual.as_deref().unwrap_or("")
+            ),
+            ExerciseStatus::Missed if session.status != SessionStatus::Missed => println!(
+                "      {} {}",
+                "missed".red(),
+                exercise.planned.as_deref().unwrap_or("")
+            ),
+            ExerciseStatus::Missed => {}
+            ExerciseStatus::Extra => println!(
+                "      {} {}",
+                "extra".dimmed(),
+                exercise.actual.as_deref().unwrap_or("")
+            ),
+        }
+    }
+}
+
+fn format_percent(percent: Option<f64>) -> String {
+    percent.map_or_else(|| "-".to_string(), |p| format!("{:.0}%", p))
+}
@@ -1,9 +1,15 @@
 use clap::{Parser, Subcommand, ValueEnum};
 use colored::*;
+use pwf_converters::ImportOptions;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
+mod analyze;
+mod body;
+mod compliance;
+mod report;
+
 #[derive(Parser)]
 #[command(name = "pwf")]
 #[command(author, version, about = "Portable Workout Format validator", long_about = None)]
@@ -31,6 +37,14 @@ enum Commands {
         /// Only show errors (suppress warnings)
         #[arg(short, long)]
         quiet: bool,
+
+        /// Treat unknown or misspelled fields as errors
+        #[arg(long)]
+        deny_unknown_fields: bool,
+
+        /// Also validate against the bundled JSON Schema
+        #[arg(long)]
+        schema: bool,
     },
 
     /// Validate PWF history export files
@@ -46,6 +60,24 @@ enum Commands {
         /// Treat warnings as errors
         #[arg(short, long)]
         strict: bool,
+
+        /// Treat unknown or misspelled fields as errors
+        #[arg(long)]
+        deny_unknown_fields: bool,
+
+        /// Also validate against the bundled JSON Schema
+        #[arg(long)]
+        schema: bool,
+
+        /// Check plan references (plan_id, plan_day_id, exercises,
+        /// modalities) against this plan
+        #[arg(long, value_name = "PLAN")]
+        plan: Option<PathBuf>,
+
+        /// Add per-sport, per-modality and per-period totals, streaks and
+        /// consistency to the statistics
+        #[arg(long)]
+        stats: bool,
     },
 
     /// Show specification version info
@@ -82,9 +114,146 @@ enum Commands {
         #[arg(long)]
         summary_only: bool,
 
+        /// Fill in missing power metrics (NP, IF, TSS, VI, work) from power data
+        #[arg(long)]
+        compute_metrics: bool,
+
+        /// FTP in watts for IF and TSS when the source file has none
+        #[arg(long, value_name = "WATTS", requires = "compute_metrics")]
+        ftp: Option<u32>,
+
+        /// Add a W' balance column to CSV output
+        #[arg(long)]
+        w_prime_balance: bool,
+
+        /// Critical Power in watts for W' balance when the file has none
+        #[arg(long, value_name = "WATTS", requires = "w_prime_balance")]
+        cp: Option<u32>,
+
+        /// W' in joules for W' balance when the file has none
+        #[arg(long, value_name = "JOULES", requires = "w_prime_balance")]
+        w_prime: Option<f64>,
+
         /// Verbose output (show conversion warnings)
         #[arg(short, long)]
         verbose: bool,
+
+        /// Format of the PWF output (defaults to JSON for .json files, YAML otherwise)
+        #[arg(long, value_enum)]
+        output_format: Option<DocumentFormat>,
+    },
+
+    /// Migrate a plan or history file to another specification version
+    Migrate {
+        /// Plan or history file to migrate
+        input: PathBuf,
+
+        /// Output file path (defaults to stdout); .json paths are written as
+        /// JSON, others as YAML
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Target version (defaults to the latest)
+        #[arg(long)]
+        to: Option<u32>,
+
+        /// Output format for the migration report
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Analyze training data in a history export
+    Analyze {
+        #[command(subcommand)]
+        command: analyze::AnalyzeCommand,
+    },
+
+    /// Compare a history against the sessions a plan prescribes
+    Compliance {
+        /// Plan file
+        plan: PathBuf,
+
+        /// History file
+        history: PathBuf,
+
+        /// Treat sessions scheduled after this date as upcoming (YYYY-MM-DD;
+        /// defaults to the latest workout)
+        #[arg(long)]
+        as_of: Option<chrono::NaiveDate>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Smoothed weight, body composition, waist-to-hip ratio and limb
+    /// asymmetry from body measurements
+    Body {
+        /// History file with body measurements
+        file: PathBuf,
+
+        /// First measurement date to report (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<chrono::NaiveDate>,
+
+        /// Last measurement date to report (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<chrono::NaiveDate>,
+
+        /// Unit for weights (defaults to the history's units)
+        #[arg(long, value_enum)]
+        unit: Option<body::Unit>,
+
+        /// Time constant of the weight trend in days
+        #[arg(long, default_value_t = pwf_core::analytics::DEFAULT_SMOOTHING_DAYS)]
+        smoothing_days: f64,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "pretty")]
+        format: OutputFormat,
+    },
+
+    /// Training summary for a week or month as Markdown, HTML or JSON
+    Report {
+        /// History file
+        file: PathBuf,
+
+        /// First day of the report (YYYY-MM-DD; defaults to a week before --to)
+        #[arg(long)]
+        from: Option<chrono::NaiveDate>,
+
+        /// Last day of the report (YYYY-MM-DD; defaults to the latest workout)
+        #[arg(long)]
+        to: Option<chrono::NaiveDate>,
+
+        /// Plan to report compliance against; its athlete profile supplies
+        /// thresholds not given below
+        #[arg(long)]
+        plan: Option<PathBuf>,
+
+        /// Functional threshold power in watts, for workouts that do not record one
+        #[arg(long)]
+        ftp: Option<u32>,
+
+        /// Lactate threshold heart rate in bpm, for hrTSS
+        #[arg(long)]
+        threshold_hr: Option<u32>,
+
+        /// Threshold pace in seconds per km, for rTSS
+        #[arg(long)]
+        threshold_pace: Option<u32>,
+
+        /// Number of key sessions to list
+        #[arg(long, default_value_t = pwf_core::report::DEFAULT_KEY_SESSIONS)]
+        key_sessions: usize,
+
+        /// Document format
+        #[arg(short, long, value_enum, default_value = "markdown")]
+        format: report::ReportFormat,
+
+        /// Output file path (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 }
 
@@ -95,6 +264,30 @@ enum OutputFormat {
     Compact,
 }
 
+/// Serialization format for PWF documents
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum DocumentFormat {
+    Yaml,
+    Json,
+}
+
+impl DocumentFormat {
+    /// JSON for `.json` files, YAML for everything else
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => DocumentFormat::Json,
+            _ => DocumentFormat::Yaml,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            DocumentFormat::Yaml => "YAML",
+            DocumentFormat::Json => "JSON",
+        }
+    }
+}
+
 fn main() -> ExitCode {
     let cli = Cli::parse();
 
@@ -104,12 +297,37 @@ fn main() -> ExitCode {
             format,
             strict,
             quiet,
-        } => validate_plans(&files, format, strict, quiet),
+            deny_unknown_fields,
+            schema,
+        } => {
+            let options = pwf_core::ValidationOptions {
+                deny_unknown_fields,
+                check_schema: schema,
+            };
+            validate_plans(&files, format, strict, quiet, &options)
+        }
         Commands::History {
             files,
             format,
             strict,
-        } => validate_history(&files, format, strict),
+            deny_unknown_fields,
+            schema,
+            plan,
+            stats,
+        } => {
+            let options = pwf_core::ValidationOptions {
+                deny_unknown_fields,
+                check_schema: schema,
+            };
+            let plan = match plan {
+                Some(path) => match compliance::load_plan(&path) {
+                    Some(plan) => Some(plan),
+                    None => return ExitCode::FAILURE,
+                },
+                None => None,
+            };
+            validate_history(&files, format, strict, &options, plan.as_ref(), stats)
+        }
         Commands::Info => {
             println!("{}", "PWF - Portable Workout Format".bold());
             println!();
@@ -145,12 +363,103 @@ fn main() -> ExitCode {
             input,
             output,
             summary_only,
+            compute_metrics,
+            ftp,
+            w_prime_balance,
+            cp,
+            w_prime,
             verbose,
-        } => convert_file(&from, &to, &input, &output, summary_only, verbose),
+            output_format,
+        } => {
+            let options = ConvertOptions {
+                import: ImportOptions {
+                    summary_only,
+                    compute_metrics,
+                    ftp_watts: ftp,
+                },
+                csv: pwf_converters::CsvExportOptions {
+                    w_prime_balance,
+                    critical_power_watts: cp,
+                    w_prime_joules: w_prime,
+                    ..Default::default()
+                },
+                output_format: output_format.unwrap_or_else(|| DocumentFormat::from_path(&output)),
+            };
+            convert_file(&from, &to, &input, &output, &options, verbose)
+        }
+        Commands::Migrate {
+            input,
+            output,
+            to,
+            format,
+        } => migrate_file(&input, output.as_ref(), to, format),
+        Commands::Analyze { command } => analyze::run(command),
+        Commands::Compliance {
+            plan,
+            history,
+            as_of,
+            format,
+        } => compliance::run(&plan, &history, as_of, format),
+        Commands::Body {
+            file,
+            from,
+            to,
+            unit,
+            smoothing_days,
+            format,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            let options = pwf_core::analytics::BodyOptions {
+                smoothing_days,
+                unit: unit.map(Into::into),
+            };
+            body::run(&file, range, &options, format)
+        }
+        Commands::Report {
+            file,
+            from,
+            to,
+            plan,
+            ftp,
+            threshold_hr,
+            threshold_pace,
+            key_sessions,
+            format,
+            output,
+        } => {
+            let Some(range) = date_range(from, to) else {
+                return ExitCode::FAILURE;
+            };
+            let options = pwf_core::report::ReportOptions {
+                thresholds: pwf_core::analytics::LoadThresholds {
+                    ftp_watts: ftp,
+                    threshold_hr_bpm: threshold_hr,
+                    threshold_pace_sec_per_km: threshold_pace,
+                },
+                key_sessions,
+                ..Default::default()
+            };
+            report::run(
+                &file,
+                plan.as_deref(),
+                range,
+                &options,
+                format,
+                output.as_deref(),
+            )
+        }
     }
 }
 
-fn validate_plans(files: &[PathBuf], format: OutputFormat, strict: bool, quiet: bool) -> ExitCode {
+fn validate_plans(
+    files: &[PathBuf],
+    format: OutputFormat,
+    strict: bool,
+    quiet: bool,
+    options: &pwf_core::ValidationOptions,
+) -> ExitCode {
     let mut all_valid = true;
     let mut results = Vec::new();
 
@@ -164,7 +473,10 @@ fn validate_plans(files: &[PathBuf], format: OutputFormat, strict: bool, quiet:
             }
         };
 
-        let result = pwf_core::plan::validate(&content);
+        let result = match DocumentFormat::from_path(path) {
+            DocumentFormat::Json => pwf_core::plan::validate_json_with_options(&content, options),
+            DocumentFormat::Yaml => pwf_core::plan::validate_with_options(&content, options),
+        };
         let is_valid = result.valid && (!strict || result.warnings.is_empty());
 
         if !is_valid {
@@ -183,7 +495,14 @@ fn validate_plans(files: &[PathBuf], format: OutputFormat, strict: bool, quiet:
     }
 }
 
-fn validate_history(files: &[PathBuf], format: OutputFormat, strict: bool) -> ExitCode {
+fn validate_history(
+    files: &[PathBuf],
+    format: OutputFormat,
+    strict: bool,
+    options: &pwf_core::ValidationOptions,
+    plan: Option<&pwf_core::plan::WpsPlan>,
+    stats: bool,
+) -> ExitCode {
     let mut all_valid = true;
     let mut results = Vec::new();
 
@@ -197,7 +516,21 @@ fn validate_history(files: &[PathBuf], format: OutputFormat, strict: bool) -> Ex
             }
         };
 
-        let result = pwf_core::history::validate(&content);
+        let result = match DocumentFormat::from_path(path) {
+            DocumentFormat::Json => {
+                pwf_core::history::validate_json_with_options(&content, options)
+            }
+            DocumentFormat::Yaml => pwf_core::history::validate_with_options(&content, options),
+        };
+        let mut result = match plan {
+            Some(plan) => result.with_plan(plan),
+            None => result,
+        };
+        if stats {
+            if let Some(history) = &result.history {
+                result.statistics = Some(pwf_core::history::history_statistics(history));
+            }
+        }
         let is_valid = result.valid && (!strict || result.warnings.is_empty());
 
         if !is_valid {
@@ -359,6 +692,9 @@ fn output_history_results(
                         {
                             println!("  Date range: {} to {}", start.cyan(), end.cyan());
                         }
+                        if let Some(ref breakdown) = stats.breakdown {
+                            print_statistics_breakdown(breakdown);
+                        }
                     }
 
                     for warning in &result.warnings {
@@ -392,6 +728,114 @@ fn output_history_results(
     }
 }
 
+/// Weeks shown in the pretty weekly table; JSON output has all of them
+const RECENT_WEEKS: usize = 12;
+
+fn print_statistics_breakdown(breakdown: &pwf_core::history::StatisticsBreakdown) {
+    use pwf_core::history::ActivityTotals;
+
+    fn header(label: &str) {
+        println!(
+            "    {:<12} {:>6} {:>8} {:>10} {:>8} {:>8} {:>10}",
+            label, "Count", "Hours", "Km", "Elev m", "kcal", "Volume kg"
+        );
+    }
+    fn row(label: &str, totals: &ActivityTotals) {
+        println!(
+            "    {:<12} {:>6} {:>8.1} {:>10.1} {:>8.0} {:>8} {:>10.0}",
+            label,
+            totals.count,
+            totals.duration_sec as f64 / 3600.0,
+            totals.distance_m / 1000.0,
+            totals.elevation_gain_m,
+            totals.calories,
+            totals.volume_kg
+        );
+    }
+
+    if !breakdown.by_sport.is_empty() {
+        println!();
+        println!("  {}", "By sport".bold());
+        header("Sport");
+        for sport in &breakdown.by_sport {
+            row(&sport.sport, &sport.totals);
+        }
+    }
+    if !breakdown.by_modality.is_empty() {
+        println!();
+        println!("  {}", "By modality".bold());
+        header("Modality");
+        for modality in &breakdown.by_modality {
+            row(&modality.modality.to_string(), &modality.totals);
+        }
+    }
+    for (title, periods) in [
+        ("Yearly", &breakdown.yearly),
+        ("Monthly", &breakdown.monthly),
+    ] {
+        if periods.is_empty() {
+            continue;
+        }
+        println!();
+        println!("  {}", title.bold());
+        header("Period");
+        for period in periods.iter() {
+            row(&period.period, &period.totals);
+        }
+    }
+    if !breakdown.weekly.is_empty() {
+        let skip = breakdown.weekly.len().saturating_sub(RECENT_WEEKS);
+        println!();
+        if skip > 0 {
+            println!(
+                "  {} {}",
+                "Weekly".bold(),
+                format!(
+                    "(last {} of {} weeks; --format json lists all)",
+                    RECENT_WEEKS,
+                    breakdown.weekly.len()
+                )
+                .dimmed()
+            );
+        } else {
+            println!("  {}", "Weekly".bold());
+        }
+        header("Week of");
+        for week in &breakdown.weekly[skip..] {
+            row(&week.period, &week.totals);
+        }
+    }
+
+    let streaks = &breakdown.streaks;
+    let consistency = &breakdown.consistency;
+    println!();
+    println!(
+        "  Streaks: longest {} day(s){}, current {}; longest {} week(s){}, current {}",
+        streaks.longest_days.to_string().cyan(),
+        streaks
+            .longest_days_start
+            .as_ref()
+            .map_or(String::new(), |d| format!(" from {}", d)),
+        streaks.current_days,
+        streaks.longest_weeks.to_string().cyan(),
+        streaks
+            .longest_weeks_start
+            .as_ref()
+            .map_or(String::new(), |d| format!(" from {}", d)),
+        streaks.current_weeks
+    );
+    println!(
+        "  Consistency: {} of {} days, {} of {} weeks active ({:.0}%), {:.1} workouts/week, longest gap {} days",
+        consistency.active_days,
+        consistency.total_days,
+        consistency.active_weeks,
+        consistency.total_weeks,
+        consistency.active_weeks_percent,
+        consistency.workouts_per_week,
+        consistency.longest_gap_days
+    );
+}
+
 fn init_plan(output: &PathBuf) -> ExitCode {
     let template = r#"# PWF Plan v1
 # Documentation: https://pwf.dev/docs/SPECIFICATION
@@ -516,12 +960,232 @@ fn write_template(output: &PathBuf, template: &str) -> ExitCode {
     }
 }
 
+fn migrate_file(
+    input: &PathBuf,
+    output: Option<&PathBuf>,
+    to: Option<u32>,
+    format: OutputFormat,
+) -> ExitCode {
+    let content = match fs::read_to_string(input) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}: {}", input.display().to_string().red(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let is_history = serde_yaml::from_str::<serde_yaml::Value>(&content)
+        .map(|doc| doc.get("history_version").is_some())
+        .unwrap_or(false);
+
+    // Stdout keeps the input's format
+    let document_format = DocumentFormat::from_path(output.unwrap_or(input));
+    let migrated = if is_history {
+        let target = to.unwrap_or(pwf_core::history::LATEST_HISTORY_VERSION);
+        pwf_core::history::migrate(&content, target).and_then(|m| {
+            let encoded = match document_format {
+                DocumentFormat::Json => {
+                    pwf_core::history::to_json(&m.history)
+                        .map_err(pwf_core::error::ParseError::from)?
+                        + "\n"
+                }
+                DocumentFormat::Yaml => {
+                    to_yaml_without_nulls(&m.history).map_err(pwf_core::error::ParseError::from)?
+                }
+            };
+            Ok((encoded, m.report))
+        })
+    } else {
+        let target = to.unwrap_or(pwf_core::plan::LATEST_PLAN_VERSION);
+        pwf_core::plan::migrate(&content, target).and_then(|m| {
+            let encoded = match document_format {
+                DocumentFormat::Json => {
+                    pwf_core::plan::to_json(&m.plan).map_err(pwf_core::error::ParseError::from)?
+                        + "\n"
+                }
+                DocumentFormat::Yaml => {
+                    to_yaml_without_nulls(&m.plan).map_err(pwf_core::error::ParseError::from)?
+                }
+            };
+            Ok((encoded, m.report))
+        })
+    };
+
+    let (document, report) = match migrated {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("{}: {}", "error".red(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match output {
+        Some(path) => {
+            if let Err(e) = fs::write(path, &document) {
+                eprintln!("{}: Failed to write output file: {}", "error".red(), e);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{}", document),
+    }
+
+    // The report goes to stderr when the document itself is on stdout
+    let report_text = match format {
+        OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+            "file": input.display().to_string(),
+            "type": if is_history { "history" } else { "plan" },
+            "from_version": report.from_version,
+            "to_version": report.to_version,
+            "changes": report.changes,
+            "lost": report.lost,
+        }))
+        .unwrap(),
+        OutputFormat::Compact => format!(
+            "{} {} v{} -> v{} ({} changes, {} lost)",
+            if report.is_lossy() {
+                "⚠".yellow()
+            } else {
+                "✓".green()
+            },
+            input.display(),
+            report.from_version,
+            report.to_version,
+            report.changes.len(),
+            report.lost.len()
+        ),
+        OutputFormat::Pretty => {
+            let mut lines = vec![format!(
+                "{} {} migrated from v{} to v{}",
+                "✓".green().bold(),
+                input.display(),
+                report.from_version,
+                report.to_version
+            )];
+            if report.is_noop() {
+                lines.push(
+                    "  Already at the target version, nothing to do"
+                        .dimmed()
+                        .to_string(),
+                );
+            }
+            for change in &report.changes {
+                lines.push(format!(
+                    "  {} {}: {}",
+                    "→".cyan(),
+                    change.path.dimmed(),
+                    change.message
+                ));
+            }
+            for lost in &report.lost {
+                lines.push(format!(
+                    "  {} {}: {}",
+                    "✗".red(),
+                    lost.path.dimmed(),
+                    lost.message.red()
+                ));
+            }
+            lines.join("\n")
+        }
+    };
+    if output.is_some() {
+        println!("{}", report_text);
+    } else {
+        eprintln!("{}", report_text);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Serialize a document without the `null` entries serde emits for unset
+/// optional fields, so migrated files stay close to hand-written ones
+///
+/// `x-` extension values are written as they are, explicit nulls included.
+fn to_yaml_without_nulls<T: serde::Serialize>(value: &T) -> Result<String, serde_yaml::Error> {
+    fn strip(value: &mut serde_yaml::Value) {
+        match value {
+            serde_yaml::Value::Mapping(map) => {
+                let is_extension =
+                    |key: &serde_yaml::Value| key.as_str().is_some_and(|k| k.starts_with("x-"));
+                map.retain(|k, v| is_extension(k) || !v.is_null());
+                map.iter_mut()
+                    .filter(|(k, _)| !is_extension(k))
+                    .for_each(|(_, v)| strip(v));
+            }
+            serde_yaml::Value::Sequence(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+
+    let mut value = serde_yaml::to_value(value)?;
+    strip(&mut value);
+    serde_yaml::to_string(&value)
+}
+
+/// The period given by `--from` and `--to`
+///
+/// Reports the error and returns `None` when the start is after the end.
+fn date_range(
+    from: Option<chrono::NaiveDate>,
+    to: Option<chrono::NaiveDate>,
+) -> Option<pwf_core::analytics::DateRange> {
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            eprintln!("{}: --from {} is after --to {}", "error".red(), from, to);
+            return None;
+        }
+    }
+    Some(pwf_core::analytics::DateRange::new(from, to))
+}
+
+/// Parse a PWF history input file, using JSON for `.json` paths
+fn parse_history_input(
+    path: &Path,
+    content: &str,
+) -> Result<pwf_core::history::WpsHistory, pwf_core::error::ParseError> {
+    match DocumentFormat::from_path(path) {
+        DocumentFormat::Json => pwf_core::history::parse_json(content),
+        DocumentFormat::Yaml => pwf_core::history::parse(content),
+    }
+}
+
+/// Parse a PWF plan input file, using JSON for `.json` paths
+fn parse_plan_input(
+    path: &Path,
+    content: &str,
+) -> Result<pwf_core::plan::WpsPlan, pwf_core::error::ParseError> {
+    match DocumentFormat::from_path(path) {
+        DocumentFormat::Json => pwf_core::plan::parse_json(content),
+        DocumentFormat::Yaml => pwf_core::plan::parse(content),
+    }
+}
+
+/// Re-encode converter output (always YAML) in the requested format
+fn encode_pwf_history(
+    pwf_yaml: &str,
+    format: DocumentFormat,
+) -> Result<String, Box<dyn std::error::Error>> {
+    match format {
+        DocumentFormat::Yaml => Ok(pwf_yaml.to_string()),
+        DocumentFormat::Json => {
+            let history = pwf_core::history::parse(pwf_yaml)?;
+            Ok(pwf_core::history::to_json(&history)?)
+        }
+    }
+}
+
+/// Options for `pwf convert`, split by conversion direction
+struct ConvertOptions {
+    import: ImportOptions,
+    csv: pwf_converters::CsvExportOptions,
+    output_format: DocumentFormat,
+}
+
 fn convert_file(
     from: &str,
     to: &str,
     input: &PathBuf,
     output: &PathBuf,
-    summary_only: bool,
+    options: &ConvertOptions,
     verbose: bool,
 ) -> ExitCode {
     // Validate format combinations
@@ -559,12 +1223,30 @@ fn convert_file(
 
     // Perform conversion based on formats
     match (from_lower.as_str(), to_lower.as_str()) {
-        ("fit", "pwf") => convert_fit_to_pwf(input, output, summary_only, verbose),
-        ("tcx", "pwf") => convert_tcx_to_pwf(input, output, summary_only, verbose),
-        ("gpx", "pwf") => convert_gpx_to_pwf(input, output, summary_only, verbose),
+        ("fit", "pwf") => convert_fit_to_pwf(
+            input,
+            output,
+            &options.import,
+            verbose,
+            options.output_format,
+        ),
+        ("tcx", "pwf") => convert_tcx_to_pwf(
+            input,
+            output,
+            &options.import,
+            verbose,
+            options.output_format,
+        ),
+        ("gpx", "pwf") => convert_gpx_to_pwf(
+            input,
+            output,
+            &options.import,
+            verbose,
+            options.output_format,
+        ),
         ("pwf", "tcx") => convert_pwf_to_tcx(input, output, verbose),
         ("pwf", "gpx") => convert_pwf_to_gpx(input, output, verbose),
-        ("pwf", "csv") => convert_pwf_to_csv(input, output, verbose),
+        ("pwf", "csv") => convert_pwf_to_csv(input, output, &options.csv, verbose),
         ("pwf", "fit") => {
             // Special error message for FIT export
             eprintln!("{}: FIT export is not currently supported", "error".red());
@@ -622,8 +1304,9 @@ fn convert_file(
 fn convert_fit_to_pwf(
     input: &PathBuf,
     output: &PathBuf,
-    summary_only: bool,
+    import_options: &ImportOptions,
     verbose: bool,
+    output_format: DocumentFormat,
 ) -> ExitCode {
     println!("{} Converting {} to PWF...", "→".cyan(), input.display());
 
@@ -655,7 +1338,7 @@ fn convert_fit_to_pwf(
     }
 
     // Convert using pwf-converters library
-    let result = match pwf_converters::fit_to_pwf(file, summary_only) {
+    let result = match pwf_converters::fit_to_pwf_with_options(file, import_options) {
         Ok(r) => r,
         Err(e) => {
             eprintln!("{}: Conversion failed: {}", "error".red(), e);
@@ -677,13 +1360,22 @@ fn convert_fit_to_pwf(
         println!();
     }
 
+    let document = match encode_pwf_history(&result.pwf_yaml, output_format) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}: Failed to encode PWF output: {}", "error".red(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
     if verbose {
         // Show conversion statistics
-        let line_count = result.pwf_yaml.lines().count();
-        let size_kb = result.pwf_yaml.len() as f64 / 1024.0;
+        let line_count = document.lines().count();
+        let size_kb = document.len() as f64 / 1024.0;
         println!(
-            "  {} Generated PWF YAML: {} lines, {:.1} KB",
+            "  {} Generated PWF {}: {} lines, {:.1} KB",
             "✓".dimmed(),
+            output_format.name(),
             line_count,
             size_kb
         );
@@ -691,7 +1383,7 @@ fn convert_fit_to_pwf(
     }
 
     // Write output file
-    match fs::write(output, &result.pwf_yaml) {
+    match fs::write(output, &document) {
         Ok(_) => {
             println!("{} Converted to {}", "✓".green(), output.display());
 
@@ -722,8 +1414,9 @@ fn convert_fit_to_pwf(
 fn convert_tcx_to_pwf(
     input: &PathBuf,
     output: &PathBuf,
-    summary_only: bool,
+    import_options: &ImportOptions,
     verbose: bool,
+    output_format: DocumentFormat,
 ) -> ExitCode {
     println!("{} Converting {} to PWF...", "→".cyan(), input.display());
 
@@ -756,7 +1449,7 @@ fn convert_tcx_to_pwf(
 
     // Convert using pwf-converters library
     let result: pwf_converters::ConversionResult =
-        match pwf_converters::tcx_to_pwf(file, summary_only) {
+        match pwf_converters::tcx_to_pwf_with_options(file, import_options) {
             Ok(r) => r,
             Err(e) => {
                 eprintln!("{}: Conversion failed: {}", "error".red(), e);
@@ -778,13 +1471,22 @@ fn convert_tcx_to_pwf(
         println!();
     }
 
+    let document = match encode_pwf_history(&result.pwf_yaml, output_format) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}: Failed to encode PWF output: {}", "error".red(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
     if verbose {
         // Show conversion statistics
-        let line_count = result.pwf_yaml.lines().count();
-        let size_kb = result.pwf_yaml.len() as f64 / 1024.0;
+        let line_count = document.lines().count();
+        let size_kb = document.len() as f64 / 1024.0;
         println!(
-            "  {} Generated PWF YAML: {} lines, {:.1} KB",
+            "  {} Generated PWF {}: {} lines, {:.1} KB",
             "✓".dimmed(),
+            output_format.name(),
             line_count,
             size_kb
         );
@@ -792,7 +1494,7 @@ fn convert_tcx_to_pwf(
     }
 
     // Write output file
-    match fs::write(output, &result.pwf_yaml) {
+    match fs::write(output, &document) {
         Ok(_) => {
             println!("{} Converted to {}", "✓".green(), output.display());
 
@@ -823,8 +1525,9 @@ fn convert_tcx_to_pwf(
 fn convert_gpx_to_pwf(
     input: &PathBuf,
     output: &PathBuf,
-    summary_only: bool,
+    import_options: &ImportOptions,
     verbose: bool,
+    output_format: DocumentFormat,
 ) -> ExitCode {
     println!("{} Converting {} to PWF...", "→".cyan(), input.display());
 
@@ -847,7 +1550,7 @@ fn convert_gpx_to_pwf(
 
     // Convert GPX to PWF
     let result: pwf_converters::ConversionResult =
-        match pwf_converters::gpx_to_pwf(file, summary_only) {
+        match pwf_converters::gpx_to_pwf_with_options(file, import_options) {
             Ok(r) => r,
             Err(e) => {
                 eprintln!("{}: Conversion failed: {}", "error".red(), e);
@@ -869,12 +1572,21 @@ fn convert_gpx_to_pwf(
         println!();
     }
 
+    let document = match encode_pwf_history(&result.pwf_yaml, output_format) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("{}: Failed to encode PWF output: {}", "error".red(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
     if verbose {
-        let line_count = result.pwf_yaml.lines().count();
-        let size_kb = result.pwf_yaml.len() as f64 / 1024.0;
+        let line_count = document.lines().count();
+        let size_kb = document.len() as f64 / 1024.0;
         println!(
-            "  {} Generated PWF YAML: {} lines, {:.1} KB",
+            "  {} Generated PWF {}: {} lines, {:.1} KB",
             "✓".dimmed(),
+            output_format.name(),
             line_count,
             size_kb
         );
@@ -882,7 +1594,7 @@ fn convert_gpx_to_pwf(
     }
 
     // Write output file
-    match fs::write(output, &result.pwf_yaml) {
+    match fs::write(output, &document) {
         Ok(_) => {
             println!("{} Converted to {}", "✓".green(), output.display());
 
@@ -931,7 +1643,7 @@ fn convert_pwf_to_gpx(input: &PathBuf, output: &PathBuf, verbose: bool) -> ExitC
     }
 
     // Parse PWF history
-    let history: pwf_core::history::WpsHistory = match pwf_core::history::parse(&content) {
+    let history: pwf_core::history::WpsHistory = match parse_history_input(input, &content) {
         Ok(h) => h,
         Err(e) => {
             eprintln!("{}: Failed to parse PWF history: {}", "error".red(), e);
@@ -1013,7 +1725,12 @@ fn convert_pwf_to_gpx(input: &PathBuf, output: &PathBuf, verbose: bool) -> ExitC
     }
 }
 
-fn convert_pwf_to_csv(input: &PathBuf, output: &PathBuf, verbose: bool) -> ExitCode {
+fn convert_pwf_to_csv(
+    input: &PathBuf,
+    output: &PathBuf,
+    options: &pwf_converters::CsvExportOptions,
+    verbose: bool,
+) -> ExitCode {
     println!("{} Exporting {} to CSV...", "→".cyan(), input.display());
 
     if verbose {
@@ -1034,7 +1751,7 @@ fn convert_pwf_to_csv(input: &PathBuf, output: &PathBuf, verbose: bool) -> ExitC
     }
 
     // Parse PWF history
-    let history: pwf_core::history::WpsHistory = match pwf_core::history::parse(&content) {
+    let history: pwf_core::history::WpsHistory = match parse_history_input(input, &content) {
         Ok(h) => h,
         Err(e) => {
             eprintln!("{}: Failed to parse PWF history: {}", "error".red(), e);
@@ -1053,8 +1770,7 @@ fn convert_pwf_to_csv(input: &PathBuf, output: &PathBuf, verbose: bool) -> ExitC
     }
 
     // Export to CSV using pwf-converters library
-    let options = pwf_converters::CsvExportOptions::default();
-    let result = match pwf_converters::export_telemetry_to_csv(&history, &options) {
+    let result = match pwf_converters::export_telemetry_to_csv(&history, options) {
         Ok(r) => r,
         Err(e) => {
             eprintln!("{}: Export failed: {}", "error".red(), e);
@@ -1162,7 +1878,7 @@ fn convert_pwf_to_tcx(input: &PathBuf, output: &PathBuf, verbose: bool) -> ExitC
     }
 
     // Parse PWF history
-    let history: pwf_core::history::WpsHistory = match pwf_core::history::parse(&content) {
+    let history: pwf_core::history::WpsHistory = match parse_history_input(input, &content) {
         Ok(h) => h,
         Err(e) => {
             eprintln!("{}: Failed to parse PWF history: {}", "error".red(), e);
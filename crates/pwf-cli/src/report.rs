@@ -0,0 +1,85 @@
+//! `pwf report`: weekly or monthly training summaries
+
+use crate::analyze::load_history;
+use crate::compliance::load_plan;
+use clap::ValueEnum;
+use colored::*;
+use pwf_core::analytics::{workout_date, DateRange};
+use pwf_core::report::{self, ReportOptions};
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Document formats for a report
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ReportFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+pub(crate) fn run(
+    path: &Path,
+    plan: Option<&Path>,
+    range: DateRange,
+    options: &ReportOptions,
+    format: ReportFormat,
+    output: Option<&Path>,
+) -> ExitCode {
+    let Some(history) = load_history(path) else {
+        return ExitCode::FAILURE;
+    };
+    let plan = match plan {
+        Some(plan_path) => match load_plan(plan_path) {
+            Some(plan) => Some(plan),
+            None => return ExitCode::FAILURE,
+        },
+        None => None,
+    };
+    let Some(report) = report::training_report(&history, plan.as_ref(), &range, options) else {
+        // A reversed --from/--to is rejected before loading, so only an open
+        // end can leave the period empty
+        let has_dates = history.workouts.iter().any(|w| workout_date(w).is_some());
+        let reason = match range.from {
+            Some(from) if has_dates => format!("--from {} is after the latest workout", from),
+            _ => "no dated workouts; pass --to to choose the period".to_string(),
+        };
+        eprintln!("{}: {}", path.display().to_string().red(), reason);
+        return ExitCode::FAILURE;
+    };
+
+    let document = match format {
+        ReportFormat::Markdown => report::render_markdown(&report),
+        ReportFormat::Html => report::render_html(&report),
+        ReportFormat::Json => {
+            let output = serde_json::json!({
+                "file": path.display().to_string(),
+                "report": report,
+            });
+            serde_json::to_string_pretty(&output).unwrap() + "\n"
+        }
+    };
+
+    match output {
+        None => {
+            print!("{}", document);
+            ExitCode::SUCCESS
+        }
+        Some(output) => match fs::write(output, &document) {
+            Ok(_) => {
+                println!(
+                    "{} Wrote report for {} to {} to {}",
+                    "✓".green(),
+                    report.from,
+                    report.to,
+                    output.display()
+                );
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("{}: {}", output.display().to_string().red(), e);
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
@@ -0,0 +1,966 @@
+//! Integration tests for the `pwf analyze` subcommands
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+/// Helper to get the binary command
+#[allow(deprecated)]
+fn pwf() -> Command {
+    Command::cargo_bin("pwf").unwrap()
+}
+
+/// Temporary file removed on drop
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn with_content(name: &str, content: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("pwf_analyze_test_{}", name));
+        fs::write(&path, content).unwrap();
+        TempFile { path }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A ride with `seconds` of power at `watts`, sampled at 1 Hz
+fn ride(date: &str, watts: u32, seconds: usize) -> String {
+    let elapsed: Vec<String> = (0..seconds).map(|i| i.to_string()).collect();
+    let timestamps = vec!["\"\""; seconds].join(", ");
+    let power = vec![watts.to_string(); seconds].join(", ");
+    format!(
+        r#"  - date: "{date}"
+    exercises:
+      - name: Ride
+        sets:
+          - telemetry:
+              time_series:
+                timestamps: [{timestamps}]
+                elapsed_sec: [{}]
+                power: [{power}]
+"#,
+        elapsed.join(", ")
+    )
+}
+
+fn power_history() -> String {
+    format!(
+        "history_version: 2\nexported_at: \"2025-02-01T00:00:00Z\"\nworkouts:\n{}{}{}",
+        ride("2025-01-05", 400, 180),
+        ride("2025-01-12", 300, 1200),
+        ride("2025-03-01", 900, 60),
+    )
+}
+
+// ============================================================================
+// Power Curve Tests
+// ============================================================================
+
+#[test]
+fn test_power_curve_pretty() {
+    let file = TempFile::with_content("curve_pretty.yaml", &power_history());
+
+    pwf()
+        .args(["analyze", "power-curve"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Power curve:"))
+        .stdout(predicate::str::contains("20m"))
+        .stdout(predicate::str::contains("Critical power:"))
+        .stdout(predicate::str::contains("Estimated FTP:"));
+}
+
+#[test]
+fn test_power_curve_json_with_date_range() {
+    let file = TempFile::with_content("curve_json.yaml", &power_history());
+
+    let output = pwf()
+        .args(["analyze", "power-curve", "--format", "json"])
+        .args(["--from", "2025-01-01", "--to", "2025-01-31"])
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["from"], "2025-01-01");
+    let curve = json["curve"].as_array().unwrap();
+    // The 900 W March ride is outside the range
+    assert_eq!(curve[0]["duration_sec"], 1);
+    assert_eq!(curve[0]["watts"], 400.0);
+    assert_eq!(curve[0]["date"], "2025-01-05");
+    assert!(json["critical_power"]["cp_watts"].as_f64().unwrap() > 0.0);
+    assert_eq!(json["estimated_ftp"]["source"], "critical_power");
+}
+
+#[test]
+fn test_power_curve_compact() {
+    let file = TempFile::with_content("curve_compact.yaml", &power_history());
+
+    pwf()
+        .args(["analyze", "power-curve", "--format", "compact"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("points"))
+        .stdout(predicate::str::contains("FTP"));
+}
+
+#[test]
+fn test_power_curve_without_power_data() {
+    pwf()
+        .args(["analyze", "power-curve"])
+        .arg(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../examples/history-export.yaml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No power data"));
+}
+
+#[test]
+fn test_power_curve_invalid_date() {
+    let file = TempFile::with_content("curve_bad_date.yaml", &power_history());
+
+    pwf()
+        .args(["analyze", "power-curve", "--from", "January"])
+        .arg(&file.path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--from"));
+}
+
+#[test]
+fn test_reversed_date_range_is_rejected() {
+    let file = TempFile::with_content("reversed.yaml", &power_history());
+
+    for command in [
+        vec!["power-curve"],
+        vec!["load"],
+        vec!["running"],
+        vec!["strength"],
+        vec!["swimming"],
+        vec!["execution", "--plan", "plan.yaml"],
+        vec!["srpe"],
+        vec!["intensity"],
+    ] {
+        pwf()
+            .arg("analyze")
+            .args(command)
+            .args(["--from", "2025-03-01", "--to", "2025-01-01"])
+            .arg(&file.path)
+            .assert()
+            .failure()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::contains(
+                "--from 2025-03-01 is after --to 2025-01-01",
+            ));
+    }
+}
+
+#[test]
+fn test_power_curve_missing_file() {
+    pwf()
+        .args(["analyze", "power-curve", "does-not-exist.yaml"])
+        .assert()
+        .failure();
+}
+
+// ============================================================================
+// Training Load Tests
+// ============================================================================
+
+fn load_history() -> String {
+    let workout = |date: &str, tss: f64| {
+        format!(
+            "  - date: \"{date}\"\n    exercises: []\n    telemetry:\n      power_metrics:\n        training_stress_score: {tss}\n"
+        )
+    };
+    format!(
+        "history_version: 2\nexported_at: \"2025-02-01T00:00:00Z\"\nworkouts:\n{}{}{}  - date: \"2025-01-04\"\n    exercises: []\n",
+        workout("2025-01-01", 100.0),
+        workout("2025-01-02", 50.0),
+        workout("2025-01-05", 80.0),
+    )
+}
+
+#[test]
+fn test_load_pretty() {
+    let file = TempFile::with_content("load_pretty.yaml", &load_history());
+
+    pwf()
+        .args(["analyze", "load"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Training load:"))
+        .stdout(predicate::str::contains("2025-01-03"))
+        .stdout(predicate::str::contains("CTL"))
+        .stdout(predicate::str::contains("1 workout(s) had no power"));
+}
+
+#[test]
+fn test_load_json_with_time_constants() {
+    let file = TempFile::with_content("load_json.yaml", &load_history());
+
+    let output = pwf()
+        .args(["analyze", "load", "--format", "json"])
+        .args([
+            "--ctl-days",
+            "10",
+            "--atl-days",
+            "5",
+            "--from",
+            "2025-01-02",
+        ])
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["ctl_days"], 10.0);
+    assert_eq!(json["unscored_workouts"], 1);
+    let days = json["days"].as_array().unwrap();
+    assert_eq!(days.len(), 4);
+    assert_eq!(days[0]["date"], "2025-01-02");
+    assert_eq!(days[0]["stress"], 50.0);
+    // Seeded by the 100 TSS ride on 1 January
+    assert_eq!(
+        days[0]["ctl"].as_f64().unwrap(),
+        10.0 + (50.0 - 10.0) / 10.0
+    );
+    assert_eq!(days[0]["tsb"].as_f64().unwrap(), 10.0 - 20.0);
+}
+
+#[test]
+fn test_load_compact() {
+    let file = TempFile::with_content("load_compact.yaml", &load_history());
+
+    pwf()
+        .args(["analyze", "load", "--format", "compact"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-01-05 CTL"));
+}
+
+// ============================================================================
+// Session-RPE Tests
+// ============================================================================
+
+/// Daily 60 min sessions at RPE 5 for four weeks, then a week at RPE 10
+fn srpe_history() -> String {
+    let mut workouts = String::new();
+    let start = chrono::NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+    for (i, date) in start.iter_days().take(35).enumerate() {
+        let rpe = if i >= 28 { 10 } else { 5 };
+        workouts.push_str(&format!(
+            "  - date: \"{date}\"\n    duration_sec: 3600\n    exercises:\n      - name: Squat\n        sets: [{{ reps: 5, rpe: {rpe} }}]\n"
+        ));
+    }
+    format!("history_version: 2\nexported_at: \"2025-03-01T00:00:00Z\"\nworkouts:\n{workouts}")
+}
+
+#[test]
+fn test_srpe_pretty_flags_spike() {
+    let file = TempFile::with_content("srpe_pretty.yaml", &srpe_history());
+
+    pwf()
+        .args(["analyze", "srpe"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Session-RPE load:"))
+        .stdout(predicate::str::contains("2025-01-06"))
+        .stdout(predicate::str::contains("spike"));
+}
+
+#[test]
+fn test_srpe_json_thresholds() {
+    let file = TempFile::with_content("srpe_json.yaml", &srpe_history());
+
+    let output = pwf()
+        .args(["analyze", "srpe", "--format", "json", "--spike", "2.0"])
+        .args(["--from", "2025-02-03"])
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let sessions = json["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 7);
+    assert_eq!(sessions[0]["load"], 600.0);
+    let days = json["days"].as_array().unwrap();
+    // Ratio peaks at 600 / 375 = 1.6, below the raised threshold
+    assert!(days.iter().all(|d| d.get("flag").is_none()));
+    assert!(days.last().unwrap()["ratio"].as_f64().unwrap() > 1.55);
+}
+
+#[test]
+fn test_srpe_compact() {
+    let file = TempFile::with_content("srpe_compact.yaml", &srpe_history());
+
+    pwf()
+        .args(["analyze", "srpe", "--format", "compact"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("35 sessions"));
+}
+
+// ============================================================================
+// Personal Record Tests
+// ============================================================================
+
+const RECORDS_HISTORY: &str = r#"history_version: 2
+exported_at: "2025-02-01T00:00:00Z"
+workouts:
+  - date: "2025-01-06"
+    exercises:
+      - name: Bench Press
+        sets:
+          - { reps: 5, weight_kg: 80, is_pr: true }
+  - date: "2025-01-13"
+    exercises:
+      - name: Bench Press
+        sets:
+          - { reps: 5, weight_kg: 85 }
+personal_records:
+  - exercise_name: Bench Press
+    record_type: max_weight
+    value: 80
+    unit: kg
+    achieved_at: "2025-01-06"
+"#;
+
+#[test]
+fn test_records_pretty_reports_disagreements() {
+    let file = TempFile::with_content("records_pretty.yaml", RECORDS_HISTORY);
+
+    pwf()
+        .args(["analyze", "records"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Bench Press"))
+        .stdout(predicate::str::contains("Estimated 1RM"))
+        .stdout(predicate::str::contains("99.2 kg"))
+        .stdout(predicate::str::contains("stored 80, data shows 85"))
+        .stdout(predicate::str::contains("is_pr true but it set no record"));
+}
+
+#[test]
+fn test_records_json_with_formula() {
+    let file = TempFile::with_content("records_json.yaml", RECORDS_HISTORY);
+
+    let output = pwf()
+        .args([
+            "analyze",
+            "records",
+            "--format",
+            "json",
+            "--formula",
+            "brzycki",
+        ])
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["formula"], "brzycki");
+    let e1rm = json["records"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["record_type"] == "1rm")
+        .unwrap();
+    assert_eq!(e1rm["value"], 95.6);
+    assert!(json["events"].as_array().unwrap().len() >= 2);
+    assert!(json["disagreements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|d| d["kind"] == "value_mismatch"));
+}
+
+#[test]
+fn test_records_fix_writes_regenerated_history() {
+    let file = TempFile::with_content("records_fix.yaml", RECORDS_HISTORY);
+    let output = TempFile::with_content("records_fixed.yaml", "");
+
+    pwf()
+        .args(["analyze", "records", "--format", "compact", "--fix"])
+        .arg(&output.path)
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("disagreements"));
+
+    pwf()
+        .args(["analyze", "records", "--format", "compact"])
+        .arg(&output.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0 disagreements"));
+
+    let fixed = fs::read_to_string(&output.path).unwrap();
+    assert!(fixed.contains("is_pr: true"));
+    assert!(fixed.contains("max_weight_5rm"));
+}
+
+// ============================================================================
+// Summaries
+// ============================================================================
+
+const SUMMARY_HISTORY: &str = r#"history_version: 2
+exported_at: "2025-02-01T00:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    x-sync-id: null
+    telemetry:
+      heart_rate_avg: 150
+      total_distance_m: 2000
+    exercises:
+      - name: Run
+        sets:
+          - telemetry:
+              time_series:
+                timestamps: ["", "", "", ""]
+                heart_rate: [130, 130, 134, 134]
+                distance_m: [0, 300, 600, 900]
+"#;
+
+#[test]
+fn test_summaries_reports_discrepancies() {
+    let file = TempFile::with_content("summaries.yaml", SUMMARY_HISTORY);
+
+    pwf()
+        .args(["analyze", "summaries"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "workouts[0].telemetry.heart_rate_avg",
+        ))
+        .stdout(predicate::str::contains("time series gives 900.0 m"));
+
+    // The same mismatches are validation warnings
+    pwf()
+        .arg("history")
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("heart_rate_avg"));
+}
+
+#[test]
+fn test_summaries_fix_writes_recomputed_history() {
+    let file = TempFile::with_content("summaries_fix.yaml", SUMMARY_HISTORY);
+    let output = TempFile::with_content("summaries_fixed.yaml", "");
+
+    pwf()
+        .args(["analyze", "summaries", "--format", "compact", "--fix"])
+        .arg(&output.path)
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 discrepancies"));
+
+    pwf()
+        .args(["analyze", "summaries", "--format", "compact"])
+        .arg(&output.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0 discrepancies"));
+
+    let fixed = fs::read_to_string(&output.path).unwrap();
+    assert!(fixed.contains("heart_rate_avg: 132"));
+    assert!(fixed.contains("total_distance_m: 900"));
+    // Explicit extension nulls survive; unset fields and empty lists are left out
+    assert!(fixed.contains("x-sync-id: null"));
+    assert!(!fixed.contains("devices"));
+    assert!(!fixed.contains("personal_records"));
+    assert!(!fixed.contains("notes: null"));
+}
+
+// ============================================================================
+// Running Tests
+// ============================================================================
+
+/// A 3 km run at 4 m/s sampled every 5 seconds, heart rate rising midway
+fn running_history() -> String {
+    let samples = 151;
+    let elapsed: Vec<String> = (0..samples).map(|i| (i * 5).to_string()).collect();
+    let distance: Vec<String> = (0..samples).map(|i| (i * 20).to_string()).collect();
+    let heart_rate: Vec<String> = (0..samples)
+        .map(|i| if i < 75 { "150" } else { "165" }.to_string())
+        .collect();
+    format!(
+        r#"history_version: 2
+exported_at: "2025-02-01T00:00:00Z"
+workouts:
+  - date: "2025-01-10"
+    sport: running
+    exercises:
+      - name: Tempo Run
+        sets:
+          - telemetry:
+              time_series:
+                timestamps: [{}]
+                elapsed_sec: [{}]
+                distance_m: [{}]
+                heart_rate: [{}]
+  - date: "2025-01-11"
+    exercises:
+      - name: Ride
+        modality: cycling
+        sets:
+          - distance_meters: 20000
+"#,
+        vec!["\"\""; samples].join(", "),
+        elapsed.join(", "),
+        distance.join(", "),
+        heart_rate.join(", ")
+    )
+}
+
+#[test]
+fn test_running_pretty() {
+    let file = TempFile::with_content("running_pretty.yaml", &running_history());
+
+    pwf()
+        .args(["analyze", "running"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-01-10"))
+        .stdout(predicate::str::contains("3.00 km in 12m30s, 4:10/km"))
+        .stdout(predicate::str::contains("1 mile"))
+        .stdout(predicate::str::contains("Aerobic decoupling"))
+        .stdout(predicate::str::contains("2025-01-11").not());
+}
+
+#[test]
+fn test_running_json_miles() {
+    let file = TempFile::with_content("running_json.yaml", &running_history());
+
+    let output = pwf()
+        .args(["analyze", "running", "--format", "json"])
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let runs = json["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["splits_km"].as_array().unwrap().len(), 3);
+    assert_eq!(runs[0]["splits_mile"].as_array().unwrap().len(), 2);
+    assert_eq!(runs[0]["best_efforts"][1]["name"], "1k");
+    assert_eq!(runs[0]["best_efforts"][1]["duration_sec"], 250.0);
+
+    pwf()
+        .args(["analyze", "running", "--miles", "--format", "compact"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.86 mi, 6:42/mi"));
+}
+
+// ============================================================================
+// Interval Tests
+// ============================================================================
+
+/// A ride recorded as one set: 2 minutes easy, then 3 × (1 minute at 300 W,
+/// 1 minute at 120 W), sampled every second
+fn interval_history() -> String {
+    let mut power = vec![120; 120];
+    for _ in 0..3 {
+        power.extend([300; 60]);
+        power.extend([120; 60]);
+    }
+    let elapsed: Vec<String> = (0..power.len()).map(|i| i.to_string()).collect();
+    let power: Vec<String> = power.iter().map(|w| w.to_string()).collect();
+    format!(
+        r#"history_version: 2
+exported_at: "2025-03-02T00:00:00Z"
+workouts:
+  - date: "2025-03-01"
+    exercises:
+      - name: Bike Repeats
+        modality: interval
+        sets:
+          - x-lap-source: head-unit
+            telemetry:
+              time_series:
+                timestamps: [{}]
+                elapsed_sec: [{}]
+                power: [{}]
+"#,
+        vec!["\"\""; power.len()].join(", "),
+        elapsed.join(", "),
+        power.join(", ")
+    )
+}
+
+#[test]
+fn test_intervals_with_plan() {
+    let file = TempFile::with_content("intervals.yaml", &interval_history());
+    let plan = TempFile::with_content(
+        "intervals_plan.yaml",
+        r#"plan_version: 1
+cycle:
+  days:
+    - scheduled_date: "2025-03-01"
+      exercises:
+        - name: Bike Repeats
+          modality: interval
+          target_sets: 3
+          interval_phases:
+            - { name: Hard, duration_sec: 60, target_power_watts: 300 }
+            - { name: Easy, duration_sec: 60, target_power_watts: 120 }
+"#,
+    );
+
+    pwf()
+        .args(["analyze", "intervals", "--plan"])
+        .arg(&plan.path)
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Bike Repeats"))
+        .stdout(predicate::str::contains("Hard #3"))
+        .stdout(predicate::str::contains("Easy #2"))
+        .stdout(predicate::str::contains("Not found").not());
+
+    pwf()
+        .args(["analyze", "intervals", "--format", "compact"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("7 segments, 3 work"));
+}
+
+#[test]
+fn test_intervals_split() {
+    let file = TempFile::with_content("intervals_split.yaml", &interval_history());
+    let output = TempFile::with_content("intervals_split_out.json", "");
+
+    pwf()
+        .args(["analyze", "intervals", "--format", "compact", "--split"])
+        .arg(&output.path)
+        .arg(&file.path)
+        .assert()
+        .success();
+
+    let split: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output.path).unwrap()).unwrap();
+    let sets = split["workouts"][0]["exercises"][0]["sets"]
+        .as_array()
+        .unwrap();
+    assert_eq!(sets.len(), 7);
+    assert_eq!(sets[1]["notes"], "Work");
+    assert!(sets[1]["telemetry"]["power_avg"].as_u64().unwrap() >= 295);
+    // Every segment keeps the original set's extension fields
+    assert!(sets.iter().all(|s| s["x-lap-source"] == "head-unit"));
+
+    pwf()
+        .args(["analyze", "intervals", "--format", "compact"])
+        .arg(&output.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no intervals found"));
+}
+
+#[test]
+fn test_execution_against_plan() {
+    let file = TempFile::with_content("execution.yaml", &interval_history());
+    let plan = TempFile::with_content(
+        "execution_plan.yaml",
+        r#"plan_version: 1
+cycle:
+  days:
+    - scheduled_date: "2025-03-01"
+      exercises:
+        - name: Bike Repeats
+          modality: interval
+          target_sets: 4
+          interval_phases:
+            - { name: Hard, duration_sec: 60, target_power_watts: 320 }
+            - { name: Easy, duration_sec: 60, target_power_watts: 120 }
+"#,
+    );
+
+    pwf()
+        .args(["analyze", "execution", "--plan"])
+        .arg(&plan.path)
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hard #1"))
+        .stdout(predicate::str::contains("300W (0% in range)"))
+        .stdout(predicate::str::contains("120W (100% in range)"))
+        .stdout(predicate::str::contains("Hard #4"))
+        .stdout(predicate::str::contains("not reached"));
+
+    let output = pwf()
+        .args([
+            "analyze",
+            "execution",
+            "--tolerance",
+            "10",
+            "--format",
+            "json",
+            "--plan",
+        ])
+        .arg(&plan.path)
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let execution = &json["executions"][0];
+    assert_eq!(execution["alignment"], "intervals");
+    assert_eq!(execution["phases"][0]["power"]["percent_in_range"], 100.0);
+    // Three of four repetitions were ridden
+    let score = execution["score"].as_f64().unwrap();
+    assert!((70.0..76.0).contains(&score), "{}", score);
+}
+
+// ============================================================================
+// Swimming Tests
+// ============================================================================
+
+/// A 25 yd pool CSS session, plus a set recorded only as a time series of
+/// four 20 s lengths
+fn swim_history() -> String {
+    let swolf: Vec<String> = (0..81)
+        .map(|i| (34 + (i / 20).min(3) % 2).to_string())
+        .collect();
+    let strokes: Vec<String> = (0..81).map(|i| (i * 14 / 20).to_string()).collect();
+    format!(
+        r#"history_version: 2
+exported_at: "2025-03-02T00:00:00Z"
+workouts:
+  - date: "2025-03-01"
+    sport: swimming
+    exercises:
+      - name: CSS test
+        pool_config: {{ pool_length: 25, pool_length_unit: yards }}
+        sets:
+          - distance_meters: 365.76
+            duration_sec: 360
+          - distance_meters: 182.88
+            duration_sec: 165
+          - telemetry:
+              time_series:
+                timestamps: [{}]
+                swolf: [{}]
+                stroke_count: [{}]
+"#,
+        vec!["\"\""; 81].join(", "),
+        swolf.join(", "),
+        strokes.join(", ")
+    )
+}
+
+#[test]
+fn test_swimming_pretty() {
+    let file = TempFile::with_content("swimming.yaml", &swim_history());
+
+    pwf()
+        .args(["analyze", "swimming"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("700 yd"))
+        .stdout(predicate::str::contains("4 lengths of 25yd, SWOLF 34"))
+        .stdout(predicate::str::contains("rebuilt from time series"))
+        .stdout(predicate::str::contains("Critical swim speed: 1:38/100yd"));
+}
+
+#[test]
+fn test_swimming_fill_lengths() {
+    let file = TempFile::with_content("swimming_fill.yaml", &swim_history());
+    let output = TempFile::with_content("swimming_fill_out.json", "");
+
+    pwf()
+        .args([
+            "analyze",
+            "swimming",
+            "--format",
+            "compact",
+            "--fill-lengths",
+        ])
+        .arg(&output.path)
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("CSS 1:38/100yd"));
+
+    let filled: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output.path).unwrap()).unwrap();
+    let swimming = &filled["workouts"][0]["exercises"][0]["sets"][2]["swimming"];
+    assert_eq!(swimming["total_lengths"], 4);
+    assert_eq!(swimming["lengths"][0]["stroke_count"], 14);
+    assert_eq!(swimming["lengths"][0]["stroke_type"], "freestyle");
+}
+
+// ============================================================================
+// Strength Volume Tests
+// ============================================================================
+
+const STRENGTH_PLAN: &str = r#"plan_version: 1
+exercise_library:
+  - id: bench
+    name: Bench Press
+    modality: strength
+    muscle_groups: [chest, triceps]
+cycle:
+  days:
+    - exercises:
+        - exercise_ref: bench
+"#;
+
+const STRENGTH_HISTORY: &str = r#"history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+workouts:
+  - date: "2025-03-04"
+    exercises:
+      - name: Bench Press
+        sets:
+          - { reps: 5, weight_kg: 100, rpe: 8 }
+          - { reps: 5, weight_kg: 100, rpe: 8.5 }
+          - { reps: 5, weight_kg: 100, rir: 1 }
+      - name: Plank
+        sets:
+          - { duration_sec: 60 }
+      - name: Curl
+        sets:
+          - { reps: 12, weight_kg: 15 }
+"#;
+
+#[test]
+fn test_strength_pretty() {
+    let file = TempFile::with_content("strength.yaml", STRENGTH_HISTORY);
+    let plan = TempFile::with_content("strength_plan.yaml", STRENGTH_PLAN);
+
+    pwf()
+        .args(["analyze", "strength", "--range", "triceps=2-6", "--plan"])
+        .arg(&plan.path)
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Week of 2025-03-03"))
+        .stdout(predicate::str::contains("4 sets, 3 hard, 27 reps, 1680 kg"))
+        .stdout(predicate::str::contains("(10-20)  below range"))
+        .stdout(predicate::str::contains("(2-6)  within range"))
+        .stdout(predicate::str::contains("No muscle groups for: Curl"))
+        .stdout(predicate::str::contains("Plank").not());
+}
+
+#[test]
+fn test_strength_json_and_bad_range() {
+    let file = TempFile::with_content("strength_json.yaml", STRENGTH_HISTORY);
+    let plan = TempFile::with_content("strength_json_plan.yaml", STRENGTH_PLAN);
+
+    let output = pwf()
+        .args(["analyze", "strength", "--min-sets", "1", "--max-sets", "2"])
+        .args(["--format", "json", "--plan"])
+        .arg(&plan.path)
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let chest = &json["weeks"][0]["muscle_groups"][0];
+    assert_eq!(chest["muscle_group"], "chest");
+    assert_eq!(chest["hard_sets"], 3);
+    assert_eq!(chest["status"], "above");
+    assert_eq!(
+        json["weeks"][0]["exercises"][0]["rep_distribution"]["low"],
+        3
+    );
+
+    pwf()
+        .args(["analyze", "strength", "--range", "chest=20-10"])
+        .arg(&file.path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("GROUP=MIN-MAX"));
+}
+
+// ============================================================================
+// Intensity Distribution Tests
+// ============================================================================
+
+const INTENSITY_HISTORY: &str = r#"
+history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+workouts:
+  - date: "2025-03-03"
+    sport: running
+    exercises: []
+    telemetry:
+      time_in_zones:
+        hr_zones_sec: [1000, 2600, 100, 50, 250]
+  - date: "2025-03-11"
+    sport: running
+    exercises: []
+    telemetry:
+      time_in_zones:
+        hr_zones_sec: [1000, 1000, 1000, 500, 500]
+  - date: "2025-03-12"
+    sport: cycling
+    exercises:
+      - name: Ride
+        sets:
+          - telemetry:
+              time_series:
+                timestamps: ["2025-03-12T07:00:00Z", "2025-03-12T07:00:01Z", "2025-03-12T07:00:02Z", "2025-03-12T07:00:03Z"]
+                power: [100, 100, 100, 300]
+"#;
+
+#[test]
+fn test_intensity_pretty() {
+    let file = TempFile::with_content("intensity.yaml", INTENSITY_HISTORY);
+
+    pwf()
+        .args(["analyze", "intensity", "--stream", "heart-rate"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2025-03-03"))
+        .stdout(predicate::str::contains("70/14/16% low/moderate/high"))
+        .stdout(predicate::str::contains("2.43  polarized"))
+        .stdout(predicate::str::contains("pyramidal"))
+        .stdout(predicate::str::contains("Trend per week: low -40.0 pts"));
+}
+
+#[test]
+fn test_intensity_json_with_ftp() {
+    let file = TempFile::with_content("intensity_json.yaml", INTENSITY_HISTORY);
+
+    let output = pwf()
+        .args(["analyze", "intensity", "--ftp", "250", "--format", "json"])
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["weeks"].as_array().unwrap().len(), 2);
+    let cycling = &json["by_sport"][1];
+    assert_eq!(cycling["sport"], "cycling");
+    // 100 W is zone 1 and 300 W zone 5 of a 250 W FTP
+    assert_eq!(cycling["overall"]["low_sec"], 3.0);
+    assert_eq!(cycling["overall"]["high_sec"], 1.0);
+    assert_eq!(cycling["overall"]["model"], "polarized");
+}
@@ -0,0 +1,103 @@
+//! Integration tests for `pwf body`
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+/// Helper to get the binary command
+#[allow(deprecated)]
+fn pwf() -> Command {
+    Command::cargo_bin("pwf").unwrap()
+}
+
+/// Temporary file removed on drop
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn with_content(name: &str, content: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("pwf_body_test_{}", name));
+        fs::write(&path, content).unwrap();
+        TempFile { path }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+const HISTORY: &str = r#"
+history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+units:
+  weight: lb
+workouts: []
+body_measurements:
+  - date: "2025-03-03"
+    weight_kg: 80.0
+    body_fat_percent: 20.0
+    measurements:
+      waist_cm: 85.0
+      hips_cm: 100.0
+  - date: "2025-03-10"
+    weight_lb: 174.0
+    measurements:
+      thigh_left_cm: 50.0
+      thigh_right_cm: 60.0
+"#;
+
+#[test]
+fn test_body_pretty_in_kg() {
+    let file = TempFile::with_content("pretty.yaml", HISTORY);
+
+    pwf()
+        .args(["body", "--unit", "kg"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Body trends:"))
+        .stdout(predicate::str::contains(
+            "80.0     80.0    20.0    16.0    64.0   0.85",
+        ))
+        .stdout(predicate::str::contains(
+            "Thigh    L 50.0 cm  R 60.0 cm  16.7%",
+        ))
+        .stdout(predicate::str::contains("kg/week"));
+}
+
+#[test]
+fn test_body_json_uses_history_units() {
+    let file = TempFile::with_content("json.yaml", HISTORY);
+
+    let output = pwf()
+        .args(["body", "--format", "json", "--from", "2025-03-10"])
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let report = &json["report"];
+    assert_eq!(report["unit"], "lb");
+    assert_eq!(report["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(report["entries"][0]["weight"], 174.0);
+    assert_eq!(report["entries"][0]["asymmetry"][0]["limb"], "thigh");
+}
+
+#[test]
+fn test_body_rejects_reversed_range() {
+    let file = TempFile::with_content("reversed.yaml", HISTORY);
+
+    pwf()
+        .args(["body", "--from", "2025-03-10", "--to", "2025-03-03"])
+        .arg(&file.path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(
+            "--from 2025-03-10 is after --to 2025-03-03",
+        ));
+}
@@ -0,0 +1,119 @@
+//! Integration tests for `pwf compliance`
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+/// Helper to get the binary command
+#[allow(deprecated)]
+fn pwf() -> Command {
+    Command::cargo_bin("pwf").unwrap()
+}
+
+/// Temporary file removed on drop
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn with_content(name: &str, content: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("pwf_compliance_test_{}", name));
+        fs::write(&path, content).unwrap();
+        TempFile { path }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+const PLAN: &str = r#"plan_version: 2
+meta:
+  id: block
+  title: Block
+cycle:
+  weeks:
+    - week_number: 1
+    - week_number: 2
+  days:
+    - id: lower
+      focus: Lower
+      scheduled_date: "2025-01-06"
+      exercises:
+        - name: Squat
+          modality: strength
+          target_sets: 3
+          target_reps: 5
+        - name: Leg Curl
+          modality: strength
+          target_sets: 2
+          target_reps: 10
+"#;
+
+const HISTORY: &str = r#"history_version: 2
+exported_at: "2025-02-01T00:00:00Z"
+workouts:
+  - date: "2025-01-06"
+    plan_id: block
+    plan_day_id: lower
+    exercises:
+      - name: Squat
+        sets: [{ reps: 5 }, { reps: 5 }, { reps: 5 }]
+      - name: Nordic Curl
+        sets: [{ reps: 6 }, { reps: 6 }]
+"#;
+
+#[test]
+fn test_compliance_reports_missed_and_substituted() {
+    let plan = TempFile::with_content("plan.yaml", PLAN);
+    let history = TempFile::with_content("history.yaml", HISTORY);
+
+    pwf()
+        .arg("compliance")
+        .arg(&plan.path)
+        .arg(&history.path)
+        .args(["--as-of", "2025-01-20"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Plan compliance:"))
+        .stdout(predicate::str::contains("substituted"))
+        .stdout(predicate::str::contains("Nordic Curl"))
+        .stdout(predicate::str::contains("missed"));
+}
+
+#[test]
+fn test_compliance_json_output() {
+    let plan = TempFile::with_content("json_plan.yaml", PLAN);
+    let history = TempFile::with_content("json_history.yaml", HISTORY);
+
+    let output = pwf()
+        .arg("compliance")
+        .arg(&plan.path)
+        .arg(&history.path)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let sessions = json["report"]["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0]["status"], "partial");
+    assert_eq!(sessions[0]["exercises"][1]["status"], "substituted");
+    // Week 2 is after the latest workout, so it is not yet due
+    assert_eq!(sessions[1]["status"], "upcoming");
+}
+
+#[test]
+fn test_compliance_missing_plan_fails() {
+    let history = TempFile::with_content("missing_history.yaml", HISTORY);
+
+    pwf()
+        .arg("compliance")
+        .arg("/nonexistent/plan.yaml")
+        .arg(&history.path)
+        .assert()
+        .failure();
+}
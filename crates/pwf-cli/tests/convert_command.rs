@@ -1778,3 +1778,168 @@ fn test_tcx_conversion_shows_hint_on_parse_error() {
     // Should show hint about validation
     assert!(stderr.contains("Hint:") || stderr.contains("pwf history"));
 }
+
+// ============================================================================
+// JSON Input and Output Tests
+// ============================================================================
+
+const MINIMAL_GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <trk>
+    <name>Morning Run</name>
+    <type>running</type>
+    <trkseg>
+      <trkpt lat="40.0" lon="-105.0"><ele>1600</ele><time>2025-01-15T07:00:00Z</time></trkpt>
+      <trkpt lat="40.001" lon="-105.0"><ele>1601</ele><time>2025-01-15T07:01:00Z</time></trkpt>
+      <trkpt lat="40.002" lon="-105.0"><ele>1602</ele><time>2025-01-15T07:02:00Z</time></trkpt>
+    </trkseg>
+  </trk>
+</gpx>
+"#;
+
+#[test]
+fn test_convert_gpx_to_pwf_json_by_extension() {
+    let temp = TempDir::new("gpx_json_ext");
+    let input = temp.join("run.gpx");
+    let output = temp.join("run.json");
+    fs::write(&input, MINIMAL_GPX).unwrap();
+
+    pwf_cmd()
+        .args(["convert", "--from", "gpx", "--to", "pwf"])
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .success();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(json["history_version"], 1);
+    assert_eq!(json["workouts"].as_array().unwrap().len(), 1);
+
+    // The JSON output feeds straight back into the CLI
+    pwf_cmd().arg("history").arg(&output).assert().success();
+}
+
+#[test]
+fn test_convert_output_format_flag_overrides_extension() {
+    let temp = TempDir::new("gpx_json_flag");
+    let input = temp.join("run.gpx");
+    let output = temp.join("run.pwf");
+    fs::write(&input, MINIMAL_GPX).unwrap();
+
+    pwf_cmd()
+        .args(["convert", "--from", "gpx", "--to", "pwf"])
+        .args(["--output-format", "json"])
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&output).unwrap();
+    assert!(content.trim_start().starts_with('{'));
+}
+
+#[test]
+fn test_convert_json_pwf_to_gpx() {
+    let temp = TempDir::new("json_to_gpx");
+    let gpx = temp.join("run.gpx");
+    let pwf = temp.join("run.json");
+    let roundtrip = temp.join("roundtrip.gpx");
+    fs::write(&gpx, MINIMAL_GPX).unwrap();
+
+    pwf_cmd()
+        .args(["convert", "--from", "gpx", "--to", "pwf"])
+        .arg(&gpx)
+        .arg(&pwf)
+        .assert()
+        .success();
+
+    pwf_cmd()
+        .args(["convert", "--from", "pwf", "--to", "gpx"])
+        .arg(&pwf)
+        .arg(&roundtrip)
+        .assert()
+        .success();
+
+    assert!(fs::read_to_string(&roundtrip).unwrap().contains("<trkpt"));
+}
+
+#[test]
+fn test_convert_compute_metrics_flag() {
+    let temp = TempDir::new("compute_metrics");
+    let input = temp.join("run.gpx");
+    let output = temp.join("run.yaml");
+    fs::write(&input, MINIMAL_GPX).unwrap();
+
+    pwf_cmd()
+        .args(["convert", "--from", "gpx", "--to", "pwf"])
+        .args(["--compute-metrics", "--ftp", "250"])
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .success();
+
+    assert!(fs::read_to_string(&output)
+        .unwrap()
+        .contains("history_version"));
+}
+
+#[test]
+fn test_convert_ftp_requires_compute_metrics() {
+    let temp = TempDir::new("ftp_requires");
+    let input = temp.join("run.gpx");
+    let output = temp.join("run.yaml");
+    fs::write(&input, MINIMAL_GPX).unwrap();
+
+    pwf_cmd()
+        .args(["convert", "--from", "gpx", "--to", "pwf", "--ftp", "250"])
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--compute-metrics"));
+}
+
+#[test]
+fn test_convert_pwf_to_csv_with_w_prime_balance() {
+    let temp = TempDir::new("csv_w_prime");
+    let input = temp.join("ride.yaml");
+    let output = temp.join("ride.csv");
+    fs::write(
+        &input,
+        r#"history_version: 2
+exported_at: "2025-01-15T12:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    exercises:
+      - name: Ride
+        sets:
+          - telemetry:
+              time_series:
+                timestamps: ["2025-01-15T10:00:00Z", "2025-01-15T10:00:01Z", "2025-01-15T10:00:02Z"]
+                power: [500, 500, 500]
+"#,
+    )
+    .unwrap();
+
+    pwf_cmd()
+        .args([
+            "convert",
+            "--from",
+            "pwf",
+            "--to",
+            "csv",
+            "--w-prime-balance",
+        ])
+        .args(["--cp", "250", "--w-prime", "20000"])
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .success();
+
+    let csv = fs::read_to_string(&output).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert!(lines[0].ends_with("w_prime_balance_j"));
+    assert!(lines[1].ends_with(",20000"));
+    assert!(lines[3].ends_with(",19500"));
+}
@@ -89,6 +89,27 @@ fn test_history_statistics_output() {
         .stdout(predicate::str::contains("volume"));
 }
 
+#[test]
+fn test_history_stats_tables() {
+    pwf()
+        .args(["history", "--stats"])
+        .arg(fixture_path("history-export.yaml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("By modality"))
+        .stdout(predicate::str::contains("strength"))
+        .stdout(predicate::str::contains("Monthly"))
+        .stdout(predicate::str::contains("Streaks: longest"))
+        .stdout(predicate::str::contains("Consistency:"));
+
+    pwf()
+        .arg("history")
+        .arg(fixture_path("history-export.yaml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("By modality").not());
+}
+
 #[test]
 fn test_history_format_json() {
     let output = pwf()
@@ -214,6 +235,163 @@ fn test_history_multiple_files_with_failures() {
         .stdout(predicate::str::contains("✓").and(predicate::str::contains("✗")));
 }
 
+#[test]
+fn test_history_deny_unknown_fields() {
+    let temp = TempFile::new("history_unknown_field.yaml");
+    fs::write(
+        temp.path(),
+        r#"history_version: 1
+exported_at: "2025-01-15T10:30:00Z"
+workouts:
+  - date: "2025-01-15"
+    exercises:
+      - name: Squat
+        sets:
+          - reps: 5
+            wieght_kg: 100
+"#,
+    )
+    .unwrap();
+
+    pwf()
+        .arg("history")
+        .arg(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("did you mean 'weight_kg'?"));
+
+    pwf()
+        .arg("history")
+        .arg(temp.path())
+        .arg("--deny-unknown-fields")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "workouts[0].exercises[0].sets[0].wieght_kg",
+        ));
+}
+
+#[test]
+fn test_history_json_input() {
+    let temp = TempFile::new("history_input.json");
+    fs::write(
+        temp.path(),
+        r#"{
+  "history_version": 1,
+  "exported_at": "2025-01-15T10:30:00Z",
+  "workouts": [
+    {
+      "date": "2025-01-15",
+      "exercises": [{ "name": "Squat", "sets": [{ "reps": 5, "weight_kg": 100 }] }]
+    }
+  ]
+}"#,
+    )
+    .unwrap();
+
+    pwf()
+        .arg("history")
+        .arg(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✓"));
+}
+
+#[test]
+fn test_history_schema_flag() {
+    pwf()
+        .arg("history")
+        .arg("--schema")
+        .arg(fixture_path("history-triathlon-v2.1.yaml"))
+        .assert()
+        .success();
+}
+
+const PLAN_FOR_HISTORY: &str = r#"plan_version: 2
+meta:
+  id: block
+  title: Block
+cycle:
+  days:
+    - id: lower
+      exercises:
+        - name: Squat
+          modality: strength
+          target_sets: 3
+          target_reps: 5
+"#;
+
+#[test]
+fn test_history_with_plan_reports_unknown_day() {
+    let plan = TempFile::new("cross_plan.yaml");
+    fs::write(plan.path(), PLAN_FOR_HISTORY).unwrap();
+    let history = TempFile::new("cross_history.yaml");
+    fs::write(
+        history.path(),
+        r#"history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-06"
+    plan_id: block
+    plan_day_id: upper
+    exercises:
+      - name: Bench
+        sets: [{ reps: 5 }]
+"#,
+    )
+    .unwrap();
+
+    pwf()
+        .arg("history")
+        .arg("--plan")
+        .arg(plan.path())
+        .arg(history.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("does not match any day"));
+}
+
+#[test]
+fn test_history_with_plan_warns_on_modality_contradiction() {
+    let plan = TempFile::new("cross_plan_warn.yaml");
+    fs::write(plan.path(), PLAN_FOR_HISTORY).unwrap();
+    let history = TempFile::new("cross_history_warn.yaml");
+    fs::write(
+        history.path(),
+        r#"history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-06"
+    plan_day_id: lower
+    exercises:
+      - name: Squat
+        sets: [{ distance_meters: 400 }]
+"#,
+    )
+    .unwrap();
+
+    let output = pwf()
+        .args(["history", "--format", "json", "--plan"])
+        .arg(plan.path())
+        .arg(history.path())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json[0]["warnings"][0]["code"], "PWF-H906");
+
+    // The same history passes without the plan, and fails with --strict
+    pwf().arg("history").arg(history.path()).assert().success();
+    pwf()
+        .args(["history", "--strict", "--plan"])
+        .arg(plan.path())
+        .arg(history.path())
+        .assert()
+        .failure();
+}
+
 // ============================================================================
 // Init Command Tests
 // ============================================================================
@@ -418,6 +596,39 @@ fn test_history_json_statistics_fields() {
     assert!(total_volume > 0.0);
 }
 
+#[test]
+fn test_history_json_stats_breakdown() {
+    let output = pwf()
+        .args(["history", "--stats", "--format", "json"])
+        .arg(fixture_path("history-export.yaml"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let stats = &json[0]["statistics"];
+    let breakdown = &stats["breakdown"];
+
+    let yearly_count: u64 = breakdown["yearly"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|y| y["count"].as_u64().unwrap())
+        .sum();
+    assert_eq!(yearly_count, stats["total_workouts"].as_u64().unwrap());
+    assert_eq!(breakdown["by_modality"][0]["modality"], "strength");
+    assert!(breakdown["streaks"]["longest_days"].as_u64().unwrap() >= 1);
+    assert!(breakdown["consistency"]["active_days"].as_u64().unwrap() >= 1);
+
+    // Without --stats the breakdown is left out
+    let output = pwf()
+        .args(["history", "--format", "json"])
+        .arg(fixture_path("history-export.yaml"))
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json[0]["statistics"].get("breakdown").is_none());
+}
+
 #[test]
 fn test_history_json_with_warnings() {
     let output = pwf()
@@ -0,0 +1,259 @@
+//! Integration tests for CLI migrate command
+//!
+//! Tests cover:
+//! - Upgrading plans and histories to the latest version
+//! - Downgrading with a loss report
+//! - Writing to a file vs stdout, JSON documents and JSON reports
+//! - Error handling for unsupported versions and missing files
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+/// Helper to get the binary command
+fn pwf_cmd() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_pwf"))
+}
+
+/// Helper to get path to example files
+fn example_path(filename: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("examples")
+        .join(filename)
+}
+
+/// Helper to create a temporary directory for test files
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("pwf_migrate_test_{}", name));
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    fn join(&self, file: &str) -> PathBuf {
+        self.path.join(file)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[test]
+fn test_migrate_plan_to_stdout() {
+    pwf_cmd()
+        .arg("migrate")
+        .arg(example_path("minimal.yaml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("plan_version: 2"))
+        .stdout(predicate::str::contains("null").not())
+        .stderr(predicate::str::contains("migrated from v1 to v2"));
+}
+
+#[test]
+fn test_migrate_plan_lifts_repeated_exercises() {
+    let dir = TempDir::new("lift");
+    let input = dir.join("plan.yaml");
+    let output = dir.join("plan-v2.yaml");
+    fs::write(
+        &input,
+        r#"plan_version: 1
+meta:
+  title: Repeats
+cycle:
+  days:
+    - exercises:
+        - name: Squat
+          modality: strength
+          target_sets: 5
+          target_reps: 5
+    - exercises:
+        - name: Squat
+          modality: strength
+          target_sets: 3
+          target_reps: 8
+"#,
+    )
+    .unwrap();
+
+    pwf_cmd()
+        .arg("migrate")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Added 'squat' to exercise_library (defined inline 2 times)",
+        ));
+
+    let migrated = fs::read_to_string(&output).unwrap();
+    assert!(migrated.contains("exercise_ref: squat"));
+
+    pwf_cmd()
+        .arg("validate")
+        .arg("--strict")
+        .arg(&output)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_migrate_history_downgrade_keeps_v1_fields() {
+    let dir = TempDir::new("downgrade");
+    let output = dir.join("history-v1.yaml");
+
+    pwf_cmd()
+        .arg("migrate")
+        .arg(example_path("history-cycling-power-v2.1.yaml"))
+        .arg("--to")
+        .arg("1")
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("migrated from v2 to v1"))
+        .stdout(predicate::str::contains("✗").not());
+
+    // v1 defines power metrics, devices and sport, so all of them survive
+    let migrated = fs::read_to_string(&output).unwrap();
+    assert!(migrated.contains("history_version: 1"));
+    assert!(migrated.contains("power_metrics:"));
+    assert!(migrated.contains("devices:"));
+
+    pwf_cmd()
+        .arg("history")
+        .arg("--schema")
+        .arg(&output)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_migrate_history_downgrade_reports_losses() {
+    let dir = TempDir::new("downgrade_lossy");
+    let input = dir.join("history-v2.yaml");
+    let output = dir.join("history-v1.yaml");
+    fs::write(
+        &input,
+        r#"history_version: 2
+exported_at: "2025-01-15T10:30:00Z"
+workouts:
+  - date: "2025-01-15"
+    telemetry:
+      power_metrics:
+        normalized_power: 240
+        w_prime_joules: 20000
+    exercises:
+      - name: Ride
+        modality: cycling
+        sets:
+          - duration_sec: 1800
+"#,
+    )
+    .unwrap();
+
+    pwf_cmd()
+        .arg("migrate")
+        .arg(&input)
+        .arg("--to")
+        .arg("1")
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("w_prime_joules requires v2"));
+
+    let migrated = fs::read_to_string(&output).unwrap();
+    assert!(migrated.contains("normalized_power: 240"));
+    assert!(!migrated.contains("w_prime_joules"));
+}
+
+#[test]
+fn test_migrate_json_document() {
+    let dir = TempDir::new("json_document");
+    let input = dir.join("plan.json");
+    let output = dir.join("plan-v2.json");
+    fs::write(
+        &input,
+        r#"{"plan_version": 1, "meta": {"title": "Json"}, "cycle": {"days": [{"exercises": [{"name": "Squat", "modality": "strength", "target_sets": 3, "target_reps": 5}]}]}}"#,
+    )
+    .unwrap();
+
+    pwf_cmd()
+        .arg("migrate")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success();
+
+    let migrated: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(migrated["plan_version"], 2);
+    assert_eq!(migrated["meta"]["title"], "Json");
+
+    // Without -o the document keeps the input's format
+    let assert = pwf_cmd().arg("migrate").arg(&input).assert().success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let migrated: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(migrated["plan_version"], 2);
+}
+
+#[test]
+fn test_migrate_json_report() {
+    let dir = TempDir::new("json");
+    let output = dir.join("history-v2.yaml");
+
+    let assert = pwf_cmd()
+        .arg("migrate")
+        .arg(example_path("history-export.yaml"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(report["type"], "history");
+    assert_eq!(report["from_version"], 1);
+    assert_eq!(report["to_version"], 2);
+    assert!(report["lost"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_migrate_unsupported_version() {
+    pwf_cmd()
+        .arg("migrate")
+        .arg(example_path("minimal.yaml"))
+        .arg("--to")
+        .arg("3")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Cannot migrate from version 1 to version 3",
+        ));
+}
+
+#[test]
+fn test_migrate_missing_file() {
+    pwf_cmd()
+        .arg("migrate")
+        .arg("does-not-exist.yaml")
+        .assert()
+        .failure();
+}
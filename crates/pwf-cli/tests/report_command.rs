@@ -0,0 +1,190 @@
+//! Integration tests for `pwf report`
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+/// Helper to get the binary command
+#[allow(deprecated)]
+fn pwf() -> Command {
+    Command::cargo_bin("pwf").unwrap()
+}
+
+/// Temporary file removed on drop
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn with_content(name: &str, content: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("pwf_report_test_{}", name));
+        fs::write(&path, content).unwrap();
+        TempFile { path }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+const HISTORY: &str = r#"
+history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+workouts:
+  - date: "2025-02-24"
+    exercises:
+      - name: Squat
+        sets:
+          - { reps: 5, weight_kg: 100 }
+  - date: "2025-03-05"
+    title: Threshold intervals
+    sport: running
+    duration_sec: 3600
+    exercises:
+      - name: Run
+        modality: stopwatch
+        sets:
+          - { duration_sec: 3600 }
+    telemetry:
+      heart_rate_avg: 170
+      total_distance_km: 12.0
+  - date: "2025-03-06"
+    exercises:
+      - name: Squat
+        sets:
+          - { reps: 5, weight_kg: 110 }
+body_measurements:
+  - date: "2025-03-03"
+    weight_kg: 80.0
+  - date: "2025-03-09"
+    weight_kg: 79.0
+"#;
+
+const PLAN: &str = r#"
+plan_version: 1
+meta:
+  title: Base week
+  athlete_profile:
+    threshold_hr_bpm: 170
+cycle:
+  days:
+    - focus: Intervals
+      scheduled_date: "2025-03-05"
+      exercises:
+        - name: Run
+          modality: stopwatch
+    - focus: Long run
+      scheduled_date: "2025-03-08"
+      exercises:
+        - name: Run
+          modality: stopwatch
+"#;
+
+#[test]
+fn test_report_markdown_to_stdout() {
+    let file = TempFile::with_content("markdown.yaml", HISTORY);
+
+    pwf()
+        .args(["report", "--from", "2025-03-03", "--to", "2025-03-09"])
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "# Training report: 2025-03-03 to 2025-03-09",
+        ))
+        .stdout(predicate::str::contains("## Key sessions"))
+        .stdout(predicate::str::contains(
+            "- **2025-03-06** Squat: 5RM 110.0 kg (was 100.0 kg)",
+        ))
+        .stdout(predicate::str::contains(
+            "Trend 80.0 kg to 79.5 kg (-0.5 kg)",
+        ))
+        .stdout(predicate::str::contains("<svg").not());
+}
+
+#[test]
+fn test_report_html_with_plan_to_file() {
+    let file = TempFile::with_content("html.yaml", HISTORY);
+    let plan = TempFile::with_content("plan.yaml", PLAN);
+    let output = TempFile::with_content("week.html", "");
+
+    pwf()
+        .args(["report", "--from", "2025-03-03", "--to", "2025-03-09"])
+        .args(["--format", "html", "--plan"])
+        .arg(&plan.path)
+        .arg("-o")
+        .arg(&output.path)
+        .arg(&file.path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Wrote report for 2025-03-03 to 2025-03-09",
+        ));
+
+    let html = fs::read_to_string(&output.path).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert_eq!(html.matches("<svg").count(), 2);
+    // The plan's threshold heart rate scores the intervals
+    assert!(html.contains("1h 00m, 12.0 km, stress 100"));
+    assert!(html.contains("1 of 2 planned sessions completed, 0 partial, 1 missed"));
+    assert!(html.contains("Missed: Long run (2025-03-08)"));
+}
+
+#[test]
+fn test_report_json_defaults_to_last_week() {
+    let file = TempFile::with_content("json.yaml", HISTORY);
+
+    let output = pwf()
+        .args(["report", "-f", "json"])
+        .arg(&file.path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["report"]["from"], "2025-02-28");
+    assert_eq!(json["report"]["to"], "2025-03-06");
+    assert_eq!(json["report"]["totals"]["count"], 2);
+    assert_eq!(json["report"]["days"].as_array().unwrap().len(), 7);
+}
+
+#[test]
+fn test_report_without_workouts_fails() {
+    let file = TempFile::with_content(
+        "empty.yaml",
+        "history_version: 2\nexported_at: \"2025-03-20T00:00:00Z\"\nworkouts: []\n",
+    );
+
+    pwf()
+        .arg("report")
+        .arg(&file.path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no dated workouts"));
+}
+
+#[test]
+fn test_report_rejects_reversed_range() {
+    let file = TempFile::with_content("reversed.yaml", HISTORY);
+
+    pwf()
+        .args(["report", "--from", "2025-03-10", "--to", "2025-03-01"])
+        .arg(&file.path)
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(
+            "--from 2025-03-10 is after --to 2025-03-01",
+        ));
+
+    pwf()
+        .args(["report", "--from", "2025-03-10"])
+        .arg(&file.path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--from 2025-03-10 is after the latest workout",
+        ));
+}
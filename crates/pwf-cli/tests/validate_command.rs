@@ -423,3 +423,130 @@ fn test_validate_empty_file() {
     // Cleanup
     let _ = fs::remove_file(&temp_file);
 }
+
+// Test 26: Unknown fields are reported as warnings with a suggestion
+#[test]
+fn test_validate_unknown_fields_warn() {
+    let example_path = examples_dir().join("invalid/unknown-fields.yaml");
+
+    pwf_cmd()
+        .arg("validate")
+        .arg(&example_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Unknown field 'target_rep' (did you mean 'target_reps'?)",
+        ))
+        .stdout(predicate::str::contains(
+            "did you mean 'rest_between_sets_sec'?",
+        ));
+}
+
+// Test 27: --deny-unknown-fields turns unknown fields into errors
+#[test]
+fn test_validate_deny_unknown_fields() {
+    let example_path = examples_dir().join("invalid/unknown-fields.yaml");
+
+    pwf_cmd()
+        .arg("validate")
+        .arg(&example_path)
+        .arg("--deny-unknown-fields")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("✗"))
+        .stdout(predicate::str::contains(
+            "cycle.days[0].exercises[0].target_rep",
+        ));
+}
+
+// Test 28: .json plans are parsed as JSON
+#[test]
+fn test_validate_json_plan() {
+    let path = std::env::temp_dir().join("pwf_validate_json_plan.json");
+    fs::write(
+        &path,
+        r#"{
+  "plan_version": 1,
+  "meta": { "title": "JSON Plan" },
+  "cycle": {
+    "days": [
+      { "exercises": [{ "name": "Squat", "modality": "strength", "target_sets": 3, "target_reps": 5 }] }
+    ]
+  }
+}"#,
+    )
+    .unwrap();
+
+    let result = pwf_cmd().arg("validate").arg(&path).assert();
+    let _ = fs::remove_file(&path);
+    result
+        .success()
+        .stdout(predicate::str::contains("✓"))
+        .stdout(predicate::str::contains("pwf_validate_json_plan.json"));
+}
+
+// Test 29: JSON syntax errors are reported
+#[test]
+fn test_validate_json_syntax_error() {
+    let path = std::env::temp_dir().join("pwf_validate_json_syntax.json");
+    fs::write(&path, r#"{ "plan_version": 1, "cycle": "#).unwrap();
+
+    let result = pwf_cmd().arg("validate").arg(&path).assert();
+    let _ = fs::remove_file(&path);
+    result
+        .failure()
+        .stdout(predicate::str::contains("JSON syntax error"));
+}
+
+// Test 30: --schema adds violations of the bundled JSON Schema
+#[test]
+fn test_validate_schema_flag() {
+    let path = std::env::temp_dir().join("pwf_validate_schema_flag.yaml");
+    fs::write(
+        &path,
+        r#"plan_version: 1
+meta:
+  title: Schema Check
+cycle:
+  start_date: "next monday"
+  days:
+    - exercises:
+        - name: Squat
+          modality: strength
+          target_sets: 3
+          target_reps: 5
+"#,
+    )
+    .unwrap();
+
+    let without = pwf_cmd().arg("validate").arg(&path).assert();
+    let with = pwf_cmd()
+        .arg("validate")
+        .arg("--schema")
+        .arg(&path)
+        .assert();
+    let _ = fs::remove_file(&path);
+
+    without.success();
+    with.failure().stdout(predicate::str::contains(
+        "cycle.start_date: Schema violation",
+    ));
+}
+
+// Test 31: every example passes the schema check
+#[test]
+fn test_validate_examples_with_schema() {
+    let plans = [
+        "beginner-strength.yaml",
+        "periodization-undulating.yaml",
+        "exercise-library-v2.yaml",
+    ];
+    for plan in plans {
+        pwf_cmd()
+            .arg("validate")
+            .arg("--schema")
+            .arg(examples_dir().join(plan))
+            .assert()
+            .success();
+    }
+}
@@ -1,3 +1,81 @@
 //! Common utilities shared across converters
 
 pub mod utils;
+
+use pwf_core::analytics::fill_history_power_metrics;
+use pwf_core::history::WpsHistory;
+
+/// Options shared by the FIT, TCX and GPX importers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportOptions {
+    /// Skip time-series data for smaller output
+    pub summary_only: bool,
+    /// Fill in power metrics the source file did not provide (NP, IF, TSS,
+    /// VI, work, pedal balance) from the imported power data
+    pub compute_metrics: bool,
+    /// FTP used for IF and TSS when the source file does not record one
+    pub ftp_watts: Option<u32>,
+}
+
+impl ImportOptions {
+    /// Apply post-processing to a freshly converted history
+    pub(crate) fn apply(&self, history: &mut WpsHistory) {
+        if self.compute_metrics {
+            fill_history_power_metrics(history, self.ftp_watts);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HISTORY: &str = r#"
+history_version: 2
+exported_at: "2025-01-15T12:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    exercises: []
+    telemetry:
+      gps_route:
+        route_id: r1
+        positions:
+          - latitude_deg: 40.0
+            longitude_deg: -105.0
+            timestamp: "2025-01-15T10:00:00Z"
+            power_watts: 200
+          - latitude_deg: 40.0
+            longitude_deg: -105.0
+            timestamp: "2025-01-15T10:00:05Z"
+            power_watts: 300
+"#;
+
+    #[test]
+    fn test_default_options_leave_history_untouched() {
+        let mut history: WpsHistory = serde_yaml::from_str(HISTORY).unwrap();
+        ImportOptions::default().apply(&mut history);
+        assert!(history.workouts[0]
+            .telemetry
+            .as_ref()
+            .unwrap()
+            .power_metrics
+            .is_none());
+    }
+
+    #[test]
+    fn test_compute_metrics_fills_power_metrics() {
+        let mut history: WpsHistory = serde_yaml::from_str(HISTORY).unwrap();
+        let options = ImportOptions {
+            compute_metrics: true,
+            ftp_watts: Some(250),
+            ..Default::default()
+        };
+        options.apply(&mut history);
+
+        let telemetry = history.workouts[0].telemetry.as_ref().unwrap();
+        assert_eq!(telemetry.power_max, Some(300));
+        let metrics = telemetry.power_metrics.as_ref().unwrap();
+        assert_eq!(metrics.ftp_watts, Some(250));
+        assert_eq!(metrics.total_work_kj, Some(1.3));
+    }
+}
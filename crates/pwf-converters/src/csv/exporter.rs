@@ -2,9 +2,13 @@
 
 use crate::error::{ConversionError, ConversionWarning, CsvExportResult};
 use csv::Writer;
+use pwf_core::analytics::workout_w_prime_balance;
 use pwf_core::history::{TimeSeriesData, Workout, WpsHistory};
 use std::io::Write as IoWrite;
 
+/// A computed per-row column, aligned with a time series
+type Column = Vec<Option<f64>>;
+
 /// Options for CSV export
 #[derive(Debug, Clone, Default)]
 pub struct CsvExportOptions {
@@ -14,6 +18,12 @@ pub struct CsvExportOptions {
     pub include_metadata: bool,
     /// Combine all workouts into one CSV (vs separate per workout)
     pub combine_workouts: bool,
+    /// Append a `w_prime_balance_j` column computed from power
+    pub w_prime_balance: bool,
+    /// Critical Power for workouts whose power metrics don't record one
+    pub critical_power_watts: Option<u32>,
+    /// W' (joules) for workouts whose power metrics don't record one
+    pub w_prime_joules: Option<f64>,
 }
 
 /// Export time-series telemetry data from PWF history to CSV format
@@ -38,21 +48,31 @@ pub struct CsvExportOptions {
 /// - Data validation fails
 pub fn export_telemetry_to_csv(
     history: &WpsHistory,
-    _options: &CsvExportOptions,
+    options: &CsvExportOptions,
 ) -> Result<CsvExportResult, ConversionError> {
     let mut csv_buffer = Vec::new();
     let mut result = CsvExportResult::new(String::new());
 
     // Collect all time-series data from all workouts
-    let mut all_time_series: Vec<(String, &TimeSeriesData)> = Vec::new();
+    let mut all_time_series: Vec<(String, &TimeSeriesData, Option<Column>)> = Vec::new();
     let mut has_any_telemetry = false;
 
     for workout in &history.workouts {
+        let mut w_prime_columns = if options.w_prime_balance {
+            w_prime_balance_columns(workout, options, &mut result)
+        } else {
+            Vec::new()
+        }
+        .into_iter();
         for exercise in &workout.exercises {
             for set in &exercise.sets {
                 if let Some(ref telemetry) = set.telemetry {
                     has_any_telemetry = true;
                     if let Some(ref ts) = telemetry.time_series {
+                        let w_prime_column = options
+                            .w_prime_balance
+                            .then(|| w_prime_columns.next().unwrap_or_default());
+
                         // Validate time series data
                         if let Err(e) = ts.validate_lengths() {
                             result.add_warning(ConversionWarning::DataQualityIssue {
@@ -63,7 +83,7 @@ pub fn export_telemetry_to_csv(
 
                         // Create a label for this time series
                         let label = create_time_series_label(workout, exercise, set);
-                        all_time_series.push((label, ts));
+                        all_time_series.push((label, ts, w_prime_column));
                     }
                 }
             }
@@ -92,11 +112,17 @@ pub fn export_telemetry_to_csv(
         let mut writer = Writer::from_writer(&mut csv_buffer);
 
         // Write CSV header
-        write_csv_header(&mut writer)?;
+        write_csv_header(&mut writer, options)?;
 
         // Export time-series data
-        for (label, ts) in &all_time_series {
-            export_time_series(&mut writer, ts, label, &mut result)?;
+        for (label, ts, w_prime_column) in &all_time_series {
+            export_time_series(
+                &mut writer,
+                ts,
+                label,
+                w_prime_column.as_deref(),
+                &mut result,
+            )?;
         }
 
         writer
@@ -116,39 +142,92 @@ pub fn export_telemetry_to_csv(
     Ok(result)
 }
 
+/// W' balance for each of a workout's time series, in set order
+///
+/// CP and W' come from the workout's power metrics, falling back to the
+/// export options. Workouts with power but without CP or W' get an empty
+/// column and a warning.
+fn w_prime_balance_columns(
+    workout: &Workout,
+    options: &CsvExportOptions,
+    result: &mut CsvExportResult,
+) -> Vec<Column> {
+    let recorded = workout
+        .telemetry
+        .as_ref()
+        .and_then(|t| t.power_metrics.as_ref());
+    let cp = recorded
+        .and_then(|m| m.critical_power_watts)
+        .or(options.critical_power_watts);
+    let w_prime = recorded
+        .and_then(|m| m.w_prime_joules)
+        .or(options.w_prime_joules);
+
+    match (cp, w_prime) {
+        (Some(cp), Some(w_prime)) => workout_w_prime_balance(workout, cp as f64, w_prime),
+        _ if has_power_series(workout) => {
+            result.add_warning(ConversionWarning::MissingField {
+                source_field: "critical_power_watts / w_prime_joules".to_string(),
+                reason: format!(
+                    "W' balance left empty for workout on {}: no CP and W' available",
+                    workout.date
+                ),
+            });
+            Vec::new()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn has_power_series(workout: &Workout) -> bool {
+    workout
+        .exercises
+        .iter()
+        .flat_map(|e| &e.sets)
+        .filter_map(|s| s.telemetry.as_ref()?.time_series.as_ref())
+        .any(|ts| ts.power.is_some())
+}
+
 /// Write CSV header row with all telemetry fields
-fn write_csv_header<W: IoWrite>(writer: &mut Writer<W>) -> Result<(), ConversionError> {
+fn write_csv_header<W: IoWrite>(
+    writer: &mut Writer<W>,
+    options: &CsvExportOptions,
+) -> Result<(), ConversionError> {
+    let mut header = vec![
+        "workout_label",
+        "timestamp",
+        "elapsed_sec",
+        "heart_rate",
+        "power",
+        "cadence",
+        "speed_mps",
+        "elevation_m",
+        "latitude",
+        "longitude",
+        "distance_m",
+        "temperature_c",
+        "grade_percent",
+        "respiration_rate",
+        "core_temperature_c",
+        "muscle_oxygen_percent",
+        "power_balance",
+        "left_pedal_smoothness",
+        "right_pedal_smoothness",
+        "left_torque_effectiveness",
+        "right_torque_effectiveness",
+        "stride_length_m",
+        "vertical_oscillation_cm",
+        "ground_contact_time_ms",
+        "ground_contact_balance",
+        "stroke_rate",
+        "stroke_count",
+        "swolf",
+    ];
+    if options.w_prime_balance {
+        header.push("w_prime_balance_j");
+    }
     writer
-        .write_record([
-            "workout_label",
-            "timestamp",
-            "elapsed_sec",
-            "heart_rate",
-            "power",
-            "cadence",
-            "speed_mps",
-            "elevation_m",
-            "latitude",
-            "longitude",
-            "distance_m",
-            "temperature_c",
-            "grade_percent",
-            "respiration_rate",
-            "core_temperature_c",
-            "muscle_oxygen_percent",
-            "power_balance",
-            "left_pedal_smoothness",
-            "right_pedal_smoothness",
-            "left_torque_effectiveness",
-            "right_torque_effectiveness",
-            "stride_length_m",
-            "vertical_oscillation_cm",
-            "ground_contact_time_ms",
-            "ground_contact_balance",
-            "stroke_rate",
-            "stroke_count",
-            "swolf",
-        ])
+        .write_record(&header)
         .map_err(|e| ConversionError::IoError(std::io::Error::other(e)))?;
 
     Ok(())
@@ -160,6 +239,7 @@ fn export_time_series<W: IoWrite>(
     writer: &mut Writer<W>,
     ts: &TimeSeriesData,
     label: &str,
+    w_prime_balance: Option<&[Option<f64>]>,
     result: &mut CsvExportResult,
 ) -> Result<(), ConversionError> {
     let num_points = ts.len();
@@ -251,6 +331,18 @@ fn export_time_series<W: IoWrite>(
         // SWOLF
         record.push(format_optional_u32(&ts.swolf, i));
 
+        // W' balance, only present when requested
+        if let Some(column) = w_prime_balance {
+            record.push(
+                column
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .map(|b| format!("{:.0}", b))
+                    .unwrap_or_default(),
+            );
+        }
+
         writer
             .write_record(&record)
             .map_err(|e| ConversionError::IoError(std::io::Error::other(e)))?;
@@ -349,6 +441,7 @@ mod tests {
             completed_at: None,
             telemetry: Some(telemetry),
             swimming: None,
+            extensions: Default::default(),
         };
 
         let exercise = CompletedExercise {
@@ -359,6 +452,7 @@ mod tests {
             sets: vec![set],
             pool_config: None,
             sport: None,
+            extensions: Default::default(),
         };
 
         let workout = pwf_core::history::Workout {
@@ -376,6 +470,7 @@ mod tests {
             devices: vec![],
             sport: None,
             sport_segments: None,
+            extensions: Default::default(),
         };
 
         WpsHistory {
@@ -386,6 +481,7 @@ mod tests {
             workouts: vec![workout],
             personal_records: vec![],
             body_measurements: vec![],
+            extensions: Default::default(),
         }
     }
 
@@ -5,4 +5,4 @@ pub mod parser;
 pub mod types;
 
 // Re-export main conversion function
-pub use parser::fit_to_pwf;
+pub use parser::{fit_to_pwf, fit_to_pwf_with_options};
@@ -1,6 +1,7 @@
 //! FIT to PWF conversion logic
 
 use crate::common::utils::{fit_timestamp_to_iso8601, meters_to_km, semicircles_to_degrees};
+use crate::common::ImportOptions;
 use crate::error::{ConversionError, ConversionResult, ConversionWarning};
 use crate::fit::mappings::map_fit_sport;
 use chrono::Utc;
@@ -22,8 +23,22 @@ use std::io::Read;
 /// # Returns
 /// ConversionResult with PWF YAML and any warnings
 pub fn fit_to_pwf<R: Read>(
+    reader: R,
+    summary_only: bool,
+) -> Result<ConversionResult, ConversionError> {
+    fit_to_pwf_with_options(
+        reader,
+        &ImportOptions {
+            summary_only,
+            ..Default::default()
+        },
+    )
+}
+
+/// Convert FIT file data to PWF YAML format with [`ImportOptions`]
+pub fn fit_to_pwf_with_options<R: Read>(
     mut reader: R,
-    _summary_only: bool,
+    options: &ImportOptions,
 ) -> Result<ConversionResult, ConversionError> {
     // Read FIT data
     let mut buffer = Vec::new();
@@ -71,6 +86,7 @@ pub fn fit_to_pwf<R: Read>(
             workouts: Vec::new(),
             personal_records: Vec::new(),
             body_measurements: Vec::new(),
+            extensions: Default::default(),
         };
         result.pwf_yaml = serde_yaml::to_string(&history)?;
         return Ok(result);
@@ -104,7 +120,7 @@ pub fn fit_to_pwf<R: Read>(
     }
 
     // Build WpsHistory structure
-    let history = WpsHistory {
+    let mut history = WpsHistory {
         history_version: 2,
         exported_at: Utc::now().to_rfc3339(),
         export_source: Some(ExportSource {
@@ -120,8 +136,11 @@ pub fn fit_to_pwf<R: Read>(
         workouts,
         personal_records: Vec::new(),
         body_measurements: Vec::new(),
+        extensions: Default::default(),
     };
 
+    options.apply(&mut history);
+
     // Serialize to YAML
     result.pwf_yaml = serde_yaml::to_string(&history)?;
 
@@ -299,6 +318,7 @@ fn convert_session_to_workout(
         devices: devices.to_vec(),
         sport: Some(sport),
         sport_segments,
+        extensions: Default::default(),
     })
 }
 
@@ -394,10 +414,12 @@ fn convert_laps_to_exercises(
                 completed_at: None,
                 telemetry: None,
                 swimming: swimming_data.clone(),
+                extensions: Default::default(),
             }],
             notes: None,
             sport: None,
             pool_config,
+            extensions: Default::default(),
         }]);
     }
 
@@ -433,12 +455,14 @@ fn convert_laps_to_exercises(
                     completed_at: None,
                     telemetry: None,
                     swimming: set_swimming_data,
+                    extensions: Default::default(),
                 }
             })
             .collect(),
         notes: None,
         sport: None,
         pool_config,
+        extensions: Default::default(),
     };
 
     exercises.push(exercise);
@@ -1507,6 +1531,7 @@ mod tests {
                 notes: None,
                 sport: None,
                 pool_config: None,
+                extensions: Default::default(),
             },
             CompletedExercise {
                 id: Some("ex2".to_string()),
@@ -1516,6 +1541,7 @@ mod tests {
                 notes: None,
                 sport: None,
                 pool_config: None,
+                extensions: Default::default(),
             },
         ];
 
@@ -187,6 +187,7 @@ mod tests {
             personal_records: vec![],
             body_measurements: vec![],
             workouts: vec![],
+            extensions: Default::default(),
         };
 
         let result = pwf_to_gpx(&history).unwrap();
@@ -263,7 +264,9 @@ mod tests {
                 devices: vec![],
                 sport: None,
                 sport_segments: None,
+                extensions: Default::default(),
             }],
+            extensions: Default::default(),
         };
 
         let result = pwf_to_gpx(&history).unwrap();
@@ -298,7 +301,9 @@ mod tests {
                 devices: vec![],
                 sport: None,
                 sport_segments: None,
+                extensions: Default::default(),
             }],
+            extensions: Default::default(),
         };
 
         let result = pwf_to_gpx(&history).unwrap();
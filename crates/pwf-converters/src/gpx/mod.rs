@@ -6,4 +6,4 @@ pub mod parser;
 
 // Re-export main conversion functions
 pub use exporter::pwf_to_gpx;
-pub use parser::gpx_to_pwf;
+pub use parser::{gpx_to_pwf, gpx_to_pwf_with_options};
@@ -2,6 +2,7 @@
 //!
 //! Converts GPX (GPS Exchange Format) files to PWF history format.
 
+use crate::common::ImportOptions;
 use crate::error::{ConversionError, ConversionResult, ConversionWarning};
 use crate::gpx::mappings::{infer_sport_from_metadata, map_gpx_type_to_sport};
 use gpx::{Gpx, Waypoint};
@@ -43,6 +44,22 @@ pub fn gpx_to_pwf<R: Read>(
     reader: R,
     summary_only: bool,
 ) -> Result<ConversionResult, ConversionError> {
+    gpx_to_pwf_with_options(
+        reader,
+        &ImportOptions {
+            summary_only,
+            ..Default::default()
+        },
+    )
+}
+
+/// Convert GPX file data to PWF YAML format with [`ImportOptions`]
+pub fn gpx_to_pwf_with_options<R: Read>(
+    reader: R,
+    options: &ImportOptions,
+) -> Result<ConversionResult, ConversionError> {
+    let summary_only = options.summary_only;
+
     let mut result = ConversionResult::new(String::new());
 
     // Parse GPX file
@@ -85,7 +102,7 @@ pub fn gpx_to_pwf<R: Read>(
     }
 
     // Create history export
-    let history = WpsHistory {
+    let mut history = WpsHistory {
         history_version: 1,
         exported_at: chrono::Utc::now().to_rfc3339(),
         export_source: Some(ExportSource {
@@ -101,8 +118,11 @@ pub fn gpx_to_pwf<R: Read>(
         workouts,
         personal_records: vec![],
         body_measurements: vec![],
+        extensions: Default::default(),
     };
 
+    options.apply(&mut history);
+
     // Serialize to YAML
     let pwf_yaml = serde_yaml::to_string(&history).map_err(ConversionError::YamlError)?;
     result.pwf_yaml = pwf_yaml;
@@ -230,8 +250,10 @@ fn convert_track_to_workout(
                 ..Default::default()
             }),
             swimming: None,
+            extensions: Default::default(),
         }],
         pool_config: None,
+        extensions: Default::default(),
     };
 
     // Create workout
@@ -250,6 +272,7 @@ fn convert_track_to_workout(
         devices: vec![],
         sport: Some(sport),
         sport_segments: None,
+        extensions: Default::default(),
     };
 
     Ok(workout)
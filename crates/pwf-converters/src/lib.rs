@@ -26,11 +26,12 @@ pub mod gpx;
 pub mod tcx;
 
 // Re-export main types and functions
+pub use common::ImportOptions;
 pub use csv::{export_telemetry_to_csv, CsvExportOptions};
 pub use error::{
     ConversionError, ConversionResult, ConversionWarning, CsvExportResult, GpxExportResult,
     TcxExportResult,
 };
-pub use fit::fit_to_pwf;
-pub use gpx::{gpx_to_pwf, pwf_to_gpx};
-pub use tcx::{pwf_to_tcx, tcx_to_pwf};
+pub use fit::{fit_to_pwf, fit_to_pwf_with_options};
+pub use gpx::{gpx_to_pwf, gpx_to_pwf_with_options, pwf_to_gpx};
+pub use tcx::{pwf_to_tcx, tcx_to_pwf, tcx_to_pwf_with_options};
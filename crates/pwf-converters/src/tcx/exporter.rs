@@ -584,6 +584,7 @@ mod tests {
             workouts: Vec::new(),
             personal_records: Vec::new(),
             body_measurements: Vec::new(),
+            extensions: Default::default(),
         };
 
         let result = pwf_to_tcx(&history).unwrap();
@@ -608,6 +609,7 @@ mod tests {
             devices: Vec::new(),
             sport: Some(Sport::Running),
             sport_segments: None,
+            extensions: Default::default(),
         };
 
         let history = WpsHistory {
@@ -618,6 +620,7 @@ mod tests {
             workouts: vec![workout],
             personal_records: Vec::new(),
             body_measurements: Vec::new(),
+            extensions: Default::default(),
         };
 
         let result = pwf_to_tcx(&history).unwrap();
@@ -6,4 +6,4 @@ pub mod parser;
 
 // Re-export main conversion functions
 pub use exporter::pwf_to_tcx;
-pub use parser::tcx_to_pwf;
+pub use parser::{tcx_to_pwf, tcx_to_pwf_with_options};
@@ -1,6 +1,7 @@
 //! TCX to PWF conversion logic
 
 use crate::common::utils::meters_to_km;
+use crate::common::ImportOptions;
 use crate::error::{ConversionError, ConversionResult, ConversionWarning};
 use crate::tcx::mappings::map_tcx_sport;
 use chrono::Utc;
@@ -22,6 +23,22 @@ pub fn tcx_to_pwf<R: Read>(
     reader: R,
     summary_only: bool,
 ) -> Result<ConversionResult, ConversionError> {
+    tcx_to_pwf_with_options(
+        reader,
+        &ImportOptions {
+            summary_only,
+            ..Default::default()
+        },
+    )
+}
+
+/// Convert TCX file data to PWF YAML format with [`ImportOptions`]
+pub fn tcx_to_pwf_with_options<R: Read>(
+    reader: R,
+    options: &ImportOptions,
+) -> Result<ConversionResult, ConversionError> {
+    let summary_only = options.summary_only;
+
     // Parse TCX file using tcx crate
     // The tcx::read function requires a mutable BufReader
     let mut buf_reader = BufReader::new(reader);
@@ -65,7 +82,7 @@ pub fn tcx_to_pwf<R: Read>(
     }
 
     // Build WpsHistory structure
-    let history = WpsHistory {
+    let mut history = WpsHistory {
         history_version: 2,
         exported_at: Utc::now().to_rfc3339(),
         export_source: Some(ExportSource {
@@ -81,8 +98,11 @@ pub fn tcx_to_pwf<R: Read>(
         workouts,
         personal_records: Vec::new(),
         body_measurements: Vec::new(),
+        extensions: Default::default(),
     };
 
+    options.apply(&mut history);
+
     // Serialize to YAML
     result.pwf_yaml = serde_yaml::to_string(&history)?;
 
@@ -104,6 +124,7 @@ fn create_empty_history() -> WpsHistory {
         workouts: Vec::new(),
         personal_records: Vec::new(),
         body_measurements: Vec::new(),
+        extensions: Default::default(),
     }
 }
 
@@ -195,6 +216,7 @@ fn convert_activity_to_workout(
             completed_at: None,
             telemetry: None,
             swimming: None,
+            extensions: Default::default(),
         };
 
         exercises.push(CompletedExercise {
@@ -205,15 +227,16 @@ fn convert_activity_to_workout(
             notes: None,
             sport: None,
             pool_config: None,
+            extensions: Default::default(),
         });
     }
 
     // Build workout telemetry
-    let mut telemetry = WorkoutTelemetry::default();
+    let mut telemetry = WorkoutTelemetry {
+        heart_rate_avg: hr_sum.checked_div(hr_count),
+        ..Default::default()
+    };
 
-    if hr_count > 0 {
-        telemetry.heart_rate_avg = Some(hr_sum / hr_count);
-    }
     if max_hr > 0 {
         telemetry.heart_rate_max = Some(max_hr as u32);
     }
@@ -223,9 +246,7 @@ fn convert_activity_to_workout(
     if total_distance_m > 0.0 {
         telemetry.total_distance_km = Some(meters_to_km(total_distance_m));
     }
-    if cadence_count > 0 {
-        telemetry.cadence_avg = Some(cadence_sum / cadence_count);
-    }
+    telemetry.cadence_avg = cadence_sum.checked_div(cadence_count);
 
     // Extract GPS route from trackpoints
     if !summary_only && !all_trackpoints.is_empty() {
@@ -268,6 +289,7 @@ fn convert_activity_to_workout(
         devices,
         sport: Some(sport),
         sport_segments: None,
+        extensions: Default::default(),
     })
 }
 
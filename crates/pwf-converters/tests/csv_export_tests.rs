@@ -54,6 +54,7 @@ fn create_test_history_with_full_telemetry() -> WpsHistory {
         completed_at: None,
         telemetry: Some(telemetry),
         swimming: None,
+        extensions: Default::default(),
     };
 
     let exercise = CompletedExercise {
@@ -64,6 +65,7 @@ fn create_test_history_with_full_telemetry() -> WpsHistory {
         sets: vec![set],
         pool_config: None,
         sport: Some(Sport::Cycling),
+        extensions: Default::default(),
     };
 
     let workout = Workout {
@@ -81,6 +83,7 @@ fn create_test_history_with_full_telemetry() -> WpsHistory {
         devices: vec![],
         sport: Some(Sport::Cycling),
         sport_segments: None,
+        extensions: Default::default(),
     };
 
     WpsHistory {
@@ -96,6 +99,7 @@ fn create_test_history_with_full_telemetry() -> WpsHistory {
         workouts: vec![workout],
         personal_records: vec![],
         body_measurements: vec![],
+        extensions: Default::default(),
     }
 }
 
@@ -357,6 +361,7 @@ fn test_csv_export_empty_history() {
         workouts: vec![],
         personal_records: vec![],
         body_measurements: vec![],
+        extensions: Default::default(),
     };
 
     let options = CsvExportOptions::default();
@@ -384,3 +389,52 @@ fn test_csv_export_multiple_sets() {
     assert!(result.csv_data.contains("set1"));
     assert!(result.csv_data.contains("set2"));
 }
+
+#[test]
+fn test_csv_export_w_prime_balance_column() {
+    let history = create_test_history_with_full_telemetry();
+    let options = CsvExportOptions {
+        w_prime_balance: true,
+        critical_power_watts: Some(200),
+        w_prime_joules: Some(20000.0),
+        ..Default::default()
+    };
+
+    let result = export_telemetry_to_csv(&history, &options).unwrap();
+    assert!(result.warnings.is_empty());
+
+    let mut reader = csv::Reader::from_reader(result.csv_data.as_bytes());
+    let header = reader.headers().unwrap().clone();
+    assert_eq!(header.iter().next_back(), Some("w_prime_balance_j"));
+
+    let balance: Vec<String> = reader
+        .records()
+        .map(|r| r.unwrap().iter().next_back().unwrap().to_string())
+        .collect();
+    assert_eq!(balance[0], "20000");
+    assert!(balance.iter().all(|b| !b.is_empty()));
+}
+
+#[test]
+fn test_csv_export_w_prime_balance_without_cp_warns() {
+    let history = create_test_history_with_full_telemetry();
+    let options = CsvExportOptions {
+        w_prime_balance: true,
+        ..Default::default()
+    };
+
+    let result = export_telemetry_to_csv(&history, &options).unwrap();
+    assert_eq!(result.warnings.len(), 1);
+    assert!(result.warnings[0].to_string().contains("W' balance"));
+
+    // The column is still present, just empty
+    let first_row = result.csv_data.lines().nth(1).unwrap();
+    assert!(first_row.ends_with(','));
+}
+
+#[test]
+fn test_csv_export_omits_w_prime_balance_by_default() {
+    let history = create_test_history_with_full_telemetry();
+    let result = export_telemetry_to_csv(&history, &CsvExportOptions::default()).unwrap();
+    assert!(!result.csv_data.contains("w_prime_balance_j"));
+}
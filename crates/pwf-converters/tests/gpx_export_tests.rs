@@ -73,7 +73,9 @@ fn create_pwf_with_gps() -> WpsHistory {
             devices: vec![],
             sport: None,
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     }
 }
 
@@ -113,6 +115,7 @@ fn test_pwf_to_gpx_empty_history() {
         personal_records: vec![],
         body_measurements: vec![],
         workouts: vec![],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_gpx(&history).unwrap();
@@ -153,7 +156,9 @@ fn test_pwf_to_gpx_workout_without_gps() {
             devices: vec![],
             sport: None,
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_gpx(&history).unwrap();
@@ -216,6 +221,7 @@ fn test_pwf_to_gpx_multiple_workouts() {
         devices: vec![],
         sport: None,
         sport_segments: None,
+        extensions: Default::default(),
     });
 
     let result = pwf_to_gpx(&history).unwrap();
@@ -286,7 +292,9 @@ fn test_gpx_export_workout_without_telemetry() {
             devices: vec![],
             sport: None,
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_gpx(&history).unwrap();
@@ -333,7 +341,9 @@ fn test_gpx_export_telemetry_without_gps_route() {
             devices: vec![],
             sport: None,
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_gpx(&history).unwrap();
@@ -397,7 +407,9 @@ fn test_gpx_export_workout_with_empty_gps_positions() {
             devices: vec![],
             sport: None,
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_gpx(&history).unwrap();
@@ -437,6 +449,7 @@ fn test_gpx_export_mixed_workouts_some_without_gps() {
         devices: vec![],
         sport: None,
         sport_segments: None,
+        extensions: Default::default(),
     });
 
     let result = pwf_to_gpx(&history).unwrap();
@@ -357,6 +357,7 @@ fn test_tcx_export_workout_with_invalid_gps_timestamp() {
                 modality: None,
                 pool_config: None,
                 sport: None,
+                extensions: Default::default(),
             }],
             telemetry: Some(WorkoutTelemetry {
                 gps_route: Some(GpsRoute {
@@ -392,7 +393,9 @@ fn test_tcx_export_workout_with_invalid_gps_timestamp() {
             devices: vec![],
             sport: Some(pwf_core::Sport::Running),
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history);
@@ -433,7 +436,9 @@ fn test_tcx_export_workout_without_started_at() {
             devices: vec![],
             sport: Some(pwf_core::Sport::Running),
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history).unwrap();
@@ -489,6 +494,7 @@ fn test_tcx_export_with_set_heart_rate_telemetry() {
                             heart_rate_max: Some(170),
                             ..Default::default()
                         }),
+                        extensions: Default::default(),
                     },
                     CompletedSet {
                         set_number: Some(2),
@@ -509,17 +515,21 @@ fn test_tcx_export_with_set_heart_rate_telemetry() {
                             heart_rate_max: Some(180),
                             ..Default::default()
                         }),
+                        extensions: Default::default(),
                     },
                 ],
                 modality: Some(pwf_core::Modality::Interval),
                 pool_config: None,
                 sport: None,
+                extensions: Default::default(),
             }],
             telemetry: None,
             devices: vec![],
             sport: Some(pwf_core::Sport::Running),
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history).unwrap();
@@ -570,16 +580,20 @@ fn test_tcx_export_strength_exercise_warning() {
                     is_pr: None,
                     swimming: None,
                     telemetry: None,
+                    extensions: Default::default(),
                 }],
                 modality: Some(pwf_core::Modality::Strength),
                 pool_config: None,
                 sport: None,
+                extensions: Default::default(),
             }],
             telemetry: None,
             devices: vec![],
             sport: Some(pwf_core::Sport::StrengthTraining),
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history).unwrap();
@@ -636,10 +650,12 @@ fn test_tcx_export_workout_level_calories() {
                     is_pr: None,
                     swimming: None,
                     telemetry: None,
+                    extensions: Default::default(),
                 }],
                 modality: Some(pwf_core::Modality::Stopwatch),
                 pool_config: None,
                 sport: None,
+                extensions: Default::default(),
             }],
             telemetry: Some(WorkoutTelemetry {
                 total_calories: Some(450),
@@ -648,7 +664,9 @@ fn test_tcx_export_workout_level_calories() {
             devices: vec![],
             sport: Some(pwf_core::Sport::Running),
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history).unwrap();
@@ -701,10 +719,12 @@ fn test_tcx_export_with_gps_power_cadence_extensions() {
                     is_pr: None,
                     swimming: None,
                     telemetry: None,
+                    extensions: Default::default(),
                 }],
                 modality: Some(pwf_core::Modality::Stopwatch),
                 pool_config: None,
                 sport: None,
+                extensions: Default::default(),
             }],
             telemetry: Some(WorkoutTelemetry {
                 gps_route: Some(GpsRoute {
@@ -755,7 +775,9 @@ fn test_tcx_export_with_gps_power_cadence_extensions() {
             devices: vec![],
             sport: Some(pwf_core::Sport::Cycling),
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history).unwrap();
@@ -813,10 +835,12 @@ fn test_tcx_export_exercise_with_bad_gps_timestamp() {
                     is_pr: None,
                     swimming: None,
                     telemetry: None,
+                    extensions: Default::default(),
                 }],
                 modality: Some(pwf_core::Modality::Stopwatch),
                 pool_config: None,
                 sport: None,
+                extensions: Default::default(),
             }],
             telemetry: Some(WorkoutTelemetry {
                 gps_route: Some(GpsRoute {
@@ -852,7 +876,9 @@ fn test_tcx_export_exercise_with_bad_gps_timestamp() {
             devices: vec![],
             sport: Some(pwf_core::Sport::Running),
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history);
@@ -895,7 +921,9 @@ fn test_tcx_export_workout_without_sport() {
             devices: vec![],
             sport: None, // No sport specified
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history).unwrap();
@@ -925,6 +953,7 @@ fn test_tcx_export_empty_workout_list() {
         personal_records: vec![],
         body_measurements: vec![],
         workouts: vec![], // No workouts
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history).unwrap();
@@ -969,7 +998,9 @@ fn test_tcx_export_workout_without_telemetry() {
             devices: vec![],
             sport: Some(Sport::StrengthTraining),
             sport_segments: None,
+            extensions: Default::default(),
         }],
+        extensions: Default::default(),
     };
 
     let result = pwf_to_tcx(&history).unwrap();
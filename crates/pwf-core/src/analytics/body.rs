@@ -0,0 +1,402 @@
+//! Trends in body measurements
+//!
+//! Scale weight swings by a kilogram or more from day to day with water and
+//! food, so the trend is an exponential moving average with a time constant
+//! in days, in the spirit of the Hacker's Diet: each measurement pulls the
+//! average `1 - (1 - 1/τ)^days` of the way towards it, where `days` is the
+//! time since the previous one. Body fat readings split each weight into
+//! fat and lean mass; tape measurements give the waist-to-hip ratio and
+//! left/right differences per limb.
+
+use super::date_range::{parse_date, DateRange};
+use crate::history::{BodyDimensions, BodyMeasurement, WpsHistory};
+use crate::{WeightUnit, LB_TO_KG};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+
+/// Time constant of the weight trend, in days
+pub const DEFAULT_SMOOTHING_DAYS: f64 = 10.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyOptions {
+    pub smoothing_days: f64,
+    /// Unit for reported weights; `None` uses the history's `units.weight`
+    pub unit: Option<WeightUnit>,
+}
+
+impl Default for BodyOptions {
+    fn default() -> Self {
+        Self {
+            smoothing_days: DEFAULT_SMOOTHING_DAYS,
+            unit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Limb {
+    Bicep,
+    Forearm,
+    Thigh,
+    Calf,
+}
+
+/// Left/right difference of one limb measurement
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LimbAsymmetry {
+    pub limb: Limb,
+    pub left_cm: f64,
+    pub right_cm: f64,
+    /// Right minus left
+    pub difference_cm: f64,
+    /// Absolute difference as a percentage of the larger side
+    pub percent: f64,
+}
+
+/// One day of measurements, with weights in the report's unit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BodyEntry {
+    pub date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    /// Moving average of weight up to and including this day
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trend_weight: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_fat_percent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fat_mass: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lean_mass: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waist_to_hip: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub asymmetry: Vec<LimbAsymmetry>,
+}
+
+/// Trend weight at the end of a Monday-to-Sunday week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeeklyBodyChange {
+    /// Monday of the week
+    pub week_start: NaiveDate,
+    pub trend_weight: f64,
+    /// Change since the previous week with a weight, scaled to seven days
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_per_week: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BodyReport {
+    pub unit: WeightUnit,
+    pub entries: Vec<BodyEntry>,
+    pub weeks: Vec<WeeklyBodyChange>,
+    /// Trend change from the first to the last weight in range, per week
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_per_week: Option<f64>,
+}
+
+/// Weight of a measurement in kilograms, from `weight_kg` or `weight_lb`
+pub fn measurement_weight_kg(measurement: &BodyMeasurement) -> Option<f64> {
+    measurement
+        .weight_kg
+        .or_else(|| measurement.weight_lb.map(|lb| lb * LB_TO_KG))
+        .filter(|w| *w > 0.0)
+}
+
+/// Waist circumference divided by hip circumference
+pub fn waist_to_hip_ratio(dimensions: &BodyDimensions) -> Option<f64> {
+    let waist = dimensions.waist_cm.filter(|w| *w > 0.0)?;
+    let hips = dimensions.hips_cm.filter(|h| *h > 0.0)?;
+    Some(waist / hips)
+}
+
+/// Left/right differences for every limb measured on both sides
+pub fn limb_asymmetry(dimensions: &BodyDimensions) -> Vec<LimbAsymmetry> {
+    [
+        (
+            Limb::Bicep,
+            dimensions.bicep_left_cm,
+            dimensions.bicep_right_cm,
+        ),
+        (
+            Limb::Forearm,
+            dimensions.forearm_left_cm,
+            dimensions.forearm_right_cm,
+        ),
+        (
+            Limb::Thigh,
+            dimensions.thigh_left_cm,
+            dimensions.thigh_right_cm,
+        ),
+        (
+            Limb::Calf,
+            dimensions.calf_left_cm,
+            dimensions.calf_right_cm,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(limb, left, right)| {
+        let (left, right) = (left.filter(|v| *v > 0.0)?, right.filter(|v| *v > 0.0)?);
+        Some(LimbAsymmetry {
+            limb,
+            left_cm: left,
+            right_cm: right,
+            difference_cm: right - left,
+            percent: (right - left).abs() / left.max(right) * 100.0,
+        })
+    })
+    .collect()
+}
+
+/// Smoothed weight, body composition, ratios and weekly change of the
+/// history's body measurements
+///
+/// The trend is computed over every measurement so that entries at the start
+/// of `range` are already smoothed; only entries inside it are reported.
+/// Measurements on the same date are merged, later values winning.
+pub fn body_trends(history: &WpsHistory, range: &DateRange, options: &BodyOptions) -> BodyReport {
+    let unit = options.unit.unwrap_or(history.units.weight);
+    let scale = match unit {
+        WeightUnit::Kg => 1.0,
+        WeightUnit::Lb => 1.0 / LB_TO_KG,
+    };
+
+    let mut days: Vec<(NaiveDate, Merged)> = Vec::new();
+    for measurement in &history.body_measurements {
+        let Some(date) = parse_date(&measurement.date) else {
+            continue;
+        };
+        match days.iter_mut().find(|(d, _)| *d == date) {
+            Some((_, merged)) => merged.add(measurement),
+            None => {
+                let mut merged = Merged::default();
+                merged.add(measurement);
+                days.push((date, merged));
+            }
+        }
+    }
+    days.sort_by_key(|(date, _)| *date);
+
+    let tau = options.smoothing_days.max(1.0);
+    let mut trend: Option<(NaiveDate, f64)> = None;
+    let mut entries = Vec::new();
+    for (date, merged) in days {
+        if let Some(weight) = merged.weight_kg {
+            let smoothed = match trend {
+                Some((previous, value)) => {
+                    let elapsed = (date - previous).num_days() as f64;
+                    let alpha = 1.0 - (1.0 - 1.0 / tau).powf(elapsed);
+                    value + (weight - value) * alpha
+                }
+                None => weight,
+            };
+            trend = Some((date, smoothed));
+        }
+        if !range.contains(date) {
+            continue;
+        }
+
+        let dimensions = merged.dimensions.unwrap_or_default();
+        let fat_kg = merged
+            .weight_kg
+            .zip(merged.body_fat_percent)
+            .map(|(weight, percent)| weight * percent / 100.0);
+        entries.push(BodyEntry {
+            date,
+            weight: merged.weight_kg.map(|w| w * scale),
+            trend_weight: trend.map(|(_, w)| w * scale),
+            body_fat_percent: merged.body_fat_percent,
+            fat_mass: fat_kg.map(|f| f * scale),
+            lean_mass: fat_kg
+                .zip(merged.weight_kg)
+                .map(|(fat, weight)| (weight - fat) * scale),
+            waist_to_hip: waist_to_hip_ratio(&dimensions),
+            asymmetry: limb_asymmetry(&dimensions),
+        });
+    }
+
+    let weeks = weekly_changes(&entries);
+    let weighed: Vec<(NaiveDate, f64)> = entries
+        .iter()
+        .filter_map(|e| Some((e.date, e.trend_weight?)))
+        .collect();
+    let change_per_week = match (weighed.first(), weighed.last()) {
+        (Some(&(first, start)), Some(&(last, end))) if last > first => {
+            Some((end - start) / (last - first).num_days() as f64 * 7.0)
+        }
+        _ => None,
+    };
+
+    BodyReport {
+        unit,
+        entries,
+        weeks,
+        change_per_week,
+    }
+}
+
+fn weekly_changes(entries: &[BodyEntry]) -> Vec<WeeklyBodyChange> {
+    // Last trend weight of each week with the date it was taken
+    let mut last: Vec<(NaiveDate, NaiveDate, f64)> = Vec::new();
+    for entry in entries {
+        let Some(weight) = entry.trend_weight else {
+            continue;
+        };
+        let date = entry.date;
+        let week_start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        match last.last_mut() {
+            Some(week) if week.0 == week_start => *week = (week_start, date, weight),
+            _ => last.push((week_start, date, weight)),
+        }
+    }
+
+    let mut weeks: Vec<WeeklyBodyChange> = Vec::with_capacity(last.len());
+    for (i, &(week_start, date, weight)) in last.iter().enumerate() {
+        let change = i
+            .checked_sub(1)
+            .map(|p| last[p])
+            .map(|(_, previous, before)| {
+                let days = (date - previous).num_days().max(1) as f64;
+                ((weight - before) / days * 7.0, before)
+            });
+        weeks.push(WeeklyBodyChange {
+            week_start,
+            trend_weight: weight,
+            change_per_week: change.map(|(c, _)| c),
+            change_percent: change.map(|(c, before)| c / before * 100.0),
+        });
+    }
+    weeks
+}
+
+/// Fields of the measurements taken on one date
+#[derive(Default)]
+struct Merged {
+    weight_kg: Option<f64>,
+    body_fat_percent: Option<f64>,
+    dimensions: Option<BodyDimensions>,
+}
+
+impl Merged {
+    fn add(&mut self, measurement: &BodyMeasurement) {
+        if let Some(weight) = measurement_weight_kg(measurement) {
+            self.weight_kg = Some(weight);
+        }
+        if let Some(percent) = measurement
+            .body_fat_percent
+            .filter(|p| *p > 0.0 && *p < 100.0)
+        {
+            self.body_fat_percent = Some(percent);
+        }
+        if let Some(new) = &measurement.measurements {
+            let dims = self.dimensions.get_or_insert_with(BodyDimensions::default);
+            let fields = [
+                (&mut dims.neck_cm, new.neck_cm),
+                (&mut dims.shoulders_cm, new.shoulders_cm),
+                (&mut dims.chest_cm, new.chest_cm),
+                (&mut dims.waist_cm, new.waist_cm),
+                (&mut dims.hips_cm, new.hips_cm),
+                (&mut dims.bicep_left_cm, new.bicep_left_cm),
+                (&mut dims.bicep_right_cm, new.bicep_right_cm),
+                (&mut dims.forearm_left_cm, new.forearm_left_cm),
+                (&mut dims.forearm_right_cm, new.forearm_right_cm),
+                (&mut dims.thigh_left_cm, new.thigh_left_cm),
+                (&mut dims.thigh_right_cm, new.thigh_right_cm),
+                (&mut dims.calf_left_cm, new.calf_left_cm),
+                (&mut dims.calf_right_cm, new.calf_right_cm),
+            ];
+            for (field, value) in fields {
+                if value.is_some() {
+                    *field = value;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(yaml: &str) -> WpsHistory {
+        crate::history::parse(yaml).unwrap()
+    }
+
+    const HISTORY: &str = r#"
+history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+workouts: []
+body_measurements:
+  - date: "2025-03-03"
+    weight_kg: 80.0
+    body_fat_percent: 20.0
+  - date: "2025-03-05"
+    weight_lb: 174.165
+  - date: "2025-03-05"
+    measurements:
+      waist_cm: 85.0
+      hips_cm: 100.0
+      bicep_left_cm: 38.0
+      bicep_right_cm: 40.0
+      thigh_left_cm: 58.0
+  - date: "2025-03-12"
+    weight_kg: 78.0
+"#;
+
+    #[test]
+    fn test_trend_composition_and_ratios() {
+        let report = body_trends(
+            &history(HISTORY),
+            &DateRange::all(),
+            &BodyOptions::default(),
+        );
+        assert_eq!(report.unit, WeightUnit::Kg);
+        assert_eq!(report.entries.len(), 3);
+
+        let first = &report.entries[0];
+        assert_eq!(first.trend_weight, Some(80.0));
+        assert_eq!(first.fat_mass, Some(16.0));
+        assert_eq!(first.lean_mass, Some(64.0));
+
+        // Same-day entries merge; 174.165 lb is 79 kg
+        let second = &report.entries[1];
+        assert!((second.weight.unwrap() - 79.0).abs() < 1e-3);
+        let alpha = 1.0 - 0.9f64.powi(2);
+        let trend = 80.0 - alpha;
+        assert!((second.trend_weight.unwrap() - trend).abs() < 1e-3);
+        assert_eq!(second.waist_to_hip, Some(0.85));
+        assert_eq!(second.asymmetry.len(), 1);
+        assert_eq!(second.asymmetry[0].limb, Limb::Bicep);
+        assert_eq!(second.asymmetry[0].difference_cm, 2.0);
+        assert_eq!(second.asymmetry[0].percent, 5.0);
+        assert!(second.fat_mass.is_none());
+
+        assert_eq!(report.weeks.len(), 2);
+        assert!(report.weeks[0].change_per_week.is_none());
+        let third = report.entries[2].trend_weight.unwrap();
+        let weekly = report.weeks[1].change_per_week.unwrap();
+        assert!((weekly - (third - second.trend_weight.unwrap())).abs() < 1e-9);
+        assert!(weekly < 0.0);
+        assert!(report.change_per_week.unwrap() < 0.0);
+    }
+
+    #[test]
+    fn test_range_and_pounds() {
+        let options = BodyOptions {
+            unit: Some(WeightUnit::Lb),
+            ..Default::default()
+        };
+        let range = DateRange::parse(Some("2025-03-10"), None).unwrap();
+        let report = body_trends(&history(HISTORY), &range, &options);
+        assert_eq!(report.unit, WeightUnit::Lb);
+        assert_eq!(report.entries.len(), 1);
+        // Smoothed with the earlier entries, not restarted at 78 kg
+        let trend = report.entries[0].trend_weight.unwrap() * LB_TO_KG;
+        assert!(trend > 78.0 && trend < 80.0);
+        assert!((report.entries[0].weight.unwrap() - 78.0 / LB_TO_KG).abs() < 1e-9);
+        assert!(report.change_per_week.is_none());
+    }
+}
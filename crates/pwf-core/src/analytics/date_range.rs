@@ -0,0 +1,104 @@
+//! Date filtering for history-wide analytics
+
+use crate::history::{Workout, WpsHistory};
+use chrono::NaiveDate;
+
+/// An inclusive range of calendar dates; either end may be open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn new(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        Self { from, to }
+    }
+
+    /// Build a range from optional `YYYY-MM-DD` strings
+    pub fn parse(from: Option<&str>, to: Option<&str>) -> Result<Self, chrono::ParseError> {
+        let parse = |value: Option<&str>| {
+            value
+                .map(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d"))
+                .transpose()
+        };
+        Ok(Self::new(parse(from)?, parse(to)?))
+    }
+
+    /// A range covering every date
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from.is_none_or(|from| date >= from) && self.to.is_none_or(|to| date <= to)
+    }
+
+    /// Whether a workout falls inside the range
+    ///
+    /// Workouts whose date cannot be parsed only match an unbounded range.
+    pub fn includes(&self, workout: &Workout) -> bool {
+        match workout_date(workout) {
+            Some(date) => self.contains(date),
+            None => self.from.is_none() && self.to.is_none(),
+        }
+    }
+
+    /// Workouts in a history that fall inside the range, in file order
+    pub fn workouts<'a>(&self, history: &'a WpsHistory) -> impl Iterator<Item = &'a Workout> {
+        let range = *self;
+        history.workouts.iter().filter(move |w| range.includes(w))
+    }
+}
+
+/// The calendar date of a workout
+///
+/// Accepts plain `YYYY-MM-DD` dates as well as full timestamps.
+pub fn workout_date(workout: &Workout) -> Option<NaiveDate> {
+    parse_date(&workout.date)
+}
+
+pub(crate) fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_contains_is_inclusive() {
+        let range = DateRange::new(Some(date("2025-01-10")), Some(date("2025-01-20")));
+        assert!(range.contains(date("2025-01-10")));
+        assert!(range.contains(date("2025-01-20")));
+        assert!(!range.contains(date("2025-01-09")));
+        assert!(!range.contains(date("2025-01-21")));
+        assert!(DateRange::all().contains(date("1999-12-31")));
+    }
+
+    #[test]
+    fn test_range_parse() {
+        let range = DateRange::parse(Some("2025-01-10"), None).unwrap();
+        assert_eq!(range.from, Some(date("2025-01-10")));
+        assert_eq!(range.to, None);
+        assert!(DateRange::parse(None, Some("10/01/2025")).is_err());
+    }
+
+    #[test]
+    fn test_parse_date_accepts_timestamps() {
+        assert_eq!(parse_date("2025-01-15T10:30:00Z"), Some(date("2025-01-15")));
+        assert_eq!(parse_date("2025-01-15"), Some(date("2025-01-15")));
+        assert_eq!(parse_date("Jan 15"), None);
+    }
+
+    #[test]
+    fn test_undated_workouts_only_match_unbounded_range() {
+        let workout: Workout = serde_yaml::from_str("date: \"soon\"\nexercises: []\n").unwrap();
+        assert!(DateRange::all().includes(&workout));
+        assert!(!DateRange::new(Some(date("2025-01-01")), None).includes(&workout));
+    }
+}
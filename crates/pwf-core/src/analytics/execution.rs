@@ -0,0 +1,653 @@
+//! How well planned interval phases were executed
+//!
+//! A plan exercise prescribes a sequence of phases, either as
+//! `interval_phases` repeated `target_sets` times or as `zones` with
+//! durations. Each phase is aligned with a stretch of the recorded time
+//! series, and for every targeted stream (power, heart rate, pace, cadence)
+//! the stretch's average and the share of time spent inside the target
+//! range are reported.
+//!
+//! Explicit targets get a band of ±[`ExecutionOptions::tolerance`] around
+//! them. Phases prescribed as a zone without a target use that zone's range
+//! from the plan's athlete profile.
+//!
+//! Each phase scores the mean of its time-in-range percentages, scaled down
+//! when the phase ran shorter or longer than planned. The overall score
+//! weights phase scores by planned duration, with phases that were never
+//! reached scoring zero.
+
+use super::date_range::DateRange;
+use super::intervals::{detect_intervals, match_phases, planned_exercise, IntervalOptions};
+use super::series::{resample_1hz, series_times, Sample};
+use super::zones::ZoneBoundaries;
+use crate::history::{TimeSeriesData, WpsHistory};
+use crate::plan::{IntervalPhase, PlanExercise, WpsPlan};
+use serde::Serialize;
+
+/// Default half-width of the band around an explicit target, as a fraction
+/// of the target
+pub const DEFAULT_TARGET_TOLERANCE: f64 = 0.05;
+
+/// How planned phases are lined up with the recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseAlignment {
+    /// Detected intervals when they match at least half the phases,
+    /// otherwise time offsets
+    Auto,
+    /// Phases laid end to end from [`ExecutionOptions::offset_sec`]
+    Offset,
+    /// Phases matched to work/recovery segments found in the stream
+    Intervals,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExecutionOptions {
+    pub alignment: PhaseAlignment,
+    /// Start of the first phase, in seconds from the start of the
+    /// recording, for offset alignment
+    pub offset_sec: f64,
+    pub tolerance: f64,
+}
+
+impl Default for ExecutionOptions {
+    fn default() -> Self {
+        Self {
+            alignment: PhaseAlignment::Auto,
+            offset_sec: 0.0,
+            tolerance: DEFAULT_TARGET_TOLERANCE,
+        }
+    }
+}
+
+/// One phase of the planned sequence, with repetitions unrolled
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedPhase {
+    pub name: String,
+    /// 1-based repetition of the phase list
+    pub repetition: u32,
+    pub duration_sec: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_power_watts: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_hr_bpm: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_pace_sec_per_km: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cadence_rpm: Option<u32>,
+}
+
+/// Execution of one targeted stream over a phase
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricExecution {
+    /// The planned value, when the phase gave one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<f64>,
+    /// Target range; an open end is unbounded (e.g. the top zone)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub high: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<f64>,
+    /// Share of recorded seconds inside the range (0–100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent_in_range: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhaseExecution {
+    #[serde(flatten)]
+    pub planned: PlannedPhase,
+    /// Where the phase was found, relative to the first row; `None` when
+    /// the recording never reached it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_sec: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_duration_sec: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<MetricExecution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heart_rate: Option<MetricExecution>,
+    /// Pace in seconds per km
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pace: Option<MetricExecution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cadence: Option<MetricExecution>,
+    /// 0–100
+    pub score: f64,
+}
+
+/// A logged set scored against the phases of its plan exercise
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IntervalExecution {
+    /// Location of the set, e.g. `workouts[0].exercises[1].sets[0]`
+    pub path: String,
+    pub date: String,
+    pub exercise: String,
+    /// The alignment actually used; never `Auto`
+    pub alignment: PhaseAlignment,
+    pub phases: Vec<PhaseExecution>,
+    /// 0–100
+    pub score: f64,
+}
+
+/// The phases a plan exercise prescribes, in order
+///
+/// `interval_phases` are repeated `target_sets` times (default once).
+/// Without them, `zones` that give a duration are used in order.
+pub fn phase_sequence(exercise: &PlanExercise) -> Vec<PlannedPhase> {
+    if let Some(phases) = exercise.interval_phases.as_ref().filter(|p| !p.is_empty()) {
+        let repetitions = exercise.target_sets.unwrap_or(1).max(1);
+        return (1..=repetitions)
+            .flat_map(|rep| {
+                phases.iter().map(move |phase| PlannedPhase {
+                    name: phase.name.clone(),
+                    repetition: rep,
+                    duration_sec: phase.duration_sec,
+                    zone: None,
+                    target_power_watts: phase.target_power_watts,
+                    target_hr_bpm: phase.target_hr_bpm,
+                    target_pace_sec_per_km: phase.target_pace_sec_per_km,
+                    cadence_rpm: phase.cadence_rpm,
+                })
+            })
+            .collect();
+    }
+    exercise
+        .zones
+        .iter()
+        .flatten()
+        .filter_map(|zone| {
+            Some(PlannedPhase {
+                name: format!("Zone {}", zone.zone),
+                repetition: 1,
+                duration_sec: zone.duration_sec?,
+                zone: Some(zone.zone),
+                target_power_watts: zone.target_power_watts,
+                target_hr_bpm: zone.target_hr_bpm,
+                target_pace_sec_per_km: zone.target_pace_sec_per_km,
+                cadence_rpm: None,
+            })
+        })
+        .collect()
+}
+
+/// Score one time series against a planned phase sequence
+///
+/// Returns the alignment used with one entry per planned phase.
+pub fn execute_phases(
+    ts: &TimeSeriesData,
+    phases: &[PlannedPhase],
+    boundaries: &ZoneBoundaries,
+    options: &ExecutionOptions,
+) -> (PhaseAlignment, Vec<PhaseExecution>) {
+    let times = series_times(ts, 0.0);
+    let (alignment, windows) = align(ts, &times, phases, options);
+    let streams = Streams::new(ts);
+
+    let executions = phases
+        .iter()
+        .zip(windows)
+        .map(|(phase, window)| {
+            let Some((first, last)) = window else {
+                return PhaseExecution {
+                    planned: phase.clone(),
+                    start_sec: None,
+                    actual_duration_sec: None,
+                    power: None,
+                    heart_rate: None,
+                    pace: None,
+                    cadence: None,
+                    score: 0.0,
+                };
+            };
+            let window_values = |column: &[Option<f64>]| window_1hz(&times, column, first, last);
+            let ranged = |target: Option<u32>, zone_range: Option<(Option<f64>, Option<f64>)>| {
+                match target {
+                    Some(target) => {
+                        let target = target as f64;
+                        Some((
+                            Some(target),
+                            Some(target * (1.0 - options.tolerance)),
+                            Some(target * (1.0 + options.tolerance)),
+                        ))
+                    }
+                    None => zone_range.map(|(low, high)| (None, low, high)),
+                }
+            };
+            let zone = |bounds: &Option<Vec<u32>>, ascending: bool| {
+                phase
+                    .zone
+                    .zip(bounds.as_deref())
+                    .and_then(|(zone, bounds)| zone_range(zone, bounds, ascending))
+            };
+
+            let power = ranged(phase.target_power_watts, zone(&boundaries.power, true))
+                .map(|range| metric(range, &window_values(&streams.power), false));
+            let heart_rate = ranged(phase.target_hr_bpm, zone(&boundaries.hr, true))
+                .map(|range| metric(range, &window_values(&streams.heart_rate), false));
+            let pace = ranged(phase.target_pace_sec_per_km, zone(&boundaries.pace, false))
+                .map(|range| metric(range, &window_values(&streams.pace), true));
+            let cadence = ranged(phase.cadence_rpm, None)
+                .map(|range| metric(range, &window_values(&streams.cadence), false));
+
+            let duration = times[last] - times[first];
+            let planned = phase.duration_sec as f64;
+            let completion = if planned > 0.0 && duration > 0.0 {
+                (duration / planned).min(planned / duration)
+            } else {
+                0.0
+            };
+            let in_range: Vec<f64> = [&power, &heart_rate, &pace, &cadence]
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m.percent_in_range)
+                .collect();
+            let score = if in_range.is_empty() {
+                completion * 100.0
+            } else {
+                completion * in_range.iter().sum::<f64>() / in_range.len() as f64
+            };
+
+            PhaseExecution {
+                planned: phase.clone(),
+                start_sec: Some(times[first] - times[0]),
+                actual_duration_sec: Some(duration),
+                power,
+                heart_rate,
+                pace,
+                cadence,
+                score,
+            }
+        })
+        .collect();
+    (alignment, executions)
+}
+
+/// Planned-duration-weighted mean of phase scores
+pub fn execution_score(phases: &[PhaseExecution]) -> f64 {
+    let total: f64 = phases.iter().map(|p| p.planned.duration_sec as f64).sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    phases
+        .iter()
+        .map(|p| p.score * p.planned.duration_sec as f64)
+        .sum::<f64>()
+        / total
+}
+
+/// Score every logged exercise in `range` whose plan exercise prescribes
+/// phases
+///
+/// The plan exercise is found as for [`super::intervals::planned_phases`].
+/// Only exercises with exactly one time series are scored: the usual
+/// single-lap recording. Zone ranges come from the plan's athlete profile.
+pub fn score_executions(
+    plan: &WpsPlan,
+    history: &WpsHistory,
+    range: &DateRange,
+    options: &ExecutionOptions,
+) -> Vec<IntervalExecution> {
+    let boundaries = plan
+        .meta
+        .as_ref()
+        .and_then(|m| m.athlete_profile.as_ref())
+        .map(ZoneBoundaries::from_profile)
+        .unwrap_or_default();
+
+    let mut out = Vec::new();
+    for (workout_idx, workout) in history.workouts.iter().enumerate() {
+        if !range.includes(workout) {
+            continue;
+        }
+        for (ex_idx, exercise) in workout.exercises.iter().enumerate() {
+            let Some(planned) =
+                planned_exercise(plan, workout, exercise, |e| !phase_sequence(e).is_empty())
+            else {
+                continue;
+            };
+            let series: Vec<(usize, &TimeSeriesData)> = exercise
+                .sets
+                .iter()
+                .enumerate()
+                .filter_map(|(i, s)| Some((i, s.telemetry.as_ref()?.time_series.as_ref()?)))
+                .filter(|(_, ts)| ts.timestamps.len() >= 2)
+                .collect();
+            let [(set_idx, ts)] = series.as_slice() else {
+                continue;
+            };
+
+            let phases = phase_sequence(planned);
+            let (alignment, phases) = execute_phases(ts, &phases, &boundaries, options);
+            out.push(IntervalExecution {
+                path: format!(
+                    "workouts[{}].exercises[{}].sets[{}]",
+                    workout_idx, ex_idx, set_idx
+                ),
+                date: workout.date.clone(),
+                exercise: exercise.name.clone(),
+                alignment,
+                score: execution_score(&phases),
+                phases,
+            });
+        }
+    }
+    out
+}
+
+/// Row windows `(first, last)` for each phase, or `None` where the phase
+/// could not be placed
+fn align(
+    ts: &TimeSeriesData,
+    times: &[f64],
+    phases: &[PlannedPhase],
+    options: &ExecutionOptions,
+) -> (PhaseAlignment, Vec<Option<(usize, usize)>>) {
+    if options.alignment != PhaseAlignment::Offset {
+        let detected = detected_windows(ts, phases);
+        let matched = detected.iter().filter(|w| w.is_some()).count();
+        if options.alignment == PhaseAlignment::Intervals || matched * 2 >= phases.len().max(1) {
+            return (PhaseAlignment::Intervals, detected);
+        }
+    }
+    (
+        PhaseAlignment::Offset,
+        offset_windows(times, phases, options.offset_sec),
+    )
+}
+
+fn detected_windows(ts: &TimeSeriesData, phases: &[PlannedPhase]) -> Vec<Option<(usize, usize)>> {
+    let as_intervals: Vec<IntervalPhase> = phases
+        .iter()
+        .map(|p| IntervalPhase {
+            name: p.name.clone(),
+            duration_sec: p.duration_sec,
+            target_power_watts: p.target_power_watts,
+            target_hr_bpm: p.target_hr_bpm,
+            target_pace_sec_per_km: p.target_pace_sec_per_km,
+            cadence_rpm: p.cadence_rpm,
+        })
+        .collect();
+    let mut segments = detect_intervals(ts, &IntervalOptions::default());
+    match_phases(&mut segments, &as_intervals, 1);
+
+    let mut windows = vec![None; phases.len()];
+    for segment in &segments {
+        if let Some(matched) = &segment.phase {
+            windows[matched.phase_index] = Some((segment.first_row, segment.last_row));
+        }
+    }
+    windows
+}
+
+fn offset_windows(
+    times: &[f64],
+    phases: &[PlannedPhase],
+    offset: f64,
+) -> Vec<Option<(usize, usize)>> {
+    let Some(&origin) = times.first() else {
+        return vec![None; phases.len()];
+    };
+    let end = times.len() - 1;
+    let row_at = |t: f64| times.iter().position(|&x| x - origin >= t);
+    let mut start = offset;
+    phases
+        .iter()
+        .map(|phase| {
+            let stop = start + phase.duration_sec as f64;
+            let window = row_at(start)
+                .filter(|&first| first < end)
+                .map(|first| (first, row_at(stop).unwrap_or(end).max(first + 1)));
+            start = stop;
+            window
+        })
+        .collect()
+}
+
+/// Range `(low, high)` of a 1-based zone
+///
+/// Boundaries are upper limits as in [`super::zones`]; for pace they run
+/// from slowest to fastest, so the zone's faster limit is its low end.
+fn zone_range(zone: u8, bounds: &[u32], ascending: bool) -> Option<(Option<f64>, Option<f64>)> {
+    if zone == 0 || bounds.is_empty() {
+        return None;
+    }
+    let n = (zone as usize).min(bounds.len());
+    let lower = (n >= 2).then(|| bounds[n - 2] as f64);
+    let upper = (n < bounds.len()).then(|| bounds[n - 1] as f64);
+    Some(if ascending {
+        (lower, upper)
+    } else {
+        (upper, lower)
+    })
+}
+
+/// Average and time in range of a window's 1 Hz values
+///
+/// Pace is averaged through speed so stops do not dominate.
+fn metric(
+    (target, low, high): (Option<f64>, Option<f64>, Option<f64>),
+    values: &[f64],
+    pace: bool,
+) -> MetricExecution {
+    let inside = |v: f64| low.is_none_or(|l| v >= l) && high.is_none_or(|h| v <= h);
+    let (actual, percent_in_range) = if values.is_empty() {
+        (None, None)
+    } else {
+        let mean = if pace {
+            let speed = values.iter().map(|p| 1000.0 / p).sum::<f64>() / values.len() as f64;
+            1000.0 / speed
+        } else {
+            values.iter().sum::<f64>() / values.len() as f64
+        };
+        let count = values.iter().filter(|&&v| inside(v)).count();
+        (Some(mean), Some(count as f64 * 100.0 / values.len() as f64))
+    };
+    MetricExecution {
+        target,
+        low,
+        high,
+        actual,
+        percent_in_range,
+    }
+}
+
+/// Readings from row `first` up to (not including) row `last`, at 1 Hz
+fn window_1hz(times: &[f64], column: &[Option<f64>], first: usize, last: usize) -> Vec<f64> {
+    if column.len() != times.len() {
+        return Vec::new();
+    }
+    // The reading at `last` only closes the window
+    let samples: Vec<Sample> = (first..=last)
+        .filter_map(|row| Some((times[row], column[row]?)))
+        .collect();
+    let mut values = resample_1hz(&samples);
+    if samples.last().is_some_and(|&(t, _)| t == times[last]) && values.len() > 1 {
+        values.pop();
+    }
+    values
+}
+
+/// Per-row readings for each targeted stream; zeros (dropouts, stopped)
+/// are missing
+struct Streams {
+    power: Vec<Option<f64>>,
+    heart_rate: Vec<Option<f64>>,
+    pace: Vec<Option<f64>>,
+    cadence: Vec<Option<f64>>,
+}
+
+impl Streams {
+    fn new(ts: &TimeSeriesData) -> Self {
+        let whole = |column: &Option<Vec<u32>>, keep_zero: bool| -> Vec<Option<f64>> {
+            column
+                .iter()
+                .flatten()
+                .map(|&v| (keep_zero || v > 0).then_some(v as f64))
+                .collect()
+        };
+        Self {
+            power: whole(&ts.power, true),
+            heart_rate: whole(&ts.heart_rate, false),
+            pace: ts
+                .speed_mps
+                .iter()
+                .flatten()
+                .map(|&s| (s.is_finite() && s > 0.0).then(|| 1000.0 / s))
+                .collect(),
+            cadence: whole(&ts.cadence, true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::series::fixtures::{interval_history, interval_ride};
+
+    fn plan(exercise: &str) -> WpsPlan {
+        crate::plan::parse(&format!(
+            r#"
+plan_version: 1
+meta:
+  title: Intervals
+  athlete_profile:
+    ftp_watts: 300
+cycle:
+  days:
+    - id: intervals
+      exercises:
+{}
+"#,
+            exercise
+        ))
+        .unwrap()
+    }
+
+    const REPEATS: &str = r#"
+        - name: Repeats
+          modality: interval
+          target_sets: 3
+          interval_phases:
+            - { name: Hard, duration_sec: 60, target_power_watts: 300 }
+            - { name: Easy, duration_sec: 60, target_power_watts: 150 }
+"#;
+
+    #[test]
+    fn test_phase_sequence() {
+        let plan = plan(REPEATS);
+        let phases = phase_sequence(&plan.cycle.days[0].exercises[0]);
+        assert_eq!(phases.len(), 6);
+        assert_eq!((phases[5].name.as_str(), phases[5].repetition), ("Easy", 3));
+
+        let plan = self::plan(
+            r#"
+        - name: Steady
+          modality: interval
+          zones:
+            - { zone: 2, duration_sec: 600 }
+            - { zone: 4 }
+"#,
+        );
+        let phases = phase_sequence(&plan.cycle.days[0].exercises[0]);
+        assert_eq!(phases.len(), 1);
+        assert_eq!(phases[0].zone, Some(2));
+    }
+
+    #[test]
+    fn test_perfect_execution_by_detected_intervals() {
+        let found = score_executions(
+            &plan(REPEATS),
+            &interval_history(interval_ride(120, 150, &[300, 300, 300])),
+            &DateRange::all(),
+            &ExecutionOptions::default(),
+        );
+        assert_eq!(found.len(), 1);
+        let execution = &found[0];
+        assert_eq!(execution.alignment, PhaseAlignment::Intervals);
+        assert_eq!(execution.phases.len(), 6);
+        let hard = &execution.phases[0];
+        assert_eq!(hard.start_sec, Some(120.0));
+        let power = hard.power.as_ref().unwrap();
+        assert_eq!(power.actual, Some(300.0));
+        assert_eq!(power.percent_in_range, Some(100.0));
+        // The final recovery runs a second short of the plan
+        assert!(execution.score > 99.0, "{}", execution.score);
+    }
+
+    #[test]
+    fn test_fading_intervals_by_offset() {
+        let options = ExecutionOptions {
+            alignment: PhaseAlignment::Offset,
+            offset_sec: 120.0,
+            ..Default::default()
+        };
+        let found = score_executions(
+            &plan(REPEATS),
+            &interval_history(interval_ride(120, 150, &[300, 280, 250])),
+            &DateRange::all(),
+            &options,
+        );
+        let execution = &found[0];
+        assert_eq!(execution.alignment, PhaseAlignment::Offset);
+        let in_range: Vec<f64> = execution
+            .phases
+            .iter()
+            .step_by(2)
+            .map(|p| p.power.as_ref().unwrap().percent_in_range.unwrap())
+            .collect();
+        // The band is 285–315 W
+        assert_eq!(in_range, vec![100.0, 0.0, 0.0]);
+        assert!((execution.score - 66.4).abs() < 0.5, "{}", execution.score);
+    }
+
+    #[test]
+    fn test_zone_targets_and_missing_phases() {
+        let plan = plan(
+            r#"
+        - name: Repeats
+          modality: interval
+          zones:
+            - { zone: 2, duration_sec: 300 }
+            - { zone: 6, duration_sec: 600 }
+"#,
+        );
+        let found = score_executions(
+            &plan,
+            &interval_history(interval_ride(120, 150, &[300, 300, 300])),
+            &DateRange::all(),
+            &ExecutionOptions {
+                alignment: PhaseAlignment::Offset,
+                ..Default::default()
+            },
+        );
+        let phases = &found[0].phases;
+        // Zone 2 is 165–225 W at 300 W FTP; the first 5 minutes never hit it
+        let zone2 = phases[0].power.as_ref().unwrap();
+        assert_eq!((zone2.low, zone2.high), (Some(165.0), Some(225.0)));
+        assert_eq!(zone2.percent_in_range, Some(0.0));
+        // Zone 6 is open-ended above 360 W
+        let zone6 = phases[1].power.as_ref().unwrap();
+        assert_eq!((zone6.low, zone6.high), (Some(360.0), None));
+        // The ride ends 3 minutes into it
+        assert_eq!(phases[1].actual_duration_sec, Some(179.0));
+
+        // Nothing recorded past the end
+        let late = execute_phases(
+            &interval_ride(120, 150, &[300, 300, 300]),
+            &phase_sequence(&plan.cycle.days[0].exercises[0]),
+            &ZoneBoundaries::default(),
+            &ExecutionOptions {
+                alignment: PhaseAlignment::Offset,
+                offset_sec: 600.0,
+                ..Default::default()
+            },
+        );
+        assert_eq!(late.1[0].score, 0.0);
+        assert!(late.1[0].start_sec.is_none());
+    }
+}
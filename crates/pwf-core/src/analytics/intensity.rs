@@ -0,0 +1,529 @@
+//! Training intensity distribution in a three-zone model
+//!
+//! Endurance training is usually audited in three zones: below the first
+//! lactate/ventilatory threshold (low), between the thresholds (moderate)
+//! and above the second (high). Each session's time in zones, as stored in
+//! `time_in_zones` or computed from its time series, is collapsed into
+//! those three: the first `low_zones` zones count as low, the next
+//! `moderate_zones` as moderate and the rest as high. The defaults depend on
+//! how many zones the session records (see [`default_zone_split`]).
+//!
+//! Weeks are classified from their zone shares and the polarization index
+//! of Treff et al. (2019), `log10(low / moderate × high × 100)` with shares
+//! as fractions.
+
+use super::date_range::{workout_date, DateRange};
+use super::zones::{segment_time_in_zones, workout_time_in_zones, ZoneBoundaries};
+use crate::history::{TimeInZones, Workout, WorkoutTelemetry, WpsHistory};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+
+/// Polarization index above which a low-dominant week counts as polarized
+pub const POLARIZED_INDEX: f64 = 2.0;
+
+/// Zone stream used for the distribution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntensityStream {
+    Power,
+    Pace,
+    HeartRate,
+}
+
+/// Shape of a three-zone distribution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionModel {
+    /// Mostly low, more high than moderate, polarization index above 2
+    Polarized,
+    /// Time decreasing from low to moderate to high
+    Pyramidal,
+    /// Moderate intensity dominates
+    Threshold,
+    /// High intensity dominates
+    HighIntensity,
+    /// Mostly low with more high than moderate work but an index of 2 or
+    /// less, or no zone clearly ahead
+    Unclassified,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IntensityOptions {
+    /// Stream to use; `None` takes the first of power, pace and heart rate
+    /// that a session has zone times for
+    pub stream: Option<IntensityStream>,
+    /// Used to compute zone times for sessions without stored ones
+    pub boundaries: ZoneBoundaries,
+    /// Override [`default_zone_split`]
+    pub low_zones: Option<usize>,
+    pub moderate_zones: Option<usize>,
+}
+
+/// Seconds and shares of the three intensity zones
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IntensityDistribution {
+    pub low_sec: f64,
+    pub moderate_sec: f64,
+    pub high_sec: f64,
+    pub low_percent: f64,
+    pub moderate_percent: f64,
+    pub high_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub polarization_index: Option<f64>,
+    pub model: DistributionModel,
+}
+
+impl IntensityDistribution {
+    /// Distribution of the given seconds; `None` when they add up to zero
+    pub fn from_seconds(low: f64, moderate: f64, high: f64) -> Option<Self> {
+        let total = low + moderate + high;
+        if total <= 0.0 {
+            return None;
+        }
+        let (l, m, h) = (low / total, moderate / total, high / total);
+        let polarization_index = polarization_index(l, m, h);
+        Some(Self {
+            low_sec: low,
+            moderate_sec: moderate,
+            high_sec: high,
+            low_percent: l * 100.0,
+            moderate_percent: m * 100.0,
+            high_percent: h * 100.0,
+            polarization_index,
+            model: classify(l, m, h, polarization_index),
+        })
+    }
+}
+
+/// Distribution for one Monday-to-Sunday week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeeklyIntensity {
+    /// Monday of the week
+    pub week_start: NaiveDate,
+    /// Sessions (or multi-sport segments) with zone times
+    pub sessions: u32,
+    #[serde(flatten)]
+    pub distribution: IntensityDistribution,
+}
+
+/// Change per week, from a least-squares fit over the weeks with data
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IntensityTrend {
+    pub low_percent_per_week: f64,
+    pub moderate_percent_per_week: f64,
+    pub high_percent_per_week: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub polarization_index_per_week: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SportIntensity {
+    /// The session's sport, or `other` when none is recorded
+    pub sport: String,
+    pub overall: IntensityDistribution,
+    pub weeks: Vec<WeeklyIntensity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trend: Option<IntensityTrend>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IntensityReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overall: Option<IntensityDistribution>,
+    pub weeks: Vec<WeeklyIntensity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trend: Option<IntensityTrend>,
+    pub by_sport: Vec<SportIntensity>,
+}
+
+/// How many zones count as low and moderate for a zone system of
+/// `zone_count` zones
+///
+/// Three zones map one to one. Four- and five-zone systems put zones 1–2
+/// below the first threshold and zone 3 between the thresholds. Six- and
+/// seven-zone systems (this crate's defaults, Coggan power) treat zones 3–4
+/// (tempo and threshold) as moderate.
+pub fn default_zone_split(zone_count: usize) -> (usize, usize) {
+    match zone_count {
+        0 => (0, 0),
+        1 => (1, 0),
+        2 | 3 => (1, 1),
+        4 | 5 => (2, 1),
+        6 | 7 => (2, 2),
+        n => (n / 3, n / 3),
+    }
+}
+
+/// Collapse per-zone seconds into low, moderate and high
+pub fn three_zone_seconds(zones: &[u32], low_zones: usize, moderate_zones: usize) -> [f64; 3] {
+    let mut out = [0.0; 3];
+    for (i, &seconds) in zones.iter().enumerate() {
+        let bucket = if i < low_zones {
+            0
+        } else if i < low_zones + moderate_zones {
+            1
+        } else {
+            2
+        };
+        out[bucket] += seconds as f64;
+    }
+    out
+}
+
+/// Treff's polarization index from zone shares (fractions)
+///
+/// Following Treff et al., a moderate share of zero is taken as 0.01 and
+/// no high-intensity time gives an index of 0. Returns `None` without any
+/// low-intensity time.
+pub fn polarization_index(low: f64, moderate: f64, high: f64) -> Option<f64> {
+    if low <= 0.0 {
+        return None;
+    }
+    if high <= 0.0 {
+        return Some(0.0);
+    }
+    let moderate = if moderate <= 0.0 { 0.01 } else { moderate };
+    Some((low / moderate * high * 100.0).log10())
+}
+
+fn classify(low: f64, moderate: f64, high: f64, index: Option<f64>) -> DistributionModel {
+    if low > high && high > moderate && index.is_some_and(|pi| pi > POLARIZED_INDEX) {
+        DistributionModel::Polarized
+    } else if low > moderate && moderate >= high {
+        DistributionModel::Pyramidal
+    } else if moderate > low && moderate >= high {
+        DistributionModel::Threshold
+    } else if high > low && high > moderate {
+        DistributionModel::HighIntensity
+    } else {
+        DistributionModel::Unclassified
+    }
+}
+
+/// Three-zone intensity distribution per week, overall and per sport
+pub fn intensity_distribution(
+    history: &WpsHistory,
+    range: &DateRange,
+    options: &IntensityOptions,
+) -> IntensityReport {
+    // (week, sport, seconds) for every session with zone times
+    let mut sessions: Vec<(NaiveDate, String, [f64; 3])> = Vec::new();
+    for workout in range.workouts(history) {
+        let Some(date) = workout_date(workout) else {
+            continue;
+        };
+        let week_start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        for (sport, zones) in session_zones(workout, &options.boundaries) {
+            if let Some(seconds) = three_zones(&zones, options) {
+                sessions.push((week_start, sport, seconds));
+            }
+        }
+    }
+
+    let mut sports: Vec<String> = Vec::new();
+    for (_, sport, _) in &sessions {
+        if !sports.contains(sport) {
+            sports.push(sport.clone());
+        }
+    }
+    let (overall, weeks, trend) = summarize(sessions.iter().map(|(w, _, s)| (*w, *s)));
+    let by_sport = sports
+        .into_iter()
+        .filter_map(|sport| {
+            let (overall, weeks, trend) = summarize(
+                sessions
+                    .iter()
+                    .filter(|(_, s, _)| *s == sport)
+                    .map(|(w, _, s)| (*w, *s)),
+            );
+            Some(SportIntensity {
+                sport,
+                overall: overall?,
+                weeks,
+                trend,
+            })
+        })
+        .collect();
+
+    IntensityReport {
+        overall,
+        weeks,
+        trend,
+        by_sport,
+    }
+}
+
+type Summary = (
+    Option<IntensityDistribution>,
+    Vec<WeeklyIntensity>,
+    Option<IntensityTrend>,
+);
+
+fn summarize(sessions: impl Iterator<Item = (NaiveDate, [f64; 3])>) -> Summary {
+    let mut weeks: Vec<(NaiveDate, u32, [f64; 3])> = Vec::new();
+    for (week_start, seconds) in sessions {
+        let index = match weeks.iter().position(|(w, _, _)| *w == week_start) {
+            Some(index) => index,
+            None => {
+                weeks.push((week_start, 0, [0.0; 3]));
+                weeks.len() - 1
+            }
+        };
+        let entry = &mut weeks[index];
+        entry.1 += 1;
+        for (total, s) in entry.2.iter_mut().zip(seconds) {
+            *total += s;
+        }
+    }
+    weeks.sort_by_key(|(w, _, _)| *w);
+
+    let mut total = [0.0; 3];
+    for (_, _, seconds) in &weeks {
+        for (t, s) in total.iter_mut().zip(seconds) {
+            *t += s;
+        }
+    }
+    let weeks: Vec<WeeklyIntensity> = weeks
+        .into_iter()
+        .filter_map(|(week_start, sessions, [l, m, h])| {
+            Some(WeeklyIntensity {
+                week_start,
+                sessions,
+                distribution: IntensityDistribution::from_seconds(l, m, h)?,
+            })
+        })
+        .collect();
+    let trend = trend(&weeks);
+    (
+        IntensityDistribution::from_seconds(total[0], total[1], total[2]),
+        weeks,
+        trend,
+    )
+}
+
+fn trend(weeks: &[WeeklyIntensity]) -> Option<IntensityTrend> {
+    let first = weeks.first()?.week_start;
+    let x: Vec<f64> = weeks
+        .iter()
+        .map(|w| (w.week_start - first).num_days() as f64 / 7.0)
+        .collect();
+    let slope_of = |value: &dyn Fn(&IntensityDistribution) -> Option<f64>| {
+        let points: Vec<(f64, f64)> = x
+            .iter()
+            .zip(weeks)
+            .filter_map(|(&x, w)| Some((x, value(&w.distribution)?)))
+            .collect();
+        slope(&points)
+    };
+    Some(IntensityTrend {
+        low_percent_per_week: slope_of(&|d| Some(d.low_percent))?,
+        moderate_percent_per_week: slope_of(&|d| Some(d.moderate_percent))?,
+        high_percent_per_week: slope_of(&|d| Some(d.high_percent))?,
+        polarization_index_per_week: slope_of(&|d| d.polarization_index),
+    })
+}
+
+/// Least-squares slope; `None` with fewer than two distinct x values
+fn slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    if n < 2.0 {
+        return None;
+    }
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    (sxx > 0.0).then(|| sxy / sxx)
+}
+
+/// Zone times per session with its sport: one entry per sport segment for
+/// multi-sport workouts, otherwise one for the workout
+///
+/// Stored `time_in_zones` is used as is; otherwise zone times are computed
+/// from the time series with the stored boundaries or `boundaries`.
+fn session_zones(workout: &Workout, boundaries: &ZoneBoundaries) -> Vec<(String, TimeInZones)> {
+    let stored = |telemetry: Option<&WorkoutTelemetry>| {
+        telemetry
+            .and_then(|t| t.time_in_zones.clone())
+            .filter(has_zone_times)
+    };
+    let recorded_bounds = workout
+        .telemetry
+        .as_ref()
+        .and_then(|t| t.time_in_zones.as_ref())
+        .map(ZoneBoundaries::from_time_in_zones)
+        .unwrap_or_default()
+        .or(boundaries);
+
+    let segments = workout.sport_segments.as_deref().unwrap_or_default();
+    if !segments.is_empty() {
+        return segments
+            .iter()
+            .map(|segment| {
+                let zones = stored(segment.telemetry.as_ref())
+                    .unwrap_or_else(|| segment_time_in_zones(workout, segment, &recorded_bounds));
+                (segment.sport.to_string(), zones)
+            })
+            .collect();
+    }
+
+    let sport = workout
+        .sport
+        .or_else(|| workout.exercises.iter().find_map(|e| e.sport))
+        .map_or_else(|| "other".to_string(), |s| s.to_string());
+    let zones = stored(workout.telemetry.as_ref())
+        .unwrap_or_else(|| workout_time_in_zones(workout, &recorded_bounds));
+    vec![(sport, zones)]
+}
+
+fn has_zone_times(zones: &TimeInZones) -> bool {
+    zones.hr_zones_sec.is_some()
+        || zones.power_zones_sec.is_some()
+        || zones.pace_zones_sec.is_some()
+}
+
+fn three_zones(zones: &TimeInZones, options: &IntensityOptions) -> Option<[f64; 3]> {
+    let column = |stream| match stream {
+        IntensityStream::Power => zones.power_zones_sec.as_ref(),
+        IntensityStream::Pace => zones.pace_zones_sec.as_ref(),
+        IntensityStream::HeartRate => zones.hr_zones_sec.as_ref(),
+    };
+    let seconds = match options.stream {
+        Some(stream) => column(stream),
+        None => [
+            IntensityStream::Power,
+            IntensityStream::Pace,
+            IntensityStream::HeartRate,
+        ]
+        .into_iter()
+        .find_map(column),
+    }?;
+    let (low, moderate) = default_zone_split(seconds.len());
+    let seconds = three_zone_seconds(
+        seconds,
+        options.low_zones.unwrap_or(low),
+        options.moderate_zones.unwrap_or(moderate),
+    );
+    (seconds.iter().sum::<f64>() > 0.0).then_some(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zone_split_and_collapse() {
+        assert_eq!(default_zone_split(5), (2, 1));
+        assert_eq!(default_zone_split(6), (2, 2));
+        assert_eq!(
+            three_zone_seconds(&[100, 200, 300, 400, 500], 2, 1),
+            [300.0, 300.0, 900.0]
+        );
+    }
+
+    #[test]
+    fn test_polarization_index_and_models() {
+        // Seiler's 80/20 with little moderate work
+        let polarized = IntensityDistribution::from_seconds(80.0, 5.0, 15.0).unwrap();
+        let pi = polarized.polarization_index.unwrap();
+        assert!((pi - (0.8f64 / 0.05 * 0.15 * 100.0).log10()).abs() < 1e-9);
+        assert_eq!(polarized.model, DistributionModel::Polarized);
+
+        let pyramidal = IntensityDistribution::from_seconds(70.0, 20.0, 10.0).unwrap();
+        assert_eq!(pyramidal.model, DistributionModel::Pyramidal);
+        let threshold = IntensityDistribution::from_seconds(30.0, 50.0, 20.0).unwrap();
+        assert_eq!(threshold.model, DistributionModel::Threshold);
+        let hiit = IntensityDistribution::from_seconds(20.0, 20.0, 60.0).unwrap();
+        assert_eq!(hiit.model, DistributionModel::HighIntensity);
+
+        assert_eq!(polarization_index(0.9, 0.1, 0.0), Some(0.0));
+        assert_eq!(polarization_index(0.0, 0.5, 0.5), None);
+        assert!(IntensityDistribution::from_seconds(0.0, 0.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_weekly_distribution_per_sport() {
+        let history = crate::history::parse(
+            r#"
+history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+workouts:
+  - date: "2025-03-03"
+    sport: running
+    exercises: []
+    telemetry:
+      time_in_zones:
+        hr_zones_sec: [1000, 2600, 100, 50, 250]
+  - date: "2025-03-05"
+    sport: cycling
+    exercises: []
+    telemetry:
+      time_in_zones:
+        power_zones_sec: [600, 1800, 600, 600, 0, 0]
+        hr_zones_sec: [3600]
+  - date: "2025-03-11"
+    sport: running
+    exercises: []
+    telemetry:
+      time_in_zones:
+        hr_zones_sec: [1000, 1000, 1000, 500, 500]
+  - date: "2025-03-12"
+    exercises:
+      - name: Ride
+        sets:
+          - telemetry:
+              time_series:
+                timestamps: ["2025-03-12T07:00:00Z", "2025-03-12T07:00:01Z", "2025-03-12T07:00:02Z", "2025-03-12T07:00:03Z", "2025-03-12T07:00:04Z", "2025-03-12T07:00:05Z", "2025-03-12T07:00:06Z", "2025-03-12T07:00:07Z", "2025-03-12T07:00:08Z", "2025-03-12T07:00:09Z"]
+                power: [100, 100, 100, 100, 100, 300, 300, 300, 300, 300]
+"#,
+        )
+        .unwrap();
+        let options = IntensityOptions {
+            boundaries: ZoneBoundaries {
+                power: Some(vec![150, 250, 350]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let report = intensity_distribution(&history, &DateRange::all(), &options);
+
+        assert_eq!(report.weeks.len(), 2);
+        let week1 = &report.weeks[0];
+        assert_eq!(week1.sessions, 2);
+        // Run: 3600 low, 100 moderate, 300 high; ride by power: 2400/1200/0
+        assert_eq!(
+            (
+                week1.distribution.low_sec,
+                week1.distribution.moderate_sec,
+                week1.distribution.high_sec
+            ),
+            (6000.0, 1300.0, 300.0)
+        );
+
+        let sports: Vec<&str> = report.by_sport.iter().map(|s| s.sport.as_str()).collect();
+        assert_eq!(sports, vec!["running", "cycling", "other"]);
+        let running = &report.by_sport[0];
+        assert_eq!(
+            running.weeks[0].distribution.model,
+            DistributionModel::Polarized
+        );
+        assert_eq!(
+            running.weeks[1].distribution.model,
+            DistributionModel::Pyramidal
+        );
+        let trend = running.trend.as_ref().unwrap();
+        assert!(
+            (trend.low_percent_per_week - (2000.0 / 4000.0 - 3600.0 / 4000.0) * 100.0).abs() < 1e-9
+        );
+        // The untagged ride is computed from its time series with the given zones
+        let other = &report.by_sport[2].overall;
+        assert_eq!((other.low_sec, other.high_sec), (5.0, 5.0));
+
+        let hr_only = IntensityOptions {
+            stream: Some(IntensityStream::HeartRate),
+            ..Default::default()
+        };
+        let report = intensity_distribution(&history, &DateRange::all(), &hr_only);
+        assert_eq!(report.by_sport[1].overall.low_sec, 3600.0);
+    }
+}
@@ -0,0 +1,763 @@
+//! Interval detection in single-lap recordings
+//!
+//! GPX imports and many FIT files store a whole session as one set, so a
+//! 6×800m workout arrives as a single blob of telemetry. This module finds
+//! the work and recovery stretches inside a set's time series and can
+//! re-emit them as separate sets.
+//!
+//! Detection smooths the chosen stream (power, speed or heart rate), puts a
+//! threshold halfway between its typical low and high values and splits
+//! wherever the stream crosses it, with hysteresis so noise around the
+//! threshold does not produce extra intervals. Segments shorter than
+//! [`IntervalOptions::min_segment_sec`] are absorbed by their neighbours.
+//! Heart rate lags effort by several seconds, so its boundaries come late;
+//! prefer power or speed when recorded. Alternatively, recordings can be
+//! cut into fixed-distance laps.
+
+use super::date_range::workout_date;
+use super::series::{parse_timestamp, series_times, MAX_GAP_SEC};
+use super::summary::fill_set_summary;
+use crate::history::{
+    CompletedExercise, CompletedSet, SetTelemetry, TimeSeriesData, Workout, WpsHistory,
+};
+use crate::plan::{expand_plan, IntervalPhase, PlanExercise, WpsPlan};
+use serde::Serialize;
+
+/// Default window for smoothing the stream before thresholding
+pub const DEFAULT_SMOOTHING_SEC: f64 = 10.0;
+
+/// Default shortest work or recovery segment
+pub const DEFAULT_MIN_SEGMENT_SEC: f64 = 20.0;
+
+/// Streams whose typical high and low differ by less than this fraction of
+/// the high are treated as a steady effort with no intervals
+pub const MIN_CONTRAST: f64 = 0.15;
+
+/// Percentiles taken as the stream's typical low and high
+const LOW_PERCENTILE: f64 = 0.1;
+const HIGH_PERCENTILE: f64 = 0.9;
+
+/// Hysteresis band around the threshold, as a fraction of high − low
+const HYSTERESIS: f64 = 0.1;
+
+/// Name fragments that mark a planned phase as recovery when the phases
+/// have no comparable targets
+const RECOVERY_NAMES: &[&str] = &["recover", "rest", "easy", "float"];
+
+/// Stream used to find intervals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntervalMetric {
+    Power,
+    Speed,
+    HeartRate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntervalOptions {
+    /// Stream to split on; `None` uses the first of power, speed and heart
+    /// rate that the time series records
+    pub metric: Option<IntervalMetric>,
+    pub smoothing_sec: f64,
+    pub min_segment_sec: f64,
+    /// Cut a lap every this many metres instead of detecting intervals
+    pub auto_lap_m: Option<f64>,
+}
+
+impl Default for IntervalOptions {
+    fn default() -> Self {
+        Self {
+            metric: None,
+            smoothing_sec: DEFAULT_SMOOTHING_SEC,
+            min_segment_sec: DEFAULT_MIN_SEGMENT_SEC,
+            auto_lap_m: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    Work,
+    Recovery,
+    /// A fixed-distance auto-lap
+    Lap,
+}
+
+/// The planned phase a detected segment was matched to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhaseMatch {
+    pub name: String,
+    /// Position of the phase in the plan's phase list
+    pub phase_index: usize,
+    /// 1-based repetition of the phase list
+    pub repetition: u32,
+    pub planned_duration_sec: u32,
+    /// Actual minus planned duration
+    pub duration_diff_sec: f64,
+}
+
+/// One work, recovery or lap stretch of a time series
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IntervalSegment {
+    pub kind: SegmentKind,
+    /// First and last row of the segment; neighbouring segments share their
+    /// boundary row
+    pub first_row: usize,
+    pub last_row: usize,
+    /// Start time relative to the first row
+    pub start_sec: f64,
+    pub duration_sec: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance_m: Option<f64>,
+    /// Mean of the detection stream over the segment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<PhaseMatch>,
+}
+
+/// The intervals found in one set
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SetIntervals {
+    /// Location of the set, e.g. `workouts[0].exercises[1].sets[0]`
+    pub path: String,
+    pub date: String,
+    pub exercise: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metric: Option<IntervalMetric>,
+    pub segments: Vec<IntervalSegment>,
+    /// Planned phases, as `name #repetition`, that no segment matched
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unmatched_phases: Vec<String>,
+}
+
+impl SetIntervals {
+    pub fn work(&self) -> impl Iterator<Item = &IntervalSegment> {
+        self.segments.iter().filter(|s| s.kind == SegmentKind::Work)
+    }
+}
+
+/// Split a time series into work/recovery segments, or into laps when
+/// `auto_lap_m` is set
+///
+/// Returns no segments when the series is too short, lacks the stream, or
+/// holds a steady effort.
+pub fn detect_intervals(ts: &TimeSeriesData, options: &IntervalOptions) -> Vec<IntervalSegment> {
+    let times = series_times(ts, 0.0);
+    if times.len() < 2 {
+        return Vec::new();
+    }
+    if let Some(lap_m) = options.auto_lap_m {
+        return auto_laps(ts, &times, lap_m);
+    }
+    let Some(metric) = choose_metric(ts, options.metric) else {
+        return Vec::new();
+    };
+    let values = filled(&metric_values(ts, metric));
+    let smoothed = smooth(&times, &values, options.smoothing_sec);
+
+    let mut sorted = smoothed.clone();
+    sorted.sort_by(f64::total_cmp);
+    let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+    let (low, high) = (percentile(LOW_PERCENTILE), percentile(HIGH_PERCENTILE));
+    if high <= 0.0 || (high - low) / high < MIN_CONTRAST {
+        return Vec::new();
+    }
+    let threshold = (low + high) / 2.0;
+    let band = (high - low) * HYSTERESIS;
+
+    // Runs of one kind as (kind, first row)
+    let mut working = smoothed[0] > threshold;
+    let mut runs: Vec<(SegmentKind, usize)> = vec![(kind(working), 0)];
+    for (row, &value) in smoothed.iter().enumerate().skip(1) {
+        if (working && value < threshold - band) || (!working && value > threshold + band) {
+            working = !working;
+            // Start the run where the stream crossed the threshold itself
+            let previous = runs.last().map_or(0, |r| r.1);
+            let mut start = row;
+            while start > previous + 1 && (smoothed[start - 1] > threshold) == working {
+                start -= 1;
+            }
+            runs.push((kind(working), start));
+        }
+    }
+    merge_short_runs(&mut runs, &times, options.min_segment_sec);
+
+    let mut segments = build_segments(ts, &times, &runs);
+    for segment in &mut segments {
+        // Each row covers the time up to the next, so the shared last row
+        // belongs to the following segment
+        let rows = match &values[segment.first_row..segment.last_row] {
+            [] => &values[segment.first_row..=segment.last_row],
+            rows => rows,
+        };
+        segment.average = Some(rows.iter().sum::<f64>() / rows.len() as f64);
+    }
+    if segments.len() < 2 {
+        return Vec::new();
+    }
+    segments
+}
+
+/// Match detected segments against planned phases in order
+///
+/// The phases are repeated `repetitions` times. Matching starts at the first
+/// work segment, so a warm-up before it is left unmatched, and pairs work
+/// with work and recovery with recovery; a planned phase with no segment of
+/// its kind at that point is skipped. Returns the phases left unmatched.
+pub fn match_phases(
+    segments: &mut [IntervalSegment],
+    phases: &[IntervalPhase],
+    repetitions: u32,
+) -> Vec<String> {
+    let planned: Vec<(u32, usize, &IntervalPhase, SegmentKind)> = (1..=repetitions.max(1))
+        .flat_map(|rep| phases.iter().enumerate().map(move |(i, p)| (rep, i, p)))
+        .map(|(rep, i, phase)| (rep, i, phase, phase_kind(phase, phases)))
+        .collect();
+
+    let mut unmatched = Vec::new();
+    let mut next = segments
+        .iter()
+        .position(|s| s.kind == SegmentKind::Work)
+        .unwrap_or(segments.len());
+    for (rep, phase_index, phase, kind) in planned {
+        match segments.get_mut(next) {
+            Some(segment) if segment.kind == kind => {
+                segment.phase = Some(PhaseMatch {
+                    name: phase.name.clone(),
+                    phase_index,
+                    repetition: rep,
+                    planned_duration_sec: phase.duration_sec,
+                    duration_diff_sec: segment.duration_sec - phase.duration_sec as f64,
+                });
+                next += 1;
+            }
+            _ => unmatched.push(format!("{} #{}", phase.name, rep)),
+        }
+    }
+    unmatched
+}
+
+/// The interval phases a plan prescribes for a logged exercise, with the
+/// number of repetitions (`target_sets`, default 1)
+///
+/// The plan day is found through the workout's `plan_day_id`, or else by
+/// scheduled date. Within the day the exercise is matched by id or name,
+/// falling back to the day's only exercise with `interval_phases`.
+pub fn planned_phases<'a>(
+    plan: &'a WpsPlan,
+    workout: &Workout,
+    exercise: &CompletedExercise,
+) -> Option<(&'a [IntervalPhase], u32)> {
+    let planned = planned_exercise(plan, workout, exercise, |e| {
+        e.interval_phases.as_ref().is_some_and(|p| !p.is_empty())
+    })?;
+    Some((
+        planned.interval_phases.as_deref()?,
+        planned.target_sets.unwrap_or(1),
+    ))
+}
+
+/// The plan exercise a logged exercise carries out, among those of its
+/// plan day that satisfy `candidate`
+pub(crate) fn planned_exercise<'a>(
+    plan: &'a WpsPlan,
+    workout: &Workout,
+    exercise: &CompletedExercise,
+    candidate: impl Fn(&PlanExercise) -> bool,
+) -> Option<&'a PlanExercise> {
+    let day = match workout.plan_day_id.as_deref() {
+        Some(day_id) => plan
+            .cycle
+            .days
+            .iter()
+            .find(|d| d.id.as_deref() == Some(day_id))?,
+        None => {
+            let date = workout_date(workout)?;
+            let session = expand_plan(plan)
+                .into_iter()
+                .find(|s| s.scheduled_date == Some(date))?;
+            plan.cycle.days.get(session.day_index)?
+        }
+    };
+
+    let candidates: Vec<&PlanExercise> = day.exercises.iter().filter(|e| candidate(e)).collect();
+    let same_name = |e: &&&PlanExercise| {
+        e.name
+            .as_deref()
+            .is_some_and(|n| n.trim().eq_ignore_ascii_case(exercise.name.trim()))
+    };
+    candidates
+        .iter()
+        .find(|e| e.id.is_some() && e.id == exercise.id)
+        .or_else(|| candidates.iter().find(same_name))
+        .or(match candidates.as_slice() {
+            [only] => Some(only),
+            _ => None,
+        })
+        .copied()
+}
+
+/// Intervals in every set of a history that splits into two or more
+/// segments, matched against `plan` when given
+pub fn analyze_intervals(
+    history: &WpsHistory,
+    options: &IntervalOptions,
+    plan: Option<&WpsPlan>,
+) -> Vec<SetIntervals> {
+    let mut out = Vec::new();
+    for (workout_idx, workout) in history.workouts.iter().enumerate() {
+        for (ex_idx, exercise) in workout.exercises.iter().enumerate() {
+            let phases = plan.and_then(|plan| planned_phases(plan, workout, exercise));
+            for (set_idx, set) in exercise.sets.iter().enumerate() {
+                let Some(ts) = set_series(set) else {
+                    continue;
+                };
+                let mut segments = detect_intervals(ts, options);
+                if segments.is_empty() {
+                    continue;
+                }
+                let unmatched_phases = match phases {
+                    Some((phases, repetitions)) if options.auto_lap_m.is_none() => {
+                        match_phases(&mut segments, phases, repetitions)
+                    }
+                    _ => Vec::new(),
+                };
+                out.push(SetIntervals {
+                    path: format!(
+                        "workouts[{}].exercises[{}].sets[{}]",
+                        workout_idx, ex_idx, set_idx
+                    ),
+                    date: workout.date.clone(),
+                    exercise: exercise.name.clone(),
+                    metric: match options.auto_lap_m {
+                        Some(_) => None,
+                        None => choose_metric(ts, options.metric),
+                    },
+                    segments,
+                    unmatched_phases,
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Replace every set that splits into intervals with one set per segment
+///
+/// Each new set carries its slice of the time series, summary telemetry
+/// recomputed from it, `duration_sec`, `distance_meters` and a
+/// `completed_at` taken from its last row. Work and lap sets keep the
+/// original `set_type`; notes name the matched phase or the segment kind.
+/// Sets in an exercise that was split are renumbered. Returns how many sets
+/// were split.
+pub fn split_intervals(
+    history: &mut WpsHistory,
+    options: &IntervalOptions,
+    plan: Option<&WpsPlan>,
+) -> usize {
+    let found = analyze_intervals(history, options, plan);
+    let mut split = 0;
+
+    for (workout_idx, workout) in history.workouts.iter_mut().enumerate() {
+        for (ex_idx, exercise) in workout.exercises.iter_mut().enumerate() {
+            let prefix = format!("workouts[{}].exercises[{}].sets[", workout_idx, ex_idx);
+            let mut sets = Vec::with_capacity(exercise.sets.len());
+            let mut changed = false;
+
+            for (set_idx, set) in exercise.sets.drain(..).enumerate() {
+                let path = format!("{}{}]", prefix, set_idx);
+                let (Some(intervals), Some(ts)) =
+                    (found.iter().find(|i| i.path == path), set_series(&set))
+                else {
+                    sets.push(set);
+                    continue;
+                };
+                sets.extend(
+                    intervals
+                        .segments
+                        .iter()
+                        .map(|segment| segment_set(&set, ts, segment)),
+                );
+                changed = true;
+                split += 1;
+            }
+
+            if changed {
+                for (i, set) in sets.iter_mut().enumerate() {
+                    set.set_number = Some(i as u32 + 1);
+                }
+            }
+            exercise.sets = sets;
+        }
+    }
+    split
+}
+
+fn set_series(set: &CompletedSet) -> Option<&TimeSeriesData> {
+    set.telemetry.as_ref()?.time_series.as_ref()
+}
+
+fn segment_set(set: &CompletedSet, ts: &TimeSeriesData, segment: &IntervalSegment) -> CompletedSet {
+    let label = match (&segment.phase, segment.kind) {
+        (Some(phase), _) => format!("{} #{}", phase.name, phase.repetition),
+        (None, SegmentKind::Work) => "Work".to_string(),
+        (None, SegmentKind::Recovery) => "Recovery".to_string(),
+        (None, SegmentKind::Lap) => "Lap".to_string(),
+    };
+    let completed_at = ts
+        .timestamps
+        .get(segment.last_row)
+        .filter(|t| parse_timestamp(t).is_some())
+        .cloned();
+
+    let mut new_set = CompletedSet {
+        set_number: None,
+        set_type: match segment.kind {
+            SegmentKind::Recovery => None,
+            _ => set.set_type,
+        },
+        reps: None,
+        weight_kg: None,
+        weight_lb: None,
+        duration_sec: Some(segment.duration_sec.round() as u32),
+        distance_meters: segment.distance_m,
+        rpe: None,
+        rir: None,
+        notes: Some(label),
+        is_pr: None,
+        completed_at,
+        telemetry: Some(SetTelemetry {
+            time_series: Some(ts.slice(segment.first_row..segment.last_row + 1)),
+            ..Default::default()
+        }),
+        swimming: None,
+        extensions: set.extensions.clone(),
+    };
+    fill_set_summary(&mut new_set);
+    new_set
+}
+
+fn kind(working: bool) -> SegmentKind {
+    if working {
+        SegmentKind::Work
+    } else {
+        SegmentKind::Recovery
+    }
+}
+
+fn choose_metric(ts: &TimeSeriesData, requested: Option<IntervalMetric>) -> Option<IntervalMetric> {
+    let has = |metric| metric_values(ts, metric).iter().any(Option::is_some);
+    match requested {
+        Some(metric) => has(metric).then_some(metric),
+        None => [
+            IntervalMetric::Power,
+            IntervalMetric::Speed,
+            IntervalMetric::HeartRate,
+        ]
+        .into_iter()
+        .find(|&m| has(m)),
+    }
+}
+
+fn metric_values(ts: &TimeSeriesData, metric: IntervalMetric) -> Vec<Option<f64>> {
+    let rows = ts.timestamps.len();
+    let column: Option<Vec<f64>> = match metric {
+        IntervalMetric::Power => ts
+            .power
+            .as_ref()
+            .map(|v| v.iter().map(|&w| w as f64).collect()),
+        IntervalMetric::Speed => ts.speed_mps.clone(),
+        IntervalMetric::HeartRate => ts
+            .heart_rate
+            .as_ref()
+            .map(|v| v.iter().map(|&hr| hr as f64).collect()),
+    };
+    match column {
+        Some(values) if values.len() == rows => values
+            .into_iter()
+            .map(|v| Some(v).filter(|v| v.is_finite()))
+            .collect(),
+        _ => vec![None; rows],
+    }
+}
+
+/// Fill gaps with the previous reading, or the first one at the start
+fn filled(values: &[Option<f64>]) -> Vec<f64> {
+    let first = values.iter().flatten().next().copied().unwrap_or(0.0);
+    let mut last = first;
+    values
+        .iter()
+        .map(|v| {
+            if let Some(v) = v {
+                last = *v;
+            }
+            last
+        })
+        .collect()
+}
+
+/// Centred moving average over `window_sec` of time
+fn smooth(times: &[f64], values: &[f64], window_sec: f64) -> Vec<f64> {
+    let half = window_sec / 2.0;
+    let (mut lo, mut hi, mut sum) = (0, 0, 0.0);
+    times
+        .iter()
+        .map(|&t| {
+            while hi < times.len() && times[hi] <= t + half {
+                sum += values[hi];
+                hi += 1;
+            }
+            while times[lo] < t - half {
+                sum -= values[lo];
+                lo += 1;
+            }
+            sum / (hi - lo) as f64
+        })
+        .collect()
+}
+
+/// Absorb runs shorter than `min_sec`, shortest first
+///
+/// Kinds alternate, so dropping an inner run joins its two neighbours.
+fn merge_short_runs(runs: &mut Vec<(SegmentKind, usize)>, times: &[f64], min_sec: f64) {
+    let end = times.len() - 1;
+    let duration = |runs: &[(SegmentKind, usize)], i: usize| {
+        let last = runs.get(i + 1).map_or(end, |r| r.1);
+        times[last] - times[runs[i].1]
+    };
+    while runs.len() > 1 {
+        let Some((shortest, length)) = (0..runs.len())
+            .map(|i| (i, duration(runs, i)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+        else {
+            break;
+        };
+        if length >= min_sec {
+            break;
+        }
+        if shortest == 0 {
+            // The next run starts at the beginning instead
+            runs.remove(0);
+            runs[0].1 = 0;
+        } else {
+            runs.remove(shortest);
+            if shortest < runs.len() {
+                runs.remove(shortest);
+            }
+        }
+    }
+}
+
+fn build_segments(
+    ts: &TimeSeriesData,
+    times: &[f64],
+    runs: &[(SegmentKind, usize)],
+) -> Vec<IntervalSegment> {
+    let end = times.len() - 1;
+    let distances = cumulative_distance(ts, times);
+    runs.iter()
+        .enumerate()
+        .map(|(i, &(kind, first_row))| {
+            let last_row = runs.get(i + 1).map_or(end, |r| r.1);
+            IntervalSegment {
+                kind,
+                first_row,
+                last_row,
+                start_sec: times[first_row] - times[0],
+                duration_sec: times[last_row] - times[first_row],
+                distance_m: distances.as_ref().map(|d| d[last_row] - d[first_row]),
+                average: None,
+                phase: None,
+            }
+        })
+        .collect()
+}
+
+/// Distance from the first row, from `distance_m` or else integrated
+/// `speed_mps`
+fn cumulative_distance(ts: &TimeSeriesData, times: &[f64]) -> Option<Vec<f64>> {
+    let rows = times.len();
+    if let Some(distance) = ts.distance_m.as_ref().filter(|d| d.len() == rows) {
+        let filled = filled(
+            &distance
+                .iter()
+                .map(|&d| Some(d).filter(|d| d.is_finite()))
+                .collect::<Vec<_>>(),
+        );
+        return Some(filled.iter().map(|d| d - filled[0]).collect());
+    }
+    let speed = ts.speed_mps.as_ref().filter(|s| s.len() == rows)?;
+    let mut total = 0.0;
+    let mut out = vec![0.0];
+    for i in 1..rows {
+        let dt = times[i] - times[i - 1];
+        if dt > 0.0 && dt <= MAX_GAP_SEC && speed[i - 1].is_finite() {
+            total += speed[i - 1] * dt;
+        }
+        out.push(total);
+    }
+    Some(out)
+}
+
+fn auto_laps(ts: &TimeSeriesData, times: &[f64], lap_m: f64) -> Vec<IntervalSegment> {
+    let Some(distances) = cumulative_distance(ts, times) else {
+        return Vec::new();
+    };
+    if lap_m <= 0.0 {
+        return Vec::new();
+    }
+    let mut runs = vec![(SegmentKind::Lap, 0)];
+    let mut mark = lap_m;
+    for (row, &d) in distances.iter().enumerate() {
+        if d >= mark && row < times.len() - 1 {
+            runs.push((SegmentKind::Lap, row));
+            while mark <= d {
+                mark += lap_m;
+            }
+        }
+    }
+    if runs.len() < 2 {
+        return Vec::new();
+    }
+    build_segments(ts, times, &runs)
+}
+
+/// Whether a planned phase is work or recovery
+///
+/// Phases are compared by target power, then pace, then heart rate: those
+/// above the midpoint of the targets are work. Without targets to compare,
+/// names such as "recovery" or "rest" mark recovery.
+fn phase_kind(phase: &IntervalPhase, phases: &[IntervalPhase]) -> SegmentKind {
+    let intensities: [fn(&IntervalPhase) -> Option<f64>; 3] = [
+        |p| p.target_power_watts.map(f64::from),
+        // Faster pace is harder
+        |p| p.target_pace_sec_per_km.map(|pace| -f64::from(pace)),
+        |p| p.target_hr_bpm.map(f64::from),
+    ];
+    for intensity in intensities {
+        let values: Vec<f64> = phases.iter().filter_map(intensity).collect();
+        let (min, max) = values
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        if let Some(value) = intensity(phase) {
+            if values.len() >= 2 && max > min {
+                return kind(value > (min + max) / 2.0);
+            }
+        }
+    }
+    let name = phase.name.to_lowercase();
+    kind(!RECOVERY_NAMES.iter().any(|r| name.contains(r)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analytics::series::fixtures::{interval_history, interval_ride};
+
+    #[test]
+    fn test_detects_work_and_recovery() {
+        let segments = detect_intervals(
+            &interval_ride(120, 120, &[300; 3]),
+            &IntervalOptions::default(),
+        );
+        let kinds: Vec<SegmentKind> = segments.iter().map(|s| s.kind).collect();
+        use SegmentKind::{Recovery, Work};
+        assert_eq!(
+            kinds,
+            vec![Recovery, Work, Recovery, Work, Recovery, Work, Recovery]
+        );
+
+        let work: Vec<&IntervalSegment> = segments.iter().filter(|s| s.kind == Work).collect();
+        for segment in &work {
+            assert!((segment.duration_sec - 60.0).abs() <= 2.0, "{:?}", segment);
+            assert!(segment.average.unwrap() > 280.0);
+        }
+        assert!((work[0].start_sec - 120.0).abs() <= 2.0);
+        let distance: f64 = segments.iter().filter_map(|s| s.distance_m).sum();
+        assert!((distance - (120.0 * 3.0 + 3.0 * 60.0 * 8.0)).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_steady_effort_and_short_spikes() {
+        let mut steady = interval_ride(600, 120, &[]);
+        assert!(detect_intervals(&steady, &IntervalOptions::default()).is_empty());
+
+        // A 5 s surge is absorbed
+        let power = steady.power.as_mut().unwrap();
+        power[300..305].fill(500);
+        assert!(detect_intervals(&steady, &IntervalOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_auto_lap_by_distance() {
+        let options = IntervalOptions {
+            auto_lap_m: Some(400.0),
+            ..Default::default()
+        };
+        // 360 m warm-up, then 3 × (300 m + 180 m) = 1800 m in total
+        let segments = detect_intervals(&interval_ride(120, 120, &[300; 3]), &options);
+        assert_eq!(segments.len(), 5);
+        assert!(segments.iter().all(|s| s.kind == SegmentKind::Lap));
+        assert!((segments[0].distance_m.unwrap() - 400.0).abs() <= 5.0);
+        assert!((segments[4].distance_m.unwrap() - 200.0).abs() <= 5.0);
+    }
+
+    #[test]
+    fn test_match_planned_phases() {
+        let plan = crate::plan::parse(
+            r#"
+plan_version: 1
+cycle:
+  days:
+    - id: intervals
+      exercises:
+        - name: Repeats
+          modality: interval
+          target_sets: 4
+          interval_phases:
+            - { name: work, duration_sec: 60, target_power_watts: 300 }
+            - { name: recovery, duration_sec: 60, target_power_watts: 120 }
+"#,
+        )
+        .unwrap();
+        let history = interval_history(interval_ride(120, 120, &[300; 3]));
+        let found = analyze_intervals(&history, &IntervalOptions::default(), Some(&plan));
+        assert_eq!(found.len(), 1);
+        let intervals = &found[0];
+        assert_eq!(intervals.metric, Some(IntervalMetric::Power));
+        assert_eq!(intervals.segments[0].phase, None);
+        let phase = intervals.segments[3].phase.as_ref().unwrap();
+        assert_eq!((phase.name.as_str(), phase.repetition), ("work", 2));
+        assert_eq!(intervals.unmatched_phases, vec!["work #4", "recovery #4"]);
+    }
+
+    #[test]
+    fn test_split_intervals_into_sets() {
+        let mut history = interval_history(interval_ride(120, 120, &[300; 2]));
+        let split = split_intervals(&mut history, &IntervalOptions::default(), None);
+        assert_eq!(split, 1);
+
+        let sets = &history.workouts[0].exercises[0].sets;
+        assert_eq!(sets.len(), 5);
+        assert_eq!(sets[1].set_number, Some(2));
+        assert_eq!(sets[1].notes.as_deref(), Some("Work"));
+        assert_eq!(sets[1].set_type, Some(crate::history::SetType::Working));
+        assert_eq!(sets[0].set_type, None);
+        let telemetry = sets[1].telemetry.as_ref().unwrap();
+        assert!(telemetry.power_avg.unwrap() > 280);
+        assert!(telemetry.speed_avg_mps.unwrap() > 4.5);
+        assert!((sets[1].duration_sec.unwrap() as i64 - 60).abs() <= 2);
+        assert!(sets[1].completed_at.is_some());
+        assert!(sets
+            .iter()
+            .all(|s| s.extensions.get("x-lap-source").is_some()));
+
+        // Splitting again finds nothing more to split
+        assert_eq!(
+            split_intervals(&mut history, &IntervalOptions::default(), None),
+            0
+        );
+    }
+}
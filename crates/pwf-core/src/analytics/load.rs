@@ -0,0 +1,483 @@
+//! Training load: per-workout stress scores and the performance-management
+//! model (CTL, ATL and TSB)
+//!
+//! Each workout gets one stress score on the TSS scale, where an hour at
+//! threshold scores 100. The best available source is used: power (TSS),
+//! then heart rate (hrTSS), then pace (rTSS, on foot only), then session
+//! RPE. All of them are `hours * IF² * 100` with IF taken against the
+//! matching threshold, so scores from different sources can be summed.
+//!
+//! CTL and ATL are exponentially weighted averages of daily stress with
+//! time constants of 42 and 7 days by default. TSB is the previous day's
+//! CTL minus ATL, i.e. the form an athlete starts the day with.
+
+use super::date_range::{workout_date, DateRange};
+use super::power::compute_power_metrics;
+use super::series::{resample_1hz, time_series_samples};
+use crate::history::{Workout, WpsHistory};
+use crate::plan::AthleteProfile;
+use crate::{Modality, Sport};
+use chrono::{DateTime, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Session RPE (CR-10 scale) treated as an hour at threshold
+pub const THRESHOLD_RPE: f64 = 7.0;
+
+/// Default chronic training load time constant (days)
+pub const DEFAULT_CTL_DAYS: f64 = 42.0;
+
+/// Default acute training load time constant (days)
+pub const DEFAULT_ATL_DAYS: f64 = 7.0;
+
+/// Athlete thresholds used to score workouts
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadThresholds {
+    pub ftp_watts: Option<u32>,
+    /// Lactate threshold heart rate
+    pub threshold_hr_bpm: Option<u32>,
+    pub threshold_pace_sec_per_km: Option<u32>,
+}
+
+impl LoadThresholds {
+    pub fn from_profile(profile: &AthleteProfile) -> Self {
+        Self {
+            ftp_watts: profile.ftp_watts,
+            threshold_hr_bpm: profile.threshold_hr_bpm,
+            threshold_pace_sec_per_km: profile.threshold_pace_sec_per_km,
+        }
+    }
+}
+
+/// Where a workout's stress score came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StressSource {
+    /// TSS from power, recorded or computed
+    Power,
+    /// hrTSS from heart rate against threshold HR
+    HeartRate,
+    /// rTSS from pace against threshold pace
+    Pace,
+    /// Session RPE and duration
+    SessionRpe,
+}
+
+/// The stress score of one workout
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkoutStress {
+    pub score: f64,
+    pub source: StressSource,
+}
+
+/// Time constants for the load model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadParams {
+    pub ctl_days: f64,
+    pub atl_days: f64,
+    /// CTL and ATL on the day before the first workout
+    pub initial_ctl: f64,
+    pub initial_atl: f64,
+}
+
+impl Default for LoadParams {
+    fn default() -> Self {
+        Self {
+            ctl_days: DEFAULT_CTL_DAYS,
+            atl_days: DEFAULT_ATL_DAYS,
+            initial_ctl: 0.0,
+            initial_atl: 0.0,
+        }
+    }
+}
+
+/// One row of the performance-management table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyLoad {
+    pub date: NaiveDate,
+    /// Total stress of the day's workouts
+    pub stress: f64,
+    /// Chronic training load ("fitness")
+    pub ctl: f64,
+    /// Acute training load ("fatigue")
+    pub atl: f64,
+    /// Training stress balance ("form") going into the day
+    pub tsb: f64,
+}
+
+/// Score a workout from the best available data
+///
+/// Returns `None` when no source has both data and a threshold to compare
+/// it with.
+pub fn workout_stress(workout: &Workout, thresholds: &LoadThresholds) -> Option<WorkoutStress> {
+    let scored = |score: Option<f64>, source| {
+        score
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .map(|score| WorkoutStress { score, source })
+    };
+    scored(power_stress(workout, thresholds), StressSource::Power)
+        .or_else(|| scored(hr_stress(workout, thresholds), StressSource::HeartRate))
+        .or_else(|| scored(pace_stress(workout, thresholds), StressSource::Pace))
+        .or_else(|| scored(rpe_stress(workout), StressSource::SessionRpe))
+}
+
+fn power_stress(workout: &Workout, thresholds: &LoadThresholds) -> Option<f64> {
+    let recorded = workout
+        .telemetry
+        .as_ref()
+        .and_then(|t| t.power_metrics.as_ref())
+        .and_then(|m| m.training_stress_score);
+    recorded.or_else(|| compute_power_metrics(workout, thresholds.ftp_watts)?.training_stress_score)
+}
+
+fn hr_stress(workout: &Workout, thresholds: &LoadThresholds) -> Option<f64> {
+    let threshold = thresholds.threshold_hr_bpm.filter(|&t| t > 0)? as f64;
+    let samples = time_series_samples(workout, |ts| {
+        ts.heart_rate.as_ref().map(|hr| {
+            hr.iter()
+                .map(|&b| Some(b as f64).filter(|&b| b > 0.0))
+                .collect()
+        })
+    });
+    let stream = resample_1hz(&samples);
+    if !stream.is_empty() {
+        return Some(stream_stress(stream.iter().map(|hr| hr / threshold)));
+    }
+
+    let average = workout.telemetry.as_ref()?.heart_rate_avg? as f64;
+    Some(stress(workout_duration_sec(workout)?, average / threshold))
+}
+
+/// rTSS, for runs, walks and hikes
+///
+/// Threshold pace is a running threshold; scoring a ride's speed against it
+/// would inflate the score several times over.
+fn pace_stress(workout: &Workout, thresholds: &LoadThresholds) -> Option<f64> {
+    if !is_on_foot(workout) {
+        return None;
+    }
+    let threshold = thresholds.threshold_pace_sec_per_km.filter(|&t| t > 0)? as f64;
+    // IF for pace is threshold pace over actual pace, i.e. speed over
+    // threshold speed
+    let threshold_speed = 1000.0 / threshold;
+    let samples = time_series_samples(workout, |ts| {
+        ts.speed_mps
+            .as_ref()
+            .map(|s| s.iter().map(|&v| Some(v)).collect())
+    });
+    let stream = resample_1hz(&samples);
+    if !stream.is_empty() {
+        return Some(stream_stress(stream.iter().map(|v| v / threshold_speed)));
+    }
+
+    let pace = workout
+        .telemetry
+        .as_ref()?
+        .pace_avg_sec_per_km
+        .filter(|&p| p > 0)? as f64;
+    Some(stress(workout_duration_sec(workout)?, threshold / pace))
+}
+
+/// Running, walking or hiking by the workout's `sport` or any exercise's
+/// `modality` or `sport`
+fn is_on_foot(workout: &Workout) -> bool {
+    let on_foot = |sport: Option<Sport>| {
+        matches!(sport, Some(Sport::Running | Sport::Walking | Sport::Hiking))
+    };
+    on_foot(workout.sport)
+        || workout
+            .exercises
+            .iter()
+            .any(|e| e.modality == Some(Modality::Running) || on_foot(e.sport))
+}
+
+fn rpe_stress(workout: &Workout) -> Option<f64> {
+    let rpe = session_rpe(workout)?;
+    Some(stress(workout_duration_sec(workout)?, rpe / THRESHOLD_RPE))
+}
+
+/// `hours * IF² * 100` summed second by second over a 1 Hz IF stream
+fn stream_stress(intensity: impl Iterator<Item = f64>) -> f64 {
+    intensity.map(|i| i * i).sum::<f64>() / 3600.0 * 100.0
+}
+
+fn stress(duration_sec: u32, intensity: f64) -> f64 {
+    duration_sec as f64 / 3600.0 * intensity * intensity * 100.0
+}
+
+/// Session RPE: the mean RPE of the sets that record one
+pub fn session_rpe(workout: &Workout) -> Option<f64> {
+    let rpes: Vec<f64> = workout
+        .exercises
+        .iter()
+        .flat_map(|e| &e.sets)
+        .filter_map(|s| s.rpe)
+        .filter(|r| r.is_finite())
+        .collect();
+    if rpes.is_empty() {
+        None
+    } else {
+        Some(rpes.iter().sum::<f64>() / rpes.len() as f64)
+    }
+}
+
+/// How long a workout lasted
+///
+/// Uses `duration_sec`, then the span between `started_at` and `ended_at`,
+/// then the sum of set durations.
+pub fn workout_duration_sec(workout: &Workout) -> Option<u32> {
+    if let Some(duration) = workout.duration_sec.filter(|&d| d > 0) {
+        return Some(duration);
+    }
+    let parse = |t: &Option<String>| DateTime::parse_from_rfc3339(t.as_deref()?).ok();
+    if let (Some(start), Some(end)) = (parse(&workout.started_at), parse(&workout.ended_at)) {
+        let seconds = (end - start).num_seconds();
+        if seconds > 0 {
+            return u32::try_from(seconds).ok();
+        }
+    }
+    let total: u32 = workout
+        .exercises
+        .iter()
+        .flat_map(|e| &e.sets)
+        .filter_map(|s| s.duration_sec)
+        .sum();
+    (total > 0).then_some(total)
+}
+
+/// Total stress per calendar day
+///
+/// Workouts without a parseable date or without a score are skipped.
+pub fn daily_stress<'a>(
+    workouts: impl IntoIterator<Item = &'a Workout>,
+    thresholds: &LoadThresholds,
+) -> BTreeMap<NaiveDate, f64> {
+    let mut days = BTreeMap::new();
+    for workout in workouts {
+        let Some(date) = workout_date(workout) else {
+            continue;
+        };
+        if let Some(stress) = workout_stress(workout, thresholds) {
+            *days.entry(date).or_insert(0.0) += stress.score;
+        }
+    }
+    days
+}
+
+/// Run the CTL/ATL model over daily stress
+///
+/// Produces a row for every day from the first entry through `until` (or
+/// the last entry), with rest days scoring zero.
+pub fn load_model(
+    stress: &BTreeMap<NaiveDate, f64>,
+    params: &LoadParams,
+    until: Option<NaiveDate>,
+) -> Vec<DailyLoad> {
+    let Some(&first) = stress.keys().next() else {
+        return Vec::new();
+    };
+    let last = until.unwrap_or_else(|| *stress.keys().next_back().unwrap());
+
+    let mut rows = Vec::new();
+    let (mut ctl, mut atl) = (params.initial_ctl, params.initial_atl);
+    for date in first.iter_days().take_while(|d| *d <= last) {
+        let day_stress = stress.get(&date).copied().unwrap_or(0.0);
+        let tsb = ctl - atl;
+        ctl += (day_stress - ctl) / params.ctl_days.max(1.0);
+        atl += (day_stress - atl) / params.atl_days.max(1.0);
+        rows.push(DailyLoad {
+            date,
+            stress: day_stress,
+            ctl,
+            atl,
+            tsb,
+        });
+    }
+    rows
+}
+
+/// The performance-management table for a history
+///
+/// The model is seeded from every workout up to the end of `range`, so
+/// CTL on the first reported day reflects the training before it; only
+/// days inside `range` are returned. An open-ended range runs to the last
+/// workout.
+pub fn training_load(
+    history: &WpsHistory,
+    thresholds: &LoadThresholds,
+    params: &LoadParams,
+    range: &DateRange,
+) -> Vec<DailyLoad> {
+    let until = DateRange::new(None, range.to);
+    let stress = daily_stress(until.workouts(history), thresholds);
+    load_model(&stress, params, range.to)
+        .into_iter()
+        .filter(|row| range.contains(row.date))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workout(yaml: &str) -> Workout {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn thresholds() -> LoadThresholds {
+        LoadThresholds {
+            ftp_watts: Some(250),
+            threshold_hr_bpm: Some(160),
+            threshold_pace_sec_per_km: Some(300),
+        }
+    }
+
+    #[test]
+    fn test_recorded_tss_wins() {
+        let w = workout(
+            "date: \"2025-01-15\"\nexercises: []\ntelemetry:\n  heart_rate_avg: 150\n  power_metrics:\n    training_stress_score: 85.0\n",
+        );
+        let stress = workout_stress(&w, &thresholds()).unwrap();
+        assert_eq!(stress.source, StressSource::Power);
+        assert_eq!(stress.score, 85.0);
+    }
+
+    #[test]
+    fn test_hr_stress_from_average() {
+        let w = workout(
+            "date: \"2025-01-15\"\nduration_sec: 3600\nexercises: []\ntelemetry:\n  heart_rate_avg: 160\n",
+        );
+        let stress = workout_stress(&w, &thresholds()).unwrap();
+        assert_eq!(stress.source, StressSource::HeartRate);
+        assert!((stress.score - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pace_stress_from_speed_stream() {
+        let seconds = 1800;
+        let yaml = format!(
+            "date: \"2025-01-15\"\nexercises:\n  - name: Run\n    modality: running\n    sets:\n      - telemetry:\n          time_series:\n            timestamps: [{}]\n            speed_mps: [{}]\n",
+            vec!["\"\""; seconds].join(", "),
+            vec!["3.333333333333333"; seconds].join(", ")
+        );
+        let stress = workout_stress(&workout(&yaml), &thresholds()).unwrap();
+        assert_eq!(stress.source, StressSource::Pace);
+        // Half an hour at threshold pace
+        assert!((stress.score - 50.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_no_pace_stress_off_foot() {
+        // An hour at 8.3 m/s would score ~400 against a 5:00/km threshold
+        let seconds = 3600;
+        let session = |sport: &str| {
+            workout(&format!(
+                "date: \"2025-01-15\"\nsport: {}\nexercises:\n  - name: Ride\n    sets:\n      - rpe: 7.0\n        duration_sec: {}\n        telemetry:\n          time_series:\n            timestamps: [{}]\n            speed_mps: [{}]\n",
+                sport,
+                seconds,
+                vec!["\"\""; seconds].join(", "),
+                vec!["8.3"; seconds].join(", ")
+            ))
+        };
+        let ride = workout_stress(&session("cycling"), &thresholds()).unwrap();
+        assert_eq!(ride.source, StressSource::SessionRpe);
+        assert!((ride.score - 100.0).abs() < 1e-9);
+
+        let hike = workout_stress(&session("hiking"), &thresholds()).unwrap();
+        assert_eq!(hike.source, StressSource::Pace);
+    }
+
+    #[test]
+    fn test_session_rpe_fallback() {
+        let w = workout(
+            r#"
+date: "2025-01-15"
+started_at: "2025-01-15T10:00:00Z"
+ended_at: "2025-01-15T11:00:00Z"
+exercises:
+  - name: Squat
+    sets:
+      - reps: 5
+        rpe: 6.0
+      - reps: 5
+        rpe: 8.0
+"#,
+        );
+        assert_eq!(workout_duration_sec(&w), Some(3600));
+        assert_eq!(session_rpe(&w), Some(7.0));
+        let stress = workout_stress(&w, &LoadThresholds::default()).unwrap();
+        assert_eq!(stress.source, StressSource::SessionRpe);
+        assert!((stress.score - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unscorable_workout() {
+        let w = workout("date: \"2025-01-15\"\nduration_sec: 3600\nexercises: []\n");
+        assert!(workout_stress(&w, &thresholds()).is_none());
+    }
+
+    #[test]
+    fn test_load_model_decays_over_rest_days() {
+        let mut stress = BTreeMap::new();
+        stress.insert(date("2025-01-01"), 100.0);
+        stress.insert(date("2025-01-03"), 50.0);
+        let rows = load_model(&stress, &LoadParams::default(), Some(date("2025-01-05")));
+
+        assert_eq!(rows.len(), 5);
+        assert!((rows[0].ctl - 100.0 / 42.0).abs() < 1e-9);
+        assert!((rows[0].atl - 100.0 / 7.0).abs() < 1e-9);
+        assert_eq!(rows[0].tsb, 0.0);
+        // Rest day: both decay and form is yesterday's CTL - ATL
+        assert_eq!(rows[1].stress, 0.0);
+        assert!(rows[1].atl < rows[0].atl);
+        assert!((rows[1].tsb - (rows[0].ctl - rows[0].atl)).abs() < 1e-9);
+        assert!(rows[2].atl > rows[1].atl);
+    }
+
+    #[test]
+    fn test_training_load_seeds_from_earlier_workouts() {
+        let history: WpsHistory = serde_yaml::from_str(
+            r#"
+history_version: 2
+exported_at: "2025-02-01T00:00:00Z"
+workouts:
+  - date: "2025-01-01"
+    exercises: []
+    telemetry:
+      power_metrics:
+        training_stress_score: 100.0
+  - date: "2025-01-10"
+    exercises: []
+    telemetry:
+      power_metrics:
+        training_stress_score: 60.0
+  - date: "2025-01-20"
+    exercises: []
+    telemetry:
+      power_metrics:
+        training_stress_score: 80.0
+"#,
+        )
+        .unwrap();
+        let range = DateRange::new(Some(date("2025-01-10")), Some(date("2025-01-12")));
+        let rows = training_load(&history, &thresholds(), &LoadParams::default(), &range);
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].date, date("2025-01-10"));
+        assert_eq!(rows[0].stress, 60.0);
+        // CTL carries the 1 January workout into the range
+        assert!(rows[0].tsb != 0.0);
+
+        let params = LoadParams {
+            ctl_days: 28.0,
+            ..Default::default()
+        };
+        let all = training_load(&history, &thresholds(), &params, &DateRange::all());
+        assert_eq!(all.len(), 20);
+        assert!((all[0].ctl - 100.0 / 28.0).abs() < 1e-9);
+    }
+}
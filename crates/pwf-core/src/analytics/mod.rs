@@ -0,0 +1,94 @@
+//! Training analytics computed from history data
+//!
+//! Functions here derive metrics from the raw telemetry stored in a
+//! [`crate::history::WpsHistory`] rather than trusting whatever summary values
+//! the source device exported.
+
+pub mod body;
+pub mod date_range;
+pub mod execution;
+pub mod intensity;
+pub mod intervals;
+pub mod load;
+pub mod power;
+pub mod power_curve;
+pub mod records;
+pub mod running;
+pub mod series;
+pub mod srpe;
+pub mod strength;
+pub mod summary;
+pub mod swimming;
+pub mod w_prime;
+pub mod zones;
+
+pub use body::{
+    body_trends, limb_asymmetry, measurement_weight_kg, waist_to_hip_ratio, BodyEntry, BodyOptions,
+    BodyReport, Limb, LimbAsymmetry, WeeklyBodyChange, DEFAULT_SMOOTHING_DAYS,
+};
+pub use date_range::{workout_date, DateRange};
+pub use execution::{
+    execute_phases, execution_score, phase_sequence, score_executions, ExecutionOptions,
+    IntervalExecution, MetricExecution, PhaseAlignment, PhaseExecution, PlannedPhase,
+    DEFAULT_TARGET_TOLERANCE,
+};
+pub use intensity::{
+    default_zone_split, intensity_distribution, polarization_index, three_zone_seconds,
+    DistributionModel, IntensityDistribution, IntensityOptions, IntensityReport, IntensityStream,
+    IntensityTrend, SportIntensity, WeeklyIntensity, POLARIZED_INDEX,
+};
+pub use intervals::{
+    analyze_intervals, detect_intervals, match_phases, planned_phases, split_intervals,
+    IntervalMetric, IntervalOptions, IntervalSegment, PhaseMatch, SegmentKind, SetIntervals,
+    DEFAULT_MIN_SEGMENT_SEC, DEFAULT_SMOOTHING_SEC, MIN_CONTRAST,
+};
+pub use load::{
+    daily_stress, load_model, session_rpe, training_load, workout_duration_sec, workout_stress,
+    DailyLoad, LoadParams, LoadThresholds, StressSource, WorkoutStress, DEFAULT_ATL_DAYS,
+    DEFAULT_CTL_DAYS, THRESHOLD_RPE,
+};
+pub use power::{
+    analyze_power, compute_power_metrics, fill_history_power_metrics, fill_power_metrics,
+    PowerAnalysis,
+};
+pub use power_curve::{
+    analyze_power_curve, estimate_ftp, fit_critical_power, history_power_curve, mean_max_power,
+    workout_power_curve, CriticalPowerModel, FtpEstimate, FtpSource, PowerCurve,
+    PowerCurveAnalysis, PowerCurvePoint, STANDARD_DURATIONS,
+};
+pub use records::{
+    analyze_records, estimate_1rm, regenerate_records, set_e1rm, E1rmFormula, PrEvent,
+    RecordAnalysis, RecordDisagreement, RecordOptions, MAX_E1RM_REPS,
+};
+pub use running::{
+    analyze_running, analyze_runs, best_efforts, grade_cost_factor, pace_decoupling, run_splits,
+    run_track, BestEffort, PaceDecoupling, RunTrack, RunningAnalysis, Split, TrackPoint,
+    TrackSource, STANDARD_RUN_DISTANCES,
+};
+pub use series::{power_series, resample_1hz, Sample};
+pub use srpe::{
+    rolling_acwr, session_load, srpe_report, weekly_load, AcwrFlag, AcwrParams, DailyAcwr,
+    SessionLoad, SrpeReport, WeeklyLoad,
+};
+pub use strength::{
+    is_hard_set, library_exercise, strength_volume, ExerciseVolume, MuscleVolume, RepDistribution,
+    SetRange, StrengthOptions, StrengthReport, Volume, VolumeStatus, WeeklyStrength,
+    DEFAULT_WEEKLY_SETS, HARD_SET_RIR, HARD_SET_RPE,
+};
+pub use summary::{
+    check_summaries, fill_set_summary, fix_summaries, route_summary, set_summary, workout_summary,
+    Discrepancy, SummaryField, SummarySource, SummaryTolerances,
+};
+pub use swimming::{
+    analyze_swim, analyze_swims, critical_swim_speed, fill_swim_lengths, reconstruct_lengths,
+    CssTest, StrokeBreakdown, SwimAnalysis, SwimReport, CSS_LONG_TEST, CSS_SHORT_TEST,
+};
+pub use w_prime::{
+    fill_w_prime_balance, matches_burned, w_prime_balance, workout_w_prime_balance,
+    MATCH_MIN_DEPLETION_JOULES, MATCH_MIN_RECOVERY_JOULES,
+};
+pub use zones::{
+    fill_time_in_zones, seconds_in_zones, segment_time_in_zones, series_time_in_zones,
+    workout_time_in_zones, ZoneBoundaries, HR_ZONES_PERCENT_MAX, HR_ZONES_PERCENT_THRESHOLD,
+    PACE_ZONES_PERCENT_THRESHOLD, POWER_ZONES_PERCENT_FTP,
+};
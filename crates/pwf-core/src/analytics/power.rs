@@ -0,0 +1,389 @@
+//! Power metrics: NP, IF, TSS, VI, work and pedal balance
+//!
+//! Normalized Power follows Coggan: a 30 second rolling average of the 1 Hz
+//! power stream, raised to the fourth power, averaged, and the fourth root
+//! taken. IF is NP / FTP and TSS is `duration_h * IF² * 100`.
+
+use super::series::{column_average, power_series};
+use super::w_prime::fill_w_prime_balance;
+use crate::history::{PowerMetrics, Workout, WorkoutTelemetry, WpsHistory};
+use serde::Serialize;
+
+/// Rolling window used for Normalized Power
+pub const NP_WINDOW_SEC: usize = 30;
+
+/// Metrics derived from a 1 Hz power stream
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PowerAnalysis {
+    /// Seconds of power data
+    pub duration_sec: u32,
+    pub average_power: f64,
+    pub max_power: f64,
+    /// `None` when there is less than one rolling window of data
+    pub normalized_power: Option<f64>,
+    pub variability_index: Option<f64>,
+    pub intensity_factor: Option<f64>,
+    pub training_stress_score: Option<f64>,
+    pub total_work_kj: f64,
+}
+
+/// Analyze a 1 Hz power stream, optionally against an FTP
+///
+/// Returns `None` for an empty stream.
+pub fn analyze_power(watts: &[f64], ftp_watts: Option<u32>) -> Option<PowerAnalysis> {
+    if watts.is_empty() {
+        return None;
+    }
+
+    let total: f64 = watts.iter().sum();
+    let average_power = total / watts.len() as f64;
+    let max_power = watts.iter().copied().fold(0.0, f64::max);
+    let normalized_power = normalized_power(watts);
+
+    let variability_index = normalized_power
+        .filter(|_| average_power > 0.0)
+        .map(|np| np / average_power);
+    let ftp = ftp_watts.filter(|&f| f > 0).map(f64::from);
+    let intensity_factor = normalized_power.zip(ftp).map(|(np, ftp)| np / ftp);
+    let training_stress_score =
+        intensity_factor.map(|if_| watts.len() as f64 / 3600.0 * if_ * if_ * 100.0);
+
+    Some(PowerAnalysis {
+        duration_sec: watts.len() as u32,
+        average_power,
+        max_power,
+        normalized_power,
+        variability_index,
+        intensity_factor,
+        training_stress_score,
+        total_work_kj: total / 1000.0,
+    })
+}
+
+fn normalized_power(watts: &[f64]) -> Option<f64> {
+    if watts.len() < NP_WINDOW_SEC {
+        return None;
+    }
+    let mut window_sum: f64 = watts[..NP_WINDOW_SEC].iter().sum();
+    let mut fourth_powers = (window_sum / NP_WINDOW_SEC as f64).powi(4);
+    for i in NP_WINDOW_SEC..watts.len() {
+        window_sum += watts[i] - watts[i - NP_WINDOW_SEC];
+        fourth_powers += (window_sum / NP_WINDOW_SEC as f64).powi(4);
+    }
+    let count = (watts.len() - NP_WINDOW_SEC + 1) as f64;
+    Some((fourth_powers / count).powf(0.25))
+}
+
+/// Compute `PowerMetrics` for a workout from its telemetry
+///
+/// FTP comes from the workout's existing `power_metrics.ftp_watts` when set,
+/// otherwise from `ftp_watts` (typically the plan's
+/// [`crate::plan::AthleteProfile`]). Without an FTP, IF and TSS are left
+/// empty. Balance, smoothness and torque effectiveness are averaged from
+/// the time series columns. Returns `None` when the workout has no power.
+pub fn compute_power_metrics(workout: &Workout, ftp_watts: Option<u32>) -> Option<PowerMetrics> {
+    let ftp = workout_ftp(workout).or(ftp_watts);
+    let analysis = analyze_power(&power_series(workout), ftp)?;
+
+    Some(PowerMetrics {
+        normalized_power: analysis.normalized_power.map(|np| np.round() as u32),
+        training_stress_score: analysis.training_stress_score.map(round1),
+        intensity_factor: analysis.intensity_factor.map(round2),
+        variability_index: analysis.variability_index.map(round2),
+        ftp_watts: ftp,
+        total_work_kj: Some(round1(analysis.total_work_kj)),
+        left_right_balance: column_average(workout, |ts| ts.power_balance.as_ref()).map(round1),
+        left_pedal_smoothness: column_average(workout, |ts| ts.left_pedal_smoothness.as_ref())
+            .map(round1),
+        right_pedal_smoothness: column_average(workout, |ts| ts.right_pedal_smoothness.as_ref())
+            .map(round1),
+        left_torque_effectiveness: column_average(workout, |ts| {
+            ts.left_torque_effectiveness.as_ref()
+        })
+        .map(round1),
+        right_torque_effectiveness: column_average(workout, |ts| {
+            ts.right_torque_effectiveness.as_ref()
+        })
+        .map(round1),
+        ..Default::default()
+    })
+}
+
+/// Fill in missing power metrics on a workout
+///
+/// Values already present are kept; only `None` fields are filled. IF, TSS
+/// and VI are derived from the stored NP and FTP, recorded or not. Also
+/// fills `power_avg` and `power_max` on the workout telemetry, and the W′
+/// balance summary when the workout records CP and W′. Returns true if
+/// anything changed.
+pub fn fill_power_metrics(workout: &mut Workout, ftp_watts: Option<u32>) -> bool {
+    let series = power_series(workout);
+    let Some(computed) = compute_power_metrics(workout, ftp_watts) else {
+        return false;
+    };
+    let analysis = analyze_power(&series, None);
+
+    let telemetry = workout
+        .telemetry
+        .get_or_insert_with(WorkoutTelemetry::default);
+    let mut changed = false;
+    if let Some(analysis) = &analysis {
+        changed |= fill(
+            &mut telemetry.power_avg,
+            Some(analysis.average_power.round() as u32),
+        );
+        changed |= fill(
+            &mut telemetry.power_max,
+            Some(analysis.max_power.round() as u32),
+        );
+    }
+
+    let metrics = telemetry
+        .power_metrics
+        .get_or_insert_with(PowerMetrics::default);
+    changed |= fill(&mut metrics.normalized_power, computed.normalized_power);
+    changed |= fill(&mut metrics.ftp_watts, computed.ftp_watts);
+    // Derive IF, TSS and VI from the NP and FTP that end up stored, so a
+    // device-recorded NP stays consistent with them
+    let np = metrics.normalized_power.map(f64::from);
+    let ftp = metrics.ftp_watts.filter(|&f| f > 0).map(f64::from);
+    let intensity_factor = np.zip(ftp).map(|(np, ftp)| np / ftp);
+    let hours = series.len() as f64 / 3600.0;
+    changed |= fill(
+        &mut metrics.training_stress_score,
+        intensity_factor.map(|if_| round1(hours * if_ * if_ * 100.0)),
+    );
+    changed |= fill(&mut metrics.intensity_factor, intensity_factor.map(round2));
+    changed |= fill(
+        &mut metrics.variability_index,
+        np.zip(analysis.as_ref())
+            .filter(|(_, a)| a.average_power > 0.0)
+            .map(|(np, a)| round2(np / a.average_power)),
+    );
+    changed |= fill(&mut metrics.total_work_kj, computed.total_work_kj);
+    changed |= fill(&mut metrics.left_right_balance, computed.left_right_balance);
+    changed |= fill(
+        &mut metrics.left_pedal_smoothness,
+        computed.left_pedal_smoothness,
+    );
+    changed |= fill(
+        &mut metrics.right_pedal_smoothness,
+        computed.right_pedal_smoothness,
+    );
+    changed |= fill(
+        &mut metrics.left_torque_effectiveness,
+        computed.left_torque_effectiveness,
+    );
+    changed |= fill(
+        &mut metrics.right_torque_effectiveness,
+        computed.right_torque_effectiveness,
+    );
+    changed |= fill_w_prime_balance(workout, None, None);
+    changed
+}
+
+/// Fill in missing power metrics on every workout in a history
+///
+/// Returns the number of workouts that changed.
+pub fn fill_history_power_metrics(history: &mut WpsHistory, ftp_watts: Option<u32>) -> usize {
+    history
+        .workouts
+        .iter_mut()
+        .map(|w| fill_power_metrics(w, ftp_watts))
+        .filter(|&changed| changed)
+        .count()
+}
+
+fn workout_ftp(workout: &Workout) -> Option<u32> {
+    workout
+        .telemetry
+        .as_ref()?
+        .power_metrics
+        .as_ref()?
+        .ftp_watts
+        .filter(|&f| f > 0)
+}
+
+pub(crate) fn fill<T>(slot: &mut Option<T>, value: Option<T>) -> bool {
+    if slot.is_none() && value.is_some() {
+        *slot = value;
+        true
+    } else {
+        false
+    }
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steady(watts: f64, seconds: usize) -> Vec<f64> {
+        vec![watts; seconds]
+    }
+
+    fn workout_with_power(power: &[u32], extra: &str) -> Workout {
+        let timestamps: Vec<String> = (0..power.len())
+            .map(|i| format!("\"2025-01-15T10:{:02}:{:02}Z\"", i / 60, i % 60))
+            .collect();
+        let power: Vec<String> = power.iter().map(|p| p.to_string()).collect();
+        let yaml = format!(
+            r#"date: "2025-01-15"
+exercises:
+  - name: Ride
+    sets:
+      - telemetry:
+          time_series:
+            timestamps: [{}]
+            power: [{}]
+{}"#,
+            timestamps.join(", "),
+            power.join(", "),
+            extra
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_steady_power_has_np_equal_to_average() {
+        let analysis = analyze_power(&steady(200.0, 3600), Some(250)).unwrap();
+        assert_eq!(analysis.average_power, 200.0);
+        assert!((analysis.normalized_power.unwrap() - 200.0).abs() < 1e-9);
+        assert!((analysis.variability_index.unwrap() - 1.0).abs() < 1e-9);
+        assert!((analysis.intensity_factor.unwrap() - 0.8).abs() < 1e-9);
+        // One hour at IF 0.8 is 64 TSS
+        assert!((analysis.training_stress_score.unwrap() - 64.0).abs() < 1e-9);
+        assert!((analysis.total_work_kj - 720.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_one_hour_at_ftp_is_100_tss() {
+        let analysis = analyze_power(&steady(250.0, 3600), Some(250)).unwrap();
+        assert!((analysis.training_stress_score.unwrap() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_variable_power_raises_np_above_average() {
+        let mut watts = Vec::new();
+        for _ in 0..20 {
+            watts.extend(steady(400.0, 60));
+            watts.extend(steady(100.0, 60));
+        }
+        let analysis = analyze_power(&watts, None).unwrap();
+        assert_eq!(analysis.average_power, 250.0);
+        assert!(analysis.normalized_power.unwrap() > 300.0);
+        assert!(analysis.variability_index.unwrap() > 1.2);
+        assert!(analysis.intensity_factor.is_none());
+        assert!(analysis.training_stress_score.is_none());
+    }
+
+    #[test]
+    fn test_short_stream_has_no_np() {
+        let analysis = analyze_power(&steady(200.0, 10), Some(250)).unwrap();
+        assert!(analysis.normalized_power.is_none());
+        assert!(analysis.training_stress_score.is_none());
+        assert_eq!(analysis.max_power, 200.0);
+        assert!(analyze_power(&[], None).is_none());
+    }
+
+    #[test]
+    fn test_compute_power_metrics_prefers_workout_ftp() {
+        let workout = workout_with_power(
+            &[200; 120],
+            "telemetry:\n  power_metrics:\n    ftp_watts: 200\n",
+        );
+        let metrics = compute_power_metrics(&workout, Some(400)).unwrap();
+        assert_eq!(metrics.ftp_watts, Some(200));
+        assert_eq!(metrics.intensity_factor, Some(1.0));
+        assert_eq!(metrics.normalized_power, Some(200));
+        assert_eq!(metrics.total_work_kj, Some(24.0));
+    }
+
+    #[test]
+    fn test_compute_power_metrics_averages_balance() {
+        let workout = workout_with_power(&[200, 210], "");
+        let mut workout = workout;
+        let ts = workout.exercises[0].sets[0]
+            .telemetry
+            .as_mut()
+            .unwrap()
+            .time_series
+            .as_mut()
+            .unwrap();
+        ts.power_balance = Some(vec![49.0, 51.0]);
+        ts.left_pedal_smoothness = Some(vec![20.0, 22.0]);
+
+        let metrics = compute_power_metrics(&workout, None).unwrap();
+        assert_eq!(metrics.left_right_balance, Some(50.0));
+        assert_eq!(metrics.left_pedal_smoothness, Some(21.0));
+        assert!(metrics.right_pedal_smoothness.is_none());
+    }
+
+    #[test]
+    fn test_fill_keeps_existing_values() {
+        let mut workout = workout_with_power(
+            &[200; 60],
+            "telemetry:\n  power_avg: 999\n  power_metrics:\n    normalized_power: 321\n",
+        );
+        assert!(fill_power_metrics(&mut workout, Some(250)));
+
+        let telemetry = workout.telemetry.as_ref().unwrap();
+        assert_eq!(telemetry.power_avg, Some(999));
+        assert_eq!(telemetry.power_max, Some(200));
+        let metrics = telemetry.power_metrics.as_ref().unwrap();
+        assert_eq!(metrics.normalized_power, Some(321));
+        assert_eq!(metrics.ftp_watts, Some(250));
+        assert!(metrics.training_stress_score.is_some());
+
+        // A second pass has nothing left to fill
+        assert!(!fill_power_metrics(&mut workout, Some(250)));
+    }
+
+    #[test]
+    fn test_fill_derives_from_recorded_np() {
+        // The device recorded NP 300 for a steady 200 W hour
+        let mut workout = workout_with_power(
+            &[200; 3600],
+            "telemetry:\n  power_metrics:\n    normalized_power: 300\n",
+        );
+        assert!(fill_power_metrics(&mut workout, Some(250)));
+
+        let metrics = workout
+            .telemetry
+            .as_ref()
+            .unwrap()
+            .power_metrics
+            .as_ref()
+            .unwrap();
+        assert_eq!(metrics.normalized_power, Some(300));
+        assert_eq!(metrics.intensity_factor, Some(1.2));
+        assert_eq!(metrics.training_stress_score, Some(144.0));
+        assert_eq!(metrics.variability_index, Some(1.5));
+    }
+
+    #[test]
+    fn test_fill_history_counts_changed_workouts() {
+        let mut history = WpsHistory {
+            history_version: 2,
+            exported_at: "2025-01-15T12:00:00Z".to_string(),
+            export_source: None,
+            units: Default::default(),
+            workouts: vec![
+                workout_with_power(&[150; 60], ""),
+                serde_yaml::from_str("date: \"2025-01-16\"\nexercises: []\n").unwrap(),
+            ],
+            personal_records: Vec::new(),
+            body_measurements: Vec::new(),
+            extensions: Default::default(),
+        };
+        assert_eq!(fill_history_power_metrics(&mut history, None), 1);
+        assert!(history.workouts[1].telemetry.is_none());
+    }
+}
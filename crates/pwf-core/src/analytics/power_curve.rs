@@ -0,0 +1,348 @@
+//! Mean-maximal power curve, critical power model and FTP estimate
+//!
+//! The curve holds the best average power sustained for each of a set of
+//! durations. Fitting the 2–20 minute part of it to the two-parameter
+//! critical power model (`P = CP + W′ / t`) gives CP, which is used as the
+//! FTP estimate; when too little of that range is covered, 95% of the best
+//! 20 minute power is used instead.
+
+use super::date_range::DateRange;
+use super::series::power_series;
+use crate::history::{Workout, WpsHistory};
+use serde::Serialize;
+
+/// Durations (seconds) reported on a power curve by default
+pub const STANDARD_DURATIONS: &[u32] = &[
+    1, 5, 10, 15, 20, 30, 60, 120, 180, 300, 360, 600, 1200, 1800, 2400, 3600,
+];
+
+/// Shortest effort used to fit the critical power model
+pub const CP_MIN_DURATION_SEC: u32 = 120;
+
+/// Longest effort used to fit the critical power model
+pub const CP_MAX_DURATION_SEC: u32 = 1200;
+
+/// Best average power for one duration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PowerCurvePoint {
+    pub duration_sec: u32,
+    pub watts: f64,
+    /// Date of the workout the effort came from (history curves only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+/// Mean-maximal power for a set of durations, shortest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PowerCurve {
+    pub points: Vec<PowerCurvePoint>,
+}
+
+impl PowerCurve {
+    /// Best power for an exact duration on the curve
+    pub fn best(&self, duration_sec: u32) -> Option<f64> {
+        self.points
+            .iter()
+            .find(|p| p.duration_sec == duration_sec)
+            .map(|p| p.watts)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Keep the higher value for every duration present on either curve
+    fn merge(&mut self, other: PowerCurve) {
+        for point in other.points {
+            match self
+                .points
+                .iter_mut()
+                .find(|p| p.duration_sec == point.duration_sec)
+            {
+                Some(existing) if existing.watts >= point.watts => {}
+                Some(existing) => *existing = point,
+                None => self.points.push(point),
+            }
+        }
+        self.points.sort_by_key(|p| p.duration_sec);
+    }
+}
+
+/// Two-parameter critical power model
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CriticalPowerModel {
+    /// Power sustainable without drawing on W′
+    pub cp_watts: f64,
+    /// Work capacity above CP
+    pub w_prime_joules: f64,
+    /// Goodness of fit of the work-time regression
+    pub r_squared: f64,
+}
+
+impl CriticalPowerModel {
+    /// Power the model predicts can be held for `duration_sec`
+    pub fn power_at(&self, duration_sec: f64) -> f64 {
+        self.cp_watts + self.w_prime_joules / duration_sec
+    }
+}
+
+/// Where an FTP estimate came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FtpSource {
+    CriticalPower,
+    TwentyMinutePower,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FtpEstimate {
+    pub watts: u32,
+    pub source: FtpSource,
+}
+
+/// A power curve with the model and FTP estimate derived from it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PowerCurveAnalysis {
+    pub curve: PowerCurve,
+    pub critical_power: Option<CriticalPowerModel>,
+    pub estimated_ftp: Option<FtpEstimate>,
+}
+
+/// Best average power for each duration in a 1 Hz stream
+///
+/// Durations longer than the stream are left off the curve.
+pub fn mean_max_power(watts: &[f64], durations: &[u32]) -> PowerCurve {
+    let mut prefix = Vec::with_capacity(watts.len() + 1);
+    prefix.push(0.0);
+    for w in watts {
+        prefix.push(prefix.last().unwrap() + w);
+    }
+
+    let mut points: Vec<PowerCurvePoint> = durations
+        .iter()
+        .filter(|&&d| d > 0 && d as usize <= watts.len())
+        .map(|&d| {
+            let d_usize = d as usize;
+            let best_sum = (d_usize..prefix.len())
+                .map(|end| prefix[end] - prefix[end - d_usize])
+                .fold(f64::MIN, f64::max);
+            PowerCurvePoint {
+                duration_sec: d,
+                watts: best_sum / d as f64,
+                date: None,
+            }
+        })
+        .collect();
+    points.sort_by_key(|p| p.duration_sec);
+    points.dedup_by_key(|p| p.duration_sec);
+    PowerCurve { points }
+}
+
+/// Power curve for a single workout over [`STANDARD_DURATIONS`]
+pub fn workout_power_curve(workout: &Workout) -> PowerCurve {
+    let mut curve = mean_max_power(&power_series(workout), STANDARD_DURATIONS);
+    for point in &mut curve.points {
+        point.date = Some(workout.date.clone());
+    }
+    curve
+}
+
+/// Best power curve across every workout in a date range
+///
+/// Each point records the date of the workout it came from.
+pub fn history_power_curve(history: &WpsHistory, range: &DateRange) -> PowerCurve {
+    let mut curve = PowerCurve::default();
+    for workout in range.workouts(history) {
+        curve.merge(workout_power_curve(workout));
+    }
+    curve
+}
+
+/// Fit the critical power model to the curve's 2–20 minute efforts
+///
+/// Uses a least-squares fit of work (`P·t`) against time, whose slope is
+/// CP and intercept W′. Needs at least two points in range and a
+/// physically meaningful result (both parameters positive).
+pub fn fit_critical_power(curve: &PowerCurve) -> Option<CriticalPowerModel> {
+    let points: Vec<(f64, f64)> = curve
+        .points
+        .iter()
+        .filter(|p| (CP_MIN_DURATION_SEC..=CP_MAX_DURATION_SEC).contains(&p.duration_sec))
+        .map(|p| {
+            let t = p.duration_sec as f64;
+            (t, p.watts * t)
+        })
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_w = points.iter().map(|(_, w)| w).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+    let sxy: f64 = points
+        .iter()
+        .map(|(t, w)| (t - mean_t) * (w - mean_w))
+        .sum();
+    let syy: f64 = points.iter().map(|(_, w)| (w - mean_w).powi(2)).sum();
+    if sxx == 0.0 {
+        return None;
+    }
+
+    let cp = sxy / sxx;
+    let w_prime = mean_w - cp * mean_t;
+    if cp <= 0.0 || w_prime <= 0.0 {
+        return None;
+    }
+    let r_squared = if syy == 0.0 {
+        1.0
+    } else {
+        (sxy * sxy) / (sxx * syy)
+    };
+
+    Some(CriticalPowerModel {
+        cp_watts: cp,
+        w_prime_joules: w_prime,
+        r_squared,
+    })
+}
+
+/// Estimate FTP from a power curve
+pub fn estimate_ftp(curve: &PowerCurve) -> Option<FtpEstimate> {
+    if let Some(model) = fit_critical_power(curve) {
+        return Some(FtpEstimate {
+            watts: model.cp_watts.round() as u32,
+            source: FtpSource::CriticalPower,
+        });
+    }
+    curve.best(1200).map(|p20| FtpEstimate {
+        watts: (p20 * 0.95).round() as u32,
+        source: FtpSource::TwentyMinutePower,
+    })
+}
+
+/// Fit the model and estimate FTP for a curve
+pub fn analyze_power_curve(curve: PowerCurve) -> PowerCurveAnalysis {
+    PowerCurveAnalysis {
+        critical_power: fit_critical_power(&curve),
+        estimated_ftp: estimate_ftp(&curve),
+        curve,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn curve(points: &[(u32, f64)]) -> PowerCurve {
+        PowerCurve {
+            points: points
+                .iter()
+                .map(|&(duration_sec, watts)| PowerCurvePoint {
+                    duration_sec,
+                    watts,
+                    date: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn ride(date: &str, watts: &[u32]) -> Workout {
+        let power: Vec<String> = watts.iter().map(|w| w.to_string()).collect();
+        let timestamps = vec!["\"\"".to_string(); watts.len()];
+        serde_yaml::from_str(&format!(
+            "date: \"{}\"\nexercises:\n  - name: Ride\n    sets:\n      - telemetry:\n          time_series:\n            timestamps: [{}]\n            power: [{}]\n",
+            date,
+            timestamps.join(", "),
+            power.join(", ")
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mean_max_finds_best_window() {
+        let watts = [100.0, 300.0, 500.0, 100.0, 100.0];
+        let curve = mean_max_power(&watts, &[1, 2, 3, 10]);
+        assert_eq!(curve.best(1), Some(500.0));
+        assert_eq!(curve.best(2), Some(400.0));
+        assert_eq!(curve.best(3), Some(300.0));
+        assert_eq!(curve.best(10), None);
+    }
+
+    #[test]
+    fn test_curve_covers_every_duration_the_stream_allows() {
+        let watts = vec![200.0; 1800];
+        let curve = mean_max_power(&watts, STANDARD_DURATIONS);
+        assert_eq!(curve.points.last().unwrap().duration_sec, 1800);
+        assert!(curve.best(2400).is_none());
+        assert!(curve.points.iter().all(|p| p.watts == 200.0));
+    }
+
+    #[test]
+    fn test_history_curve_keeps_best_per_duration_with_dates() {
+        let mut sprint = vec![150; 30];
+        sprint[10] = 900;
+        let history = WpsHistory {
+            history_version: 2,
+            exported_at: "2025-02-01T00:00:00Z".to_string(),
+            export_source: None,
+            units: Default::default(),
+            workouts: vec![
+                ride("2025-01-10", &sprint),
+                ride("2025-01-20", &[250; 30]),
+                ride("2025-03-01", &[1200; 30]),
+            ],
+            personal_records: Vec::new(),
+            body_measurements: Vec::new(),
+            extensions: Default::default(),
+        };
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2025, 1, 1),
+            NaiveDate::from_ymd_opt(2025, 1, 31),
+        );
+        let curve = history_power_curve(&history, &range);
+
+        let one = &curve.points[0];
+        assert_eq!((one.duration_sec, one.watts), (1, 900.0));
+        assert_eq!(one.date.as_deref(), Some("2025-01-10"));
+        let thirty = curve.points.iter().find(|p| p.duration_sec == 30).unwrap();
+        assert_eq!(thirty.watts, 250.0);
+        assert_eq!(thirty.date.as_deref(), Some("2025-01-20"));
+    }
+
+    #[test]
+    fn test_fit_recovers_model_parameters() {
+        let (cp, w_prime) = (280.0, 20_000.0);
+        let curve = curve(&[60, 180, 300, 600, 1200, 3600].map(|t| (t, cp + w_prime / t as f64)));
+        let model = fit_critical_power(&curve).unwrap();
+        assert!((model.cp_watts - cp).abs() < 1e-6);
+        assert!((model.w_prime_joules - w_prime).abs() < 1e-3);
+        assert!((model.r_squared - 1.0).abs() < 1e-9);
+        assert!((model.power_at(600.0) - (cp + w_prime / 600.0)).abs() < 1e-6);
+
+        let ftp = estimate_ftp(&curve).unwrap();
+        assert_eq!(ftp.watts, 280);
+        assert_eq!(ftp.source, FtpSource::CriticalPower);
+    }
+
+    #[test]
+    fn test_ftp_falls_back_to_twenty_minute_power() {
+        let curve = curve(&[(60, 400.0), (1200, 300.0)]);
+        assert!(fit_critical_power(&curve).is_none());
+        let ftp = estimate_ftp(&curve).unwrap();
+        assert_eq!(ftp.watts, 285);
+        assert_eq!(ftp.source, FtpSource::TwentyMinutePower);
+    }
+
+    #[test]
+    fn test_fit_rejects_non_physical_results() {
+        // Longer efforts at higher power give a negative W′
+        let curve = curve(&[(180, 200.0), (600, 250.0)]);
+        assert!(fit_critical_power(&curve).is_none());
+        assert!(analyze_power_curve(PowerCurve::default())
+            .estimated_ftp
+            .is_none());
+    }
+}
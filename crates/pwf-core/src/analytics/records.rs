@@ -0,0 +1,612 @@
+//! Estimated one-rep max and personal-record detection
+//!
+//! Records are recomputed from the sets themselves instead of trusting the
+//! exporting app's `personal_records` and `is_pr`. Workouts are scanned in
+//! chronological order; a set is a PR when it strictly beats the best
+//! earlier performance of the same exercise. The first performance of an
+//! exercise sets the baseline and is not flagged.
+//!
+//! Exercises are matched by name, ignoring case and surrounding whitespace.
+//! Weights are compared in kilograms and records are reported in kg.
+
+use super::date_range::workout_date;
+use crate::history::{
+    CompletedExercise, CompletedSet, PersonalRecord, RecordType, SetType, WpsHistory,
+};
+use crate::LB_TO_KG;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Highest rep count an e1RM is estimated from; beyond it the formulas
+/// drift too far from tested maxes
+pub const MAX_E1RM_REPS: u32 = 12;
+
+/// Percentage of 1RM for a set taken to failure, by reps (index 0 = 1 rep).
+/// A set with reps in reserve counts as `reps + RIR` reps to failure.
+const RPE_TABLE: [f64; MAX_E1RM_REPS as usize] = [
+    1.000, 0.955, 0.922, 0.892, 0.863, 0.837, 0.811, 0.786, 0.762, 0.739, 0.707, 0.680,
+];
+
+/// Formula for estimating a one-rep max from a submaximal set
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum E1rmFormula {
+    /// `weight * (1 + reps / 30)`
+    #[default]
+    Epley,
+    /// `weight * 36 / (37 - reps)`
+    Brzycki,
+    /// RPE percentage table using reps plus reps in reserve; needs `rpe` or
+    /// `rir` on the set
+    RpeTable,
+}
+
+/// Estimated one-rep max for `reps` at `weight`
+///
+/// `rpe` is only used by [`E1rmFormula::RpeTable`]. Returns `None` for
+/// zero reps, more than [`MAX_E1RM_REPS`] reps, or a missing RPE.
+pub fn estimate_1rm(weight: f64, reps: u32, rpe: Option<f64>, formula: E1rmFormula) -> Option<f64> {
+    if reps == 0 || reps > MAX_E1RM_REPS || weight.is_nan() || weight <= 0.0 {
+        return None;
+    }
+    if reps == 1 && formula != E1rmFormula::RpeTable {
+        return Some(weight);
+    }
+    let reps_f = reps as f64;
+    match formula {
+        E1rmFormula::Epley => Some(weight + weight * reps_f / 30.0),
+        E1rmFormula::Brzycki => Some(weight * 36.0 / (37.0 - reps_f)),
+        E1rmFormula::RpeTable => {
+            let reserve = (10.0 - rpe?).max(0.0).round() as u32;
+            let percent = RPE_TABLE.get((reps + reserve) as usize - 1)?;
+            Some(weight / percent)
+        }
+    }
+}
+
+/// Estimated one-rep max of a completed set, in kg
+///
+/// With the RPE table, RIR is used when the set records no RPE.
+pub fn set_e1rm(set: &CompletedSet, formula: E1rmFormula) -> Option<f64> {
+    let rpe = set.rpe.or_else(|| set.rir.map(|rir| 10.0 - rir as f64));
+    estimate_1rm(set_weight_kg(set)?, set.reps?, rpe, formula)
+}
+
+pub(crate) fn set_weight_kg(set: &CompletedSet) -> Option<f64> {
+    set.weight_kg
+        .or_else(|| set.weight_lb.map(|lb| lb * LB_TO_KG))
+        .filter(|w| *w > 0.0)
+}
+
+/// Options for record detection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecordOptions {
+    pub formula: E1rmFormula,
+}
+
+/// A set (or session, for volume) that beat the previous best
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PrEvent {
+    pub exercise_name: String,
+    pub record_type: RecordType,
+    pub value: f64,
+    /// The best before this one
+    pub previous: f64,
+    pub achieved_at: String,
+    pub workout_index: usize,
+    pub exercise_index: usize,
+    /// `None` for records over a whole session, like `max_volume`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_index: Option<usize>,
+}
+
+/// A difference between the stored records and the recomputed ones
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordDisagreement {
+    /// A stored record whose value differs from the best in the data
+    /// (`computed` is `None` when the data has no such performance)
+    ValueMismatch {
+        exercise_name: String,
+        record_type: RecordType,
+        stored: f64,
+        computed: Option<f64>,
+    },
+    /// A record found in the data that `personal_records` does not list
+    Missing {
+        exercise_name: String,
+        record_type: RecordType,
+        computed: f64,
+    },
+    /// A set whose `is_pr` flag contradicts the recomputed PRs; sets
+    /// without a flag are not reported
+    IsPrMismatch {
+        workout_index: usize,
+        exercise_index: usize,
+        set_index: usize,
+        stored: bool,
+        computed: bool,
+    },
+}
+
+/// Recomputed personal records for a history
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecordAnalysis {
+    /// Current best of every record type for every exercise
+    pub records: Vec<PersonalRecord>,
+    /// Every time a record was broken, in chronological order
+    pub events: Vec<PrEvent>,
+    pub disagreements: Vec<RecordDisagreement>,
+}
+
+impl RecordAnalysis {
+    /// Whether a set broke any record
+    pub fn is_pr(&self, workout_index: usize, exercise_index: usize, set_index: usize) -> bool {
+        self.events.iter().any(|e| {
+            e.workout_index == workout_index
+                && e.exercise_index == exercise_index
+                && e.set_index == Some(set_index)
+        })
+    }
+}
+
+/// Set-level record types, in the order they are reported
+const SET_RECORDS: [RecordType; 9] = [
+    RecordType::OneRepMax,
+    RecordType::MaxWeight3rm,
+    RecordType::MaxWeight5rm,
+    RecordType::MaxWeight8rm,
+    RecordType::MaxWeight10rm,
+    RecordType::MaxWeight,
+    RecordType::MaxReps,
+    RecordType::MaxDuration,
+    RecordType::MaxDistance,
+];
+
+/// The value a set scores for a record type; higher is better
+fn set_value(set: &CompletedSet, record_type: RecordType, options: &RecordOptions) -> Option<f64> {
+    let heaviest_for =
+        |min_reps: u32| set_weight_kg(set).filter(|_| set.reps.is_some_and(|r| r >= min_reps));
+    match record_type {
+        RecordType::OneRepMax => set_e1rm(set, options.formula),
+        RecordType::MaxWeight3rm => heaviest_for(3),
+        RecordType::MaxWeight5rm => heaviest_for(5),
+        RecordType::MaxWeight8rm => heaviest_for(8),
+        RecordType::MaxWeight10rm => heaviest_for(10),
+        RecordType::MaxWeight => heaviest_for(1),
+        RecordType::MaxReps => set.reps.filter(|&r| r > 0).map(f64::from),
+        RecordType::MaxDuration => set.duration_sec.filter(|&d| d > 0).map(f64::from),
+        RecordType::MaxDistance => set.distance_meters.filter(|&d| d > 0.0),
+        RecordType::MaxVolume | RecordType::FastestTime => None,
+    }
+}
+
+/// Total weight × reps of an exercise in one session, in kg
+fn exercise_volume(exercise: &CompletedExercise) -> Option<f64> {
+    let volume: f64 = counted_sets(exercise)
+        .filter_map(|(_, s)| Some(set_weight_kg(s)? * s.reps? as f64))
+        .sum();
+    (volume > 0.0).then_some(volume)
+}
+
+fn counted_sets(exercise: &CompletedExercise) -> impl Iterator<Item = (usize, &CompletedSet)> {
+    exercise
+        .sets
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.set_type != Some(SetType::Warmup))
+}
+
+fn unit(record_type: RecordType) -> Option<&'static str> {
+    match record_type {
+        RecordType::MaxReps => None,
+        RecordType::MaxDuration | RecordType::FastestTime => Some("seconds"),
+        RecordType::MaxDistance => Some("meters"),
+        _ => Some("kg"),
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Best value so far, keyed by normalized exercise name and record type
+/// (plus distance in metres for fastest times)
+#[derive(Default)]
+struct Bests {
+    best: HashMap<(String, RecordType, u64), PersonalRecord>,
+    order: Vec<(String, RecordType, u64)>,
+}
+
+impl Bests {
+    /// Offer a value; returns the previous best when it was beaten.
+    /// Lower values win for `fastest_time`.
+    fn offer(
+        &mut self,
+        key: (String, RecordType, u64),
+        value: f64,
+        record: impl FnOnce() -> PersonalRecord,
+    ) -> Option<f64> {
+        let lower_wins = key.1 == RecordType::FastestTime;
+        match self.best.get_mut(&key) {
+            Some(best) => {
+                let better = if lower_wins {
+                    value < best.value
+                } else {
+                    value > best.value
+                };
+                if !better {
+                    return None;
+                }
+                let previous = best.value;
+                *best = record();
+                Some(previous)
+            }
+            None => {
+                self.order.push(key.clone());
+                self.best.insert(key, record());
+                None
+            }
+        }
+    }
+
+    /// Records grouped by exercise, in the order exercises first appeared
+    fn into_records(mut self) -> Vec<PersonalRecord> {
+        let mut exercises: Vec<&String> = Vec::new();
+        for (name, _, _) in &self.order {
+            if !exercises.contains(&name) {
+                exercises.push(name);
+            }
+        }
+        let mut order = self.order.clone();
+        order.sort_by_key(|(name, _, _)| exercises.iter().position(|e| *e == name));
+        order
+            .iter()
+            .filter_map(|key| self.best.remove(key))
+            .collect()
+    }
+}
+
+/// Recompute personal records and PR flags for a history
+///
+/// Compares the result with the stored `personal_records` and `is_pr`
+/// flags. Values are rounded to 0.1 before comparing.
+pub fn analyze_records(history: &WpsHistory, options: &RecordOptions) -> RecordAnalysis {
+    let mut order: Vec<usize> = (0..history.workouts.len()).collect();
+    // Stable, so same-day workouts keep file order
+    order.sort_by_key(|&i| {
+        let w = &history.workouts[i];
+        (workout_date(w), w.started_at.clone())
+    });
+
+    let mut bests = Bests::default();
+    let mut events = Vec::new();
+    for &wi in &order {
+        let workout = &history.workouts[wi];
+        for (ei, exercise) in workout.exercises.iter().enumerate() {
+            let name = normalize(&exercise.name);
+            let record = |record_type, value| PersonalRecord {
+                exercise_name: exercise.name.clone(),
+                record_type,
+                value,
+                unit: unit(record_type).map(str::to_string),
+                achieved_at: workout.date.clone(),
+                workout_id: workout.id.clone(),
+                notes: None,
+            };
+            let mut offer = |record_type, value: f64, distance: u64, set_index| {
+                let value = round1(value);
+                let mut pr = record(record_type, value);
+                if record_type == RecordType::FastestTime {
+                    pr.notes = Some(format!("{} m", distance));
+                }
+                let key = (name.clone(), record_type, distance);
+                if let Some(previous) = bests.offer(key, value, || pr) {
+                    events.push(PrEvent {
+                        exercise_name: exercise.name.clone(),
+                        record_type,
+                        value,
+                        previous,
+                        achieved_at: workout.date.clone(),
+                        workout_index: wi,
+                        exercise_index: ei,
+                        set_index,
+                    });
+                }
+            };
+
+            for (si, set) in counted_sets(exercise) {
+                for record_type in SET_RECORDS {
+                    if let Some(value) = set_value(set, record_type, options) {
+                        offer(record_type, value, 0, Some(si));
+                    }
+                }
+                if let (Some(time), Some(distance)) = (set.duration_sec, set.distance_meters) {
+                    if time > 0 && distance > 0.0 {
+                        offer(
+                            RecordType::FastestTime,
+                            time as f64,
+                            distance.round() as u64,
+                            Some(si),
+                        );
+                    }
+                }
+            }
+            if let Some(volume) = exercise_volume(exercise) {
+                offer(RecordType::MaxVolume, volume, 0, None);
+            }
+        }
+    }
+
+    let records = bests.into_records();
+    let mut analysis = RecordAnalysis {
+        disagreements: Vec::new(),
+        records,
+        events,
+    };
+    analysis.disagreements = disagreements(history, &analysis);
+    analysis
+}
+
+fn disagreements(history: &WpsHistory, analysis: &RecordAnalysis) -> Vec<RecordDisagreement> {
+    let mut out = Vec::new();
+    let same_kind = |a: &PersonalRecord, b: &PersonalRecord| {
+        normalize(&a.exercise_name) == normalize(&b.exercise_name) && a.record_type == b.record_type
+    };
+
+    for stored in &history.personal_records {
+        let stored_value = match stored.unit.as_deref() {
+            Some("lb") | Some("lbs") => stored.value * LB_TO_KG,
+            _ => stored.value,
+        };
+        let candidates: Vec<&PersonalRecord> = analysis
+            .records
+            .iter()
+            .filter(|r| same_kind(r, stored))
+            .collect();
+        if candidates
+            .iter()
+            .any(|r| (r.value - stored_value).abs() < 0.05 + r.value * 1e-3)
+        {
+            continue;
+        }
+        out.push(RecordDisagreement::ValueMismatch {
+            exercise_name: stored.exercise_name.clone(),
+            record_type: stored.record_type,
+            stored: stored.value,
+            computed: candidates.first().map(|r| r.value),
+        });
+    }
+
+    for record in &analysis.records {
+        if !history
+            .personal_records
+            .iter()
+            .any(|stored| same_kind(record, stored))
+        {
+            out.push(RecordDisagreement::Missing {
+                exercise_name: record.exercise_name.clone(),
+                record_type: record.record_type,
+                computed: record.value,
+            });
+        }
+    }
+
+    for (wi, workout) in history.workouts.iter().enumerate() {
+        for (ei, exercise) in workout.exercises.iter().enumerate() {
+            for (si, set) in exercise.sets.iter().enumerate() {
+                let Some(stored) = set.is_pr else {
+                    continue;
+                };
+                let computed = analysis.is_pr(wi, ei, si);
+                if stored != computed {
+                    out.push(RecordDisagreement::IsPrMismatch {
+                        workout_index: wi,
+                        exercise_index: ei,
+                        set_index: si,
+                        stored,
+                        computed,
+                    });
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Replace a history's `personal_records` and `is_pr` flags with
+/// recomputed ones
+///
+/// PR sets get `is_pr: true`; every other flag is cleared. Returns the
+/// analysis, whose disagreements describe what was replaced.
+pub fn regenerate_records(history: &mut WpsHistory, options: &RecordOptions) -> RecordAnalysis {
+    let analysis = analyze_records(history, options);
+    history.personal_records = analysis.records.clone();
+    for (wi, workout) in history.workouts.iter_mut().enumerate() {
+        for (ei, exercise) in workout.exercises.iter_mut().enumerate() {
+            for (si, set) in exercise.sets.iter_mut().enumerate() {
+                set.is_pr = analysis.is_pr(wi, ei, si).then_some(true);
+            }
+        }
+    }
+    analysis
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_e1rm_formulas() {
+        assert_eq!(
+            estimate_1rm(100.0, 1, None, E1rmFormula::Epley),
+            Some(100.0)
+        );
+        assert_eq!(
+            estimate_1rm(100.0, 3, None, E1rmFormula::Epley),
+            Some(110.0)
+        );
+        assert_eq!(
+            estimate_1rm(100.0, 1, None, E1rmFormula::Brzycki),
+            Some(100.0)
+        );
+        assert!(
+            (estimate_1rm(100.0, 10, None, E1rmFormula::Brzycki).unwrap() - 133.33).abs() < 0.01
+        );
+        // 5 reps at RPE 8 is 7 reps to failure: 81.1%
+        let rpe = estimate_1rm(100.0, 5, Some(8.0), E1rmFormula::RpeTable).unwrap();
+        assert!((rpe - 100.0 / 0.811).abs() < 1e-9);
+        assert!(estimate_1rm(100.0, 5, None, E1rmFormula::RpeTable).is_none());
+        assert!(estimate_1rm(100.0, 0, None, E1rmFormula::Epley).is_none());
+        assert!(estimate_1rm(100.0, 20, None, E1rmFormula::Epley).is_none());
+    }
+
+    #[test]
+    fn test_set_e1rm_uses_rir_and_pounds() {
+        let set: CompletedSet = serde_yaml::from_str("reps: 5\nweight_lb: 225\nrir: 2\n").unwrap();
+        let kg = 225.0 * LB_TO_KG;
+        let e1rm = set_e1rm(&set, E1rmFormula::RpeTable).unwrap();
+        assert!((e1rm - kg / 0.811).abs() < 1e-9);
+    }
+
+    const HISTORY: &str = r#"
+history_version: 2
+exported_at: "2025-02-01T00:00:00Z"
+workouts:
+  - id: w3
+    date: "2025-01-20"
+    exercises:
+      - name: Squat
+        sets:
+          - { reps: 5, weight_kg: 105, is_pr: true }
+  - id: w1
+    date: "2025-01-06"
+    exercises:
+      - name: Squat
+        sets:
+          - { reps: 10, weight_kg: 60, set_type: warmup }
+          - { reps: 5, weight_kg: 100 }
+          - { reps: 5, weight_kg: 100 }
+      - name: Run
+        sets:
+          - { distance_meters: 5000, duration_sec: 1500 }
+  - id: w2
+    date: "2025-01-13"
+    exercises:
+      - name: squat
+        sets:
+          - { reps: 3, weight_kg: 110, is_pr: false }
+      - name: Run
+        sets:
+          - { distance_meters: 5000, duration_sec: 1450 }
+personal_records:
+  - exercise_name: Squat
+    record_type: max_weight
+    value: 110
+    unit: kg
+    achieved_at: "2025-01-13"
+  - exercise_name: Squat
+    record_type: max_weight_5rm
+    value: 100
+    unit: kg
+    achieved_at: "2025-01-06"
+"#;
+
+    fn find(records: &[PersonalRecord], record_type: RecordType, name: &str) -> f64 {
+        records
+            .iter()
+            .find(|r| r.record_type == record_type && r.exercise_name.eq_ignore_ascii_case(name))
+            .unwrap()
+            .value
+    }
+
+    #[test]
+    fn test_records_scan_chronologically() {
+        let history: WpsHistory = serde_yaml::from_str(HISTORY).unwrap();
+        let analysis = analyze_records(&history, &RecordOptions::default());
+
+        let records = &analysis.records;
+        assert_eq!(find(records, RecordType::MaxWeight, "Squat"), 110.0);
+        assert_eq!(find(records, RecordType::MaxWeight3rm, "Squat"), 110.0);
+        assert_eq!(find(records, RecordType::MaxWeight5rm, "Squat"), 105.0);
+        assert_eq!(find(records, RecordType::OneRepMax, "Squat"), 122.5);
+        // 2 × 500 kg on 6 January; warmups do not count
+        assert_eq!(find(records, RecordType::MaxVolume, "Squat"), 1000.0);
+        assert_eq!(find(records, RecordType::FastestTime, "Run"), 1450.0);
+        assert!(!records
+            .iter()
+            .any(|r| r.record_type == RecordType::MaxWeight10rm));
+
+        // The repeat of 100 kg × 5 on 6 January is not a PR, nor is the baseline
+        assert!(!analysis.is_pr(1, 0, 1));
+        assert!(!analysis.is_pr(1, 0, 2));
+        assert!(analysis.is_pr(2, 0, 0));
+        assert!(analysis.is_pr(0, 0, 0));
+        assert!(analysis.is_pr(2, 1, 0));
+        let run = analysis
+            .events
+            .iter()
+            .find(|e| e.record_type == RecordType::FastestTime)
+            .unwrap();
+        assert_eq!(run.previous, 1500.0);
+    }
+
+    #[test]
+    fn test_disagreements_with_stored_values() {
+        let history: WpsHistory = serde_yaml::from_str(HISTORY).unwrap();
+        let analysis = analyze_records(&history, &RecordOptions::default());
+
+        assert!(analysis
+            .disagreements
+            .contains(&RecordDisagreement::ValueMismatch {
+                exercise_name: "Squat".into(),
+                record_type: RecordType::MaxWeight5rm,
+                stored: 100.0,
+                computed: Some(105.0),
+            }));
+        assert!(!analysis.disagreements.iter().any(|d| matches!(
+            d,
+            RecordDisagreement::ValueMismatch {
+                record_type: RecordType::MaxWeight,
+                ..
+            }
+        )));
+        assert!(analysis
+            .disagreements
+            .contains(&RecordDisagreement::IsPrMismatch {
+                workout_index: 2,
+                exercise_index: 0,
+                set_index: 0,
+                stored: false,
+                computed: true,
+            }));
+        assert!(analysis.disagreements.iter().any(|d| matches!(
+            d,
+            RecordDisagreement::Missing {
+                record_type: RecordType::FastestTime,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn test_regenerate_records() {
+        let mut history: WpsHistory = serde_yaml::from_str(HISTORY).unwrap();
+        let options = RecordOptions {
+            formula: E1rmFormula::Brzycki,
+        };
+        regenerate_records(&mut history, &options);
+
+        assert_eq!(history.workouts[2].exercises[0].sets[0].is_pr, Some(true));
+        assert_eq!(history.workouts[1].exercises[0].sets[1].is_pr, None);
+        assert_eq!(
+            find(&history.personal_records, RecordType::OneRepMax, "Squat"),
+            118.1
+        );
+        assert!(analyze_records(&history, &options).disagreements.is_empty());
+    }
+}
@@ -0,0 +1,714 @@
+//! Running analysis: splits, best efforts, grade-adjusted pace and decoupling
+//!
+//! Everything here works on a [`RunTrack`]: cumulative distance against
+//! moving time, built from the time series (`distance_m`, else integrated
+//! `speed_mps`, else latitude/longitude) or, failing that, the GPS route.
+//! Distances between samples are interpolated linearly, so splits and best
+//! efforts start and end exactly on their distance marks.
+//!
+//! Grade-adjusted pace scales each stretch by the energy cost of running on
+//! its grade relative to the flat (Minetti et al., 2002), so a 6:00/km climb
+//! can be compared with flat running. Grades come from `grade_percent` when
+//! recorded and are otherwise derived from elevation.
+
+use super::date_range::DateRange;
+use super::series::{parse_timestamp, timed_series, MAX_GAP_SEC};
+use super::summary::haversine_m;
+use crate::history::{TimeSeriesData, Workout, WpsHistory};
+use crate::{Modality, Sport, METERS_PER_MILE};
+use serde::Serialize;
+
+/// Distances reported as best efforts by default
+pub const STANDARD_RUN_DISTANCES: &[(&str, f64)] = &[
+    ("400m", 400.0),
+    ("1k", 1000.0),
+    ("1 mile", METERS_PER_MILE),
+    ("5k", 5000.0),
+    ("10k", 10_000.0),
+    ("Half marathon", 21_097.5),
+    ("Marathon", 42_195.0),
+];
+
+/// Steepest grade (as a fraction) the cost model is applied to; the Minetti
+/// polynomial was fitted on ±45%
+pub const MAX_GRADE: f64 = 0.45;
+
+/// Distance over which grade is derived from elevation, to keep GPS and
+/// barometer noise from producing absurd short-range grades
+pub const GRADE_WINDOW_M: f64 = 20.0;
+
+/// Remainders shorter than this are not reported as a partial split
+const MIN_PARTIAL_SPLIT_M: f64 = 1.0;
+
+/// Where a run's distance came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackSource {
+    TimeSeries,
+    GpsRoute,
+}
+
+/// One sample of a run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TrackPoint {
+    /// Moving time since the first sample
+    pub time_sec: f64,
+    /// Cumulative distance
+    pub distance_m: f64,
+    /// Cumulative distance adjusted for grade: the flat distance that would
+    /// have cost the same effort
+    pub flat_distance_m: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elevation_m: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heart_rate_bpm: Option<f64>,
+}
+
+/// Distance against moving time for one run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunTrack {
+    pub source: TrackSource,
+    /// Whether any grade or elevation was available to adjust pace with
+    pub graded: bool,
+    pub points: Vec<TrackPoint>,
+    /// Cumulative heart rate × seconds, and seconds with heart rate
+    hr_area: Vec<f64>,
+    hr_time: Vec<f64>,
+}
+
+/// One split of a run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Split {
+    /// 1-based split number
+    pub number: usize,
+    /// Split length; shorter than the split distance for a final partial split
+    pub distance_m: f64,
+    pub duration_sec: f64,
+    pub pace_sec_per_km: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade_adjusted_pace_sec_per_km: Option<f64>,
+    /// Net elevation change across the split
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elevation_change_m: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heart_rate_avg: Option<f64>,
+}
+
+/// Fastest stretch of a run covering a given distance
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BestEffort {
+    pub name: &'static str,
+    pub distance_m: f64,
+    pub duration_sec: f64,
+    pub pace_sec_per_km: f64,
+    /// Moving time at which the effort started
+    pub start_sec: f64,
+}
+
+/// Pace-to-heart-rate drift between the two halves of a run
+///
+/// Efficiency is speed per heartbeat (grade-adjusted when the run has
+/// grade). Positive decoupling means the second half took more heart rate
+/// for the same pace; under about 5% is usually read as aerobically fit
+/// for the duration.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PaceDecoupling {
+    pub first_half_pace_sec_per_km: f64,
+    pub second_half_pace_sec_per_km: f64,
+    pub first_half_heart_rate: f64,
+    pub second_half_heart_rate: f64,
+    pub decoupling_percent: f64,
+}
+
+/// Running metrics for one workout
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunningAnalysis {
+    pub date: String,
+    pub source: TrackSource,
+    pub distance_m: f64,
+    pub moving_time_sec: f64,
+    pub pace_sec_per_km: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade_adjusted_pace_sec_per_km: Option<f64>,
+    pub splits_km: Vec<Split>,
+    pub splits_mile: Vec<Split>,
+    pub best_efforts: Vec<BestEffort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decoupling: Option<PaceDecoupling>,
+}
+
+/// Energy cost of running on `grade` relative to the flat
+///
+/// Uses the Minetti polynomial for cost in J/kg/m, with the grade (a
+/// fraction, positive uphill) clamped to ±[`MAX_GRADE`]. Steep downhills
+/// cost less than the flat, very steep ones more again.
+pub fn grade_cost_factor(grade: f64) -> f64 {
+    fn cost(g: f64) -> f64 {
+        155.4 * g.powi(5) - 30.4 * g.powi(4) - 43.3 * g.powi(3) + 46.3 * g.powi(2) + 19.5 * g + 3.6
+    }
+    cost(grade.clamp(-MAX_GRADE, MAX_GRADE)) / cost(0.0)
+}
+
+/// Every running metric for a workout
+///
+/// Returns `None` when the workout has no distance data to build a track
+/// from.
+pub fn analyze_running(workout: &Workout) -> Option<RunningAnalysis> {
+    let track = run_track(workout)?;
+    let last = track.points.last()?;
+    Some(RunningAnalysis {
+        date: workout.date.clone(),
+        source: track.source,
+        distance_m: last.distance_m,
+        moving_time_sec: last.time_sec,
+        pace_sec_per_km: pace(last.time_sec, last.distance_m),
+        grade_adjusted_pace_sec_per_km: track
+            .graded
+            .then(|| pace(last.time_sec, last.flat_distance_m)),
+        splits_km: run_splits(&track, 1000.0),
+        splits_mile: run_splits(&track, METERS_PER_MILE),
+        best_efforts: best_efforts(&track, STANDARD_RUN_DISTANCES),
+        decoupling: pace_decoupling(&track),
+    })
+}
+
+/// Running analyses for every run in a date range, in history order
+///
+/// A workout is a run when its `sport` or any exercise's `modality` or
+/// `sport` is running. Runs without distance data are left out.
+pub fn analyze_runs(history: &WpsHistory, range: &DateRange) -> Vec<RunningAnalysis> {
+    range
+        .workouts(history)
+        .filter(|w| is_run(w))
+        .filter_map(analyze_running)
+        .collect()
+}
+
+fn is_run(workout: &Workout) -> bool {
+    workout.sport == Some(Sport::Running)
+        || workout
+            .exercises
+            .iter()
+            .any(|e| e.modality == Some(Modality::Running) || e.sport == Some(Sport::Running))
+}
+
+/// Build a workout's track from its time series, falling back to the GPS
+/// route
+///
+/// Moving time skips pauses: gaps longer than [`MAX_GAP_SEC`] count as a
+/// single second, as in [`super::resample_1hz`]. Returns `None` when no
+/// distance is covered.
+pub fn run_track(workout: &Workout) -> Option<RunTrack> {
+    let mut rows: Vec<Row> = timed_series(workout)
+        .into_iter()
+        .flat_map(|(ts, times)| series_rows(ts, &times))
+        .collect();
+    let mut source = TrackSource::TimeSeries;
+
+    if rows.iter().all(|r| r.increment_m == 0.0) {
+        rows = route_rows(workout);
+        source = TrackSource::GpsRoute;
+    }
+    rows.sort_by(|a, b| a.time.total_cmp(&b.time));
+    RunTrack::from_rows(source, &rows)
+}
+
+/// Splits of `split_m` metres, with a final partial split for the remainder
+pub fn run_splits(track: &RunTrack, split_m: f64) -> Vec<Split> {
+    let Some(total) = track.points.last().map(|p| p.distance_m) else {
+        return Vec::new();
+    };
+    if split_m <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut splits = Vec::new();
+    let mut start = track.at_distance(0.0);
+    let mut mark = 0.0;
+    while total - mark >= MIN_PARTIAL_SPLIT_M {
+        let end_mark = (mark + split_m).min(total);
+        let end = track.at_distance(end_mark);
+        let distance = end_mark - mark;
+        let duration = end.time - start.time;
+        splits.push(Split {
+            number: splits.len() + 1,
+            distance_m: distance,
+            duration_sec: duration,
+            pace_sec_per_km: pace(duration, distance),
+            grade_adjusted_pace_sec_per_km: track
+                .graded
+                .then(|| pace(duration, end.flat - start.flat)),
+            elevation_change_m: end.elevation.zip(start.elevation).map(|(e, s)| e - s),
+            heart_rate_avg: heart_rate_between(&start, &end),
+        });
+        start = end;
+        mark = end_mark;
+    }
+    splits
+}
+
+/// Fastest time over each distance, for distances the run covers
+pub fn best_efforts(track: &RunTrack, distances: &[(&'static str, f64)]) -> Vec<BestEffort> {
+    let Some(total) = track.points.last().map(|p| p.distance_m) else {
+        return Vec::new();
+    };
+
+    distances
+        .iter()
+        .filter(|&&(_, d)| d > 0.0 && d <= total)
+        .filter_map(|&(name, distance)| {
+            // Every effort ends on a sample; its start is interpolated
+            let (duration, start) = track
+                .points
+                .iter()
+                .filter(|p| p.distance_m >= distance)
+                .map(|p| {
+                    let start = track.at_distance(p.distance_m - distance).time;
+                    (p.time_sec - start, start)
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0))?;
+            Some(BestEffort {
+                name,
+                distance_m: distance,
+                duration_sec: duration,
+                pace_sec_per_km: pace(duration, distance),
+                start_sec: start,
+            })
+        })
+        .collect()
+}
+
+/// Compare efficiency between the first and second half of moving time
+///
+/// Returns `None` when either half has no heart rate.
+pub fn pace_decoupling(track: &RunTrack) -> Option<PaceDecoupling> {
+    let total = track.points.last()?.time_sec;
+    let start = track.at_time(0.0);
+    let middle = track.at_time(total / 2.0);
+    let end = track.at_time(total);
+
+    let half = |from: &Cursor, to: &Cursor| -> Option<(f64, f64)> {
+        let distance = if track.graded {
+            to.flat - from.flat
+        } else {
+            to.distance - from.distance
+        };
+        let duration = to.time - from.time;
+        let heart_rate = heart_rate_between(from, to)?;
+        (distance > 0.0 && duration > 0.0).then_some((pace(duration, distance), heart_rate))
+    };
+    let (first_pace, first_hr) = half(&start, &middle)?;
+    let (second_pace, second_hr) = half(&middle, &end)?;
+
+    // Efficiency as speed per beat; pace is its inverse
+    let first = 1.0 / (first_pace * first_hr);
+    let second = 1.0 / (second_pace * second_hr);
+    Some(PaceDecoupling {
+        first_half_pace_sec_per_km: first_pace,
+        second_half_pace_sec_per_km: second_pace,
+        first_half_heart_rate: first_hr,
+        second_half_heart_rate: second_hr,
+        decoupling_percent: (first - second) / first * 100.0,
+    })
+}
+
+fn pace(duration_sec: f64, distance_m: f64) -> f64 {
+    if distance_m > 0.0 {
+        duration_sec / distance_m * 1000.0
+    } else {
+        0.0
+    }
+}
+
+fn heart_rate_between(from: &Cursor, to: &Cursor) -> Option<f64> {
+    let seconds = to.hr_time - from.hr_time;
+    (seconds > 0.0).then(|| (to.hr_area - from.hr_area) / seconds)
+}
+
+/// A sample before the track is assembled: absolute time, distance covered
+/// since the previous sample and optional extras
+#[derive(Debug, Clone, Copy)]
+struct Row {
+    time: f64,
+    increment_m: f64,
+    elevation: Option<f64>,
+    grade: Option<f64>,
+    heart_rate: Option<f64>,
+}
+
+fn series_rows(ts: &TimeSeriesData, times: &[f64]) -> Vec<Row> {
+    let at = |column: &Option<Vec<f64>>, i: usize| {
+        column
+            .as_ref()
+            .and_then(|c| c.get(i).copied())
+            .filter(|v| v.is_finite())
+    };
+    let coordinates = |i: usize| at(&ts.latitude, i).zip(at(&ts.longitude, i));
+
+    let mut rows = Vec::with_capacity(times.len());
+    let mut last_distance: Option<f64> = None;
+    let mut last_position: Option<(f64, f64)> = None;
+    for (i, &time) in times.iter().enumerate() {
+        let increment_m = if ts.distance_m.is_some() {
+            let distance = at(&ts.distance_m, i);
+            let increment = match (last_distance, distance) {
+                (Some(prev), Some(d)) => (d - prev).max(0.0),
+                _ => 0.0,
+            };
+            last_distance = distance.or(last_distance);
+            increment
+        } else if ts.speed_mps.is_some() {
+            match (i.checked_sub(1), at(&ts.speed_mps, i.saturating_sub(1))) {
+                (Some(prev), Some(speed)) => speed.max(0.0) * moving_seconds(time - times[prev]),
+                _ => 0.0,
+            }
+        } else {
+            let position = coordinates(i);
+            let increment = match (last_position, position) {
+                (Some(prev), Some(p)) => haversine_m(prev, p),
+                _ => 0.0,
+            };
+            last_position = position.or(last_position);
+            increment
+        };
+        rows.push(Row {
+            time,
+            increment_m,
+            elevation: at(&ts.elevation_m, i),
+            grade: at(&ts.grade_percent, i).map(|g| g / 100.0),
+            heart_rate: ts
+                .heart_rate
+                .as_ref()
+                .and_then(|hr| hr.get(i))
+                .map(|&hr| hr as f64),
+        });
+    }
+    rows
+}
+
+fn route_rows(workout: &Workout) -> Vec<Row> {
+    let positions = workout
+        .telemetry
+        .as_ref()
+        .and_then(|t| t.gps_route.as_ref())
+        .map(|r| r.positions.as_slice())
+        .unwrap_or_default();
+
+    let mut rows = Vec::with_capacity(positions.len());
+    let mut last: Option<(f64, f64)> = None;
+    for position in positions {
+        let Some(time) = parse_timestamp(&position.timestamp) else {
+            continue;
+        };
+        let here = (position.latitude_deg, position.longitude_deg);
+        rows.push(Row {
+            time,
+            increment_m: last.map_or(0.0, |prev| haversine_m(prev, here)),
+            elevation: position.elevation_m,
+            grade: None,
+            heart_rate: position.heart_rate_bpm.map(f64::from),
+        });
+        last = Some(here);
+    }
+    rows
+}
+
+/// Seconds a gap between samples contributes to moving time
+fn moving_seconds(dt: f64) -> f64 {
+    if dt <= 0.0 {
+        0.0
+    } else if dt > MAX_GAP_SEC {
+        1.0
+    } else {
+        dt
+    }
+}
+
+/// Values interpolated at a point along the track
+#[derive(Debug, Clone, Copy)]
+struct Cursor {
+    time: f64,
+    distance: f64,
+    flat: f64,
+    elevation: Option<f64>,
+    hr_area: f64,
+    hr_time: f64,
+}
+
+impl RunTrack {
+    fn from_rows(source: TrackSource, rows: &[Row]) -> Option<Self> {
+        let first = rows.first()?;
+        let graded = rows
+            .iter()
+            .any(|r| r.grade.is_some() || r.elevation.is_some());
+
+        let mut points = Vec::with_capacity(rows.len());
+        let mut hr_area = Vec::with_capacity(rows.len());
+        let mut hr_time = Vec::with_capacity(rows.len());
+        points.push(TrackPoint {
+            time_sec: 0.0,
+            distance_m: 0.0,
+            flat_distance_m: 0.0,
+            elevation_m: first.elevation,
+            heart_rate_bpm: first.heart_rate,
+        });
+        hr_area.push(0.0);
+        hr_time.push(0.0);
+
+        // Index of the most recent sample at least GRADE_WINDOW_M behind
+        let mut anchor = 0;
+        for (i, pair) in rows.windows(2).enumerate() {
+            let (prev, row) = (&pair[0], &pair[1]);
+            let last = points[i];
+            let dt = moving_seconds(row.time - prev.time);
+            let distance = last.distance_m + row.increment_m;
+
+            while anchor < i && distance - points[anchor + 1].distance_m >= GRADE_WINDOW_M {
+                anchor += 1;
+            }
+            let grade = row.grade.or_else(|| {
+                let (from, to) = (points[anchor].elevation_m?, row.elevation?);
+                let run = distance - points[anchor].distance_m;
+                (run >= GRADE_WINDOW_M).then(|| (to - from) / run)
+            });
+
+            points.push(TrackPoint {
+                time_sec: last.time_sec + dt,
+                distance_m: distance,
+                flat_distance_m: last.flat_distance_m
+                    + row.increment_m * grade.map_or(1.0, grade_cost_factor),
+                elevation_m: row.elevation,
+                heart_rate_bpm: row.heart_rate,
+            });
+            let (area, time) = match row.heart_rate {
+                Some(hr) => (hr * dt, dt),
+                None => (0.0, 0.0),
+            };
+            hr_area.push(hr_area[i] + area);
+            hr_time.push(hr_time[i] + time);
+        }
+
+        if points.last()?.distance_m <= 0.0 {
+            return None;
+        }
+        Some(RunTrack {
+            source,
+            graded,
+            points,
+            hr_area,
+            hr_time,
+        })
+    }
+
+    fn at_distance(&self, distance: f64) -> Cursor {
+        let idx = self.points.partition_point(|p| p.distance_m < distance);
+        self.interpolate(idx, |a, b| {
+            (distance - a.distance_m) / (b.distance_m - a.distance_m)
+        })
+    }
+
+    fn at_time(&self, time: f64) -> Cursor {
+        let idx = self.points.partition_point(|p| p.time_sec < time);
+        self.interpolate(idx, |a, b| (time - a.time_sec) / (b.time_sec - a.time_sec))
+    }
+
+    /// Interpolate between point `idx - 1` and `idx` at the fraction
+    /// `fraction` computes
+    fn interpolate(
+        &self,
+        idx: usize,
+        fraction: impl Fn(&TrackPoint, &TrackPoint) -> f64,
+    ) -> Cursor {
+        let cursor = |i: usize| {
+            let p = &self.points[i];
+            Cursor {
+                time: p.time_sec,
+                distance: p.distance_m,
+                flat: p.flat_distance_m,
+                elevation: p.elevation_m,
+                hr_area: self.hr_area[i],
+                hr_time: self.hr_time[i],
+            }
+        };
+        if idx == 0 {
+            return cursor(0);
+        }
+        if idx >= self.points.len() {
+            return cursor(self.points.len() - 1);
+        }
+
+        let (a, b) = (cursor(idx - 1), cursor(idx));
+        let f = fraction(&self.points[idx - 1], &self.points[idx]).clamp(0.0, 1.0);
+        let lerp = |x: f64, y: f64| x + (y - x) * f;
+        Cursor {
+            time: lerp(a.time, b.time),
+            distance: lerp(a.distance, b.distance),
+            flat: lerp(a.flat, b.flat),
+            elevation: match (a.elevation, b.elevation) {
+                (Some(x), Some(y)) => Some(lerp(x, y)),
+                (x, y) => x.or(y),
+            },
+            hr_area: lerp(a.hr_area, b.hr_area),
+            hr_time: lerp(a.hr_time, b.hr_time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A run sampled every second at constant speed, with optional columns
+    fn workout(seconds: usize, speed: f64, extra: &str) -> Workout {
+        let timestamps: Vec<String> = (0..=seconds)
+            .map(|i| format!("\"2025-03-01T08:{:02}:{:02}Z\"", i / 60, i % 60))
+            .collect();
+        let distance: Vec<String> = (0..=seconds)
+            .map(|i| format!("{}", i as f64 * speed))
+            .collect();
+        let yaml = format!(
+            "date: \"2025-03-01\"\nexercises:\n  - name: Run\n    modality: running\n    sets:\n      - duration_sec: {}\n        telemetry:\n          time_series:\n            timestamps: [{}]\n            distance_m: [{}]\n{}",
+            seconds,
+            timestamps.join(", "),
+            distance.join(", "),
+            extra
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    fn column(name: &str, values: impl Iterator<Item = f64>) -> String {
+        let values: Vec<String> = values.map(|v| v.to_string()).collect();
+        format!("            {}: [{}]\n", name, values.join(", "))
+    }
+
+    #[test]
+    fn test_grade_cost_factor() {
+        assert!((grade_cost_factor(0.0) - 1.0).abs() < 1e-12);
+        assert!(grade_cost_factor(0.1) > 1.5);
+        assert!(grade_cost_factor(-0.1) < 1.0);
+        assert_eq!(grade_cost_factor(0.9), grade_cost_factor(MAX_GRADE));
+    }
+
+    #[test]
+    fn test_splits_and_best_efforts_on_even_pace() {
+        // 2.5 km at 4 m/s: 250 s per km
+        let analysis = analyze_running(&workout(625, 4.0, "")).unwrap();
+        assert_eq!(analysis.source, TrackSource::TimeSeries);
+        assert!((analysis.distance_m - 2500.0).abs() < 1e-9);
+        assert!((analysis.pace_sec_per_km - 250.0).abs() < 1e-9);
+        assert_eq!(analysis.grade_adjusted_pace_sec_per_km, None);
+
+        let distances: Vec<f64> = analysis.splits_km.iter().map(|s| s.distance_m).collect();
+        assert_eq!(distances, vec![1000.0, 1000.0, 500.0]);
+        assert!(analysis
+            .splits_km
+            .iter()
+            .all(|s| (s.pace_sec_per_km - 250.0).abs() < 1e-9));
+        assert_eq!(analysis.splits_mile.len(), 2);
+        assert!((analysis.splits_mile[0].duration_sec - 402.336).abs() < 1e-6);
+
+        let names: Vec<&str> = analysis.best_efforts.iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["400m", "1k", "1 mile"]);
+        assert!((analysis.best_efforts[0].duration_sec - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_best_effort_finds_fastest_stretch() {
+        // 300 s at 3 m/s, then 300 s at 5 m/s
+        let mut distance = 0.0;
+        let values: Vec<f64> = (0..=600)
+            .map(|i| {
+                let d = distance;
+                distance += if i < 300 { 3.0 } else { 5.0 };
+                d
+            })
+            .collect();
+        let mut run = workout(600, 0.0, "");
+        let ts = run.exercises[0].sets[0]
+            .telemetry
+            .as_mut()
+            .unwrap()
+            .time_series
+            .as_mut()
+            .unwrap();
+        ts.distance_m = Some(values);
+
+        let track = run_track(&run).unwrap();
+        let efforts = best_efforts(&track, &[("1k", 1000.0)]);
+        assert!((efforts[0].duration_sec - 200.0).abs() < 1e-9);
+        assert!(efforts[0].start_sec >= 300.0);
+    }
+
+    #[test]
+    fn test_grade_adjusted_pace_on_climb() {
+        // Steady 5% climb at 3 m/s
+        let extra = column("grade_percent", (0..=400).map(|_| 5.0));
+        let analysis = analyze_running(&workout(400, 3.0, &extra)).unwrap();
+        let gap = analysis.grade_adjusted_pace_sec_per_km.unwrap();
+        assert!(gap < analysis.pace_sec_per_km);
+
+        // The same climb derived from elevation gives the same adjustment
+        let extra = column("elevation_m", (0..=400).map(|i| i as f64 * 0.15));
+        let derived = analyze_running(&workout(400, 3.0, &extra)).unwrap();
+        let derived_gap = derived.grade_adjusted_pace_sec_per_km.unwrap();
+        assert!(derived_gap < derived.pace_sec_per_km);
+        // The first GRADE_WINDOW_M is unadjusted
+        assert!((derived_gap - gap).abs() / gap < 0.01);
+        assert!(derived.splits_km[0].elevation_change_m.unwrap() > 49.0);
+    }
+
+    #[test]
+    fn test_decoupling_with_rising_heart_rate() {
+        let extra = column(
+            "heart_rate",
+            (0..=600).map(|i| if i < 300 { 140.0 } else { 154.0 }),
+        );
+        let analysis = analyze_running(&workout(600, 3.0, &extra)).unwrap();
+        let decoupling = analysis.decoupling.unwrap();
+        assert!((decoupling.first_half_heart_rate - 140.0).abs() < 0.5);
+        assert!((decoupling.second_half_heart_rate - 154.0).abs() < 0.5);
+        assert!((decoupling.decoupling_percent - 9.09).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_analyze_runs_skips_other_sports() {
+        let run = workout(300, 3.0, "");
+        let mut ride = run.clone();
+        ride.exercises[0].modality = Some(Modality::Cycling);
+        let history = WpsHistory {
+            history_version: 2,
+            exported_at: "2025-03-02T00:00:00Z".to_string(),
+            export_source: None,
+            units: Default::default(),
+            workouts: vec![run, ride],
+            personal_records: Vec::new(),
+            body_measurements: Vec::new(),
+            extensions: Default::default(),
+        };
+        assert_eq!(analyze_runs(&history, &DateRange::default()).len(), 1);
+    }
+
+    #[test]
+    fn test_gps_route_fallback_and_no_distance() {
+        let yaml = r#"
+date: "2025-03-01"
+telemetry:
+  gps_route:
+    route_id: r
+    positions:
+      - { latitude_deg: 0.0, longitude_deg: 0.0, timestamp: "2025-03-01T08:00:00Z" }
+      - { latitude_deg: 0.0, longitude_deg: 0.001, timestamp: "2025-03-01T08:00:30Z" }
+      - { latitude_deg: 0.0, longitude_deg: 0.002, timestamp: "2025-03-01T08:00:35Z" }
+exercises: []
+"#;
+        let run: Workout = serde_yaml::from_str(yaml).unwrap();
+        let analysis = analyze_running(&run).unwrap();
+        assert_eq!(analysis.source, TrackSource::GpsRoute);
+        assert!((analysis.distance_m - 222.4).abs() < 1.0);
+        // The 30 s gap is a pause and counts as one second
+        assert_eq!(analysis.moving_time_sec, 6.0);
+
+        let empty: Workout = serde_yaml::from_str("date: \"2025-03-01\"\nexercises: []\n").unwrap();
+        assert!(analyze_running(&empty).is_none());
+    }
+}
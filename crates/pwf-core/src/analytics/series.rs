@@ -0,0 +1,311 @@
+//! Extracting evenly spaced sample streams from workout telemetry
+//!
+//! Devices record at irregular intervals ("smart recording") and PWF splits
+//! time series across sets. Most analytics need a single 1 Hz stream per
+//! workout, which is what this module produces.
+
+use crate::history::{TimeSeriesData, Workout};
+use chrono::DateTime;
+
+/// Gaps longer than this are treated as pauses rather than held values
+pub const MAX_GAP_SEC: f64 = 10.0;
+
+/// A single reading: seconds since an arbitrary origin and its value
+pub type Sample = (f64, f64);
+
+/// Resample irregular readings to one value per second
+///
+/// Each reading is held until the next one. Gaps longer than
+/// [`MAX_GAP_SEC`] (auto-pause, dropouts) contribute a single second, so
+/// stopped time does not dilute averages. Samples must be in time order;
+/// out-of-order or duplicate timestamps are skipped.
+pub fn resample_1hz(samples: &[Sample]) -> Vec<f64> {
+    let mut out = Vec::new();
+    for pair in samples.windows(2) {
+        let (t0, value) = pair[0];
+        let dt = pair[1].0 - t0;
+        if dt <= 0.0 {
+            continue;
+        }
+        let seconds = if dt > MAX_GAP_SEC {
+            1
+        } else {
+            (dt.round() as usize).max(1)
+        };
+        out.extend(std::iter::repeat_n(value, seconds));
+    }
+    if let Some(&(_, value)) = samples.last() {
+        out.push(value);
+    }
+    out
+}
+
+/// Power readings for a workout as a 1 Hz stream
+///
+/// Uses the `power` column of every set's time series, in order. Workouts
+/// without power time series fall back to the power recorded on GPS route
+/// positions. Returns an empty vector when neither source has power.
+pub fn power_series(workout: &Workout) -> Vec<f64> {
+    let samples = time_series_samples(workout, |ts| {
+        ts.power
+            .as_ref()
+            .map(|p| p.iter().map(|&w| Some(w as f64)).collect())
+    });
+    if !samples.is_empty() {
+        return resample_1hz(&samples);
+    }
+
+    let positions = workout
+        .telemetry
+        .as_ref()
+        .and_then(|t| t.gps_route.as_ref())
+        .map(|r| r.positions.as_slice())
+        .unwrap_or_default();
+    let samples: Vec<Sample> = positions
+        .iter()
+        .filter_map(|p| Some((parse_timestamp(&p.timestamp)?, p.power_watts? as f64)))
+        .collect();
+    resample_1hz(&samples)
+}
+
+/// Average of a per-sample column across all of a workout's time series
+pub(crate) fn column_average(
+    workout: &Workout,
+    column: impl Fn(&TimeSeriesData) -> Option<&Vec<f64>>,
+) -> Option<f64> {
+    let values: Vec<f64> = time_series(workout)
+        .filter_map(column)
+        .flatten()
+        .copied()
+        .filter(|v| v.is_finite())
+        .collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+fn time_series(workout: &Workout) -> impl Iterator<Item = &TimeSeriesData> {
+    workout
+        .exercises
+        .iter()
+        .flat_map(|e| &e.sets)
+        .filter_map(|s| s.telemetry.as_ref()?.time_series.as_ref())
+}
+
+/// Each of a workout's time series with the time (seconds) of every row
+///
+/// Times come from the ISO 8601 timestamps when they all parse, otherwise
+/// from `elapsed_sec`, otherwise the row index (assumed 1 Hz). Series timed
+/// by offsets are laid end to end after the previous one.
+pub(crate) fn timed_series(workout: &Workout) -> Vec<(&TimeSeriesData, Vec<f64>)> {
+    let mut out: Vec<(&TimeSeriesData, Vec<f64>)> = Vec::new();
+    for ts in time_series(workout) {
+        let offset = out
+            .iter()
+            .filter_map(|(_, times)| times.last())
+            .fold(-1.0, |a: f64, &b| a.max(b))
+            + 1.0;
+        out.push((ts, series_times(ts, offset)));
+    }
+    out
+}
+
+/// The time (seconds) of every row of one time series
+///
+/// Uses the timestamps when they all parse; otherwise `elapsed_sec` or the
+/// row index, counted from `offset`.
+pub(crate) fn series_times(ts: &TimeSeriesData, offset: f64) -> Vec<f64> {
+    let rows = ts.timestamps.len();
+    let absolute: Option<Vec<f64>> = ts.timestamps.iter().map(|t| parse_timestamp(t)).collect();
+    match absolute {
+        Some(times) => times,
+        None => match &ts.elapsed_sec {
+            Some(elapsed) if elapsed.len() == rows => {
+                let start = elapsed.first().copied().unwrap_or(0) as f64;
+                elapsed.iter().map(|&e| offset + e as f64 - start).collect()
+            }
+            _ => (0..rows).map(|i| offset + i as f64).collect(),
+        },
+    }
+}
+
+/// Collect `(seconds, value)` readings for one column across every set,
+/// in time order
+pub(crate) fn time_series_samples(
+    workout: &Workout,
+    column: impl Fn(&TimeSeriesData) -> Option<Vec<Option<f64>>>,
+) -> Vec<Sample> {
+    let mut samples: Vec<Sample> = Vec::new();
+    for (ts, times) in timed_series(workout) {
+        let Some(values) = column(ts) else {
+            continue;
+        };
+        samples.extend(
+            times
+                .into_iter()
+                .zip(values)
+                .filter_map(|(t, v)| Some((t, v?))),
+        );
+    }
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    samples
+}
+
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<f64> {
+    let dt = DateTime::parse_from_rfc3339(timestamp).ok()?;
+    Some(dt.timestamp() as f64 + dt.timestamp_subsec_millis() as f64 / 1000.0)
+}
+
+/// Interval workouts shared by the interval detection and execution tests
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::history::{TimeSeriesData, WpsHistory};
+
+    /// 1 Hz power: `warmup` s at `easy` W, then 60 s at each `hard` power
+    /// followed by 60 s at `easy` W; speed 5 m/s above 200 W, 3 m/s otherwise
+    pub(crate) fn interval_ride(warmup: usize, easy: u32, hard: &[u32]) -> TimeSeriesData {
+        let mut power = vec![easy; warmup];
+        for &watts in hard {
+            power.extend(vec![watts; 60]);
+            power.extend(vec![easy; 60]);
+        }
+        let rows = power.len();
+        TimeSeriesData {
+            timestamps: (0..rows)
+                .map(|i| format!("2025-03-01T08:{:02}:{:02}Z", i / 60, i % 60))
+                .collect(),
+            speed_mps: Some(
+                power
+                    .iter()
+                    .map(|&w| if w > 200 { 5.0 } else { 3.0 })
+                    .collect(),
+            ),
+            power: Some(power),
+            ..Default::default()
+        }
+    }
+
+    /// One workout on plan day `intervals` with a single working set
+    /// carrying `ts`
+    pub(crate) fn interval_history(ts: TimeSeriesData) -> WpsHistory {
+        let mut history = crate::history::parse(
+            r#"
+history_version: 2
+exported_at: "2025-03-02T00:00:00Z"
+workouts:
+  - date: "2025-03-01"
+    plan_day_id: intervals
+    exercises:
+      - name: Repeats
+        sets:
+          - set_type: working
+            x-lap-source: head-unit
+            telemetry: {}
+"#,
+        )
+        .unwrap();
+        history.workouts[0].exercises[0].sets[0]
+            .telemetry
+            .as_mut()
+            .unwrap()
+            .time_series = Some(ts);
+        history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workout(sets_yaml: &str) -> Workout {
+        let yaml = format!(
+            "date: \"2025-01-15\"\nexercises:\n  - name: Ride\n    sets:\n{}",
+            sets_yaml
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_resample_holds_values_across_short_gaps() {
+        let out = resample_1hz(&[(0.0, 100.0), (3.0, 200.0), (4.0, 300.0)]);
+        assert_eq!(out, vec![100.0, 100.0, 100.0, 200.0, 300.0]);
+    }
+
+    #[test]
+    fn test_resample_collapses_pauses() {
+        let out = resample_1hz(&[(0.0, 100.0), (120.0, 200.0)]);
+        assert_eq!(out, vec![100.0, 200.0]);
+    }
+
+    #[test]
+    fn test_resample_skips_duplicate_timestamps() {
+        let out = resample_1hz(&[(0.0, 100.0), (0.0, 150.0), (1.0, 200.0)]);
+        assert_eq!(out, vec![150.0, 200.0]);
+    }
+
+    #[test]
+    fn test_power_series_from_timestamps() {
+        let workout = workout(
+            r#"      - telemetry:
+          time_series:
+            timestamps: ["2025-01-15T10:00:00Z", "2025-01-15T10:00:02Z", "2025-01-15T10:00:03Z"]
+            power: [100, 200, 300]
+"#,
+        );
+        assert_eq!(power_series(&workout), vec![100.0, 100.0, 200.0, 300.0]);
+    }
+
+    #[test]
+    fn test_power_series_concatenates_offset_series() {
+        let workout = workout(
+            r#"      - telemetry:
+          time_series:
+            timestamps: ["", ""]
+            elapsed_sec: [0, 1]
+            power: [100, 100]
+      - telemetry:
+          time_series:
+            timestamps: ["", ""]
+            elapsed_sec: [0, 1]
+            power: [300, 300]
+"#,
+        );
+        assert_eq!(power_series(&workout), vec![100.0, 100.0, 300.0, 300.0]);
+    }
+
+    #[test]
+    fn test_power_series_falls_back_to_gps_positions() {
+        let yaml = r#"
+date: "2025-01-15"
+exercises: []
+telemetry:
+  gps_route:
+    route_id: r1
+    positions:
+      - latitude_deg: 40.0
+        longitude_deg: -105.0
+        timestamp: "2025-01-15T10:00:00Z"
+        power_watts: 150
+      - latitude_deg: 40.0
+        longitude_deg: -105.0
+        timestamp: "2025-01-15T10:00:01Z"
+        power_watts: 250
+"#;
+        let workout: Workout = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(power_series(&workout), vec![150.0, 250.0]);
+    }
+
+    #[test]
+    fn test_power_series_empty_without_power() {
+        let workout = workout(
+            r#"      - telemetry:
+          time_series:
+            timestamps: ["2025-01-15T10:00:00Z"]
+            heart_rate: [120]
+"#,
+        );
+        assert!(power_series(&workout).is_empty());
+    }
+}
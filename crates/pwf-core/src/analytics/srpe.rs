@@ -0,0 +1,338 @@
+//! Session-RPE load, weekly monotony and strain, and acute:chronic workload
+//! ratio
+//!
+//! Session load is Foster's session RPE (CR-10) multiplied by the session's
+//! duration in minutes, in arbitrary units (AU). It needs nothing beyond RPE
+//! and a duration, so it works for teams that do not record telemetry.
+
+use super::date_range::{workout_date, DateRange};
+use super::load::{session_rpe, workout_duration_sec};
+use crate::history::{Workout, WpsHistory};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Parameters for the rolling acute:chronic workload ratio
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AcwrParams {
+    pub acute_days: u32,
+    pub chronic_days: u32,
+    /// Ratios above this are flagged as spikes
+    pub spike_threshold: f64,
+    /// Ratios below this are flagged as underloading
+    pub low_threshold: f64,
+}
+
+impl Default for AcwrParams {
+    fn default() -> Self {
+        Self {
+            acute_days: 7,
+            chronic_days: 28,
+            spike_threshold: 1.5,
+            low_threshold: 0.8,
+        }
+    }
+}
+
+/// Load of one workout
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionLoad {
+    pub date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub rpe: f64,
+    pub duration_min: f64,
+    /// RPE × minutes (AU)
+    pub load: f64,
+}
+
+/// Foster's weekly summary, Monday to Sunday
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeeklyLoad {
+    /// Monday of the week
+    pub week_start: NaiveDate,
+    pub total_load: f64,
+    /// Days with any load
+    pub training_days: u32,
+    /// Mean daily load over its standard deviation, rest days included;
+    /// `None` when every day had the same load
+    pub monotony: Option<f64>,
+    /// Weekly load × monotony
+    pub strain: Option<f64>,
+}
+
+/// How a day's acute:chronic ratio compares with the thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AcwrFlag {
+    Spike,
+    Low,
+}
+
+/// Rolling workload on one day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyAcwr {
+    pub date: NaiveDate,
+    pub load: f64,
+    /// Mean daily load over the acute window
+    pub acute: f64,
+    /// Mean daily load over the chronic window
+    pub chronic: f64,
+    /// `None` until a full chronic window of history exists, or when the
+    /// chronic load is zero
+    pub ratio: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flag: Option<AcwrFlag>,
+}
+
+/// Session-RPE report for a history
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SrpeReport {
+    pub sessions: Vec<SessionLoad>,
+    pub weeks: Vec<WeeklyLoad>,
+    pub days: Vec<DailyAcwr>,
+}
+
+impl SrpeReport {
+    /// Days whose ratio was flagged
+    pub fn flagged(&self) -> impl Iterator<Item = &DailyAcwr> {
+        self.days.iter().filter(|d| d.flag.is_some())
+    }
+}
+
+/// Session-RPE load of a workout
+///
+/// Returns `None` without a dated workout, an RPE or a duration.
+pub fn session_load(workout: &Workout) -> Option<SessionLoad> {
+    let date = workout_date(workout)?;
+    let rpe = session_rpe(workout)?;
+    let duration_min = workout_duration_sec(workout)? as f64 / 60.0;
+    Some(SessionLoad {
+        date,
+        title: workout.title.clone(),
+        rpe,
+        duration_min,
+        load: rpe * duration_min,
+    })
+}
+
+/// Monotony and strain for the week starting `week_start`
+pub fn weekly_load(daily: &BTreeMap<NaiveDate, f64>, week_start: NaiveDate) -> WeeklyLoad {
+    let loads: Vec<f64> = week_start
+        .iter_days()
+        .take(7)
+        .map(|d| daily.get(&d).copied().unwrap_or(0.0))
+        .collect();
+    let total_load: f64 = loads.iter().sum();
+    let mean = total_load / 7.0;
+    let sd = (loads.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / 7.0).sqrt();
+    let monotony = (sd > 1e-9).then(|| mean / sd);
+    WeeklyLoad {
+        week_start,
+        total_load,
+        training_days: loads.iter().filter(|&&l| l > 0.0).count() as u32,
+        monotony,
+        strain: monotony.map(|m| total_load * m),
+    }
+}
+
+/// Rolling acute:chronic workload ratio for each day from `first` to `last`
+///
+/// Both windows end on (and include) the day. Ratios are only reported once
+/// `chronic_days` of history precede the day, counted from `since`.
+pub fn rolling_acwr(
+    daily: &BTreeMap<NaiveDate, f64>,
+    since: NaiveDate,
+    first: NaiveDate,
+    last: NaiveDate,
+    params: &AcwrParams,
+) -> Vec<DailyAcwr> {
+    let window_mean = |end: NaiveDate, days: u32| -> f64 {
+        let start = end - Duration::days(days.max(1) as i64 - 1);
+        daily.range(start..=end).map(|(_, l)| l).sum::<f64>() / days.max(1) as f64
+    };
+
+    first
+        .iter_days()
+        .take_while(|d| *d <= last)
+        .map(|date| {
+            let acute = window_mean(date, params.acute_days);
+            let chronic = window_mean(date, params.chronic_days);
+            let history_days = (date - since).num_days() + 1;
+            let ratio = (history_days >= params.chronic_days as i64 && chronic > 0.0)
+                .then(|| acute / chronic);
+            let flag = ratio.and_then(|r| {
+                if r > params.spike_threshold {
+                    Some(AcwrFlag::Spike)
+                } else if r < params.low_threshold {
+                    Some(AcwrFlag::Low)
+                } else {
+                    None
+                }
+            });
+            DailyAcwr {
+                date,
+                load: daily.get(&date).copied().unwrap_or(0.0),
+                acute,
+                chronic,
+                ratio,
+                flag,
+            }
+        })
+        .collect()
+}
+
+/// Session loads, weekly monotony and strain, and daily ACWR for a history
+///
+/// Rolling windows look back before `range.from`, so the first reported
+/// days already have a full chronic window when the history allows it.
+pub fn srpe_report(history: &WpsHistory, range: &DateRange, params: &AcwrParams) -> SrpeReport {
+    let until = DateRange::new(None, range.to);
+    let all: Vec<SessionLoad> = until.workouts(history).filter_map(session_load).collect();
+
+    let mut daily: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for session in &all {
+        *daily.entry(session.date).or_insert(0.0) += session.load;
+    }
+    let (Some(&since), Some(&latest)) = (daily.keys().next(), daily.keys().next_back()) else {
+        return SrpeReport::default();
+    };
+    let first = range.from.map_or(since, |from| from.max(since));
+    let last = range.to.unwrap_or(latest);
+    if first > last {
+        return SrpeReport::default();
+    }
+
+    let sessions: Vec<SessionLoad> = all.into_iter().filter(|s| range.contains(s.date)).collect();
+
+    let mut weeks = Vec::new();
+    let mut week_start = first - Duration::days(first.weekday().num_days_from_monday() as i64);
+    while week_start <= last {
+        weeks.push(weekly_load(&daily, week_start));
+        week_start += Duration::days(7);
+    }
+
+    SrpeReport {
+        sessions,
+        weeks,
+        days: rolling_acwr(&daily, since, first, last, params),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_session_load_is_rpe_times_minutes() {
+        let workout: Workout = serde_yaml::from_str(
+            r#"
+date: "2025-01-15"
+title: Lower body
+exercises:
+  - name: Squat
+    sets:
+      - reps: 5
+        rpe: 7
+        duration_sec: 1200
+      - reps: 5
+        rpe: 9
+        duration_sec: 1200
+"#,
+        )
+        .unwrap();
+        let load = session_load(&workout).unwrap();
+        assert_eq!(load.rpe, 8.0);
+        assert_eq!(load.duration_min, 40.0);
+        assert_eq!(load.load, 320.0);
+    }
+
+    #[test]
+    fn test_weekly_monotony_and_strain() {
+        let monday = date("2025-01-06");
+        let mut daily = BTreeMap::new();
+        for i in [0, 2, 4] {
+            daily.insert(monday + Duration::days(i), 400.0);
+        }
+        let week = weekly_load(&daily, monday);
+        assert_eq!(week.total_load, 1200.0);
+        // Mean 171.4, SD 197.9
+        let monotony = week.monotony.unwrap();
+        assert!((monotony - 0.866).abs() < 0.001);
+        assert!((week.strain.unwrap() - 1200.0 * monotony).abs() < 1e-9);
+
+        // The same load every day has no variation to divide by
+        let flat: BTreeMap<_, _> = monday.iter_days().take(7).map(|d| (d, 100.0)).collect();
+        assert!(weekly_load(&flat, monday).monotony.is_none());
+    }
+
+    #[test]
+    fn test_acwr_flags_spikes_after_a_full_chronic_window() {
+        let start = date("2025-01-01");
+        let mut daily = BTreeMap::new();
+        for i in 0..35 {
+            let load = if i >= 28 { 600.0 } else { 300.0 };
+            daily.insert(start + Duration::days(i), load);
+        }
+        let days = rolling_acwr(
+            &daily,
+            start,
+            start,
+            start + Duration::days(34),
+            &AcwrParams::default(),
+        );
+
+        assert!(days[26].ratio.is_none());
+        assert_eq!(days[27].ratio, Some(1.0));
+        assert!(days[27].flag.is_none());
+        // A week at double the load: acute 600, chronic (21·300 + 7·600)/28
+        let last = days.last().unwrap();
+        assert!((last.ratio.unwrap() - 600.0 / 375.0).abs() < 1e-9);
+        assert_eq!(last.flag, Some(AcwrFlag::Spike));
+    }
+
+    #[test]
+    fn test_srpe_report_over_range() {
+        let history: WpsHistory = serde_yaml::from_str(
+            r#"
+history_version: 2
+exported_at: "2025-02-01T00:00:00Z"
+workouts:
+  - date: "2025-01-06"
+    duration_sec: 3600
+    exercises:
+      - name: Squat
+        sets: [{ reps: 5, rpe: 6 }]
+  - date: "2025-01-08"
+    duration_sec: 1800
+    exercises:
+      - name: Bench
+        sets: [{ reps: 5, rpe: 8 }]
+  - date: "2025-01-14"
+    duration_sec: 3600
+    exercises:
+      - name: Deadlift
+        sets: [{ reps: 3 }]
+"#,
+        )
+        .unwrap();
+        let range = DateRange::new(Some(date("2025-01-07")), Some(date("2025-01-14")));
+        let report = srpe_report(&history, &range, &AcwrParams::default());
+
+        assert_eq!(report.sessions.len(), 1);
+        assert_eq!(report.sessions[0].load, 240.0);
+        assert_eq!(report.weeks.len(), 2);
+        assert_eq!(report.weeks[0].week_start, date("2025-01-06"));
+        // The week total includes the Monday session before the range
+        assert_eq!(report.weeks[0].total_load, 600.0);
+        assert_eq!(report.weeks[0].training_days, 2);
+        assert_eq!(report.weeks[1].total_load, 0.0);
+        assert_eq!(report.days.len(), 8);
+        assert_eq!(report.days[0].date, date("2025-01-07"));
+        assert_eq!(report.flagged().count(), 0);
+    }
+}
@@ -0,0 +1,468 @@
+//! Weekly strength volume per exercise and muscle group
+//!
+//! Logged exercises are mapped to muscle groups through a plan's
+//! `exercise_library`: by library id, by the `exercise_ref` of a plan
+//! exercise with the same id or name, or by library name. Every listed
+//! muscle group is credited with the whole set; the library does not
+//! distinguish primary from secondary movers.
+//!
+//! Warm-up sets are left out. A set is hard when it was taken close to
+//! failure: RPE of at least [`HARD_SET_RPE`] or at most [`HARD_SET_RIR`]
+//! reps in reserve. Sets logged without either are counted as sets but not
+//! as hard sets. Weekly hard-set counts per muscle group are compared with
+//! a target range, by default [`DEFAULT_WEEKLY_SETS`].
+
+use super::date_range::{workout_date, DateRange};
+use super::records::set_weight_kg;
+use crate::history::{CompletedExercise, CompletedSet, SetType, WpsHistory};
+use crate::plan::{LibraryExercise, WpsPlan};
+use crate::Modality;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Lowest RPE that counts as a hard set
+pub const HARD_SET_RPE: f64 = 7.0;
+
+/// Most reps in reserve that still count as a hard set
+pub const HARD_SET_RIR: u32 = 3;
+
+/// Default weekly hard sets per muscle group
+pub const DEFAULT_WEEKLY_SETS: SetRange = SetRange { min: 10, max: 20 };
+
+/// Inclusive range of weekly hard sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SetRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrengthOptions {
+    pub weekly_sets: SetRange,
+    /// Ranges for particular muscle groups (matched case-insensitively),
+    /// overriding `weekly_sets`
+    pub muscle_ranges: HashMap<String, SetRange>,
+}
+
+impl Default for StrengthOptions {
+    fn default() -> Self {
+        Self {
+            weekly_sets: DEFAULT_WEEKLY_SETS,
+            muscle_ranges: HashMap::new(),
+        }
+    }
+}
+
+impl StrengthOptions {
+    pub fn range_for(&self, muscle_group: &str) -> SetRange {
+        self.muscle_ranges
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(muscle_group.trim()))
+            .map_or(self.weekly_sets, |(_, range)| *range)
+    }
+}
+
+/// Working sets by rep count
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RepDistribution {
+    /// 1–5 reps
+    pub low: u32,
+    /// 6–12 reps
+    pub moderate: u32,
+    /// 13 reps or more
+    pub high: u32,
+}
+
+impl RepDistribution {
+    fn add(&mut self, reps: u32) {
+        match reps {
+            0 => {}
+            1..=5 => self.low += 1,
+            6..=12 => self.moderate += 1,
+            _ => self.high += 1,
+        }
+    }
+
+    fn merge(&mut self, other: &RepDistribution) {
+        self.low += other.low;
+        self.moderate += other.moderate;
+        self.high += other.high;
+    }
+}
+
+/// Sets, reps and tonnage of one exercise or muscle group
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Volume {
+    pub sets: u32,
+    pub hard_sets: u32,
+    pub reps: u32,
+    /// Sum of reps × load, for sets with a load
+    pub tonnage_kg: f64,
+    pub rep_distribution: RepDistribution,
+}
+
+impl Volume {
+    fn add_set(&mut self, set: &CompletedSet) {
+        let reps = set.reps.unwrap_or(0);
+        self.sets += 1;
+        self.reps += reps;
+        if is_hard_set(set) {
+            self.hard_sets += 1;
+        }
+        if let Some(weight) = set_weight_kg(set) {
+            self.tonnage_kg += reps as f64 * weight;
+        }
+        self.rep_distribution.add(reps);
+    }
+
+    fn merge(&mut self, other: &Volume) {
+        self.sets += other.sets;
+        self.hard_sets += other.hard_sets;
+        self.reps += other.reps;
+        self.tonnage_kg += other.tonnage_kg;
+        self.rep_distribution.merge(&other.rep_distribution);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExerciseVolume {
+    pub exercise: String,
+    /// Empty when the exercise is not in the library
+    pub muscle_groups: Vec<String>,
+    #[serde(flatten)]
+    pub volume: Volume,
+}
+
+/// How a muscle group's weekly hard sets compare with its range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeStatus {
+    Below,
+    Within,
+    Above,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MuscleVolume {
+    pub muscle_group: String,
+    #[serde(flatten)]
+    pub volume: Volume,
+    pub range: SetRange,
+    pub status: VolumeStatus,
+}
+
+/// Strength volume for one Monday-to-Sunday week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeeklyStrength {
+    /// Monday of the week
+    pub week_start: NaiveDate,
+    pub total: Volume,
+    pub exercises: Vec<ExerciseVolume>,
+    /// Every muscle group trained in the analyzed range, including those
+    /// with no sets this week
+    pub muscle_groups: Vec<MuscleVolume>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrengthReport {
+    pub weeks: Vec<WeeklyStrength>,
+    /// Exercises with sets that could not be mapped to muscle groups
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unmapped_exercises: Vec<String>,
+}
+
+/// Whether a set was taken close enough to failure to count as hard
+pub fn is_hard_set(set: &CompletedSet) -> bool {
+    set.rpe.is_some_and(|rpe| rpe >= HARD_SET_RPE) || set.rir.is_some_and(|rir| rir <= HARD_SET_RIR)
+}
+
+/// The library entry for a logged exercise, if the plan has one
+pub fn library_exercise<'a>(
+    plan: &'a WpsPlan,
+    exercise: &CompletedExercise,
+) -> Option<&'a LibraryExercise> {
+    let library = &plan.exercise_library;
+    let by_id = |id: &str| library.iter().find(|l| l.id == id);
+    let same_name = |a: &str, b: &str| a.trim().eq_ignore_ascii_case(b.trim());
+
+    if let Some(found) = exercise.id.as_deref().and_then(by_id) {
+        return Some(found);
+    }
+    let plan_exercises = plan.cycle.days.iter().flat_map(|d| &d.exercises);
+    let referenced = plan_exercises
+        .filter(|e| {
+            (e.id.is_some() && e.id == exercise.id)
+                || e.name
+                    .as_deref()
+                    .is_some_and(|n| same_name(n, &exercise.name))
+        })
+        .find_map(|e| e.exercise_ref.as_deref().and_then(by_id));
+    referenced.or_else(|| library.iter().find(|l| same_name(&l.name, &exercise.name)))
+}
+
+/// Weekly strength volume per exercise and muscle group
+///
+/// Counts sets with reps from exercises that are not endurance modalities.
+/// Without a plan nothing maps to muscle groups and only per-exercise
+/// volume is reported.
+pub fn strength_volume(
+    history: &WpsHistory,
+    plan: Option<&WpsPlan>,
+    range: &DateRange,
+    options: &StrengthOptions,
+) -> StrengthReport {
+    // Exercise volume per week, in first-seen order
+    let mut weeks: Vec<(NaiveDate, Vec<ExerciseVolume>)> = Vec::new();
+    let mut unmapped: Vec<String> = Vec::new();
+    let mut trained: Vec<String> = Vec::new();
+
+    for workout in range.workouts(history) {
+        let Some(date) = workout_date(workout) else {
+            continue;
+        };
+        let week_start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        for exercise in workout.exercises.iter().filter(|e| is_strength(e)) {
+            let sets: Vec<&CompletedSet> = exercise
+                .sets
+                .iter()
+                .filter(|s| s.set_type != Some(SetType::Warmup) && s.reps.is_some_and(|r| r > 0))
+                .collect();
+            if sets.is_empty() {
+                continue;
+            }
+            let muscle_groups = plan
+                .and_then(|plan| library_exercise(plan, exercise))
+                .map(|l| l.muscle_groups.clone())
+                .unwrap_or_default();
+            if muscle_groups.is_empty() && !unmapped.contains(&exercise.name) {
+                unmapped.push(exercise.name.clone());
+            }
+            for group in &muscle_groups {
+                if !trained.iter().any(|t| t.eq_ignore_ascii_case(group)) {
+                    trained.push(group.clone());
+                }
+            }
+
+            let week = match weeks.iter().position(|(start, _)| *start == week_start) {
+                Some(index) => &mut weeks[index].1,
+                None => {
+                    weeks.push((week_start, Vec::new()));
+                    &mut weeks.last_mut().unwrap().1
+                }
+            };
+            let entry = match week
+                .iter()
+                .position(|e| e.exercise.eq_ignore_ascii_case(&exercise.name))
+            {
+                Some(index) => &mut week[index],
+                None => {
+                    week.push(ExerciseVolume {
+                        exercise: exercise.name.clone(),
+                        muscle_groups,
+                        volume: Volume::default(),
+                    });
+                    week.last_mut().unwrap()
+                }
+            };
+            for set in sets {
+                entry.volume.add_set(set);
+            }
+        }
+    }
+
+    weeks.sort_by_key(|(start, _)| *start);
+    let weeks = weeks
+        .into_iter()
+        .map(|(week_start, exercises)| {
+            let mut total = Volume::default();
+            for exercise in &exercises {
+                total.merge(&exercise.volume);
+            }
+            let muscle_groups = trained
+                .iter()
+                .map(|group| {
+                    let mut volume = Volume::default();
+                    for exercise in exercises.iter().filter(|e| {
+                        e.muscle_groups
+                            .iter()
+                            .any(|g| g.eq_ignore_ascii_case(group))
+                    }) {
+                        volume.merge(&exercise.volume);
+                    }
+                    let range = options.range_for(group);
+                    let status = if volume.hard_sets < range.min {
+                        VolumeStatus::Below
+                    } else if volume.hard_sets > range.max {
+                        VolumeStatus::Above
+                    } else {
+                        VolumeStatus::Within
+                    };
+                    MuscleVolume {
+                        muscle_group: group.clone(),
+                        volume,
+                        range,
+                        status,
+                    }
+                })
+                .collect();
+            WeeklyStrength {
+                week_start,
+                total,
+                exercises,
+                muscle_groups,
+            }
+        })
+        .collect();
+
+    StrengthReport {
+        weeks,
+        unmapped_exercises: unmapped,
+    }
+}
+
+fn is_strength(exercise: &CompletedExercise) -> bool {
+    !matches!(
+        exercise.modality,
+        Some(
+            Modality::Cycling
+                | Modality::Running
+                | Modality::Rowing
+                | Modality::Swimming
+                | Modality::Interval
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> WpsPlan {
+        crate::plan::parse(
+            r#"
+plan_version: 1
+exercise_library:
+  - id: bench
+    name: Bench Press
+    modality: strength
+    muscle_groups: [chest, triceps]
+  - id: squat
+    name: Back Squat
+    modality: strength
+    muscle_groups: [quads, glutes]
+cycle:
+  days:
+    - exercises:
+        - name: Squat
+          exercise_ref: squat
+"#,
+        )
+        .unwrap()
+    }
+
+    fn history() -> WpsHistory {
+        crate::history::parse(
+            r#"
+history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+workouts:
+  - date: "2025-03-03"
+    exercises:
+      - name: bench press
+        sets:
+          - { set_type: warmup, reps: 10, weight_kg: 40 }
+          - { reps: 5, weight_kg: 100, rpe: 8 }
+          - { reps: 5, weight_kg: 100, rir: 2 }
+          - { reps: 8, weight_lb: 180, rpe: 6 }
+      - name: Squat
+        sets:
+          - { reps: 15, weight_kg: 60 }
+      - name: Easy Run
+        modality: running
+        sets:
+          - { distance_meters: 5000 }
+      - name: Curl
+        sets:
+          - { reps: 12, weight_kg: 15, rpe: 9 }
+  - date: "2025-03-12"
+    exercises:
+      - name: Bench Press
+        sets:
+          - { reps: 3, weight_kg: 110, rpe: 9 }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_hard_sets() {
+        let set = |yaml: &str| -> CompletedSet { serde_yaml::from_str(yaml).unwrap() };
+        assert!(is_hard_set(&set("reps: 5\nrpe: 7")));
+        assert!(is_hard_set(&set("reps: 5\nrir: 3")));
+        assert!(!is_hard_set(&set("reps: 5\nrpe: 6.5")));
+        assert!(!is_hard_set(&set("reps: 5")));
+    }
+
+    #[test]
+    fn test_library_mapping() {
+        let plan = plan();
+        let history = history();
+        let exercises = &history.workouts[0].exercises;
+        assert_eq!(library_exercise(&plan, &exercises[0]).unwrap().id, "bench");
+        // Through the plan exercise's exercise_ref
+        assert_eq!(library_exercise(&plan, &exercises[1]).unwrap().id, "squat");
+        assert!(library_exercise(&plan, &exercises[3]).is_none());
+    }
+
+    #[test]
+    fn test_weekly_volume_per_muscle_group() {
+        let options = StrengthOptions {
+            muscle_ranges: HashMap::from([("Quads".to_string(), SetRange { min: 0, max: 0 })]),
+            ..Default::default()
+        };
+        let report = strength_volume(&history(), Some(&plan()), &DateRange::all(), &options);
+        assert_eq!(report.weeks.len(), 2);
+        assert_eq!(report.unmapped_exercises, vec!["Curl"]);
+
+        let week = &report.weeks[0];
+        assert_eq!(
+            week.week_start,
+            NaiveDate::from_ymd_opt(2025, 3, 3).unwrap()
+        );
+        let bench = &week.exercises[0].volume;
+        assert_eq!((bench.sets, bench.hard_sets, bench.reps), (3, 2, 18));
+        assert!((bench.tonnage_kg - (1000.0 + 8.0 * 180.0 * crate::LB_TO_KG)).abs() < 1e-9);
+        assert_eq!(
+            bench.rep_distribution,
+            RepDistribution {
+                low: 2,
+                moderate: 1,
+                high: 0
+            }
+        );
+        assert_eq!(week.total.sets, 5);
+
+        let groups: Vec<(&str, u32, VolumeStatus)> = week
+            .muscle_groups
+            .iter()
+            .map(|m| (m.muscle_group.as_str(), m.volume.sets, m.status))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("chest", 3, VolumeStatus::Below),
+                ("triceps", 3, VolumeStatus::Below),
+                ("quads", 1, VolumeStatus::Within),
+                ("glutes", 1, VolumeStatus::Below),
+            ]
+        );
+
+        // Weeks without a muscle group's exercises still list it
+        let quads = &report.weeks[1].muscle_groups[2];
+        assert_eq!(
+            (quads.muscle_group.as_str(), quads.volume.sets),
+            ("quads", 0)
+        );
+    }
+}
@@ -0,0 +1,984 @@
+//! Cross-checking summary telemetry against the raw streams
+//!
+//! Devices and converters export summaries (`heart_rate_avg`,
+//! `total_distance_m`, ...) next to the time series they came from, and the
+//! two drift apart when files are edited, trimmed or merged. This module
+//! recomputes the summaries and reports the ones that disagree:
+//!
+//! - set telemetry from the set's own time series,
+//! - workout telemetry from every set's time series, with the GPS route
+//!   filling in anything the series lack,
+//! - GPS route totals from its positions.
+//!
+//! Averages are time-weighted over a 1 Hz resampling (see
+//! [`super::resample_1hz`]), so irregular recording does not skew them.
+//! Streams spanning less than [`MIN_COVERAGE`] of the recorded duration are
+//! treated as excerpts and not compared.
+
+use super::load::workout_duration_sec;
+use super::series::{parse_timestamp, resample_1hz, series_times, timed_series, Sample};
+use crate::history::{
+    CompletedSet, GpsPosition, GpsRoute, SetTelemetry, TimeSeriesData, Workout, WorkoutTelemetry,
+    WpsHistory,
+};
+use crate::{KPH_PER_MPS, METERS_PER_FOOT, METERS_PER_MILE, MPS_PER_MPH};
+use serde::Serialize;
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+/// Elevation changes smaller than this are treated as sensor noise
+const ELEVATION_NOISE_M: f64 = 1.0;
+/// Share of metabolic energy that ends up as work at the pedals
+const GROSS_EFFICIENCY: f64 = 0.24;
+const JOULES_PER_KCAL: f64 = 4184.0;
+
+/// Share of a workout's or set's duration a stream must span to be compared
+pub const MIN_COVERAGE: f64 = 0.9;
+
+/// How far a recorded summary may be from the recomputed value
+///
+/// A value is flagged when it is off by more than the larger of the
+/// metric's absolute allowance and `relative` × the recomputed value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SummaryTolerances {
+    pub relative: f64,
+    pub heart_rate_bpm: f64,
+    pub power_watts: f64,
+    pub speed_mps: f64,
+    pub distance_m: f64,
+    pub elevation_m: f64,
+    pub calories_kcal: f64,
+    /// Calories are estimated from mechanical work, so they get a wider
+    /// relative allowance
+    pub calories_relative: f64,
+}
+
+impl Default for SummaryTolerances {
+    fn default() -> Self {
+        Self {
+            relative: 0.05,
+            heart_rate_bpm: 3.0,
+            power_watts: 5.0,
+            speed_mps: 0.3,
+            distance_m: 50.0,
+            elevation_m: 10.0,
+            calories_kcal: 25.0,
+            calories_relative: 0.25,
+        }
+    }
+}
+
+/// A summary metric, independent of the unit it is recorded in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryField {
+    HeartRateAvg,
+    HeartRateMax,
+    HeartRateMin,
+    PowerAvg,
+    PowerMax,
+    PowerMin,
+    SpeedAvg,
+    SpeedMax,
+    Distance,
+    ElevationGain,
+    ElevationLoss,
+    Calories,
+}
+
+impl SummaryField {
+    /// Unit of [`Discrepancy::recorded`] and [`Discrepancy::computed`]
+    pub fn unit(self) -> &'static str {
+        match self {
+            Self::HeartRateAvg | Self::HeartRateMax | Self::HeartRateMin => "bpm",
+            Self::PowerAvg | Self::PowerMax | Self::PowerMin => "W",
+            Self::SpeedAvg | Self::SpeedMax => "m/s",
+            Self::Distance | Self::ElevationGain | Self::ElevationLoss => "m",
+            Self::Calories => "kcal",
+        }
+    }
+
+    fn tolerance(self, computed: f64, tolerances: &SummaryTolerances) -> f64 {
+        let (absolute, relative) = match self {
+            Self::HeartRateAvg | Self::HeartRateMax | Self::HeartRateMin => {
+                (tolerances.heart_rate_bpm, tolerances.relative)
+            }
+            Self::PowerAvg | Self::PowerMax | Self::PowerMin => {
+                (tolerances.power_watts, tolerances.relative)
+            }
+            Self::SpeedAvg | Self::SpeedMax => (tolerances.speed_mps, tolerances.relative),
+            Self::Distance => (tolerances.distance_m, tolerances.relative),
+            Self::ElevationGain | Self::ElevationLoss => {
+                (tolerances.elevation_m, tolerances.relative)
+            }
+            Self::Calories => (tolerances.calories_kcal, tolerances.calories_relative),
+        };
+        absolute.max(relative * computed.abs())
+    }
+}
+
+/// Where a recomputed value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummarySource {
+    TimeSeries,
+    GpsRoute,
+}
+
+/// A recorded summary that disagrees with the raw data
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Discrepancy {
+    /// Path of the recorded field, e.g. `workouts[0].telemetry.heart_rate_avg`
+    pub path: String,
+    pub field: SummaryField,
+    /// Recorded value, converted to the field's base unit
+    pub recorded: f64,
+    pub computed: f64,
+    pub source: SummarySource,
+}
+
+impl Discrepancy {
+    pub fn message(&self) -> String {
+        let source = match self.source {
+            SummarySource::TimeSeries => "time series",
+            SummarySource::GpsRoute => "GPS route",
+        };
+        format!(
+            "Recorded {:.1} {} but the {} gives {:.1} {}",
+            self.recorded,
+            self.field.unit(),
+            source,
+            self.computed,
+            self.field.unit()
+        )
+    }
+}
+
+type Values = Vec<(SummaryField, f64)>;
+
+/// Summaries in `history` that disagree with its time series and GPS routes
+pub fn check_summaries(history: &WpsHistory, tolerances: &SummaryTolerances) -> Vec<Discrepancy> {
+    let mut out = Vec::new();
+    for (workout_idx, workout) in history.workouts.iter().enumerate() {
+        out.extend(workout_discrepancies(workout_idx, workout, tolerances));
+    }
+    out
+}
+
+/// Overwrite every summary [`check_summaries`] flags with its recomputed
+/// value, in each unit the summary is recorded in
+///
+/// Returns the discrepancies that were fixed. Summaries that are not
+/// recorded are left unset.
+pub fn fix_summaries(history: &mut WpsHistory, tolerances: &SummaryTolerances) -> Vec<Discrepancy> {
+    let mut out = Vec::new();
+    for (workout_idx, workout) in history.workouts.iter_mut().enumerate() {
+        let found = workout_discrepancies(workout_idx, workout, tolerances);
+        for discrepancy in &found {
+            apply(workout, discrepancy);
+        }
+        out.extend(found);
+    }
+    out
+}
+
+/// Summary values recomputed from one set's time series
+pub fn set_summary(ts: &TimeSeriesData) -> Values {
+    series_summary(&[(ts, series_times(ts, 0.0))])
+}
+
+/// Fill a set's summary telemetry from its own time series
+///
+/// Unlike [`fix_summaries`], which only corrects values already recorded,
+/// this writes every value the series supports, in metric units, plus
+/// `distance_meters`. Sets without a time series are left alone.
+pub fn fill_set_summary(set: &mut CompletedSet) {
+    let Some(values) = set
+        .telemetry
+        .as_ref()
+        .and_then(|t| t.time_series.as_ref())
+        .map(set_summary)
+    else {
+        return;
+    };
+    let telemetry = set.telemetry.get_or_insert_with(Default::default);
+    let whole = |value: f64| Some(value.round().max(0.0) as u32);
+    for (field, value) in values {
+        match field {
+            SummaryField::HeartRateAvg => telemetry.heart_rate_avg = whole(value),
+            SummaryField::HeartRateMax => telemetry.heart_rate_max = whole(value),
+            SummaryField::HeartRateMin => telemetry.heart_rate_min = whole(value),
+            SummaryField::PowerAvg => telemetry.power_avg = whole(value),
+            SummaryField::PowerMax => telemetry.power_max = whole(value),
+            SummaryField::PowerMin => telemetry.power_min = whole(value),
+            SummaryField::SpeedAvg => telemetry.speed_avg_mps = Some(value),
+            SummaryField::SpeedMax => telemetry.speed_max_mps = Some(value),
+            SummaryField::ElevationGain => telemetry.elevation_gain_m = Some(value),
+            SummaryField::ElevationLoss => telemetry.elevation_loss_m = Some(value),
+            SummaryField::Calories => telemetry.calories = whole(value),
+            SummaryField::Distance => set.distance_meters = Some(value),
+        }
+    }
+}
+
+/// Summary values recomputed from a workout's time series, falling back to
+/// its GPS route for anything the series do not cover
+pub fn workout_summary(workout: &Workout) -> Vec<(SummaryField, f64, SummarySource)> {
+    covered_workout_summary(workout, None)
+}
+
+/// [`workout_summary`] without the sources spanning less than
+/// [`MIN_COVERAGE`] of `duration_sec`
+fn covered_workout_summary(
+    workout: &Workout,
+    duration_sec: Option<u32>,
+) -> Vec<(SummaryField, f64, SummarySource)> {
+    let series = timed_series(workout);
+    let span: f64 = series.iter().map(|(_, times)| span(times)).sum();
+    let mut out: Vec<_> = if covers(span, duration_sec) {
+        series_summary(&series)
+            .into_iter()
+            .map(|(field, value)| (field, value, SummarySource::TimeSeries))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let route = workout
+        .telemetry
+        .as_ref()
+        .and_then(|t| t.gps_route.as_ref());
+    if let Some(route) = route.filter(|r| covers(route_span(r), duration_sec)) {
+        for (field, value) in route_summary(route) {
+            if !out.iter().any(|(f, _, _)| *f == field) {
+                out.push((field, value, SummarySource::GpsRoute));
+            }
+        }
+    }
+    out
+}
+
+fn span(times: &[f64]) -> f64 {
+    match (times.first(), times.last()) {
+        (Some(first), Some(last)) => last - first,
+        _ => 0.0,
+    }
+}
+
+fn route_span(route: &GpsRoute) -> f64 {
+    let times: Vec<f64> = route
+        .positions
+        .iter()
+        .filter_map(|p| parse_timestamp(&p.timestamp))
+        .collect();
+    span(&times)
+}
+
+/// Whether a stream spanning `span` seconds covers the recorded duration
+fn covers(span: f64, duration_sec: Option<u32>) -> bool {
+    duration_sec.is_none_or(|d| span >= MIN_COVERAGE * d as f64)
+}
+
+/// Distance, elevation, heart rate and power recomputed from route positions
+pub fn route_summary(route: &GpsRoute) -> Values {
+    let positions = &route.positions;
+    let mut out = Vec::new();
+    if positions.len() >= 2 {
+        let distance: f64 = positions
+            .windows(2)
+            .map(|p| haversine_m(position(&p[0]), position(&p[1])))
+            .sum();
+        out.push((SummaryField::Distance, distance));
+    }
+    let elevation: Vec<f64> = positions.iter().filter_map(|p| p.elevation_m).collect();
+    if elevation.len() >= 2 {
+        let (gain, loss) = elevation_change(&elevation);
+        out.push((SummaryField::ElevationGain, gain));
+        out.push((SummaryField::ElevationLoss, loss));
+    }
+    let column = |value: fn(&GpsPosition) -> Option<u32>| -> Vec<Sample> {
+        positions
+            .iter()
+            .filter_map(|p| Some((parse_timestamp(&p.timestamp)?, value(p)? as f64)))
+            .collect()
+    };
+    push_stats(
+        &mut out,
+        &without_dropouts(column(|p| p.heart_rate_bpm)),
+        [
+            SummaryField::HeartRateAvg,
+            SummaryField::HeartRateMax,
+            SummaryField::HeartRateMin,
+        ],
+    );
+    push_stats(
+        &mut out,
+        &column(|p| p.power_watts),
+        [
+            SummaryField::PowerAvg,
+            SummaryField::PowerMax,
+            SummaryField::PowerMin,
+        ],
+    );
+    out
+}
+
+fn series_summary(series: &[(&TimeSeriesData, Vec<f64>)]) -> Values {
+    let samples = |column: fn(&TimeSeriesData) -> Option<Vec<f64>>| -> Vec<Sample> {
+        let mut samples: Vec<Sample> = series
+            .iter()
+            .filter_map(|(ts, times)| Some(times.iter().copied().zip(column(ts)?)))
+            .flatten()
+            .filter(|(_, v)| v.is_finite())
+            .collect();
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+        samples
+    };
+    fn to_f64(values: &Option<Vec<u32>>) -> Option<Vec<f64>> {
+        values
+            .as_ref()
+            .map(|v| v.iter().map(|&x| x as f64).collect())
+    }
+
+    let mut out = Vec::new();
+    push_stats(
+        &mut out,
+        &without_dropouts(samples(|ts| to_f64(&ts.heart_rate))),
+        [
+            SummaryField::HeartRateAvg,
+            SummaryField::HeartRateMax,
+            SummaryField::HeartRateMin,
+        ],
+    );
+    let power = samples(|ts| to_f64(&ts.power));
+    push_stats(
+        &mut out,
+        &power,
+        [
+            SummaryField::PowerAvg,
+            SummaryField::PowerMax,
+            SummaryField::PowerMin,
+        ],
+    );
+    let speed = samples(|ts| ts.speed_mps.clone());
+    if !speed.is_empty() {
+        let resampled = resample_1hz(&speed);
+        out.push((SummaryField::SpeedAvg, mean(&resampled)));
+        out.push((
+            SummaryField::SpeedMax,
+            speed.iter().map(|s| s.1).fold(f64::MIN, f64::max),
+        ));
+    }
+
+    // Distance is cumulative within each series
+    let distances: Vec<f64> = series
+        .iter()
+        .filter_map(|(ts, _)| {
+            let d: Vec<f64> = ts
+                .distance_m
+                .as_ref()?
+                .iter()
+                .copied()
+                .filter(|v| v.is_finite())
+                .collect();
+            Some(d.last()? - d.first()?)
+        })
+        .collect();
+    if !distances.is_empty() {
+        out.push((SummaryField::Distance, distances.iter().sum()));
+    }
+
+    let elevations: Vec<(f64, f64)> = series
+        .iter()
+        .filter_map(|(ts, _)| {
+            let e: Vec<f64> = ts
+                .elevation_m
+                .as_ref()?
+                .iter()
+                .copied()
+                .filter(|v| v.is_finite())
+                .collect();
+            (e.len() >= 2).then(|| elevation_change(&e))
+        })
+        .collect();
+    if !elevations.is_empty() {
+        out.push((
+            SummaryField::ElevationGain,
+            elevations.iter().map(|e| e.0).sum(),
+        ));
+        out.push((
+            SummaryField::ElevationLoss,
+            elevations.iter().map(|e| e.1).sum(),
+        ));
+    }
+
+    if !power.is_empty() {
+        let work_joules: f64 = resample_1hz(&power).iter().sum();
+        out.push((
+            SummaryField::Calories,
+            work_joules / GROSS_EFFICIENCY / JOULES_PER_KCAL,
+        ));
+    }
+    out
+}
+
+/// Time-weighted average, maximum and minimum of the samples
+/// Drop 0 bpm heart rate readings, which are sensor dropouts
+fn without_dropouts(samples: Vec<Sample>) -> Vec<Sample> {
+    samples.into_iter().filter(|s| s.1 > 0.0).collect()
+}
+
+fn push_stats(out: &mut Values, samples: &[Sample], fields: [SummaryField; 3]) {
+    if samples.is_empty() {
+        return;
+    }
+    let values = samples.iter().map(|s| s.1);
+    out.push((fields[0], mean(&resample_1hz(samples))));
+    out.push((fields[1], values.clone().fold(f64::MIN, f64::max)));
+    out.push((fields[2], values.fold(f64::MAX, f64::min)));
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+/// Total ascent and descent, ignoring wobble below [`ELEVATION_NOISE_M`]
+fn elevation_change(elevation: &[f64]) -> (f64, f64) {
+    let (mut gain, mut loss) = (0.0, 0.0);
+    let mut reference = elevation[0];
+    for &e in &elevation[1..] {
+        let delta = e - reference;
+        if delta.abs() >= ELEVATION_NOISE_M {
+            if delta > 0.0 {
+                gain += delta;
+            } else {
+                loss -= delta;
+            }
+            reference = e;
+        }
+    }
+    (gain, loss)
+}
+
+fn position(p: &GpsPosition) -> (f64, f64) {
+    (p.latitude_deg, p.longitude_deg)
+}
+
+/// Great-circle distance between two `(latitude, longitude)` points in degrees
+pub(crate) fn haversine_m(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.1 - a.1).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * h.sqrt().asin()
+}
+
+fn workout_discrepancies(
+    workout_idx: usize,
+    workout: &Workout,
+    tolerances: &SummaryTolerances,
+) -> Vec<Discrepancy> {
+    let mut out = Vec::new();
+    let workout_path = format!("workouts[{}]", workout_idx);
+
+    for (ex_idx, exercise) in workout.exercises.iter().enumerate() {
+        for (set_idx, set) in exercise.sets.iter().enumerate() {
+            let Some(ts) = set.telemetry.as_ref().and_then(|t| t.time_series.as_ref()) else {
+                continue;
+            };
+            let times = series_times(ts, 0.0);
+            if !covers(span(&times), set.duration_sec) {
+                continue;
+            }
+            let path = format!("{}.exercises[{}].sets[{}]", workout_path, ex_idx, set_idx);
+            for (field, computed) in series_summary(&[(ts, times)]) {
+                if let Some((name, recorded)) = set_recorded(set, field) {
+                    push_if_off(
+                        &mut out,
+                        &path,
+                        name,
+                        field,
+                        recorded,
+                        computed,
+                        SummarySource::TimeSeries,
+                        tolerances,
+                    );
+                }
+            }
+        }
+    }
+
+    let Some(telemetry) = &workout.telemetry else {
+        return out;
+    };
+    let duration_sec = workout_duration_sec(workout);
+    let path = format!("{}.telemetry", workout_path);
+    for (field, computed, source) in covered_workout_summary(workout, duration_sec) {
+        if let Some((name, recorded)) = workout_recorded(telemetry, field) {
+            push_if_off(
+                &mut out, &path, name, field, recorded, computed, source, tolerances,
+            );
+        }
+    }
+    if let Some(route) = telemetry
+        .gps_route
+        .as_ref()
+        .filter(|r| covers(route_span(r), duration_sec))
+    {
+        let path = format!("{}.gps_route", path);
+        for (field, computed) in route_summary(route) {
+            if let Some((name, recorded)) = route_recorded(route, field) {
+                push_if_off(
+                    &mut out,
+                    &path,
+                    name,
+                    field,
+                    recorded,
+                    computed,
+                    SummarySource::GpsRoute,
+                    tolerances,
+                );
+            }
+        }
+    }
+    out
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_if_off(
+    out: &mut Vec<Discrepancy>,
+    path: &str,
+    name: &str,
+    field: SummaryField,
+    recorded: f64,
+    computed: f64,
+    source: SummarySource,
+    tolerances: &SummaryTolerances,
+) {
+    if (recorded - computed).abs() > field.tolerance(computed, tolerances) {
+        out.push(Discrepancy {
+            path: format!("{}.{}", path, name),
+            field,
+            recorded,
+            computed,
+            source,
+        });
+    }
+}
+
+/// First recorded unit variant of `field` in base units, with its name
+fn set_recorded(set: &CompletedSet, field: SummaryField) -> Option<(&'static str, f64)> {
+    if field == SummaryField::Distance {
+        return Some(("distance_meters", set.distance_meters?));
+    }
+    let t = set.telemetry.as_ref()?;
+    let u = |v: Option<u32>| v.map(f64::from);
+    let candidates: &[(&'static str, Option<f64>)] = match field {
+        SummaryField::HeartRateAvg => &[("telemetry.heart_rate_avg", u(t.heart_rate_avg))],
+        SummaryField::HeartRateMax => &[("telemetry.heart_rate_max", u(t.heart_rate_max))],
+        SummaryField::HeartRateMin => &[("telemetry.heart_rate_min", u(t.heart_rate_min))],
+        SummaryField::PowerAvg => &[("telemetry.power_avg", u(t.power_avg))],
+        SummaryField::PowerMax => &[("telemetry.power_max", u(t.power_max))],
+        SummaryField::PowerMin => &[("telemetry.power_min", u(t.power_min))],
+        SummaryField::SpeedAvg => &[
+            ("telemetry.speed_avg_mps", t.speed_avg_mps),
+            (
+                "telemetry.speed_avg_kph",
+                t.speed_avg_kph.map(|v| v / KPH_PER_MPS),
+            ),
+            (
+                "telemetry.speed_avg_mph",
+                t.speed_avg_mph.map(|v| v * MPS_PER_MPH),
+            ),
+        ],
+        SummaryField::SpeedMax => &[
+            ("telemetry.speed_max_mps", t.speed_max_mps),
+            (
+                "telemetry.speed_max_kph",
+                t.speed_max_kph.map(|v| v / KPH_PER_MPS),
+            ),
+            (
+                "telemetry.speed_max_mph",
+                t.speed_max_mph.map(|v| v * MPS_PER_MPH),
+            ),
+        ],
+        SummaryField::ElevationGain => &[
+            ("telemetry.elevation_gain_m", t.elevation_gain_m),
+            (
+                "telemetry.elevation_gain_ft",
+                t.elevation_gain_ft.map(|v| v * METERS_PER_FOOT),
+            ),
+        ],
+        SummaryField::ElevationLoss => &[
+            ("telemetry.elevation_loss_m", t.elevation_loss_m),
+            (
+                "telemetry.elevation_loss_ft",
+                t.elevation_loss_ft.map(|v| v * METERS_PER_FOOT),
+            ),
+        ],
+        SummaryField::Calories => &[("telemetry.calories", u(t.calories))],
+        SummaryField::Distance => unreachable!(),
+    };
+    first_recorded(candidates)
+}
+
+fn workout_recorded(t: &WorkoutTelemetry, field: SummaryField) -> Option<(&'static str, f64)> {
+    let u = |v: Option<u32>| v.map(f64::from);
+    let candidates: &[(&'static str, Option<f64>)] = match field {
+        SummaryField::HeartRateAvg => &[("heart_rate_avg", u(t.heart_rate_avg))],
+        SummaryField::HeartRateMax => &[("heart_rate_max", u(t.heart_rate_max))],
+        SummaryField::HeartRateMin => &[("heart_rate_min", u(t.heart_rate_min))],
+        SummaryField::PowerAvg => &[("power_avg", u(t.power_avg))],
+        SummaryField::PowerMax => &[("power_max", u(t.power_max))],
+        SummaryField::PowerMin => &[],
+        SummaryField::SpeedAvg => &[
+            ("speed_avg_kph", t.speed_avg_kph.map(|v| v / KPH_PER_MPS)),
+            ("speed_avg_mph", t.speed_avg_mph.map(|v| v * MPS_PER_MPH)),
+        ],
+        SummaryField::SpeedMax => &[
+            ("speed_max_kph", t.speed_max_kph.map(|v| v / KPH_PER_MPS)),
+            ("speed_max_mph", t.speed_max_mph.map(|v| v * MPS_PER_MPH)),
+        ],
+        SummaryField::Distance => &[
+            ("total_distance_m", t.total_distance_m),
+            ("total_distance_km", t.total_distance_km.map(|v| v * 1000.0)),
+            (
+                "total_distance_mi",
+                t.total_distance_mi.map(|v| v * METERS_PER_MILE),
+            ),
+        ],
+        SummaryField::ElevationGain => &[
+            ("total_elevation_gain_m", t.total_elevation_gain_m),
+            (
+                "total_elevation_gain_ft",
+                t.total_elevation_gain_ft.map(|v| v * METERS_PER_FOOT),
+            ),
+        ],
+        SummaryField::ElevationLoss => &[
+            ("total_elevation_loss_m", t.total_elevation_loss_m),
+            (
+                "total_elevation_loss_ft",
+                t.total_elevation_loss_ft.map(|v| v * METERS_PER_FOOT),
+            ),
+        ],
+        SummaryField::Calories => &[("total_calories", u(t.total_calories))],
+    };
+    first_recorded(candidates)
+}
+
+fn route_recorded(route: &GpsRoute, field: SummaryField) -> Option<(&'static str, f64)> {
+    match field {
+        SummaryField::Distance => Some(("total_distance_m", route.total_distance_m?)),
+        SummaryField::ElevationGain => Some(("total_ascent_m", route.total_ascent_m?)),
+        SummaryField::ElevationLoss => Some(("total_descent_m", route.total_descent_m?)),
+        _ => None,
+    }
+}
+
+fn first_recorded(candidates: &[(&'static str, Option<f64>)]) -> Option<(&'static str, f64)> {
+    candidates
+        .iter()
+        .find_map(|&(name, value)| Some((name, value.filter(|v| v.is_finite())?)))
+}
+
+/// Write a discrepancy's computed value back into the workout
+fn apply(workout: &mut Workout, discrepancy: &Discrepancy) {
+    let value = discrepancy.computed;
+    let path = discrepancy.path.as_str();
+    if let Some(set) = set_at(workout, path) {
+        if discrepancy.field == SummaryField::Distance {
+            set.distance_meters = Some(value);
+        } else if let Some(t) = set.telemetry.as_mut() {
+            apply_set(t, discrepancy.field, value);
+        }
+    } else if let Some(t) = workout.telemetry.as_mut() {
+        if path.contains(".gps_route.") {
+            if let Some(route) = t.gps_route.as_mut() {
+                match discrepancy.field {
+                    SummaryField::Distance => route.total_distance_m = Some(value),
+                    SummaryField::ElevationGain => route.total_ascent_m = Some(value),
+                    SummaryField::ElevationLoss => route.total_descent_m = Some(value),
+                    _ => {}
+                }
+            }
+        } else {
+            apply_workout(t, discrepancy.field, value);
+        }
+    }
+}
+
+/// The set a `workouts[i].exercises[j].sets[k]...` path points into
+fn set_at<'a>(workout: &'a mut Workout, path: &str) -> Option<&'a mut CompletedSet> {
+    let index = |key: &str| -> Option<usize> {
+        let start = path.find(key)? + key.len();
+        path[start..].split(']').next()?.parse().ok()
+    };
+    let exercise = index(".exercises[")?;
+    let set = index(".sets[")?;
+    workout.exercises.get_mut(exercise)?.sets.get_mut(set)
+}
+
+/// Set `slot` to `value` if it is recorded, rounding for integer fields
+fn update(slot: &mut Option<f64>, value: f64) {
+    if slot.is_some() {
+        *slot = Some(value);
+    }
+}
+
+fn update_u32(slot: &mut Option<u32>, value: f64) {
+    if slot.is_some() {
+        *slot = Some(value.round().max(0.0) as u32);
+    }
+}
+
+fn apply_set(t: &mut SetTelemetry, field: SummaryField, value: f64) {
+    match field {
+        SummaryField::HeartRateAvg => update_u32(&mut t.heart_rate_avg, value),
+        SummaryField::HeartRateMax => update_u32(&mut t.heart_rate_max, value),
+        SummaryField::HeartRateMin => update_u32(&mut t.heart_rate_min, value),
+        SummaryField::PowerAvg => update_u32(&mut t.power_avg, value),
+        SummaryField::PowerMax => update_u32(&mut t.power_max, value),
+        SummaryField::PowerMin => update_u32(&mut t.power_min, value),
+        SummaryField::SpeedAvg => {
+            update(&mut t.speed_avg_mps, value);
+            update(&mut t.speed_avg_kph, value * KPH_PER_MPS);
+            update(&mut t.speed_avg_mph, value / MPS_PER_MPH);
+        }
+        SummaryField::SpeedMax => {
+            update(&mut t.speed_max_mps, value);
+            update(&mut t.speed_max_kph, value * KPH_PER_MPS);
+            update(&mut t.speed_max_mph, value / MPS_PER_MPH);
+        }
+        SummaryField::ElevationGain => {
+            update(&mut t.elevation_gain_m, value);
+            update(&mut t.elevation_gain_ft, value / METERS_PER_FOOT);
+        }
+        SummaryField::ElevationLoss => {
+            update(&mut t.elevation_loss_m, value);
+            update(&mut t.elevation_loss_ft, value / METERS_PER_FOOT);
+        }
+        SummaryField::Calories => update_u32(&mut t.calories, value),
+        SummaryField::Distance => {}
+    }
+}
+
+fn apply_workout(t: &mut WorkoutTelemetry, field: SummaryField, value: f64) {
+    match field {
+        SummaryField::HeartRateAvg => update_u32(&mut t.heart_rate_avg, value),
+        SummaryField::HeartRateMax => update_u32(&mut t.heart_rate_max, value),
+        SummaryField::HeartRateMin => update_u32(&mut t.heart_rate_min, value),
+        SummaryField::PowerAvg => update_u32(&mut t.power_avg, value),
+        SummaryField::PowerMax => update_u32(&mut t.power_max, value),
+        SummaryField::PowerMin => {}
+        SummaryField::SpeedAvg => {
+            update(&mut t.speed_avg_kph, value * KPH_PER_MPS);
+            update(&mut t.speed_avg_mph, value / MPS_PER_MPH);
+        }
+        SummaryField::SpeedMax => {
+            update(&mut t.speed_max_kph, value * KPH_PER_MPS);
+            update(&mut t.speed_max_mph, value / MPS_PER_MPH);
+        }
+        SummaryField::Distance => {
+            update(&mut t.total_distance_m, value);
+            update(&mut t.total_distance_km, value / 1000.0);
+            update(&mut t.total_distance_mi, value / METERS_PER_MILE);
+        }
+        SummaryField::ElevationGain => {
+            update(&mut t.total_elevation_gain_m, value);
+            update(&mut t.total_elevation_gain_ft, value / METERS_PER_FOOT);
+        }
+        SummaryField::ElevationLoss => {
+            update(&mut t.total_elevation_loss_m, value);
+            update(&mut t.total_elevation_loss_ft, value / METERS_PER_FOOT);
+        }
+        SummaryField::Calories => update_u32(&mut t.total_calories, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(yaml: &str) -> WpsHistory {
+        crate::history::parse(yaml).unwrap()
+    }
+
+    const RIDE: &str = r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    telemetry:
+      heart_rate_avg: 150
+      heart_rate_max: 155
+      total_distance_km: 2.0
+      total_calories: 40
+    exercises:
+      - name: Ride
+        sets:
+          - distance_meters: 1000
+            telemetry:
+              power_avg: 200
+              speed_avg_kph: 36
+              elevation_gain_m: 5
+              time_series:
+                timestamps:
+                  - "2025-01-15T10:00:00Z"
+                  - "2025-01-15T10:00:01Z"
+                  - "2025-01-15T10:00:04Z"
+                heart_rate: [120, 140, 130]
+                power: [200, 200, 200]
+                speed_mps: [10, 10, 10]
+                distance_m: [0, 500, 1000]
+                elevation_m: [100, 102, 120]
+"#;
+
+    fn fields(found: &[Discrepancy]) -> Vec<&str> {
+        found.iter().map(|d| d.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_set_summary_is_time_weighted() {
+        let h = history(RIDE);
+        let ts = h.workouts[0].exercises[0].sets[0]
+            .telemetry
+            .as_ref()
+            .unwrap()
+            .time_series
+            .as_ref()
+            .unwrap();
+        let summary = set_summary(ts);
+        let get = |field| summary.iter().find(|(f, _)| *f == field).unwrap().1;
+        // 120 for 1 s, 140 for 3 s, 130 for the last second
+        assert_eq!(get(SummaryField::HeartRateAvg), 134.0);
+        assert_eq!(get(SummaryField::HeartRateMin), 120.0);
+        assert_eq!(get(SummaryField::Distance), 1000.0);
+        assert_eq!(get(SummaryField::ElevationGain), 20.0);
+        // 5 s at 200 W = 1 kJ of work
+        assert!((get(SummaryField::Calories) - 1000.0 / 0.24 / 4184.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_heart_rate_dropouts_are_ignored() {
+        let h = history(
+            r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    telemetry:
+      heart_rate_avg: 132
+      heart_rate_max: 140
+      heart_rate_min: 120
+    exercises:
+      - name: Ride
+        sets:
+          - telemetry:
+              time_series:
+                timestamps:
+                  - "2025-01-15T10:00:00Z"
+                  - "2025-01-15T10:00:01Z"
+                  - "2025-01-15T10:00:02Z"
+                  - "2025-01-15T10:00:03Z"
+                  - "2025-01-15T10:00:04Z"
+                heart_rate: [120, 0, 140, 0, 140]
+"#,
+        );
+        let ts = h.workouts[0].exercises[0].sets[0]
+            .telemetry
+            .as_ref()
+            .unwrap()
+            .time_series
+            .as_ref()
+            .unwrap();
+        let summary = set_summary(ts);
+        let get = |field| summary.iter().find(|(f, _)| *f == field).unwrap().1;
+        // Each dropout holds the previous reading
+        assert_eq!(get(SummaryField::HeartRateAvg), 132.0);
+        assert_eq!(get(SummaryField::HeartRateMin), 120.0);
+        assert!(check_summaries(&h, &SummaryTolerances::default()).is_empty());
+    }
+
+    #[test]
+    fn test_check_flags_only_values_out_of_tolerance() {
+        let found = check_summaries(&history(RIDE), &SummaryTolerances::default());
+        assert_eq!(
+            fields(&found),
+            vec![
+                "workouts[0].exercises[0].sets[0].telemetry.elevation_gain_m",
+                "workouts[0].telemetry.heart_rate_avg",
+                "workouts[0].telemetry.heart_rate_max",
+                "workouts[0].telemetry.total_distance_km",
+                "workouts[0].telemetry.total_calories",
+            ]
+        );
+        let distance = &found[3];
+        assert_eq!(distance.recorded, 2000.0);
+        assert_eq!(distance.computed, 1000.0);
+        assert_eq!(distance.source, SummarySource::TimeSeries);
+    }
+
+    #[test]
+    fn test_fix_overwrites_recorded_units() {
+        let mut h = history(RIDE);
+        let fixed = fix_summaries(&mut h, &SummaryTolerances::default());
+        assert_eq!(fixed.len(), 5);
+        let t = h.workouts[0].telemetry.as_ref().unwrap();
+        assert_eq!(t.heart_rate_avg, Some(134));
+        assert_eq!(t.total_distance_km, Some(1.0));
+        assert!(t.total_distance_m.is_none());
+        let set = h.workouts[0].exercises[0].sets[0]
+            .telemetry
+            .as_ref()
+            .unwrap();
+        assert_eq!(set.elevation_gain_m, Some(20.0));
+        assert!(check_summaries(&h, &SummaryTolerances::default()).is_empty());
+    }
+
+    #[test]
+    fn test_route_length_and_ascent() {
+        let h = history(
+            r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    telemetry:
+      total_distance_m: 5000
+      gps_route:
+        route_id: r1
+        total_distance_m: 1112
+        total_ascent_m: 50
+        positions:
+          - { latitude_deg: 0.0, longitude_deg: 0.0, timestamp: "2025-01-15T10:00:00Z", elevation_m: 10 }
+          - { latitude_deg: 0.01, longitude_deg: 0.0, timestamp: "2025-01-15T10:05:00Z", elevation_m: 20 }
+    exercises:
+      - name: Run
+        sets: [{ duration_sec: 300 }]
+"#,
+        );
+        let found = check_summaries(&h, &SummaryTolerances::default());
+        assert_eq!(
+            fields(&found),
+            vec![
+                "workouts[0].telemetry.total_distance_m",
+                "workouts[0].telemetry.gps_route.total_ascent_m",
+            ]
+        );
+        // 0.01° of latitude is about 1112 m
+        assert!((found[0].computed - 1111.95).abs() < 0.1);
+        assert_eq!(found[0].source, SummarySource::GpsRoute);
+        assert_eq!(found[1].computed, 10.0);
+    }
+
+    #[test]
+    fn test_excerpts_are_not_compared() {
+        // Five seconds of samples from an hour-long set
+        let h = history(&RIDE.replace(
+            "          - distance_meters: 1000\n",
+            "          - distance_meters: 1000\n            duration_sec: 3600\n",
+        ));
+        let found = check_summaries(&h, &SummaryTolerances::default());
+        assert!(found.iter().all(|d| !d.path.contains(".sets[")));
+        // The workout's duration falls back to the set's, so it is skipped too
+        assert!(found.is_empty());
+    }
+}
@@ -0,0 +1,634 @@
+//! Swim analytics: pace, stroke breakdown, critical swim speed and length
+//! reconstruction
+//!
+//! Pool swims are measured in lengths. Distances and paces are reported in
+//! the pool's unit, so a 25 yd pool gives pace per 100 yd. Sets recorded
+//! only as a time series (as some importers produce) can have their
+//! lengths rebuilt: from pool-length crossings of `distance_m` when it is
+//! recorded, otherwise from changes of the per-length `swolf` and
+//! `stroke_type` values.
+//!
+//! Critical swim speed (CSS) is the slope of distance over time between a
+//! maximal 400 and 200 test, an estimate of threshold pace.
+
+use super::date_range::{workout_date, DateRange};
+use super::series::{parse_timestamp, series_times};
+use crate::history::{
+    CompletedExercise, CompletedSet, PoolConfig, PoolLengthUnit, StrokeType, SwimmingLength,
+    SwimmingSetData, TimeSeriesData, Workout, WpsHistory,
+};
+use crate::{Sport, METERS_PER_YARD};
+use serde::Serialize;
+
+/// Test distances, in pool units, for critical swim speed
+pub const CSS_LONG_TEST: f64 = 400.0;
+pub const CSS_SHORT_TEST: f64 = 200.0;
+
+/// Stroke statistics over one stroke type's active lengths
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrokeBreakdown {
+    pub stroke_type: StrokeType,
+    pub lengths: u32,
+    /// In pool units
+    pub distance: f64,
+    pub duration_sec: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pace_per_100_sec: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swolf_avg: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strokes_per_length: Option<f64>,
+}
+
+/// One swim workout
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SwimAnalysis {
+    pub date: String,
+    /// Unit of every distance and pace in this analysis
+    pub unit: PoolLengthUnit,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_length: Option<f64>,
+    pub distance: f64,
+    /// Swimming time, excluding rest lengths
+    pub active_time_sec: f64,
+    pub rest_time_sec: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pace_per_100_sec: Option<f64>,
+    pub active_lengths: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swolf_avg: Option<f64>,
+    pub strokes: Vec<StrokeBreakdown>,
+    /// Whether any lengths were rebuilt from time series
+    pub reconstructed: bool,
+}
+
+/// Critical swim speed from a 400 and 200 test in one session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CssTest {
+    pub date: String,
+    pub unit: PoolLengthUnit,
+    pub long_time_sec: f64,
+    pub short_time_sec: f64,
+    /// Pool units per second
+    pub speed: f64,
+    pub pace_per_100_sec: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SwimReport {
+    pub swims: Vec<SwimAnalysis>,
+    /// From the latest session in range with both test distances
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub css: Option<CssTest>,
+}
+
+/// Critical swim speed in distance units per second
+///
+/// Returns `None` unless the longer test is both longer and slower.
+pub fn critical_swim_speed(
+    long_distance: f64,
+    long_time_sec: f64,
+    short_distance: f64,
+    short_time_sec: f64,
+) -> Option<f64> {
+    let (distance, time) = (
+        long_distance - short_distance,
+        long_time_sec - short_time_sec,
+    );
+    (distance > 0.0 && time > 0.0).then(|| distance / time)
+}
+
+/// Rebuild per-length records from a set's time series
+///
+/// With a pool and a `distance_m` column, each length ends where the
+/// distance reaches the next multiple of the pool length. Otherwise a new
+/// length starts whenever `swolf` or `stroke_type` changes, so consecutive
+/// lengths with identical SWOLF and stroke merge. Stroke counts come from
+/// the cumulative `stroke_count` column; a length without strokes counts as
+/// rest. Lengths whose stroke the series does not record use
+/// `default_stroke`. Returns no lengths when there is nothing to split on.
+pub fn reconstruct_lengths(
+    ts: &TimeSeriesData,
+    pool: Option<&PoolConfig>,
+    default_stroke: StrokeType,
+) -> Vec<SwimmingLength> {
+    let times = series_times(ts, 0.0);
+    let rows = times.len();
+    if rows < 2 {
+        return Vec::new();
+    }
+    let strokes = ts.stroke_count.as_ref().filter(|v| v.len() == rows);
+    let swolf = ts.swolf.as_ref().filter(|v| v.len() == rows);
+    let stroke_types = ts.stroke_type.as_ref().filter(|v| v.len() == rows);
+
+    let mut starts = vec![0];
+    match (pool, ts.distance_m.as_ref().filter(|d| d.len() == rows)) {
+        (Some(pool), Some(distance)) if pool.length_in_meters() > 0.0 => {
+            let length_m = pool.length_in_meters();
+            let origin = distance[0];
+            let mut completed = 0.0;
+            for (row, &d) in distance.iter().enumerate().skip(1) {
+                // Allow for rounding in the recorded distance
+                let lengths = ((d - origin) / length_m + 0.01).floor();
+                if lengths > completed && row < rows - 1 {
+                    starts.push(row);
+                    completed = lengths;
+                }
+            }
+        }
+        _ if swolf.is_some() || stroke_types.is_some() => {
+            for row in 1..rows - 1 {
+                let changed = swolf.is_some_and(|s| s[row] != s[row - 1])
+                    || stroke_types.is_some_and(|s| s[row] != s[row - 1]);
+                if changed {
+                    starts.push(row);
+                }
+            }
+        }
+        _ => return Vec::new(),
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &first)| {
+            let last = starts.get(i + 1).copied().unwrap_or(rows - 1);
+            let duration_sec = (times[last] - times[first]).round().max(0.0) as u32;
+            let stroke_count = strokes.map(|s| s[last].saturating_sub(s[first]));
+            let stroke_type = stroke_types
+                .map(|s| majority(&s[first..last.max(first + 1)]))
+                .unwrap_or(default_stroke);
+            SwimmingLength {
+                length_number: i as u32 + 1,
+                stroke_type,
+                duration_sec,
+                stroke_count,
+                swolf: stroke_count.map(|count| duration_sec + count),
+                started_at: ts
+                    .timestamps
+                    .get(first)
+                    .filter(|t| parse_timestamp(t).is_some())
+                    .cloned(),
+                active: Some(stroke_count != Some(0)),
+            }
+        })
+        .collect()
+}
+
+/// Fill `swimming` on swim sets that have a time series but no lengths
+///
+/// Returns how many sets were filled.
+pub fn fill_swim_lengths(history: &mut WpsHistory) -> usize {
+    let mut filled = 0;
+    for workout in &mut history.workouts {
+        let swim_workout = workout.sport == Some(Sport::Swimming);
+        for exercise in &mut workout.exercises {
+            if !swim_workout && !is_swim_exercise(exercise) {
+                continue;
+            }
+            let pool = exercise.pool_config.clone();
+            for set in &mut exercise.sets {
+                if set.swimming.as_ref().is_some_and(|s| !s.lengths.is_empty()) {
+                    continue;
+                }
+                let lengths = reconstructed(set, pool.as_ref());
+                if lengths.is_empty() {
+                    continue;
+                }
+                let swimming = set.swimming.get_or_insert_with(Default::default);
+                fill_set_data(swimming, lengths);
+                filled += 1;
+            }
+        }
+    }
+    filled
+}
+
+/// Totals, pace and stroke breakdown for one swim workout
+///
+/// Sets with lengths (recorded or rebuilt from time series) count their
+/// active lengths at the pool length; other sets count `distance_meters`
+/// and `duration_sec`. Returns `None` for workouts without swimming or
+/// without any distance.
+pub fn analyze_swim(workout: &Workout) -> Option<SwimAnalysis> {
+    let swim_workout = workout.sport == Some(Sport::Swimming);
+    let exercises: Vec<&CompletedExercise> = workout
+        .exercises
+        .iter()
+        .filter(|e| swim_workout || is_swim_exercise(e))
+        .collect();
+    let pool = exercises.iter().find_map(|e| e.pool_config.as_ref());
+    let unit = pool.map_or(PoolLengthUnit::Meters, |p| p.pool_length_unit);
+
+    let mut analysis = SwimAnalysis {
+        date: workout.date.clone(),
+        unit,
+        pool_length: pool.map(|p| p.pool_length),
+        distance: 0.0,
+        active_time_sec: 0.0,
+        rest_time_sec: 0.0,
+        pace_per_100_sec: None,
+        active_lengths: 0,
+        swolf_avg: None,
+        strokes: Vec::new(),
+        reconstructed: false,
+    };
+    let mut swolf = Vec::new();
+
+    for exercise in exercises {
+        let pool_length = exercise
+            .pool_config
+            .as_ref()
+            .map(|p| in_units(p.length_in_meters(), unit));
+        for set in &exercise.sets {
+            let recorded = set
+                .swimming
+                .as_ref()
+                .map(|s| s.lengths.clone())
+                .filter(|l| !l.is_empty());
+            let lengths = match (recorded, pool_length) {
+                (Some(lengths), Some(_)) => lengths,
+                (None, Some(_)) => {
+                    let lengths = reconstructed(set, exercise.pool_config.as_ref());
+                    analysis.reconstructed |= !lengths.is_empty();
+                    lengths
+                }
+                _ => Vec::new(),
+            };
+
+            let (Some(pool_length), false) = (pool_length, lengths.is_empty()) else {
+                // Sets without distance (rest, drills on the clock) are not
+                // swimming time
+                if let Some(distance) = set.distance_meters.filter(|&d| d > 0.0) {
+                    analysis.distance += in_units(distance, unit);
+                    analysis.active_time_sec += set.duration_sec.unwrap_or(0) as f64;
+                }
+                continue;
+            };
+            for length in &lengths {
+                if length.active == Some(false) {
+                    analysis.rest_time_sec += length.duration_sec as f64;
+                    continue;
+                }
+                analysis.active_lengths += 1;
+                analysis.distance += pool_length;
+                analysis.active_time_sec += length.duration_sec as f64;
+                if let Some(s) = length.swolf.or_else(|| length.calculate_swolf()) {
+                    swolf.push(s as f64);
+                }
+                add_to_breakdown(&mut analysis.strokes, length, pool_length);
+            }
+        }
+    }
+
+    if analysis.distance <= 0.0 {
+        return None;
+    }
+    analysis.pace_per_100_sec = pace_per_100(analysis.active_time_sec, analysis.distance);
+    analysis.swolf_avg = mean(&swolf);
+    Some(analysis)
+}
+
+/// Swim analysis for every swim workout in range, with critical swim speed
+/// from the latest test session
+pub fn analyze_swims(history: &WpsHistory, range: &DateRange) -> SwimReport {
+    let swims = range.workouts(history).filter_map(analyze_swim).collect();
+    let css = range
+        .workouts(history)
+        .filter_map(css_test)
+        .max_by_key(|(date, _)| *date)
+        .map(|(_, test)| test);
+    SwimReport { swims, css }
+}
+
+/// Critical swim speed from a workout's fastest 400 and 200 sets, if it
+/// has both
+///
+/// A set matches a test distance when it is within half a pool length of it
+/// (5% without a pool), in the pool's unit.
+fn css_test(workout: &Workout) -> Option<(chrono::NaiveDate, CssTest)> {
+    let swim_workout = workout.sport == Some(Sport::Swimming);
+    let mut unit = PoolLengthUnit::Meters;
+    let mut best = [None::<f64>; 2];
+    for exercise in workout
+        .exercises
+        .iter()
+        .filter(|e| swim_workout || is_swim_exercise(e))
+    {
+        let pool = exercise.pool_config.as_ref();
+        if let Some(pool) = pool {
+            unit = pool.pool_length_unit;
+        }
+        let set_unit = pool.map_or(PoolLengthUnit::Meters, |p| p.pool_length_unit);
+        let slack = pool.map_or(0.05 * CSS_SHORT_TEST, |p| p.pool_length / 2.0);
+        for set in &exercise.sets {
+            let Some((distance, time)) = set_distance_time(set, pool) else {
+                continue;
+            };
+            let distance = in_units(distance, set_unit);
+            for (slot, target) in [CSS_LONG_TEST, CSS_SHORT_TEST].into_iter().enumerate() {
+                if (distance - target).abs() <= slack.max(0.05 * target) {
+                    best[slot] = Some(best[slot].map_or(time, |b: f64| b.min(time)));
+                }
+            }
+        }
+    }
+    let [Some(long), Some(short)] = best else {
+        return None;
+    };
+    let speed = critical_swim_speed(CSS_LONG_TEST, long, CSS_SHORT_TEST, short)?;
+    Some((
+        workout_date(workout)?,
+        CssTest {
+            date: workout.date.clone(),
+            unit,
+            long_time_sec: long,
+            short_time_sec: short,
+            speed,
+            pace_per_100_sec: 100.0 / speed,
+        },
+    ))
+}
+
+/// A set's distance in meters and swimming time
+fn set_distance_time(set: &CompletedSet, pool: Option<&PoolConfig>) -> Option<(f64, f64)> {
+    let active: Vec<&SwimmingLength> = set
+        .swimming
+        .iter()
+        .flat_map(|s| &s.lengths)
+        .filter(|l| l.active != Some(false))
+        .collect();
+    let distance = set.distance_meters.or_else(|| {
+        let pool = pool?;
+        (!active.is_empty()).then(|| active.len() as f64 * pool.length_in_meters())
+    })?;
+    let time = set.duration_sec.map(f64::from).or_else(|| {
+        (!active.is_empty()).then(|| active.iter().map(|l| l.duration_sec as f64).sum())
+    })?;
+    (distance > 0.0 && time > 0.0).then_some((distance, time))
+}
+
+fn is_swim_exercise(exercise: &CompletedExercise) -> bool {
+    exercise.sport == Some(Sport::Swimming)
+        || exercise.pool_config.is_some()
+        || exercise.sets.iter().any(|s| s.swimming.is_some())
+}
+
+fn reconstructed(set: &CompletedSet, pool: Option<&PoolConfig>) -> Vec<SwimmingLength> {
+    let Some(ts) = set.telemetry.as_ref().and_then(|t| t.time_series.as_ref()) else {
+        return Vec::new();
+    };
+    let default_stroke = set
+        .swimming
+        .as_ref()
+        .and_then(|s| s.stroke_type)
+        .unwrap_or(StrokeType::Freestyle);
+    reconstruct_lengths(ts, pool, default_stroke)
+}
+
+fn fill_set_data(swimming: &mut SwimmingSetData, lengths: Vec<SwimmingLength>) {
+    let active = lengths.iter().filter(|l| l.active != Some(false)).count() as u32;
+    let first = lengths.first().map(|l| l.stroke_type);
+    if lengths.iter().all(|l| Some(l.stroke_type) == first) {
+        swimming.stroke_type = first;
+    }
+    swimming.total_lengths = Some(lengths.len() as u32);
+    swimming.active_lengths = Some(active);
+    swimming.lengths = lengths;
+    swimming.swolf_avg = swimming.calculate_avg_swolf();
+}
+
+fn add_to_breakdown(strokes: &mut Vec<StrokeBreakdown>, length: &SwimmingLength, pool_length: f64) {
+    let index = match strokes
+        .iter()
+        .position(|s| s.stroke_type == length.stroke_type)
+    {
+        Some(index) => index,
+        None => {
+            strokes.push(StrokeBreakdown {
+                stroke_type: length.stroke_type,
+                lengths: 0,
+                distance: 0.0,
+                duration_sec: 0.0,
+                pace_per_100_sec: None,
+                swolf_avg: None,
+                strokes_per_length: None,
+            });
+            strokes.len() - 1
+        }
+    };
+    let entry = &mut strokes[index];
+    let n = entry.lengths as f64;
+    let running_mean = |mean: Option<f64>, value: Option<u32>, count: f64| match (mean, value) {
+        (Some(mean), Some(value)) => Some((mean * count + value as f64) / (count + 1.0)),
+        (None, Some(value)) if count == 0.0 => Some(value as f64),
+        (mean, _) => mean,
+    };
+    entry.swolf_avg = running_mean(
+        entry.swolf_avg,
+        length.swolf.or_else(|| length.calculate_swolf()),
+        n,
+    );
+    entry.strokes_per_length = running_mean(entry.strokes_per_length, length.stroke_count, n);
+    entry.lengths += 1;
+    entry.distance += pool_length;
+    entry.duration_sec += length.duration_sec as f64;
+    entry.pace_per_100_sec = pace_per_100(entry.duration_sec, entry.distance);
+}
+
+fn in_units(meters: f64, unit: PoolLengthUnit) -> f64 {
+    match unit {
+        PoolLengthUnit::Meters => meters,
+        PoolLengthUnit::Yards => meters / METERS_PER_YARD,
+    }
+}
+
+fn pace_per_100(seconds: f64, distance: f64) -> Option<f64> {
+    (distance > 0.0 && seconds > 0.0).then(|| seconds * 100.0 / distance)
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn majority(values: &[StrokeType]) -> StrokeType {
+    let mut counts: Vec<(StrokeType, usize)> = Vec::new();
+    for &value in values {
+        match counts.iter_mut().find(|(s, _)| *s == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(_, count)| count)
+        .map_or(StrokeType::Freestyle, |(stroke, _)| stroke)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::parse;
+
+    /// Four 25 m lengths at 1 Hz: 20 s freestyle (15 strokes) ×2, then 30 s
+    /// breaststroke (12 strokes) ×2
+    fn swim_series(with_distance: bool) -> TimeSeriesData {
+        let plan = [
+            (20, 15, StrokeType::Freestyle),
+            (20, 15, StrokeType::Freestyle),
+            (30, 12, StrokeType::Breaststroke),
+            (30, 12, StrokeType::Breaststroke),
+        ];
+        let (mut strokes, mut swolf, mut types, mut distance) = (vec![], vec![], vec![], vec![]);
+        let mut total_strokes = 0;
+        for (i, &(seconds, count, stroke)) in plan.iter().enumerate() {
+            for s in 0..seconds {
+                strokes.push(total_strokes + count * s / seconds);
+                swolf.push(seconds + count + i as u32 % 2);
+                types.push(stroke);
+                distance.push(25.0 * i as f64 + 25.0 * s as f64 / seconds as f64);
+            }
+            total_strokes += count;
+        }
+        // Closing row
+        strokes.push(total_strokes);
+        swolf.push(*swolf.last().unwrap());
+        types.push(StrokeType::Breaststroke);
+        distance.push(100.0);
+
+        TimeSeriesData {
+            timestamps: vec![String::new(); strokes.len()],
+            stroke_count: Some(strokes),
+            swolf: Some(swolf),
+            stroke_type: Some(types),
+            distance_m: with_distance.then_some(distance),
+            ..Default::default()
+        }
+    }
+
+    fn swim_history() -> WpsHistory {
+        parse(
+            r#"
+history_version: 2
+exported_at: "2025-03-02T00:00:00Z"
+workouts:
+  - date: "2025-03-01"
+    sport: swimming
+    exercises:
+      - name: CSS test
+        pool_config: { pool_length: 25, pool_length_unit: yards }
+        sets:
+          - distance_meters: 365.76
+            duration_sec: 360
+          - duration_sec: 60
+            notes: rest
+          - duration_sec: 165
+            swimming:
+              lengths: []
+"#,
+        )
+        .map(|mut history| {
+            let lengths = &mut history.workouts[0].exercises[0].sets[2];
+            lengths.swimming.as_mut().unwrap().lengths = (1..=8)
+                .map(|n| SwimmingLength {
+                    length_number: n,
+                    stroke_type: StrokeType::Freestyle,
+                    duration_sec: if n == 8 { 25 } else { 20 },
+                    stroke_count: Some(14),
+                    swolf: None,
+                    started_at: None,
+                    active: None,
+                })
+                .collect();
+            history
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_critical_swim_speed() {
+        // 400 in 6:00, 200 in 2:45: 200 / 195 s
+        let css = critical_swim_speed(400.0, 360.0, 200.0, 165.0).unwrap();
+        assert!((100.0 / css - 97.5).abs() < 1e-9);
+        assert_eq!(critical_swim_speed(400.0, 160.0, 200.0, 165.0), None);
+    }
+
+    #[test]
+    fn test_reconstruct_lengths_from_distance() {
+        let lengths = reconstruct_lengths(
+            &swim_series(true),
+            Some(&PoolConfig::pool_25m()),
+            StrokeType::Freestyle,
+        );
+        assert_eq!(lengths.len(), 4);
+        let summary: Vec<(u32, StrokeType, Option<u32>)> = lengths
+            .iter()
+            .map(|l| (l.duration_sec, l.stroke_type, l.stroke_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (20, StrokeType::Freestyle, Some(15)),
+                (20, StrokeType::Freestyle, Some(15)),
+                (30, StrokeType::Breaststroke, Some(12)),
+                (30, StrokeType::Breaststroke, Some(12)),
+            ]
+        );
+        assert_eq!(lengths[0].swolf, Some(35));
+    }
+
+    #[test]
+    fn test_reconstruct_lengths_from_swolf() {
+        let lengths = reconstruct_lengths(&swim_series(false), None, StrokeType::Freestyle);
+        assert_eq!(lengths.len(), 4);
+        assert_eq!(lengths[3].duration_sec, 30);
+        assert_eq!(lengths[3].length_number, 4);
+
+        // Nothing to split on
+        let bare = TimeSeriesData {
+            timestamps: vec![String::new(); 10],
+            ..Default::default()
+        };
+        assert!(reconstruct_lengths(&bare, None, StrokeType::Freestyle).is_empty());
+    }
+
+    #[test]
+    fn test_analyze_swim_yards() {
+        let history = swim_history();
+        let report = analyze_swims(&history, &DateRange::all());
+        let swim = &report.swims[0];
+        assert_eq!(swim.unit, PoolLengthUnit::Yards);
+        assert!((swim.distance - 600.0).abs() < 1e-6);
+        assert_eq!(swim.active_lengths, 8);
+        // 360 + 165 s over 600 yd; the rest set does not count
+        assert!((swim.pace_per_100_sec.unwrap() - 87.5).abs() < 1e-6);
+        assert_eq!(swim.strokes.len(), 1);
+        assert_eq!(swim.strokes[0].strokes_per_length, Some(14.0));
+        assert!((swim.strokes[0].pace_per_100_sec.unwrap() - 82.5).abs() < 1e-6);
+
+        let css = report.css.unwrap();
+        assert_eq!((css.long_time_sec, css.short_time_sec), (360.0, 165.0));
+        assert!((css.pace_per_100_sec - 97.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fill_swim_lengths() {
+        let mut history = swim_history();
+        let set = &mut history.workouts[0].exercises[0].sets[1];
+        set.telemetry = Some(crate::history::SetTelemetry {
+            time_series: Some(swim_series(false)),
+            ..Default::default()
+        });
+        assert_eq!(fill_swim_lengths(&mut history), 1);
+
+        let swimming = history.workouts[0].exercises[0].sets[1]
+            .swimming
+            .as_ref()
+            .unwrap();
+        assert_eq!(swimming.total_lengths, Some(4));
+        assert_eq!(swimming.active_lengths, Some(4));
+        assert_eq!(swimming.stroke_type, None);
+        assert_eq!(swimming.swolf_avg, Some(38));
+        assert_eq!(fill_swim_lengths(&mut history), 0);
+    }
+}
@@ -0,0 +1,341 @@
+//! W′ balance (Skiba differential model)
+//!
+//! W′ is depleted one joule per joule of work done above CP and recovers
+//! below CP at a rate proportional to how far below CP the athlete is and
+//! how much W′ is missing: `dW′bal/dt = (CP - P) · (W′ - W′bal) / W′`.
+//! Recovery uses the exact solution of that equation over each interval, so
+//! long gaps between samples do not overshoot.
+
+use super::power::fill;
+use super::series::{timed_series, Sample, MAX_GAP_SEC};
+use crate::history::{PowerMetrics, TimeSeriesData, Workout, WorkoutTelemetry};
+
+/// Depletion from the preceding peak that counts as burning a match
+pub const MATCH_MIN_DEPLETION_JOULES: f64 = 2000.0;
+
+/// Recovery from the lowest balance that ends an effort, so a brief easing
+/// inside one effort does not split it into two matches
+pub const MATCH_MIN_RECOVERY_JOULES: f64 = 500.0;
+
+/// W′ balance at each sample, starting full
+///
+/// The power of each sample is held until the next one. Gaps longer than
+/// [`MAX_GAP_SEC`] are pauses and recover at zero power. Samples must be in
+/// time order.
+pub fn w_prime_balance(samples: &[Sample], cp_watts: f64, w_prime_joules: f64) -> Vec<f64> {
+    let mut out = Vec::with_capacity(samples.len());
+    let mut balance = w_prime_joules;
+    for (i, &(t, _)) in samples.iter().enumerate() {
+        if i > 0 {
+            let (prev_t, prev_power) = samples[i - 1];
+            let dt = (t - prev_t).max(0.0);
+            let power = if dt > MAX_GAP_SEC { 0.0 } else { prev_power };
+            balance = step(balance, power, dt, cp_watts, w_prime_joules);
+        }
+        out.push(balance);
+    }
+    out
+}
+
+fn step(balance: f64, power: f64, dt: f64, cp: f64, w_prime: f64) -> f64 {
+    if power > cp {
+        balance - (power - cp) * dt
+    } else if w_prime > 0.0 {
+        w_prime - (w_prime - balance) * (-(cp - power) * dt / w_prime).exp()
+    } else {
+        balance
+    }
+}
+
+/// W′ balance for every row of a workout's time series
+///
+/// Returns one column per time series, in set order, aligned with its rows.
+/// Balance carries over from one set to the next, recovering through the
+/// rest between them. Rows without power are `None`.
+pub fn workout_w_prime_balance(
+    workout: &Workout,
+    cp_watts: f64,
+    w_prime_joules: f64,
+) -> Vec<Vec<Option<f64>>> {
+    let series = timed_series(workout);
+    let mut columns: Vec<Vec<Option<f64>>> = series
+        .iter()
+        .map(|(_, times)| vec![None; times.len()])
+        .collect();
+
+    let rows = power_rows(&series);
+    let balance = rows_balance(&rows, cp_watts, w_prime_joules);
+    for (&(_, _, s, r), b) in rows.iter().zip(balance) {
+        columns[s][r] = Some(b);
+    }
+    columns
+}
+
+/// (time, power, series index, row index) for every row with power, in
+/// time order
+fn power_rows(series: &[(&TimeSeriesData, Vec<f64>)]) -> Vec<(f64, f64, usize, usize)> {
+    let mut rows = Vec::new();
+    for (s, (ts, times)) in series.iter().enumerate() {
+        let Some(power) = &ts.power else {
+            continue;
+        };
+        for (r, (&t, &p)) in times.iter().zip(power).enumerate() {
+            rows.push((t, p as f64, s, r));
+        }
+    }
+    rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+    rows
+}
+
+fn rows_balance(rows: &[(f64, f64, usize, usize)], cp_watts: f64, w_prime_joules: f64) -> Vec<f64> {
+    let samples: Vec<Sample> = rows.iter().map(|&(t, p, _, _)| (t, p)).collect();
+    w_prime_balance(&samples, cp_watts, w_prime_joules)
+}
+
+/// Count the distinct efforts that each drew W′ down by at least
+/// [`MATCH_MIN_DEPLETION_JOULES`] from the balance where they began
+///
+/// An effort ends once the balance recovers [`MATCH_MIN_RECOVERY_JOULES`]
+/// above its lowest point. `balance` must be in time order.
+pub fn matches_burned(balance: &[f64]) -> u32 {
+    let mut matches = 0;
+    let (mut peak, mut low) = match balance.first() {
+        Some(&b) => (b, b),
+        None => return 0,
+    };
+    let mut counted = false;
+    for &b in &balance[1..] {
+        low = low.min(b);
+        if b > peak || b - low >= MATCH_MIN_RECOVERY_JOULES {
+            peak = b;
+            low = b;
+            counted = false;
+        } else if !counted && peak - b >= MATCH_MIN_DEPLETION_JOULES {
+            matches += 1;
+            counted = true;
+        }
+    }
+    matches
+}
+
+/// Fill in the W′ balance summary on a workout's power metrics
+///
+/// CP and W′ come from the workout's own `power_metrics` when recorded,
+/// otherwise from the arguments (which are then stored alongside the
+/// result). Existing values are kept. Returns true if anything changed.
+pub fn fill_w_prime_balance(
+    workout: &mut Workout,
+    cp_watts: Option<u32>,
+    w_prime_joules: Option<f64>,
+) -> bool {
+    let recorded = workout
+        .telemetry
+        .as_ref()
+        .and_then(|t| t.power_metrics.as_ref());
+    let cp = recorded
+        .and_then(|m| m.critical_power_watts)
+        .or(cp_watts)
+        .filter(|&cp| cp > 0);
+    let w_prime = recorded
+        .and_then(|m| m.w_prime_joules)
+        .or(w_prime_joules)
+        .filter(|&w| w > 0.0);
+    let (Some(cp), Some(w_prime)) = (cp, w_prime) else {
+        return false;
+    };
+
+    let balance = rows_balance(&power_rows(&timed_series(workout)), cp as f64, w_prime);
+    if balance.is_empty() {
+        return false;
+    }
+    let min = balance.iter().copied().fold(f64::INFINITY, f64::min);
+
+    let metrics = workout
+        .telemetry
+        .get_or_insert_with(WorkoutTelemetry::default)
+        .power_metrics
+        .get_or_insert_with(PowerMetrics::default);
+    let mut changed = false;
+    changed |= fill(&mut metrics.critical_power_watts, Some(cp));
+    changed |= fill(&mut metrics.w_prime_joules, Some(w_prime));
+    changed |= fill(&mut metrics.min_w_prime_balance_joules, Some(min.round()));
+    changed |= fill(&mut metrics.matches_burned, Some(matches_burned(&balance)));
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CP: f64 = 250.0;
+    const W_PRIME: f64 = 20_000.0;
+
+    fn steady(watts: f64, from: usize, seconds: usize) -> Vec<Sample> {
+        (from..from + seconds).map(|t| (t as f64, watts)).collect()
+    }
+
+    #[test]
+    fn test_depletes_linearly_above_cp() {
+        let balance = w_prime_balance(&steady(350.0, 0, 61), CP, W_PRIME);
+        assert_eq!(balance[0], W_PRIME);
+        // 60 s at 100 W above CP uses 6 kJ
+        assert!((balance[60] - 14_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_recovers_towards_full_below_cp() {
+        let mut samples = steady(350.0, 0, 60);
+        samples.extend(steady(100.0, 60, 600));
+        let balance = w_prime_balance(&samples, CP, W_PRIME);
+        let low = balance[60];
+        let end = *balance.last().unwrap();
+        assert!(end > low);
+        assert!(end < W_PRIME);
+        for pair in balance[60..].windows(2) {
+            assert!(pair[1] >= pair[0]);
+        }
+    }
+
+    #[test]
+    fn test_pause_recovers_at_zero_power() {
+        let samples = vec![(0.0, 500.0), (30.0, 500.0), (630.0, 500.0)];
+        let balance = w_prime_balance(&samples, CP, W_PRIME);
+        // The 30 s gap is a pause, so no depletion happens at all
+        assert_eq!(balance[1], W_PRIME);
+        assert_eq!(balance[2], W_PRIME);
+    }
+
+    #[test]
+    fn test_matches_burned_counts_separate_efforts() {
+        let mut samples = Vec::new();
+        let mut t = 0;
+        for _ in 0..3 {
+            samples.extend(steady(400.0, t, 30)); // 4.5 kJ each
+            samples.extend(steady(100.0, t + 30, 120));
+            t += 150;
+        }
+        // A short surge that only costs 1 kJ is not a match
+        samples.extend(steady(350.0, t, 10));
+        samples.extend(steady(100.0, t + 10, 60));
+
+        let balance = w_prime_balance(&samples, CP, W_PRIME);
+        assert_eq!(matches_burned(&balance), 3);
+        assert_eq!(matches_burned(&[]), 0);
+    }
+
+    #[test]
+    fn test_brief_easing_stays_one_match() {
+        // 4.5 kJ of work above CP with a one second soft pedal in the middle
+        let mut samples = steady(400.0, 0, 15);
+        samples.extend(steady(100.0, 15, 1));
+        samples.extend(steady(400.0, 16, 15));
+        samples.extend(steady(100.0, 31, 300));
+
+        let balance = w_prime_balance(&samples, CP, W_PRIME);
+        assert_eq!(matches_burned(&balance), 1);
+    }
+
+    /// A one-set-per-block workout with 1 Hz timestamps
+    fn blocks_workout(blocks: &[(usize, usize, u32)]) -> Workout {
+        let sets: Vec<String> = blocks
+            .iter()
+            .map(|&(from, seconds, watts)| {
+                let timestamps: Vec<String> = (from..from + seconds)
+                    .map(|t| format!("\"2025-01-15T10:{:02}:{:02}Z\"", t / 60, t % 60))
+                    .collect();
+                format!(
+                    "      - telemetry:\n          time_series:\n            timestamps: [{}]\n            power: [{}]\n",
+                    timestamps.join(", "),
+                    vec![watts.to_string(); seconds].join(", ")
+                )
+            })
+            .collect();
+        let yaml = format!(
+            "date: \"2025-01-15\"\nexercises:\n  - name: Ride\n    sets:\n{}",
+            sets.concat()
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_fill_uses_time_order() {
+        // One effort then recovery, with the sets out of order in the file
+        let mut workout = blocks_workout(&[(0, 30, 400), (150, 300, 100), (30, 120, 100)]);
+        assert!(fill_w_prime_balance(
+            &mut workout,
+            Some(250),
+            Some(20_000.0)
+        ));
+        let metrics = workout.telemetry.unwrap().power_metrics.unwrap();
+        assert_eq!(metrics.min_w_prime_balance_joules, Some(15_500.0));
+        assert_eq!(metrics.matches_burned, Some(1));
+    }
+
+    #[test]
+    fn test_workout_balance_carries_across_sets() {
+        let yaml = r#"
+date: "2025-01-15"
+exercises:
+  - name: Intervals
+    sets:
+      - telemetry:
+          time_series:
+            timestamps: ["2025-01-15T10:00:00Z", "2025-01-15T10:00:01Z", "2025-01-15T10:00:02Z"]
+            power: [1250, 1250, 100]
+      - telemetry:
+          time_series:
+            timestamps: ["2025-01-15T10:00:03Z", "2025-01-15T10:00:04Z"]
+            heart_rate: [150, 150]
+      - telemetry:
+          time_series:
+            timestamps: ["2025-01-15T10:00:05Z", "2025-01-15T10:00:06Z"]
+            power: [1250, 100]
+"#;
+        let workout: Workout = serde_yaml::from_str(yaml).unwrap();
+        let columns = workout_w_prime_balance(&workout, CP, W_PRIME);
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns[0][0], Some(20_000.0));
+        assert_eq!(columns[0][2], Some(18_000.0));
+        assert_eq!(columns[1], vec![None, None]);
+        let after = columns[2][0].unwrap();
+        assert!(after > 18_000.0 && after < 20_000.0);
+        assert!((columns[2][1].unwrap() - (after - 1000.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fill_prefers_recorded_cp_and_w_prime() {
+        let yaml = r#"
+date: "2025-01-15"
+exercises:
+  - name: Ride
+    sets:
+      - telemetry:
+          time_series:
+            timestamps: ["", "", "", ""]
+            power: [1250, 1250, 1250, 100]
+telemetry:
+  power_metrics:
+    critical_power_watts: 250
+    w_prime_joules: 10000
+"#;
+        let mut workout: Workout = serde_yaml::from_str(yaml).unwrap();
+        assert!(fill_w_prime_balance(
+            &mut workout,
+            Some(400),
+            Some(30_000.0)
+        ));
+        let metrics = workout.telemetry.unwrap().power_metrics.unwrap();
+        assert_eq!(metrics.critical_power_watts, Some(250));
+        assert_eq!(metrics.w_prime_joules, Some(10_000.0));
+        assert_eq!(metrics.min_w_prime_balance_joules, Some(7_000.0));
+        assert_eq!(metrics.matches_burned, Some(1));
+    }
+
+    #[test]
+    fn test_fill_without_cp_does_nothing() {
+        let yaml = "date: \"2025-01-15\"\nexercises: []\n";
+        let mut workout: Workout = serde_yaml::from_str(yaml).unwrap();
+        assert!(!fill_w_prime_balance(&mut workout, None, Some(20_000.0)));
+        assert!(workout.telemetry.is_none());
+    }
+}
@@ -0,0 +1,499 @@
+//! Time in heart rate, power and pace zones
+//!
+//! Boundaries are the upper limit of each zone, so `n` boundaries describe
+//! `n` zones and anything above the last boundary counts toward the top
+//! zone. Pace boundaries work the same way in terms of intensity: they run
+//! from the slowest zone to the fastest, in seconds per km.
+//!
+//! Streams are resampled to 1 Hz first, so irregular recording, gaps and
+//! auto-pause spans are handled the same way as the power analytics.
+
+use super::series::{parse_timestamp, resample_1hz, timed_series, Sample};
+use crate::history::{
+    CompletedExercise, SportSegment, TimeInZones, TimeSeriesData, Workout, WorkoutTelemetry,
+};
+use crate::plan::AthleteProfile;
+
+/// Upper limits of the default HR zones as a fraction of max HR
+pub const HR_ZONES_PERCENT_MAX: [f64; 6] = [0.60, 0.70, 0.80, 0.90, 0.95, 1.00];
+
+/// Upper limits of the default HR zones as a fraction of threshold HR,
+/// used when only threshold HR is known
+pub const HR_ZONES_PERCENT_THRESHOLD: [f64; 6] = [0.81, 0.89, 0.93, 0.99, 1.02, 1.06];
+
+/// Upper limits of the default power zones as a fraction of FTP
+pub const POWER_ZONES_PERCENT_FTP: [f64; 6] = [0.55, 0.75, 0.90, 1.05, 1.20, 1.50];
+
+/// Upper (fastest) limit of each default pace zone as a fraction of
+/// threshold pace
+pub const PACE_ZONES_PERCENT_THRESHOLD: [f64; 6] = [1.29, 1.14, 1.06, 0.99, 0.97, 0.90];
+
+/// Zone boundaries for each stream; `None` skips that stream
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ZoneBoundaries {
+    /// Upper limit of each HR zone (bpm)
+    pub hr: Option<Vec<u32>>,
+    /// Upper limit of each power zone (watts)
+    pub power: Option<Vec<u32>>,
+    /// Upper (fastest) limit of each pace zone (seconds per km); anything
+    /// slower than the first boundary is zone 1
+    pub pace: Option<Vec<u32>>,
+}
+
+impl ZoneBoundaries {
+    /// Default zones derived from an athlete's thresholds
+    ///
+    /// HR zones come from max HR, or threshold HR when max is unknown.
+    pub fn from_profile(profile: &AthleteProfile) -> Self {
+        let scale = |base: u32, fractions: &[f64]| -> Vec<u32> {
+            fractions
+                .iter()
+                .map(|f| (base as f64 * f).round() as u32)
+                .collect()
+        };
+        let hr = match (profile.max_hr_bpm, profile.threshold_hr_bpm) {
+            (Some(max), _) => Some(scale(max, &HR_ZONES_PERCENT_MAX)),
+            (None, Some(threshold)) => Some(scale(threshold, &HR_ZONES_PERCENT_THRESHOLD)),
+            (None, None) => None,
+        };
+        Self {
+            hr,
+            power: profile
+                .ftp_watts
+                .map(|ftp| scale(ftp, &POWER_ZONES_PERCENT_FTP)),
+            pace: profile
+                .threshold_pace_sec_per_km
+                .map(|pace| scale(pace, &PACE_ZONES_PERCENT_THRESHOLD)),
+        }
+    }
+
+    /// The boundaries recorded on an existing `TimeInZones` block
+    pub fn from_time_in_zones(zones: &TimeInZones) -> Self {
+        Self {
+            hr: zones.hr_zone_boundaries.clone(),
+            power: zones.power_zone_boundaries.clone(),
+            pace: zones.pace_zone_boundaries.clone(),
+        }
+    }
+
+    /// Fill any missing streams from `fallback`
+    pub fn or(self, fallback: &ZoneBoundaries) -> Self {
+        Self {
+            hr: self.hr.or_else(|| fallback.hr.clone()),
+            power: self.power.or_else(|| fallback.power.clone()),
+            pace: self.pace.or_else(|| fallback.pace.clone()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hr.is_none() && self.power.is_none() && self.pace.is_none()
+    }
+}
+
+/// Seconds spent in each zone of a 1 Hz stream
+///
+/// Zones are ordered by intensity. With `ascending` boundaries (HR, power)
+/// higher values are harder; otherwise (pace) lower values are harder.
+/// Returns `None` when there are no boundaries.
+pub fn seconds_in_zones(values: &[f64], boundaries: &[u32], ascending: bool) -> Option<Vec<u32>> {
+    if boundaries.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<f64> = boundaries.iter().map(|&b| b as f64).collect();
+    sorted.sort_by(f64::total_cmp);
+    if !ascending {
+        sorted.reverse();
+    }
+
+    let top = sorted.len() - 1;
+    let mut seconds = vec![0u32; sorted.len()];
+    for &value in values {
+        let passed = sorted
+            .iter()
+            .take_while(|&&b| if ascending { value >= b } else { value <= b })
+            .count();
+        seconds[passed.min(top)] += 1;
+    }
+    Some(seconds)
+}
+
+/// Time in zones for a set of time series
+///
+/// Readings of zero (HR dropouts, stopped for pace) are ignored. Streams
+/// without boundaries or without any readings are left `None`. Boundaries
+/// used are recorded on the result.
+pub fn series_time_in_zones(
+    series: &[(&TimeSeriesData, Vec<f64>)],
+    boundaries: &ZoneBoundaries,
+) -> TimeInZones {
+    let hr = stream(series, |ts| {
+        ts.heart_rate
+            .as_ref()
+            .map(|v| v.iter().map(|&x| Some(x as f64)).collect())
+    });
+    let power = stream(series, |ts| {
+        ts.power
+            .as_ref()
+            .map(|v| v.iter().map(|&x| Some(x as f64)).collect())
+    });
+    let pace = stream(series, |ts| {
+        ts.speed_mps
+            .as_ref()
+            .map(|v| v.iter().map(|&x| Some(x)).collect())
+    });
+    // Convert speed to pace after resampling so stopped spans stay stopped
+    let pace: Vec<f64> = pace
+        .into_iter()
+        .filter(|&speed| speed > 0.0)
+        .map(|speed| 1000.0 / speed)
+        .collect();
+
+    let zones = |values: &[f64], bounds: &Option<Vec<u32>>, ascending: bool| {
+        let bounds = bounds.as_ref()?;
+        if values.is_empty() {
+            return None;
+        }
+        seconds_in_zones(values, bounds, ascending)
+    };
+    let hr: Vec<f64> = hr.into_iter().filter(|&v| v > 0.0).collect();
+
+    let hr_zones_sec = zones(&hr, &boundaries.hr, true);
+    let power_zones_sec = zones(&power, &boundaries.power, true);
+    let pace_zones_sec = zones(&pace, &boundaries.pace, false);
+    TimeInZones {
+        hr_zone_boundaries: hr_zones_sec.as_ref().and(boundaries.hr.clone()),
+        power_zone_boundaries: power_zones_sec.as_ref().and(boundaries.power.clone()),
+        pace_zone_boundaries: pace_zones_sec.as_ref().and(boundaries.pace.clone()),
+        hr_zones_sec,
+        power_zones_sec,
+        pace_zones_sec,
+    }
+}
+
+fn stream(
+    series: &[(&TimeSeriesData, Vec<f64>)],
+    column: impl Fn(&TimeSeriesData) -> Option<Vec<Option<f64>>>,
+) -> Vec<f64> {
+    let mut samples: Vec<Sample> = Vec::new();
+    for (ts, times) in series {
+        if let Some(values) = column(ts) {
+            samples.extend(times.iter().zip(values).filter_map(|(&t, v)| Some((t, v?))));
+        }
+    }
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    resample_1hz(&samples)
+}
+
+/// Time in zones across all of a workout's time series
+pub fn workout_time_in_zones(workout: &Workout, boundaries: &ZoneBoundaries) -> TimeInZones {
+    series_time_in_zones(&timed_series(workout), boundaries)
+}
+
+/// Time in zones for one segment of a multi-sport workout
+///
+/// A segment covers the exercises listed in its `exercise_ids`; segments
+/// without any are matched by time from `started_at` over `duration_sec`.
+pub fn segment_time_in_zones(
+    workout: &Workout,
+    segment: &SportSegment,
+    boundaries: &ZoneBoundaries,
+) -> TimeInZones {
+    let series: Vec<(&TimeSeriesData, Vec<f64>)> = if segment.exercise_ids.is_empty() {
+        let window = segment
+            .started_at
+            .as_deref()
+            .and_then(parse_timestamp)
+            .zip(segment.duration_sec)
+            .map(|(start, duration)| (start, start + duration as f64));
+        let Some((start, end)) = window else {
+            return TimeInZones::default();
+        };
+        timed_series(workout)
+            .into_iter()
+            .filter(|(_, times)| times.first().is_some_and(|&t| t >= start && t < end))
+            .collect()
+    } else {
+        let mut series = Vec::new();
+        for exercise in &workout.exercises {
+            if !exercise
+                .id
+                .as_ref()
+                .is_some_and(|id| segment.exercise_ids.contains(id))
+            {
+                continue;
+            }
+            series.extend(exercise_series(workout, exercise));
+        }
+        series
+    };
+    series_time_in_zones(&series, boundaries)
+}
+
+/// The timed series belonging to one exercise
+fn exercise_series<'a>(
+    workout: &'a Workout,
+    exercise: &'a CompletedExercise,
+) -> Vec<(&'a TimeSeriesData, Vec<f64>)> {
+    timed_series(workout)
+        .into_iter()
+        .filter(|(ts, _)| {
+            exercise.sets.iter().any(|s| {
+                s.telemetry
+                    .as_ref()
+                    .and_then(|t| t.time_series.as_ref())
+                    .is_some_and(|own| std::ptr::eq(own, *ts))
+            })
+        })
+        .collect()
+}
+
+/// Compute and store time in zones on a workout's sets, segments and
+/// overall telemetry
+///
+/// Boundaries already recorded on the workout's (or a segment's)
+/// `time_in_zones` take precedence over `defaults`, which typically come
+/// from [`ZoneBoundaries::from_profile`]. Existing zone times are kept.
+/// Returns true if anything changed.
+pub fn fill_time_in_zones(workout: &mut Workout, defaults: &ZoneBoundaries) -> bool {
+    let workout_bounds = recorded(workout.telemetry.as_ref()).or(defaults);
+    if workout_bounds.is_empty() {
+        return false;
+    }
+
+    // Compute everything against the immutable workout first
+    let series = timed_series(workout);
+    let set_zones: Vec<TimeInZones> = series
+        .iter()
+        .map(|(ts, times)| series_time_in_zones(&[(*ts, times.clone())], &workout_bounds))
+        .collect();
+    let overall = series_time_in_zones(&series, &workout_bounds);
+    let segment_zones: Vec<TimeInZones> = workout
+        .sport_segments
+        .iter()
+        .flatten()
+        .map(|segment| {
+            let bounds = recorded(segment.telemetry.as_ref()).or(&workout_bounds);
+            segment_time_in_zones(workout, segment, &bounds)
+        })
+        .collect();
+
+    let mut changed = false;
+    let mut set_zones = set_zones.into_iter();
+    for set in workout.exercises.iter_mut().flat_map(|e| &mut e.sets) {
+        let Some(telemetry) = set.telemetry.as_mut() else {
+            continue;
+        };
+        if telemetry.time_series.is_none() {
+            continue;
+        }
+        if let Some(zones) = set_zones.next() {
+            changed |= merge(&mut telemetry.time_in_zones, zones);
+        }
+    }
+    for (segment, zones) in workout
+        .sport_segments
+        .iter_mut()
+        .flatten()
+        .zip(segment_zones)
+    {
+        let telemetry = segment
+            .telemetry
+            .get_or_insert_with(WorkoutTelemetry::default);
+        changed |= merge(&mut telemetry.time_in_zones, zones);
+    }
+    let telemetry = workout
+        .telemetry
+        .get_or_insert_with(WorkoutTelemetry::default);
+    changed |= merge(&mut telemetry.time_in_zones, overall);
+    changed
+}
+
+fn recorded(telemetry: Option<&WorkoutTelemetry>) -> ZoneBoundaries {
+    telemetry
+        .and_then(|t| t.time_in_zones.as_ref())
+        .map(ZoneBoundaries::from_time_in_zones)
+        .unwrap_or_default()
+}
+
+/// Copy computed zone times into `slot`, keeping anything already there
+fn merge(slot: &mut Option<TimeInZones>, computed: TimeInZones) -> bool {
+    let empty = computed.hr_zones_sec.is_none()
+        && computed.power_zones_sec.is_none()
+        && computed.pace_zones_sec.is_none();
+    if empty {
+        return false;
+    }
+    let target = slot.get_or_insert_with(TimeInZones::default);
+    let mut changed = false;
+    if target.hr_zones_sec.is_none() && computed.hr_zones_sec.is_some() {
+        target.hr_zones_sec = computed.hr_zones_sec;
+        target.hr_zone_boundaries = target
+            .hr_zone_boundaries
+            .take()
+            .or(computed.hr_zone_boundaries);
+        changed = true;
+    }
+    if target.power_zones_sec.is_none() && computed.power_zones_sec.is_some() {
+        target.power_zones_sec = computed.power_zones_sec;
+        target.power_zone_boundaries = target
+            .power_zone_boundaries
+            .take()
+            .or(computed.power_zone_boundaries);
+        changed = true;
+    }
+    if target.pace_zones_sec.is_none() && computed.pace_zones_sec.is_some() {
+        target.pace_zones_sec = computed.pace_zones_sec;
+        target.pace_zone_boundaries = target
+            .pace_zone_boundaries
+            .take()
+            .or(computed.pace_zone_boundaries);
+        changed = true;
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds() -> ZoneBoundaries {
+        ZoneBoundaries {
+            hr: Some(vec![120, 140, 160]),
+            power: Some(vec![150, 250]),
+            pace: Some(vec![360, 300]),
+        }
+    }
+
+    #[test]
+    fn test_seconds_in_zones_ascending() {
+        let values = [100.0, 120.0, 139.0, 150.0, 200.0];
+        let zones = seconds_in_zones(&values, &[120, 140, 160], true).unwrap();
+        // 200 bpm is above the last boundary and counts toward the top zone
+        assert_eq!(zones, vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn test_seconds_in_zones_pace_is_descending() {
+        // 400 s/km is slower than the first zone limit, 240 faster than all
+        let zones = seconds_in_zones(&[400.0, 330.0, 240.0], &[300, 360], false).unwrap();
+        assert_eq!(zones, vec![1, 2]);
+        assert!(seconds_in_zones(&[1.0], &[], true).is_none());
+    }
+
+    #[test]
+    fn test_profile_zones() {
+        let profile = AthleteProfile {
+            ftp_watts: Some(200),
+            threshold_hr_bpm: Some(170),
+            max_hr_bpm: None,
+            threshold_pace_sec_per_km: Some(300),
+            weight_kg: None,
+        };
+        let zones = ZoneBoundaries::from_profile(&profile);
+        assert_eq!(zones.power, Some(vec![110, 150, 180, 210, 240, 300]));
+        assert_eq!(zones.hr.unwrap()[3], 168);
+        assert_eq!(zones.pace.unwrap()[0], 387);
+    }
+
+    fn workout(yaml: &str) -> Workout {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    const WORKOUT: &str = r#"
+date: "2025-01-15"
+exercises:
+  - id: run
+    name: Run
+    sets:
+      - telemetry:
+          time_series:
+            timestamps: ["2025-01-15T10:00:00Z", "2025-01-15T10:00:01Z", "2025-01-15T10:00:02Z", "2025-01-15T10:05:00Z"]
+            heart_rate: [110, 130, 0, 150]
+            speed_mps: [3.0, 3.0, 0.0, 4.0]
+  - id: ride
+    name: Ride
+    sets:
+      - telemetry:
+          time_series:
+            timestamps: ["2025-01-15T11:00:00Z", "2025-01-15T11:00:02Z"]
+            power: [100, 300]
+sport_segments:
+  - segment_id: s1
+    sport: running
+    segment_index: 0
+    exercise_ids: [run]
+  - segment_id: s2
+    sport: cycling
+    segment_index: 1
+    started_at: "2025-01-15T11:00:00Z"
+    duration_sec: 600
+"#;
+
+    #[test]
+    fn test_workout_time_in_zones_handles_gaps_and_dropouts() {
+        let zones = workout_time_in_zones(&workout(WORKOUT), &bounds());
+        // 110, 130 and 150 bpm; the 0 is a dropout and the 5 min gap is a pause
+        assert_eq!(zones.hr_zones_sec, Some(vec![1, 1, 1]));
+        // Power is held for the 2 s between readings
+        assert_eq!(zones.power_zones_sec, Some(vec![2, 1]));
+        // 3 m/s is 333 s/km (zone 2), 4 m/s is 250 s/km (zone 2)
+        assert_eq!(zones.pace_zones_sec, Some(vec![0, 3]));
+        assert_eq!(zones.power_zone_boundaries, Some(vec![150, 250]));
+    }
+
+    #[test]
+    fn test_segment_time_in_zones_by_id_and_time() {
+        let workout = workout(WORKOUT);
+        let segments = workout.sport_segments.as_ref().unwrap();
+
+        let run = segment_time_in_zones(&workout, &segments[0], &bounds());
+        assert!(run.hr_zones_sec.is_some());
+        assert!(run.power_zones_sec.is_none());
+
+        let ride = segment_time_in_zones(&workout, &segments[1], &bounds());
+        assert_eq!(ride.power_zones_sec, Some(vec![2, 1]));
+        assert!(ride.hr_zones_sec.is_none());
+    }
+
+    #[test]
+    fn test_fill_time_in_zones_prefers_recorded_boundaries() {
+        let mut workout = workout(&format!(
+            "{}telemetry:\n  time_in_zones:\n    power_zone_boundaries: [400]\n    hr_zones_sec: [9, 9, 9]\n",
+            WORKOUT
+        ));
+        assert!(fill_time_in_zones(&mut workout, &bounds()));
+
+        let zones = workout
+            .telemetry
+            .as_ref()
+            .unwrap()
+            .time_in_zones
+            .as_ref()
+            .unwrap();
+        assert_eq!(zones.power_zones_sec, Some(vec![3]));
+        assert_eq!(zones.hr_zones_sec, Some(vec![9, 9, 9]));
+        assert_eq!(zones.pace_zones_sec, Some(vec![0, 3]));
+
+        let set = workout.exercises[1].sets[0].telemetry.as_ref().unwrap();
+        assert_eq!(
+            set.time_in_zones.as_ref().unwrap().power_zones_sec,
+            Some(vec![3])
+        );
+
+        let segment = &workout.sport_segments.as_ref().unwrap()[1];
+        let segment_zones = segment.telemetry.as_ref().unwrap().time_in_zones.as_ref();
+        assert_eq!(segment_zones.unwrap().power_zones_sec, Some(vec![3]));
+
+        assert!(!fill_time_in_zones(&mut workout, &bounds()));
+    }
+
+    #[test]
+    fn test_fill_without_boundaries_does_nothing() {
+        let mut workout = workout(WORKOUT);
+        assert!(!fill_time_in_zones(
+            &mut workout,
+            &ZoneBoundaries::default()
+        ));
+        assert!(workout.telemetry.is_none());
+    }
+}
@@ -0,0 +1,650 @@
+//! Plan adherence: how closely a history followed a plan
+//!
+//! The plan is expanded into its sessions (see [`crate::plan::expand_plan`])
+//! and each session is paired with a logged workout:
+//!
+//! 1. Workouts whose `plan_day_id` names the session's day, taken in date
+//!    order, so the second workout for a day pairs with its second week.
+//! 2. Otherwise a workout without a `plan_day_id` on the session's
+//!    scheduled date.
+//!
+//! Workouts linked to another plan through `plan_id` are ignored. Linked
+//! workouts that pair with no session are reported as unplanned.
+//!
+//! Within a session, exercises are matched by id, then by name. Planned
+//! exercises left over are paired in order with extra logged exercises as
+//! substitutions. Each target (sets, reps, load, duration, distance) scores
+//! `actual / planned`, capped at 100%; an exercise scores the mean of its
+//! targets and a session the mean of its planned exercises.
+
+use crate::analytics::workout_date;
+use crate::history::{CompletedExercise, CompletedSet, SetType, Workout, WpsHistory};
+use crate::plan::{expand_plan, PlannedExercise, PlannedSession, WpsPlan};
+use crate::LB_TO_KG;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Options for [`plan_compliance`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComplianceOptions {
+    /// Sessions scheduled after this date are upcoming rather than missed.
+    /// Defaults to the date of the latest workout considered.
+    pub as_of: Option<NaiveDate>,
+}
+
+/// Outcome of one planned session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// Logged and every target met
+    Completed,
+    /// Logged but short of some targets
+    Partial,
+    Missed,
+    /// Not yet due
+    Upcoming,
+}
+
+/// How a planned exercise was carried out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExerciseStatus {
+    Done,
+    /// A different exercise was logged in its place
+    Substituted,
+    Missed,
+    /// Logged but not in the plan
+    Extra,
+}
+
+/// A prescribed value against what was logged
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TargetComparison {
+    pub planned: f64,
+    pub actual: f64,
+    /// `actual / planned`, capped at 100
+    pub percent: f64,
+}
+
+impl TargetComparison {
+    fn new(planned: f64, actual: f64) -> Option<Self> {
+        (planned > 0.0).then(|| Self {
+            planned,
+            actual,
+            percent: (actual / planned * 100.0).min(100.0),
+        })
+    }
+}
+
+/// One exercise of a session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExerciseCompliance {
+    /// Planned exercise name (`None` for extras)
+    pub planned: Option<String>,
+    /// Logged exercise name (`None` when missed)
+    pub actual: Option<String>,
+    pub status: ExerciseStatus,
+    /// Working sets (warm-ups excluded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sets: Option<TargetComparison>,
+    /// Mean reps per set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reps: Option<TargetComparison>,
+    /// Mean weight per set in kg, for absolute `target_load`s
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_kg: Option<TargetComparison>,
+    /// Mean duration per set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_sec: Option<TargetComparison>,
+    /// Mean distance per set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance_meters: Option<TargetComparison>,
+    /// `None` for extras, which do not count toward the session
+    pub completion_percent: Option<f64>,
+}
+
+/// One planned session and the workout paired with it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionCompliance {
+    pub week: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_id: Option<String>,
+    pub day_index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_date: Option<NaiveDate>,
+    pub status: SessionStatus,
+    /// Index of the paired workout in the history
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workout_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workout_date: Option<String>,
+    /// `None` for upcoming sessions
+    pub completion_percent: Option<f64>,
+    pub exercises: Vec<ExerciseCompliance>,
+}
+
+impl SessionCompliance {
+    /// Planned exercises logged as something else
+    pub fn substitutions(&self) -> impl Iterator<Item = &ExerciseCompliance> {
+        self.exercises
+            .iter()
+            .filter(|e| e.status == ExerciseStatus::Substituted)
+    }
+}
+
+/// Totals for one plan week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeekCompliance {
+    pub week: u32,
+    /// Sessions due so far (upcoming ones excluded)
+    pub planned: u32,
+    pub completed: u32,
+    pub partial: u32,
+    pub missed: u32,
+    pub upcoming: u32,
+    /// Mean completion of the due sessions; `None` when none are due
+    pub completion_percent: Option<f64>,
+}
+
+/// Adherence of a history to a plan
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComplianceReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_id: Option<String>,
+    pub as_of: Option<NaiveDate>,
+    pub sessions: Vec<SessionCompliance>,
+    pub weeks: Vec<WeekCompliance>,
+    /// Indexes of workouts linked to the plan that match no session
+    pub unplanned_workouts: Vec<usize>,
+    /// Mean completion of every due session
+    pub completion_percent: Option<f64>,
+}
+
+impl ComplianceReport {
+    pub fn missed(&self) -> impl Iterator<Item = &SessionCompliance> {
+        self.sessions
+            .iter()
+            .filter(|s| s.status == SessionStatus::Missed)
+    }
+}
+
+/// Compare a history against the sessions a plan prescribes
+pub fn plan_compliance(
+    plan: &WpsPlan,
+    history: &WpsHistory,
+    options: &ComplianceOptions,
+) -> ComplianceReport {
+    let plan_id = plan.meta.as_ref().and_then(|m| m.id.clone());
+    let planned = expand_plan(plan);
+
+    // Workouts that may belong to this plan, in date order
+    let mut candidates: Vec<usize> = (0..history.workouts.len())
+        .filter(|&i| {
+            let linked = history.workouts[i].plan_id.as_deref();
+            linked.is_none() || linked == plan_id.as_deref()
+        })
+        .collect();
+    candidates.sort_by_key(|&i| workout_date(&history.workouts[i]));
+
+    let day_ids: Vec<&str> = plan
+        .cycle
+        .days
+        .iter()
+        .filter_map(|d| d.id.as_deref())
+        .collect();
+    let mut used = vec![false; history.workouts.len()];
+    let mut pairs: Vec<Option<usize>> = vec![None; planned.len()];
+
+    for (slot, session) in pairs.iter_mut().zip(&planned) {
+        let Some(day_id) = session.day_id.as_deref() else {
+            continue;
+        };
+        *slot = candidates
+            .iter()
+            .copied()
+            .find(|&i| !used[i] && history.workouts[i].plan_day_id.as_deref() == Some(day_id));
+        if let Some(i) = *slot {
+            used[i] = true;
+        }
+    }
+    for (slot, session) in pairs.iter_mut().zip(&planned) {
+        if slot.is_some() {
+            continue;
+        }
+        let Some(date) = session.scheduled_date else {
+            continue;
+        };
+        *slot = candidates.iter().copied().find(|&i| {
+            let workout = &history.workouts[i];
+            let unlinked = workout
+                .plan_day_id
+                .as_deref()
+                .is_none_or(|id| !day_ids.contains(&id));
+            !used[i] && unlinked && workout_date(workout) == Some(date)
+        });
+        if let Some(i) = *slot {
+            used[i] = true;
+        }
+    }
+
+    let as_of = options.as_of.or_else(|| {
+        candidates
+            .iter()
+            .filter_map(|&i| workout_date(&history.workouts[i]))
+            .max()
+    });
+    let last_paired = pairs.iter().rposition(Option::is_some);
+
+    let sessions: Vec<SessionCompliance> = planned
+        .iter()
+        .zip(&pairs)
+        .enumerate()
+        .map(|(index, (session, pair))| {
+            let due = match session.scheduled_date {
+                Some(date) => as_of.is_some_and(|as_of| date <= as_of),
+                None => last_paired.is_some_and(|last| index < last),
+            };
+            match pair {
+                Some(i) => paired_session(session, *i, &history.workouts[*i]),
+                None => unpaired_session(session, due),
+            }
+        })
+        .collect();
+
+    let unplanned_workouts = candidates
+        .iter()
+        .copied()
+        .filter(|&i| !used[i] && plan_id.is_some() && history.workouts[i].plan_id == plan_id)
+        .collect();
+
+    ComplianceReport {
+        plan_id,
+        as_of,
+        weeks: weeks(&sessions),
+        completion_percent: mean(sessions.iter().filter_map(|s| s.completion_percent)),
+        sessions,
+        unplanned_workouts,
+    }
+}
+
+fn session_header(session: &PlannedSession, status: SessionStatus) -> SessionCompliance {
+    SessionCompliance {
+        week: session.week,
+        day_id: session.day_id.clone(),
+        day_index: session.day_index,
+        focus: session.focus.clone(),
+        scheduled_date: session.scheduled_date,
+        status,
+        workout_index: None,
+        workout_date: None,
+        completion_percent: None,
+        exercises: Vec::new(),
+    }
+}
+
+fn unpaired_session(session: &PlannedSession, due: bool) -> SessionCompliance {
+    if !due {
+        return session_header(session, SessionStatus::Upcoming);
+    }
+    let mut out = session_header(session, SessionStatus::Missed);
+    out.completion_percent = Some(0.0);
+    out.exercises = session.exercises.iter().map(missed_exercise).collect();
+    out
+}
+
+fn paired_session(session: &PlannedSession, index: usize, workout: &Workout) -> SessionCompliance {
+    let exercises = match_exercises(&session.exercises, &workout.exercises);
+    let completion = if session.exercises.is_empty() {
+        100.0
+    } else {
+        mean(exercises.iter().filter_map(|e| e.completion_percent)).unwrap_or(0.0)
+    };
+    let status = if completion >= 100.0 - 1e-9 {
+        SessionStatus::Completed
+    } else {
+        SessionStatus::Partial
+    };
+    let mut out = session_header(session, status);
+    out.workout_index = Some(index);
+    out.workout_date = Some(workout.date.clone());
+    out.completion_percent = Some(completion);
+    out.exercises = exercises;
+    out
+}
+
+fn match_exercises(
+    planned: &[PlannedExercise],
+    logged: &[CompletedExercise],
+) -> Vec<ExerciseCompliance> {
+    let mut taken = vec![false; logged.len()];
+    let mut matched: Vec<Option<usize>> = planned
+        .iter()
+        .map(|p| {
+            let index = logged
+                .iter()
+                .enumerate()
+                .position(|(i, e)| !taken[i] && p.id.is_some() && e.id.is_some() && p.id == e.id);
+            if let Some(i) = index {
+                taken[i] = true;
+            }
+            index
+        })
+        .collect();
+    for (slot, p) in matched.iter_mut().zip(planned) {
+        if slot.is_none() {
+            *slot = logged
+                .iter()
+                .enumerate()
+                .position(|(i, e)| !taken[i] && same_name(&p.name, &e.name));
+            if let Some(i) = *slot {
+                taken[i] = true;
+            }
+        }
+    }
+
+    // Leftover logged exercises stand in for skipped ones, in order
+    let mut extras = (0..logged.len())
+        .filter(|&i| !taken[i])
+        .collect::<Vec<_>>()
+        .into_iter();
+    let mut out = Vec::new();
+    for (p, slot) in planned.iter().zip(matched) {
+        match slot {
+            Some(i) => out.push(compare(p, &logged[i], ExerciseStatus::Done)),
+            None => match extras.next() {
+                Some(i) => out.push(compare(p, &logged[i], ExerciseStatus::Substituted)),
+                None => out.push(missed_exercise(p)),
+            },
+        }
+    }
+    for i in extras {
+        out.push(ExerciseCompliance {
+            planned: None,
+            actual: Some(logged[i].name.clone()),
+            status: ExerciseStatus::Extra,
+            sets: None,
+            reps: None,
+            load_kg: None,
+            duration_sec: None,
+            distance_meters: None,
+            completion_percent: None,
+        });
+    }
+    out
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+fn missed_exercise(planned: &PlannedExercise) -> ExerciseCompliance {
+    ExerciseCompliance {
+        planned: Some(planned.name.clone()),
+        actual: None,
+        status: ExerciseStatus::Missed,
+        sets: None,
+        reps: None,
+        load_kg: None,
+        duration_sec: None,
+        distance_meters: None,
+        completion_percent: Some(0.0),
+    }
+}
+
+fn compare(
+    planned: &PlannedExercise,
+    logged: &CompletedExercise,
+    status: ExerciseStatus,
+) -> ExerciseCompliance {
+    let sets: Vec<&CompletedSet> = logged
+        .sets
+        .iter()
+        .filter(|s| s.set_type != Some(SetType::Warmup))
+        .collect();
+    let per_set = |value: fn(&CompletedSet) -> Option<f64>| {
+        mean(sets.iter().filter_map(|s| value(s))).unwrap_or(0.0)
+    };
+    let target =
+        |planned: Option<f64>, actual: f64| planned.and_then(|p| TargetComparison::new(p, actual));
+
+    let comparison = ExerciseCompliance {
+        planned: Some(planned.name.clone()),
+        actual: Some(logged.name.clone()),
+        status,
+        sets: target(planned.target_sets.map(f64::from), sets.len() as f64),
+        reps: target(
+            planned.target_reps.map(f64::from),
+            per_set(|s| s.reps.map(f64::from)),
+        ),
+        load_kg: target(
+            planned.target_load_kg(),
+            per_set(|s| s.weight_kg.or_else(|| s.weight_lb.map(|lb| lb * LB_TO_KG))),
+        ),
+        duration_sec: target(
+            planned.target_duration_sec.map(f64::from),
+            per_set(|s| s.duration_sec.map(f64::from)),
+        ),
+        distance_meters: target(
+            planned.target_distance_meters,
+            per_set(|s| s.distance_meters),
+        ),
+        completion_percent: None,
+    };
+    let percents = [
+        &comparison.sets,
+        &comparison.reps,
+        &comparison.load_kg,
+        &comparison.duration_sec,
+        &comparison.distance_meters,
+    ];
+    let completion = mean(percents.iter().filter_map(|t| t.map(|t| t.percent)));
+    ExerciseCompliance {
+        // An exercise without numeric targets is complete once logged
+        completion_percent: Some(completion.unwrap_or(if sets.is_empty() { 0.0 } else { 100.0 })),
+        ..comparison
+    }
+}
+
+fn weeks(sessions: &[SessionCompliance]) -> Vec<WeekCompliance> {
+    let mut weeks: Vec<WeekCompliance> = Vec::new();
+    for session in sessions {
+        let index = match weeks.iter().position(|w| w.week == session.week) {
+            Some(index) => index,
+            None => {
+                weeks.push(WeekCompliance {
+                    week: session.week,
+                    planned: 0,
+                    completed: 0,
+                    partial: 0,
+                    missed: 0,
+                    upcoming: 0,
+                    completion_percent: None,
+                });
+                weeks.len() - 1
+            }
+        };
+        let week = &mut weeks[index];
+        match session.status {
+            SessionStatus::Completed => week.completed += 1,
+            SessionStatus::Partial => week.partial += 1,
+            SessionStatus::Missed => week.missed += 1,
+            SessionStatus::Upcoming => week.upcoming += 1,
+        }
+        if session.status != SessionStatus::Upcoming {
+            week.planned += 1;
+        }
+    }
+    for week in &mut weeks {
+        week.completion_percent = mean(
+            sessions
+                .iter()
+                .filter(|s| s.week == week.week)
+                .filter_map(|s| s.completion_percent),
+        );
+    }
+    weeks
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = r#"
+plan_version: 2
+meta:
+  id: strength-block
+  title: Strength Block
+cycle:
+  weeks:
+    - week_number: 1
+    - week_number: 2
+      overrides:
+        - exercise_id: squat
+          target_load: "110kg"
+  days:
+    - id: lower
+      exercises:
+        - id: squat
+          name: Squat
+          modality: strength
+          target_sets: 3
+          target_reps: 5
+          target_load: "100kg"
+        - name: Leg Curl
+          modality: strength
+          target_sets: 2
+          target_reps: 10
+    - id: cardio
+      scheduled_date: "2025-01-08"
+      exercises:
+        - name: Run
+          modality: countdown
+          target_duration_sec: 1800
+"#;
+
+    const HISTORY: &str = r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-06"
+    plan_id: strength-block
+    plan_day_id: lower
+    exercises:
+      - id: squat
+        name: Back Squat
+        sets:
+          - { reps: 5, weight_kg: 60, set_type: warmup }
+          - { reps: 5, weight_kg: 100 }
+          - { reps: 5, weight_kg: 100 }
+          - { reps: 5, weight_kg: 100 }
+      - name: leg curl
+        sets:
+          - { reps: 10, weight_kg: 40 }
+          - { reps: 10, weight_kg: 40 }
+  - date: "2025-01-08"
+    exercises:
+      - name: Run
+        sets:
+          - { duration_sec: 1500 }
+  - date: "2025-01-13"
+    plan_id: strength-block
+    plan_day_id: lower
+    exercises:
+      - id: squat
+        name: Squat
+        sets:
+          - { reps: 5, weight_kg: 110 }
+          - { reps: 5, weight_kg: 110 }
+      - name: Nordic Curl
+        sets:
+          - { reps: 6 }
+          - { reps: 6 }
+  - date: "2025-01-14"
+    plan_id: strength-block
+    exercises:
+      - name: Mobility
+        sets: [{ duration_sec: 600 }]
+  - date: "2025-01-15"
+    plan_id: other-plan
+    plan_day_id: cardio
+    exercises: []
+"#;
+
+    fn report(options: &ComplianceOptions) -> ComplianceReport {
+        let plan = crate::plan::parse(PLAN).unwrap();
+        let history = crate::history::parse(HISTORY).unwrap();
+        plan_compliance(&plan, &history, options)
+    }
+
+    #[test]
+    fn test_pairs_sessions_by_day_id_and_date() {
+        let as_of = NaiveDate::from_ymd_opt(2025, 1, 20);
+        let report = report(&ComplianceOptions { as_of });
+        assert_eq!(report.sessions.len(), 4);
+
+        let week1_lower = &report.sessions[0];
+        assert_eq!(week1_lower.workout_index, Some(0));
+        assert_eq!(week1_lower.status, SessionStatus::Completed);
+        assert_eq!(week1_lower.exercises[0].status, ExerciseStatus::Done);
+        // Warm-ups do not count as working sets
+        assert_eq!(week1_lower.exercises[0].sets.unwrap().actual, 3.0);
+
+        let week1_cardio = &report.sessions[1];
+        assert_eq!(week1_cardio.workout_index, Some(1));
+        let run = week1_cardio.exercises[0].duration_sec.unwrap();
+        assert!((run.percent - 1500.0 / 1800.0 * 100.0).abs() < 1e-9);
+        assert_eq!(week1_cardio.status, SessionStatus::Partial);
+
+        let week2_lower = &report.sessions[2];
+        assert_eq!(week2_lower.workout_index, Some(2));
+        assert_eq!(week2_lower.exercises[0].load_kg.unwrap().planned, 110.0);
+        assert!((week2_lower.exercises[0].sets.unwrap().percent - 200.0 / 3.0).abs() < 1e-9);
+        let substitution = week2_lower.substitutions().next().unwrap();
+        assert_eq!(substitution.planned.as_deref(), Some("Leg Curl"));
+        assert_eq!(substitution.actual.as_deref(), Some("Nordic Curl"));
+
+        // The cardio workout on the 15th belongs to another plan
+        assert_eq!(report.sessions[3].status, SessionStatus::Missed);
+        assert_eq!(report.missed().count(), 1);
+        assert_eq!(report.unplanned_workouts, vec![3]);
+    }
+
+    #[test]
+    fn test_weekly_totals_and_upcoming_sessions() {
+        // Defaults to the last workout of this plan, on the 14th
+        let report = report(&ComplianceOptions::default());
+        assert_eq!(report.as_of, NaiveDate::from_ymd_opt(2025, 1, 14));
+        assert_eq!(report.sessions[3].status, SessionStatus::Upcoming);
+        assert!(report.sessions[3].completion_percent.is_none());
+
+        assert_eq!(report.weeks.len(), 2);
+        let week1 = &report.weeks[0];
+        assert_eq!((week1.planned, week1.completed, week1.partial), (2, 1, 1));
+        let week2 = &report.weeks[1];
+        assert_eq!((week2.planned, week2.upcoming, week2.missed), (1, 1, 0));
+        assert!(week2.completion_percent.unwrap() < 100.0);
+        assert!(report.completion_percent.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_empty_history_misses_nothing_undated() {
+        let plan = crate::plan::parse(PLAN).unwrap();
+        let history = crate::history::parse(
+            "history_version: 2\nexported_at: \"2025-01-20T00:00:00Z\"\nworkouts: []\n",
+        )
+        .unwrap();
+        let report = plan_compliance(&plan, &history, &ComplianceOptions::default());
+        assert!(report
+            .sessions
+            .iter()
+            .all(|s| s.status == SessionStatus::Upcoming));
+        assert!(report.completion_percent.is_none());
+    }
+}
@@ -8,6 +8,9 @@ pub enum ParseError {
     #[error("YAML syntax error: {0}")]
     YamlSyntax(#[from] serde_yaml::Error),
 
+    #[error("JSON syntax error: {0}")]
+    JsonSyntax(#[from] serde_json::Error),
+
     #[error("Missing required field: {field}")]
     MissingField { field: String },
 
@@ -79,6 +82,16 @@ impl ValidationIssue {
     }
 }
 
+/// Errors that can occur while migrating a document between versions
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+
+    #[error("Cannot migrate from version {from} to version {to}")]
+    UnsupportedVersion { from: u32, to: u32 },
+}
+
 /// Combined error type for PWF operations
 #[derive(Debug, Error)]
 pub enum WpsError {
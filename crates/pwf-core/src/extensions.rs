@@ -0,0 +1,198 @@
+//! Vendor extension fields
+//!
+//! Third-party apps may attach their own data to PWF documents using keys
+//! prefixed with `x-` (e.g. `x-ourapp-session-id`). These keys are captured
+//! into an [`Extensions`] map on the major plan and history types so they
+//! survive a parse → serialize round trip unchanged.
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_yaml::Value;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Prefix reserved for vendor extension keys
+pub const EXTENSION_PREFIX: &str = "x-";
+
+/// Returns true if `key` is a vendor extension key
+pub fn is_extension_key(key: &str) -> bool {
+    key.starts_with(EXTENSION_PREFIX) && key.len() > EXTENSION_PREFIX.len()
+}
+
+/// Ordered map of `x-` prefixed keys to arbitrary values
+///
+/// Used with `#[serde(flatten)]`, so extension keys sit alongside the
+/// regular fields of the containing object. Keys without the `x-` prefix
+/// are never captured.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Extensions(BTreeMap<String, Value>);
+
+impl Extensions {
+    /// Create an empty extension map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if no extension keys are present
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Number of extension keys
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Look up an extension value by its full key (including `x-`)
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    /// Insert an extension value, returning the previous value for the key
+    ///
+    /// Keys without the `x-` prefix are rejected and returned as `Err`.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: Value,
+    ) -> Result<Option<Value>, String> {
+        let key = key.into();
+        if !is_extension_key(&key) {
+            return Err(key);
+        }
+        Ok(self.0.insert(key, value))
+    }
+
+    /// Remove an extension value by key
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.0.remove(key)
+    }
+
+    /// Iterate over extension keys and values in key order
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Extensions {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = std::collections::btree_map::Iter<'a, String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl Serialize for Extensions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Extensions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ExtensionsVisitor;
+
+        impl<'de> Visitor<'de> for ExtensionsVisitor {
+            type Value = Extensions;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of x- prefixed extension fields")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Extensions, A::Error> {
+                let mut map = BTreeMap::new();
+                while let Some(key) = access.next_key::<String>()? {
+                    if is_extension_key(&key) {
+                        map.insert(key, access.next_value::<Value>()?);
+                    } else {
+                        access.next_value::<IgnoredAny>()?;
+                    }
+                }
+                Ok(Extensions(map))
+            }
+        }
+
+        deserializer.deserialize_map(ExtensionsVisitor)
+    }
+}
+
+impl JsonSchema for Extensions {
+    fn schema_name() -> Cow<'static, str> {
+        "Extensions".into()
+    }
+
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "patternProperties": { "^x-": {} }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Host {
+        name: String,
+        #[serde(flatten)]
+        extensions: Extensions,
+    }
+
+    #[test]
+    fn test_is_extension_key() {
+        assert!(is_extension_key("x-app-id"));
+        assert!(!is_extension_key("x-"));
+        assert!(!is_extension_key("name"));
+        assert!(!is_extension_key("X-app-id"));
+    }
+
+    #[test]
+    fn test_flatten_captures_only_prefixed_keys() {
+        let host: Host =
+            serde_yaml::from_str("name: a\nx-app-id: 42\nunknown: 1\nx-tags: [a, b]\n").unwrap();
+        assert_eq!(host.extensions.len(), 2);
+        assert_eq!(host.extensions.get("x-app-id"), Some(&Value::from(42)));
+        assert!(host.extensions.get("unknown").is_none());
+    }
+
+    #[test]
+    fn test_round_trip_preserves_nested_values() {
+        let yaml = "name: a\nx-app:\n  session: abc\n  ids:\n  - 1\n  - 2\n";
+        let host: Host = serde_yaml::from_str(yaml).unwrap();
+        let out = serde_yaml::to_string(&host).unwrap();
+        let again: Host = serde_yaml::from_str(&out).unwrap();
+        assert_eq!(host.extensions, again.extensions);
+        assert!(out.contains("x-app:"));
+    }
+
+    #[test]
+    fn test_empty_extensions_serialize_nothing() {
+        let host = Host {
+            name: "a".into(),
+            extensions: Extensions::new(),
+        };
+        assert_eq!(serde_yaml::to_string(&host).unwrap(), "name: a\n");
+    }
+
+    #[test]
+    fn test_insert_rejects_unprefixed_keys() {
+        let mut ext = Extensions::new();
+        assert_eq!(ext.insert("x-a", Value::from(1)), Ok(None));
+        assert_eq!(ext.insert("a", Value::from(1)), Err("a".to_string()));
+        assert_eq!(ext.len(), 1);
+    }
+}
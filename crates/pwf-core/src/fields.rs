@@ -0,0 +1,985 @@
+//! Known-field tables and unknown key detection
+//!
+//! All PWF types deserialize with `#[serde(default)]` and without
+//! `deny_unknown_fields`, so a misspelled key is silently dropped by serde.
+//! The tables below mirror the serde field names of every plan and history
+//! type and are walked against the raw YAML to report keys serde ignored.
+
+use crate::extensions::is_extension_key;
+use serde_yaml::Value;
+
+/// A key that does not correspond to any field of the enclosing type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnknownField {
+    /// Path to the unknown key, e.g. `cycle.days[0].exercises[1].target_rep`
+    pub path: String,
+    /// The unknown key itself
+    pub key: String,
+    /// Closest known key at the same level, if any is close enough
+    pub suggestion: Option<&'static str>,
+}
+
+impl UnknownField {
+    /// Human-readable message used for validation issues
+    pub fn message(&self) -> String {
+        match self.suggestion {
+            Some(suggestion) => format!(
+                "Unknown field '{}' (did you mean '{}'?)",
+                self.key, suggestion
+            ),
+            None => format!("Unknown field '{}'", self.key),
+        }
+    }
+}
+
+/// The set of keys accepted by one PWF type
+pub(crate) struct Shape {
+    fields: &'static [(&'static str, Field)],
+}
+
+/// How to descend into the value of a known key
+#[derive(Clone, Copy)]
+enum Field {
+    /// Scalar, enum, or list of scalars - nothing to check below
+    Scalar,
+    /// Nested object
+    Object(&'static Shape),
+    /// List of objects
+    List(&'static Shape),
+    /// Mapping with user-defined keys (e.g. `glossary`)
+    FreeMap,
+}
+
+use Field::{FreeMap, List, Object, Scalar};
+
+/// Find keys in a parsed plan document that `WpsPlan` does not know about
+pub(crate) fn unknown_plan_fields(value: &Value) -> Vec<UnknownField> {
+    let mut found = Vec::new();
+    walk(value, &WPS_PLAN, "", &mut found);
+    found
+}
+
+/// Find keys in a parsed history document that `WpsHistory` does not know about
+pub(crate) fn unknown_history_fields(value: &Value) -> Vec<UnknownField> {
+    let mut found = Vec::new();
+    walk(value, &WPS_HISTORY, "", &mut found);
+    found
+}
+
+fn walk(value: &Value, shape: &Shape, path: &str, found: &mut Vec<UnknownField>) {
+    let Value::Mapping(map) = value else {
+        return;
+    };
+
+    for (key, child) in map {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        // Vendor extensions are never misspellings of known fields
+        if is_extension_key(key) {
+            continue;
+        }
+        let child_path = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        };
+
+        match shape.fields.iter().find(|(name, _)| *name == key) {
+            Some((_, Object(inner))) => walk(child, inner, &child_path, found),
+            Some((_, List(inner))) => {
+                if let Value::Sequence(items) = child {
+                    for (idx, item) in items.iter().enumerate() {
+                        walk(item, inner, &format!("{}[{}]", child_path, idx), found);
+                    }
+                }
+            }
+            Some((_, Scalar | FreeMap)) => {}
+            None => found.push(UnknownField {
+                path: child_path,
+                key: key.to_string(),
+                suggestion: suggest(key, shape),
+            }),
+        }
+    }
+}
+
+/// Pick the known key closest to `key` by edit distance, if it is close enough
+/// to plausibly be a typo
+fn suggest(key: &str, shape: &Shape) -> Option<&'static str> {
+    let key_lower = key.to_lowercase();
+    let max_distance = std::cmp::max(2, key.chars().count() / 4);
+
+    shape
+        .fields
+        .iter()
+        .map(|(name, _)| (*name, edit_distance(&key_lower, &name.to_lowercase())))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(name, _)| name)
+}
+
+/// Levenshtein distance between two strings
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b_chars.len()).collect();
+    let mut curr = vec![0; b_chars.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b_chars.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b_chars.len()]
+}
+
+// ============================================================================
+// Plan shapes
+// ============================================================================
+
+static WPS_PLAN: Shape = Shape {
+    fields: &[
+        ("plan_version", Scalar),
+        ("meta", Object(&PLAN_META)),
+        ("glossary", FreeMap),
+        ("exercise_library", List(&LIBRARY_EXERCISE)),
+        ("workout_templates", List(&WORKOUT_TEMPLATE)),
+        ("cycle", Object(&PLAN_CYCLE)),
+    ],
+};
+
+static PLAN_META: Shape = Shape {
+    fields: &[
+        ("id", Scalar),
+        ("title", Scalar),
+        ("description", Scalar),
+        ("author", Scalar),
+        ("status", Scalar),
+        ("activated_at", Scalar),
+        ("completed_at", Scalar),
+        ("equipment", Scalar),
+        ("daysPerWeek", Scalar),
+        ("recommendedFirst", Scalar),
+        ("tags", Scalar),
+        ("athlete_profile", Object(&ATHLETE_PROFILE)),
+    ],
+};
+
+static ATHLETE_PROFILE: Shape = Shape {
+    fields: &[
+        ("ftp_watts", Scalar),
+        ("threshold_hr_bpm", Scalar),
+        ("max_hr_bpm", Scalar),
+        ("threshold_pace_sec_per_km", Scalar),
+        ("weight_kg", Scalar),
+    ],
+};
+
+static PLAN_CYCLE: Shape = Shape {
+    fields: &[
+        ("start_date", Scalar),
+        ("notes", Scalar),
+        ("weeks", List(&PLAN_WEEK)),
+        ("days", List(&PLAN_DAY)),
+    ],
+};
+
+static PLAN_WEEK: Shape = Shape {
+    fields: &[
+        ("week_number", Scalar),
+        ("name", Scalar),
+        ("notes", Scalar),
+        ("overrides", List(&EXERCISE_OVERRIDE)),
+    ],
+};
+
+static EXERCISE_OVERRIDE: Shape = Shape {
+    fields: &[
+        ("exercise_id", Scalar),
+        ("exercise_name", Scalar),
+        ("day_id", Scalar),
+        ("day_order", Scalar),
+        ("target_sets", Scalar),
+        ("target_reps", Scalar),
+        ("target_duration_sec", Scalar),
+        ("target_distance_meters", Scalar),
+        ("target_load", Scalar),
+        ("target_weight_percent", Scalar),
+        ("percent_of", Scalar),
+        ("target_notes", Scalar),
+        ("rest_between_sets_sec", Scalar),
+    ],
+};
+
+static PLAN_DAY: Shape = Shape {
+    fields: &[
+        ("id", Scalar),
+        ("order", Scalar),
+        ("focus", Scalar),
+        ("notes", Scalar),
+        ("scheduled_date", Scalar),
+        ("target_session_length_min", Scalar),
+        ("template_ref", Scalar),
+        ("exercises", List(&PLAN_EXERCISE)),
+    ],
+};
+
+static LIBRARY_EXERCISE: Shape = Shape {
+    fields: &[
+        ("id", Scalar),
+        ("name", Scalar),
+        ("description", Scalar),
+        ("equipment", Scalar),
+        ("muscle_groups", Scalar),
+        ("difficulty", Scalar),
+        ("modality", Scalar),
+        ("default_sets", Scalar),
+        ("default_reps", Scalar),
+        ("default_duration_sec", Scalar),
+        ("default_distance_meters", Scalar),
+        ("cues", Scalar),
+        ("link", Scalar),
+        ("image", Scalar),
+    ],
+};
+
+static WORKOUT_TEMPLATE: Shape = Shape {
+    fields: &[
+        ("id", Scalar),
+        ("name", Scalar),
+        ("description", Scalar),
+        ("focus", Scalar),
+        ("target_session_length_min", Scalar),
+        ("tags", Scalar),
+        ("exercises", List(&PLAN_EXERCISE)),
+    ],
+};
+
+static TRAINING_ZONE: Shape = Shape {
+    fields: &[
+        ("zone", Scalar),
+        ("duration_sec", Scalar),
+        ("target_power_watts", Scalar),
+        ("target_hr_bpm", Scalar),
+        ("target_pace_sec_per_km", Scalar),
+    ],
+};
+
+static RAMP_CONFIG: Shape = Shape {
+    fields: &[
+        ("start_power_watts", Scalar),
+        ("end_power_watts", Scalar),
+        ("duration_sec", Scalar),
+        ("step_duration_sec", Scalar),
+    ],
+};
+
+static INTERVAL_PHASE: Shape = Shape {
+    fields: &[
+        ("name", Scalar),
+        ("duration_sec", Scalar),
+        ("target_power_watts", Scalar),
+        ("target_hr_bpm", Scalar),
+        ("target_pace_sec_per_km", Scalar),
+        ("cadence_rpm", Scalar),
+    ],
+};
+
+static PROGRESSION_RULES: Shape = Shape {
+    fields: &[
+        ("type", Scalar),
+        ("success_condition", Scalar),
+        ("weight_increment_kg", Scalar),
+        ("weight_increment_lbs", Scalar),
+        ("reps_increment", Scalar),
+        ("reps_range_min", Scalar),
+        ("reps_range_max", Scalar),
+        ("deload_condition", Scalar),
+        ("deload_percent", Scalar),
+        ("deload_weeks", Scalar),
+        ("max_weight_kg", Scalar),
+        ("max_weight_lbs", Scalar),
+        ("notes", Scalar),
+    ],
+};
+
+static PLAN_EXERCISE: Shape = Shape {
+    fields: &[
+        ("id", Scalar),
+        ("name", Scalar),
+        ("exercise_ref", Scalar),
+        ("modality", Scalar),
+        ("equipment", Scalar),
+        ("target_sets", Scalar),
+        ("target_reps", Scalar),
+        ("target_duration_sec", Scalar),
+        ("target_distance_meters", Scalar),
+        ("target_load", Scalar),
+        ("target_weight_percent", Scalar),
+        ("percent_of", Scalar),
+        ("reference_exercise", Scalar),
+        ("cues", Scalar),
+        ("target_notes", Scalar),
+        ("link", Scalar),
+        ("image", Scalar),
+        ("group", Scalar),
+        ("group_type", Scalar),
+        ("rest_between_sets_sec", Scalar),
+        ("rest_after_sec", Scalar),
+        ("zones", List(&TRAINING_ZONE)),
+        ("ramp", Object(&RAMP_CONFIG)),
+        ("interval_phases", List(&INTERVAL_PHASE)),
+        ("progression_rules", Object(&PROGRESSION_RULES)),
+    ],
+};
+
+// ============================================================================
+// History shapes
+// ============================================================================
+
+static WPS_HISTORY: Shape = Shape {
+    fields: &[
+        ("history_version", Scalar),
+        ("exported_at", Scalar),
+        ("export_source", Object(&EXPORT_SOURCE)),
+        ("units", Object(&UNITS)),
+        ("workouts", List(&WORKOUT)),
+        ("personal_records", List(&PERSONAL_RECORD)),
+        ("body_measurements", List(&BODY_MEASUREMENT)),
+    ],
+};
+
+static EXPORT_SOURCE: Shape = Shape {
+    fields: &[
+        ("app_name", Scalar),
+        ("app_version", Scalar),
+        ("platform", Scalar),
+        ("preferred_units", Object(&UNITS)),
+    ],
+};
+
+static UNITS: Shape = Shape {
+    fields: &[("weight", Scalar), ("distance", Scalar)],
+};
+
+static WORKOUT: Shape = Shape {
+    fields: &[
+        ("id", Scalar),
+        ("date", Scalar),
+        ("started_at", Scalar),
+        ("ended_at", Scalar),
+        ("duration_sec", Scalar),
+        ("title", Scalar),
+        ("notes", Scalar),
+        ("plan_id", Scalar),
+        ("plan_day_id", Scalar),
+        ("exercises", List(&COMPLETED_EXERCISE)),
+        ("telemetry", Object(&WORKOUT_TELEMETRY)),
+        ("devices", List(&DEVICE_INFO)),
+        ("sport", Scalar),
+        ("sport_segments", List(&SPORT_SEGMENT)),
+    ],
+};
+
+static WORKOUT_TELEMETRY: Shape = Shape {
+    fields: &[
+        ("heart_rate_avg", Scalar),
+        ("heart_rate_max", Scalar),
+        ("heart_rate_min", Scalar),
+        ("power_avg", Scalar),
+        ("power_max", Scalar),
+        ("total_distance_m", Scalar),
+        ("total_distance_km", Scalar),
+        ("total_distance_mi", Scalar),
+        ("total_elevation_gain_m", Scalar),
+        ("total_elevation_gain_ft", Scalar),
+        ("total_elevation_loss_m", Scalar),
+        ("total_elevation_loss_ft", Scalar),
+        ("speed_avg_kph", Scalar),
+        ("speed_avg_mph", Scalar),
+        ("speed_max_kph", Scalar),
+        ("speed_max_mph", Scalar),
+        ("pace_avg_sec_per_km", Scalar),
+        ("pace_avg_sec_per_mi", Scalar),
+        ("cadence_avg", Scalar),
+        ("temperature_c", Scalar),
+        ("temperature_f", Scalar),
+        ("humidity_percent", Scalar),
+        ("total_calories", Scalar),
+        ("gps_route_id", Scalar),
+        ("gps_route", Object(&GPS_ROUTE)),
+        ("advanced_metrics", Object(&ADVANCED_METRICS)),
+        ("power_metrics", Object(&POWER_METRICS)),
+        ("time_in_zones", Object(&TIME_IN_ZONES)),
+    ],
+};
+
+static ADVANCED_METRICS: Shape = Shape {
+    fields: &[
+        ("training_effect", Scalar),
+        ("anaerobic_training_effect", Scalar),
+        ("recovery_time_hours", Scalar),
+        ("vo2_max_estimate", Scalar),
+        ("lactate_threshold", Object(&LACTATE_THRESHOLD)),
+        ("performance_condition", Scalar),
+        ("training_load", Scalar),
+        ("training_status", Scalar),
+    ],
+};
+
+static LACTATE_THRESHOLD: Shape = Shape {
+    fields: &[
+        ("heart_rate_bpm", Scalar),
+        ("speed_mps", Scalar),
+        ("power_watts", Scalar),
+        ("detected_at", Scalar),
+    ],
+};
+
+static POWER_METRICS: Shape = Shape {
+    fields: &[
+        ("normalized_power", Scalar),
+        ("training_stress_score", Scalar),
+        ("intensity_factor", Scalar),
+        ("variability_index", Scalar),
+        ("ftp_watts", Scalar),
+        ("total_work_kj", Scalar),
+        ("left_right_balance", Scalar),
+        ("left_pedal_smoothness", Scalar),
+        ("right_pedal_smoothness", Scalar),
+        ("left_torque_effectiveness", Scalar),
+        ("right_torque_effectiveness", Scalar),
+        ("critical_power_watts", Scalar),
+        ("w_prime_joules", Scalar),
+        ("min_w_prime_balance_joules", Scalar),
+        ("matches_burned", Scalar),
+    ],
+};
+
+static TIME_IN_ZONES: Shape = Shape {
+    fields: &[
+        ("hr_zones_sec", Scalar),
+        ("power_zones_sec", Scalar),
+        ("hr_zone_boundaries", Scalar),
+        ("power_zone_boundaries", Scalar),
+        ("pace_zones_sec", Scalar),
+        ("pace_zone_boundaries", Scalar),
+    ],
+};
+
+static SPORT_SEGMENT: Shape = Shape {
+    fields: &[
+        ("segment_id", Scalar),
+        ("sport", Scalar),
+        ("segment_index", Scalar),
+        ("started_at", Scalar),
+        ("duration_sec", Scalar),
+        ("distance_m", Scalar),
+        ("exercise_ids", Scalar),
+        ("telemetry", Object(&WORKOUT_TELEMETRY)),
+        ("transition", Object(&TRANSITION_DATA)),
+        ("notes", Scalar),
+    ],
+};
+
+static TRANSITION_DATA: Shape = Shape {
+    fields: &[
+        ("transition_id", Scalar),
+        ("from_sport", Scalar),
+        ("to_sport", Scalar),
+        ("duration_sec", Scalar),
+        ("started_at", Scalar),
+        ("heart_rate_avg", Scalar),
+        ("notes", Scalar),
+    ],
+};
+
+static GPS_POSITION: Shape = Shape {
+    fields: &[
+        ("latitude_deg", Scalar),
+        ("longitude_deg", Scalar),
+        ("timestamp", Scalar),
+        ("elevation_m", Scalar),
+        ("accuracy_m", Scalar),
+        ("speed_mps", Scalar),
+        ("heading_deg", Scalar),
+        ("heart_rate_bpm", Scalar),
+        ("power_watts", Scalar),
+        ("cadence", Scalar),
+        ("temperature_c", Scalar),
+    ],
+};
+
+static GPS_ROUTE: Shape = Shape {
+    fields: &[
+        ("route_id", Scalar),
+        ("name", Scalar),
+        ("positions", List(&GPS_POSITION)),
+        ("total_distance_m", Scalar),
+        ("total_ascent_m", Scalar),
+        ("total_descent_m", Scalar),
+        ("min_elevation_m", Scalar),
+        ("max_elevation_m", Scalar),
+        ("bbox_sw_lat", Scalar),
+        ("bbox_sw_lng", Scalar),
+        ("bbox_ne_lat", Scalar),
+        ("bbox_ne_lng", Scalar),
+        ("recording_mode", Scalar),
+        ("gps_fix", Scalar),
+    ],
+};
+
+static TIME_SERIES_DATA: Shape = Shape {
+    fields: &[
+        ("timestamps", Scalar),
+        ("elapsed_sec", Scalar),
+        ("heart_rate", Scalar),
+        ("power", Scalar),
+        ("cadence", Scalar),
+        ("speed_mps", Scalar),
+        ("distance_m", Scalar),
+        ("elevation_m", Scalar),
+        ("temperature_c", Scalar),
+        ("latitude", Scalar),
+        ("longitude", Scalar),
+        ("grade_percent", Scalar),
+        ("respiration_rate", Scalar),
+        ("core_temperature_c", Scalar),
+        ("muscle_oxygen_percent", Scalar),
+        ("power_balance", Scalar),
+        ("left_pedal_smoothness", Scalar),
+        ("right_pedal_smoothness", Scalar),
+        ("left_torque_effectiveness", Scalar),
+        ("right_torque_effectiveness", Scalar),
+        ("stride_length_m", Scalar),
+        ("vertical_oscillation_cm", Scalar),
+        ("ground_contact_time_ms", Scalar),
+        ("ground_contact_balance", Scalar),
+        ("stroke_rate", Scalar),
+        ("stroke_count", Scalar),
+        ("swolf", Scalar),
+        ("stroke_type", Scalar),
+    ],
+};
+
+static COMPLETED_EXERCISE: Shape = Shape {
+    fields: &[
+        ("id", Scalar),
+        ("name", Scalar),
+        ("modality", Scalar),
+        ("notes", Scalar),
+        ("sets", List(&COMPLETED_SET)),
+        ("pool_config", Object(&POOL_CONFIG)),
+        ("sport", Scalar),
+    ],
+};
+
+static COMPLETED_SET: Shape = Shape {
+    fields: &[
+        ("set_number", Scalar),
+        ("set_type", Scalar),
+        ("reps", Scalar),
+        ("weight_kg", Scalar),
+        ("weight_lb", Scalar),
+        ("duration_sec", Scalar),
+        ("distance_meters", Scalar),
+        ("rpe", Scalar),
+        ("rir", Scalar),
+        ("notes", Scalar),
+        ("is_pr", Scalar),
+        ("completed_at", Scalar),
+        ("telemetry", Object(&SET_TELEMETRY)),
+        ("swimming", Object(&SWIMMING_SET_DATA)),
+    ],
+};
+
+static SET_TELEMETRY: Shape = Shape {
+    fields: &[
+        ("heart_rate_avg", Scalar),
+        ("heart_rate_max", Scalar),
+        ("heart_rate_min", Scalar),
+        ("power_avg", Scalar),
+        ("power_max", Scalar),
+        ("power_min", Scalar),
+        ("elevation_gain_m", Scalar),
+        ("elevation_gain_ft", Scalar),
+        ("elevation_loss_m", Scalar),
+        ("elevation_loss_ft", Scalar),
+        ("speed_avg_mps", Scalar),
+        ("speed_avg_kph", Scalar),
+        ("speed_avg_mph", Scalar),
+        ("speed_max_mps", Scalar),
+        ("speed_max_kph", Scalar),
+        ("speed_max_mph", Scalar),
+        ("pace_avg_sec_per_km", Scalar),
+        ("pace_avg_sec_per_mi", Scalar),
+        ("cadence_avg", Scalar),
+        ("cadence_max", Scalar),
+        ("temperature_c", Scalar),
+        ("temperature_f", Scalar),
+        ("humidity_percent", Scalar),
+        ("calories", Scalar),
+        ("stroke_rate", Scalar),
+        ("gps_route_id", Scalar),
+        ("time_series", Object(&TIME_SERIES_DATA)),
+        ("time_in_zones", Object(&TIME_IN_ZONES)),
+    ],
+};
+
+static POOL_CONFIG: Shape = Shape {
+    fields: &[("pool_length", Scalar), ("pool_length_unit", Scalar)],
+};
+
+static SWIMMING_LENGTH: Shape = Shape {
+    fields: &[
+        ("length_number", Scalar),
+        ("stroke_type", Scalar),
+        ("duration_sec", Scalar),
+        ("stroke_count", Scalar),
+        ("swolf", Scalar),
+        ("started_at", Scalar),
+        ("active", Scalar),
+    ],
+};
+
+static SWIMMING_SET_DATA: Shape = Shape {
+    fields: &[
+        ("lengths", List(&SWIMMING_LENGTH)),
+        ("stroke_type", Scalar),
+        ("total_lengths", Scalar),
+        ("active_lengths", Scalar),
+        ("swolf_avg", Scalar),
+        ("drill_mode", Scalar),
+    ],
+};
+
+static PERSONAL_RECORD: Shape = Shape {
+    fields: &[
+        ("exercise_name", Scalar),
+        ("record_type", Scalar),
+        ("value", Scalar),
+        ("unit", Scalar),
+        ("achieved_at", Scalar),
+        ("workout_id", Scalar),
+        ("notes", Scalar),
+    ],
+};
+
+static BODY_MEASUREMENT: Shape = Shape {
+    fields: &[
+        ("date", Scalar),
+        ("recorded_at", Scalar),
+        ("weight_kg", Scalar),
+        ("weight_lb", Scalar),
+        ("body_fat_percent", Scalar),
+        ("notes", Scalar),
+        ("measurements", Object(&BODY_DIMENSIONS)),
+    ],
+};
+
+static BODY_DIMENSIONS: Shape = Shape {
+    fields: &[
+        ("neck_cm", Scalar),
+        ("shoulders_cm", Scalar),
+        ("chest_cm", Scalar),
+        ("waist_cm", Scalar),
+        ("hips_cm", Scalar),
+        ("bicep_left_cm", Scalar),
+        ("bicep_right_cm", Scalar),
+        ("forearm_left_cm", Scalar),
+        ("forearm_right_cm", Scalar),
+        ("thigh_left_cm", Scalar),
+        ("thigh_right_cm", Scalar),
+        ("calf_left_cm", Scalar),
+        ("calf_right_cm", Scalar),
+    ],
+};
+
+static DEVICE_INFO: Shape = Shape {
+    fields: &[
+        ("device_index", Scalar),
+        ("device_type", Scalar),
+        ("manufacturer", Scalar),
+        ("product", Scalar),
+        ("serial_number", Scalar),
+        ("software_version", Scalar),
+        ("hardware_version", Scalar),
+        ("battery", Object(&BATTERY_INFO)),
+        ("cumulative_operating_time_hours", Scalar),
+        ("connection", Object(&CONNECTION_INFO)),
+        ("calibration", Object(&CALIBRATION_INFO)),
+    ],
+};
+
+static BATTERY_INFO: Shape = Shape {
+    fields: &[
+        ("start_percent", Scalar),
+        ("end_percent", Scalar),
+        ("voltage", Scalar),
+        ("status", Scalar),
+    ],
+};
+
+static CONNECTION_INFO: Shape = Shape {
+    fields: &[
+        ("connection_type", Scalar),
+        ("ant_device_number", Scalar),
+        ("bluetooth_id", Scalar),
+    ],
+};
+
+static CALIBRATION_INFO: Shape = Shape {
+    fields: &[
+        ("calibration_factor", Scalar),
+        ("last_calibrated", Scalar),
+        ("auto_zero_enabled", Scalar),
+    ],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::*;
+    use crate::plan::*;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    /// Deserialize a minimal document into `T`, serialize it back and compare
+    /// the emitted keys with the table. Optional fields serialize as null and
+    /// the minimal documents fill every collection that is skipped when
+    /// empty, so the round trip yields every field serde knows about.
+    fn assert_shape_matches<T: DeserializeOwned + Serialize>(minimal: &str, shape: &Shape) {
+        let typed: T = serde_yaml::from_str(minimal).unwrap();
+        let value = serde_yaml::to_value(&typed).unwrap();
+        let mut serde_keys: Vec<&str> = value
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str().unwrap())
+            .collect();
+        let mut table_keys: Vec<&str> = shape.fields.iter().map(|(name, _)| *name).collect();
+        serde_keys.sort_unstable();
+        table_keys.sort_unstable();
+        assert_eq!(serde_keys, table_keys, "{}", std::any::type_name::<T>());
+    }
+
+    #[test]
+    fn test_plan_shapes_match_types() {
+        assert_shape_matches::<WpsPlan>(
+            "{plan_version: 1, glossary: {a: b}, cycle: {days: []},
+              exercise_library: [{id: a, name: b, modality: strength}],
+              workout_templates: [{id: a, name: b, exercises: []}]}",
+            &WPS_PLAN,
+        );
+        assert_shape_matches::<PlanMeta>("{title: t, equipment: [a], tags: [a]}", &PLAN_META);
+        assert_shape_matches::<AthleteProfile>("{}", &ATHLETE_PROFILE);
+        assert_shape_matches::<PlanCycle>("days: []", &PLAN_CYCLE);
+        assert_shape_matches::<PlanWeek>("overrides: [{}]", &PLAN_WEEK);
+        assert_shape_matches::<ExerciseOverride>("{}", &EXERCISE_OVERRIDE);
+        assert_shape_matches::<PlanDay>("{}", &PLAN_DAY);
+        assert_shape_matches::<LibraryExercise>(
+            "{id: a, name: b, modality: strength, equipment: [a], muscle_groups: [a]}",
+            &LIBRARY_EXERCISE,
+        );
+        assert_shape_matches::<WorkoutTemplate>(
+            "{id: a, name: b, exercises: [], tags: [a]}",
+            &WORKOUT_TEMPLATE,
+        );
+        assert_shape_matches::<TrainingZone>("zone: 1", &TRAINING_ZONE);
+        assert_shape_matches::<RampConfig>(
+            "{start_power_watts: 1, end_power_watts: 2, duration_sec: 3}",
+            &RAMP_CONFIG,
+        );
+        assert_shape_matches::<IntervalPhase>("{name: a, duration_sec: 1}", &INTERVAL_PHASE);
+        assert_shape_matches::<ProgressionRules>(
+            "{type: linear, success_condition: all_sets_completed}",
+            &PROGRESSION_RULES,
+        );
+        assert_shape_matches::<PlanExercise>("{}", &PLAN_EXERCISE);
+    }
+
+    #[test]
+    fn test_history_shapes_match_types() {
+        assert_shape_matches::<WpsHistory>(
+            "{history_version: 1, exported_at: x, workouts: [], body_measurements: [{date: x}],
+              personal_records: [{exercise_name: a, record_type: max_reps, value: 1, achieved_at: x}]}",
+            &WPS_HISTORY,
+        );
+        assert_shape_matches::<ExportSource>("{}", &EXPORT_SOURCE);
+        assert_shape_matches::<Units>("{}", &UNITS);
+        assert_shape_matches::<Workout>(
+            "{date: x, exercises: [], devices: [{device_type: watch, manufacturer: garmin}]}",
+            &WORKOUT,
+        );
+        assert_shape_matches::<WorkoutTelemetry>("{}", &WORKOUT_TELEMETRY);
+        assert_shape_matches::<AdvancedMetrics>("{}", &ADVANCED_METRICS);
+        assert_shape_matches::<LactateThreshold>("{}", &LACTATE_THRESHOLD);
+        assert_shape_matches::<PowerMetrics>("{}", &POWER_METRICS);
+        assert_shape_matches::<TimeInZones>("{}", &TIME_IN_ZONES);
+        assert_shape_matches::<SportSegment>(
+            "{segment_id: a, sport: running, segment_index: 0, exercise_ids: [a]}",
+            &SPORT_SEGMENT,
+        );
+        assert_shape_matches::<TransitionData>(
+            "{transition_id: a, from_sport: running, to_sport: cycling}",
+            &TRANSITION_DATA,
+        );
+        assert_shape_matches::<GpsPosition>(
+            "{latitude_deg: 0, longitude_deg: 0, timestamp: x}",
+            &GPS_POSITION,
+        );
+        assert_shape_matches::<GpsRoute>("{route_id: a, positions: []}", &GPS_ROUTE);
+        assert_shape_matches::<TimeSeriesData>("timestamps: []", &TIME_SERIES_DATA);
+        assert_shape_matches::<CompletedExercise>("{name: a, sets: []}", &COMPLETED_EXERCISE);
+        assert_shape_matches::<CompletedSet>("{}", &COMPLETED_SET);
+        assert_shape_matches::<SetTelemetry>("{}", &SET_TELEMETRY);
+        assert_shape_matches::<PoolConfig>("pool_length: 25", &POOL_CONFIG);
+        assert_shape_matches::<SwimmingLength>(
+            "{length_number: 1, stroke_type: freestyle, duration_sec: 20}",
+            &SWIMMING_LENGTH,
+        );
+        assert_shape_matches::<SwimmingSetData>(
+            "lengths: [{length_number: 1, stroke_type: freestyle, duration_sec: 20}]",
+            &SWIMMING_SET_DATA,
+        );
+        assert_shape_matches::<PersonalRecord>(
+            "{exercise_name: a, record_type: max_reps, value: 1, achieved_at: x}",
+            &PERSONAL_RECORD,
+        );
+        assert_shape_matches::<BodyMeasurement>("date: x", &BODY_MEASUREMENT);
+        assert_shape_matches::<BodyDimensions>("{}", &BODY_DIMENSIONS);
+        assert_shape_matches::<DeviceInfo>(
+            "{device_type: watch, manufacturer: garmin}",
+            &DEVICE_INFO,
+        );
+        assert_shape_matches::<BatteryInfo>("{}", &BATTERY_INFO);
+        assert_shape_matches::<ConnectionInfo>("connection_type: local", &CONNECTION_INFO);
+        assert_shape_matches::<CalibrationInfo>("{}", &CALIBRATION_INFO);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", "abc"), 0);
+        assert_eq!(edit_distance("target_rep", "target_reps"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_unknown_plan_field_with_suggestion() {
+        let value: Value = serde_yaml::from_str(
+            r#"
+plan_version: 1
+cycle:
+  days:
+    - exercises:
+        - name: Squat
+          modality: strength
+          target_rep: 5
+          rest_betwen_sets_sec: 90
+"#,
+        )
+        .unwrap();
+
+        let found = unknown_plan_fields(&value);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].path, "cycle.days[0].exercises[0].target_rep");
+        assert_eq!(found[0].suggestion, Some("target_reps"));
+        assert_eq!(
+            found[1].path,
+            "cycle.days[0].exercises[0].rest_betwen_sets_sec"
+        );
+        assert_eq!(found[1].suggestion, Some("rest_between_sets_sec"));
+    }
+
+    #[test]
+    fn test_unknown_field_without_suggestion() {
+        let value: Value =
+            serde_yaml::from_str("plan_version: 1\nfavourite_colour: blue\ncycle: {days: []}")
+                .unwrap();
+
+        let found = unknown_plan_fields(&value);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].suggestion, None);
+        assert_eq!(found[0].message(), "Unknown field 'favourite_colour'");
+    }
+
+    #[test]
+    fn test_suggestion_is_case_insensitive() {
+        let value: Value = serde_yaml::from_str(
+            "plan_version: 1\nmeta: {title: t, daysperweek: 3}\ncycle: {days: []}",
+        )
+        .unwrap();
+
+        let found = unknown_plan_fields(&value);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].suggestion, Some("daysPerWeek"));
+        assert_eq!(
+            found[0].message(),
+            "Unknown field 'daysperweek' (did you mean 'daysPerWeek'?)"
+        );
+    }
+
+    #[test]
+    fn test_glossary_keys_are_not_checked() {
+        let value: Value = serde_yaml::from_str(
+            "plan_version: 1\nglossary: {RPE: Rate of perceived exertion}\ncycle: {days: []}",
+        )
+        .unwrap();
+
+        assert!(unknown_plan_fields(&value).is_empty());
+    }
+
+    #[test]
+    fn test_extension_keys_are_not_reported() {
+        let value: Value = serde_yaml::from_str(
+            "plan_version: 1\nx-app-id: 7\ncycle: {days: [{x-app-day: a, exercises: []}]}",
+        )
+        .unwrap();
+
+        assert!(unknown_plan_fields(&value).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_history_fields_nested() {
+        let value: Value = serde_yaml::from_str(
+            r#"
+history_version: 2
+exported_at: "2025-01-15T10:30:00Z"
+workouts:
+  - date: "2025-01-15"
+    exercises:
+      - name: Run
+        sets:
+          - duration_sec: 600
+            telemetry:
+              heart_rate_ave: 150
+              time_series:
+                timestamps: []
+                hr: []
+body_measurements:
+  - date: "2025-01-15"
+    measurements:
+      waist: 80
+"#,
+        )
+        .unwrap();
+
+        let found = unknown_history_fields(&value);
+        let paths: Vec<&str> = found.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "workouts[0].exercises[0].sets[0].telemetry.heart_rate_ave",
+                "workouts[0].exercises[0].sets[0].telemetry.time_series.hr",
+                "body_measurements[0].measurements.waist",
+            ]
+        );
+        assert_eq!(found[0].suggestion, Some("heart_rate_avg"));
+    }
+}
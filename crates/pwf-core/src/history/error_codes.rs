@@ -6,12 +6,16 @@
 // Root level validation (001-099)
 pub const INVALID_VERSION: &str = "PWF-H001";
 pub const MISSING_EXPORTED_AT: &str = "PWF-H002";
+pub const UNKNOWN_FIELD: &str = "PWF-H003";
+pub const SCHEMA_VIOLATION: &str = "PWF-H004";
 
 // Workout validation (100-199)
 pub const MISSING_WORKOUT_DATE: &str = "PWF-H101";
 pub const NO_EXERCISES: &str = "PWF-H102";
 #[allow(dead_code)]
 pub const INVALID_WORKOUT_DATE: &str = "PWF-H103"; // Reserved for future use
+pub const WORKOUT_ENDS_BEFORE_START: &str = "PWF-H104";
+pub const WORKOUT_DURATION_MISMATCH: &str = "PWF-H105";
 
 // Exercise validation (200-299)
 pub const MISSING_EXERCISE_NAME: &str = "PWF-H201";
@@ -22,6 +26,7 @@ pub const NO_METRICS: &str = "PWF-H301";
 pub const RPE_OUT_OF_RANGE: &str = "PWF-H302";
 pub const RIR_OUT_OF_RANGE: &str = "PWF-H303";
 pub const RPE_RIR_BOTH_SET: &str = "PWF-H304";
+pub const SET_COMPLETED_OUTSIDE_WORKOUT: &str = "PWF-H305";
 
 // Personal record validation (400-499)
 pub const MISSING_PR_EXERCISE: &str = "PWF-H401";
@@ -50,6 +55,7 @@ pub const CALORIES_NEGATIVE: &str = "PWF-H707";
 pub const STROKE_RATE_NEGATIVE: &str = "PWF-H708";
 pub const TELEMETRY_UNIT_MISMATCH: &str = "PWF-H709";
 pub const PACE_NEGATIVE: &str = "PWF-H710";
+pub const TELEMETRY_SUMMARY_MISMATCH: &str = "PWF-H711";
 
 // PWF v2.1 validation (800-899)
 // Swimming validation (800-819)
@@ -58,10 +64,14 @@ pub const POOL_LENGTH_INVALID: &str = "PWF-H802";
 
 // Time-series validation (820-839)
 pub const TIME_SERIES_LENGTH_MISMATCH: &str = "PWF-H821";
+pub const TIMESTAMPS_NOT_MONOTONIC: &str = "PWF-H822";
+pub const ELAPSED_NOT_MONOTONIC: &str = "PWF-H823";
 
 // Sport segment validation (840-859)
 pub const SEGMENT_INDEX_GAP: &str = "PWF-H841";
 pub const SEGMENT_INDEX_DUPLICATE: &str = "PWF-H842";
+pub const SEGMENT_OVERLAP: &str = "PWF-H843";
+pub const SEGMENT_OUTSIDE_WORKOUT: &str = "PWF-H844";
 
 // Transition validation (860-869)
 pub const TRANSITION_SPORT_MISMATCH: &str = "PWF-H861";
@@ -73,9 +83,18 @@ pub const ZONE_ARRAY_LENGTH_MISMATCH: &str = "PWF-H871";
 pub const GPS_LATITUDE_OUT_OF_RANGE: &str = "PWF-H881";
 pub const GPS_LONGITUDE_OUT_OF_RANGE: &str = "PWF-H882";
 pub const GPS_HEADING_OUT_OF_RANGE: &str = "PWF-H883";
+pub const GPS_POSITIONS_OUT_OF_ORDER: &str = "PWF-H884";
 
 // Advanced metrics validation (890-899)
 pub const TRAINING_EFFECT_OUT_OF_RANGE: &str = "PWF-H891";
 pub const PERFORMANCE_CONDITION_OUT_OF_RANGE: &str = "PWF-H892";
 pub const INTENSITY_FACTOR_MISMATCH: &str = "PWF-H893";
 pub const VARIABILITY_INDEX_MISMATCH: &str = "PWF-H894";
+
+// Plan cross-reference validation (900-999)
+pub const PLAN_ID_MISMATCH: &str = "PWF-H901";
+pub const UNKNOWN_PLAN_DAY: &str = "PWF-H902";
+pub const EXERCISE_NOT_IN_PLAN_DAY: &str = "PWF-H903";
+pub const WORKOUT_OUTSIDE_PLAN_CYCLE: &str = "PWF-H904";
+pub const PLAN_MODALITY_MISMATCH: &str = "PWF-H905";
+pub const SET_CONTRADICTS_PLAN_MODALITY: &str = "PWF-H906";
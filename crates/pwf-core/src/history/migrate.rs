@@ -0,0 +1,410 @@
+//! Version migration for PWF history exports
+//!
+//! Upgrading from v1 fills in `sport` classification from exercise modalities
+//! and builds workout-level telemetry from per-set telemetry. Downgrading to
+//! v1 keeps everything the v1 schema defines, which includes sports, devices,
+//! swimming, GPS and time series; only set-level `time_in_zones` and the W'
+//! balance fields of `power_metrics` are dropped, and each one is reported.
+
+use super::parser::parse;
+use super::types::{CompletedSet, PowerMetrics, SetTelemetry, WorkoutTelemetry, WpsHistory};
+use crate::error::MigrationError;
+use crate::migration::MigrationReport;
+use crate::{Modality, Sport, KPH_PER_MPS};
+
+/// Newest `history_version` produced by this crate
+pub const LATEST_HISTORY_VERSION: u32 = 2;
+
+/// A migrated history export and the report of what was done to it
+#[derive(Debug, Clone)]
+pub struct HistoryMigration {
+    pub history: WpsHistory,
+    pub report: MigrationReport,
+}
+
+/// Parse a YAML history export and migrate it to `target_version`
+pub fn migrate(yaml: &str, target_version: u32) -> Result<HistoryMigration, MigrationError> {
+    migrate_history(parse(yaml)?, target_version)
+}
+
+/// Migrate an already parsed history export to `target_version`
+pub fn migrate_history(
+    mut history: WpsHistory,
+    target_version: u32,
+) -> Result<HistoryMigration, MigrationError> {
+    let from = history.history_version;
+    let mut report = MigrationReport::new(from, target_version);
+
+    match (from, target_version) {
+        (1, 1) | (2, 2) => {}
+        (1, 2) => upgrade_v1_to_v2(&mut history, &mut report),
+        (2, 1) => downgrade_v2_to_v1(&mut history, &mut report),
+        _ => {
+            return Err(MigrationError::UnsupportedVersion {
+                from,
+                to: target_version,
+            })
+        }
+    }
+
+    Ok(HistoryMigration { history, report })
+}
+
+fn upgrade_v1_to_v2(history: &mut WpsHistory, report: &mut MigrationReport) {
+    history.history_version = 2;
+    report.change("history_version", "Set history_version to 2");
+
+    for (w_idx, workout) in history.workouts.iter_mut().enumerate() {
+        let w_path = format!("workouts[{}]", w_idx);
+
+        for (e_idx, exercise) in workout.exercises.iter_mut().enumerate() {
+            if exercise.sport.is_some() {
+                continue;
+            }
+            if let Some(sport) = exercise.modality.and_then(sport_for_modality) {
+                exercise.sport = Some(sport);
+                report.change(
+                    format!("{}.exercises[{}].sport", w_path, e_idx),
+                    format!("Set sport to {} from modality", sport),
+                );
+            }
+        }
+
+        if workout.sport.is_none() && workout.sport_segments.is_none() {
+            let mut sports = workout.exercises.iter().map(|e| e.sport);
+            if let Some(Some(first)) = sports.next() {
+                if sports.all(|s| s == Some(first)) {
+                    workout.sport = Some(first);
+                    report.change(
+                        format!("{}.sport", w_path),
+                        format!("Set sport to {} from its exercises", first),
+                    );
+                }
+            }
+        }
+
+        if workout.telemetry.is_none() {
+            let sets: Vec<&CompletedSet> = workout.exercises.iter().flat_map(|e| &e.sets).collect();
+            let with_telemetry = sets.iter().filter(|s| s.telemetry.is_some()).count();
+            if with_telemetry > 0 {
+                workout.telemetry = Some(aggregate_set_telemetry(&sets));
+                report.change(
+                    format!("{}.telemetry", w_path),
+                    format!(
+                        "Built workout telemetry from {} set(s) with telemetry",
+                        with_telemetry
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn downgrade_v2_to_v1(history: &mut WpsHistory, report: &mut MigrationReport) {
+    history.history_version = 1;
+    report.change("history_version", "Set history_version to 1");
+
+    for (w_idx, workout) in history.workouts.iter_mut().enumerate() {
+        let w_path = format!("workouts[{}]", w_idx);
+
+        if let Some(metrics) = workout
+            .telemetry
+            .as_mut()
+            .and_then(|t| t.power_metrics.as_mut())
+        {
+            drop_w_prime(
+                metrics,
+                &format!("{}.telemetry.power_metrics", w_path),
+                report,
+            );
+        }
+
+        for (e_idx, exercise) in workout.exercises.iter_mut().enumerate() {
+            for (s_idx, set) in exercise.sets.iter_mut().enumerate() {
+                let Some(telemetry) = set.telemetry.as_mut() else {
+                    continue;
+                };
+                if telemetry.time_in_zones.take().is_some() {
+                    report.lose(
+                        format!(
+                            "{}.exercises[{}].sets[{}].telemetry.time_in_zones",
+                            w_path, e_idx, s_idx
+                        ),
+                        "set-level time_in_zones requires v2",
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Remove the W' balance fields, which v1 `power_metrics` does not define
+fn drop_w_prime(metrics: &mut PowerMetrics, path: &str, report: &mut MigrationReport) {
+    let fields = [
+        (
+            "critical_power_watts",
+            metrics.critical_power_watts.take().is_some(),
+        ),
+        ("w_prime_joules", metrics.w_prime_joules.take().is_some()),
+        (
+            "min_w_prime_balance_joules",
+            metrics.min_w_prime_balance_joules.take().is_some(),
+        ),
+        ("matches_burned", metrics.matches_burned.take().is_some()),
+    ];
+    for (field, dropped) in fields {
+        if dropped {
+            report.lose(
+                format!("{}.{}", path, field),
+                format!("{} requires v2", field),
+            );
+        }
+    }
+}
+
+fn sport_for_modality(modality: Modality) -> Option<Sport> {
+    match modality {
+        Modality::Strength => Some(Sport::Strength),
+        Modality::Cycling => Some(Sport::Cycling),
+        Modality::Running => Some(Sport::Running),
+        Modality::Rowing => Some(Sport::Rowing),
+        Modality::Swimming => Some(Sport::Swimming),
+        Modality::Countdown | Modality::Stopwatch | Modality::Interval => None,
+    }
+}
+
+/// Combine per-set telemetry into a workout summary
+///
+/// Averages are weighted by set duration when every contributing set has
+/// one, otherwise each set counts equally.
+fn aggregate_set_telemetry(sets: &[&CompletedSet]) -> WorkoutTelemetry {
+    let telemetry: Vec<(&SetTelemetry, Option<u32>)> = sets
+        .iter()
+        .filter_map(|s| s.telemetry.as_ref().map(|t| (t, s.duration_sec)))
+        .collect();
+
+    let weighted_avg = |value: fn(&SetTelemetry) -> Option<u32>| -> Option<u32> {
+        let samples: Vec<(f64, Option<u32>)> = telemetry
+            .iter()
+            .filter_map(|(t, d)| value(t).map(|v| (v as f64, *d)))
+            .collect();
+        if samples.is_empty() {
+            return None;
+        }
+        let use_duration = samples.iter().all(|(_, d)| d.is_some_and(|d| d > 0));
+        let (sum, weight) = samples.iter().fold((0.0, 0.0), |(sum, weight), (v, d)| {
+            let w = if use_duration { d.unwrap() as f64 } else { 1.0 };
+            (sum + v * w, weight + w)
+        });
+        Some((sum / weight).round() as u32)
+    };
+    let max = |value: fn(&SetTelemetry) -> Option<u32>| {
+        telemetry.iter().filter_map(|(t, _)| value(t)).max()
+    };
+    let min = |value: fn(&SetTelemetry) -> Option<u32>| {
+        telemetry.iter().filter_map(|(t, _)| value(t)).min()
+    };
+    let sum_f64 = |value: fn(&SetTelemetry) -> Option<f64>| {
+        telemetry
+            .iter()
+            .filter_map(|(t, _)| value(t))
+            .reduce(|a, b| a + b)
+    };
+
+    let distance_m = sets
+        .iter()
+        .filter_map(|s| s.distance_meters)
+        .reduce(|a, b| a + b);
+    let calories = telemetry
+        .iter()
+        .filter_map(|(t, _)| t.calories)
+        .reduce(|a, b| a + b);
+    let speed_max_mps = telemetry
+        .iter()
+        .filter_map(|(t, _)| t.speed_max_mps)
+        .reduce(f64::max);
+
+    let mut route_ids = telemetry
+        .iter()
+        .filter_map(|(t, _)| t.gps_route_id.as_ref());
+    let gps_route_id = match (route_ids.next(), route_ids.next()) {
+        (Some(id), None) => Some(id.clone()),
+        _ => None,
+    };
+
+    WorkoutTelemetry {
+        heart_rate_avg: weighted_avg(|t| t.heart_rate_avg),
+        heart_rate_max: max(|t| t.heart_rate_max),
+        heart_rate_min: min(|t| t.heart_rate_min),
+        power_avg: weighted_avg(|t| t.power_avg),
+        power_max: max(|t| t.power_max),
+        total_distance_m: distance_m,
+        total_elevation_gain_m: sum_f64(|t| t.elevation_gain_m),
+        total_elevation_loss_m: sum_f64(|t| t.elevation_loss_m),
+        speed_max_kph: speed_max_mps.map(|mps| mps * KPH_PER_MPS),
+        cadence_avg: weighted_avg(|t| t.cadence_avg),
+        total_calories: calories,
+        gps_route_id,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::validate;
+
+    const V1_HISTORY: &str = r#"
+history_version: 1
+exported_at: "2025-01-15T10:30:00Z"
+workouts:
+  - date: "2025-01-14"
+    exercises:
+      - name: Intervals
+        modality: running
+        sets:
+          - duration_sec: 600
+            distance_meters: 2000
+            telemetry:
+              heart_rate_avg: 140
+              heart_rate_max: 150
+              calories: 120
+          - duration_sec: 300
+            distance_meters: 1200
+            telemetry:
+              heart_rate_avg: 170
+              heart_rate_max: 182
+              calories: 80
+  - date: "2025-01-15"
+    exercises:
+      - name: Squat
+        modality: strength
+        sets:
+          - reps: 5
+            weight_kg: 100
+      - name: Plank
+        modality: countdown
+        sets:
+          - duration_sec: 60
+"#;
+
+    #[test]
+    fn test_upgrade_sets_sport_and_workout_telemetry() {
+        let migration = migrate(V1_HISTORY, 2).unwrap();
+        let history = &migration.history;
+
+        assert_eq!(history.history_version, 2);
+        let run = &history.workouts[0];
+        assert_eq!(run.sport, Some(Sport::Running));
+        assert_eq!(run.exercises[0].sport, Some(Sport::Running));
+
+        let telemetry = run.telemetry.as_ref().unwrap();
+        assert_eq!(telemetry.heart_rate_avg, Some(150));
+        assert_eq!(telemetry.heart_rate_max, Some(182));
+        assert_eq!(telemetry.total_calories, Some(200));
+        assert_eq!(telemetry.total_distance_m, Some(3200.0));
+
+        // Mixed strength/countdown workout: exercise sport only where it maps
+        let lift = &history.workouts[1];
+        assert_eq!(lift.exercises[0].sport, Some(Sport::Strength));
+        assert!(lift.exercises[1].sport.is_none());
+        assert!(lift.sport.is_none());
+        assert!(lift.telemetry.is_none());
+
+        let paths: Vec<&str> = migration
+            .report
+            .changes
+            .iter()
+            .map(|c| c.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "history_version",
+                "workouts[0].exercises[0].sport",
+                "workouts[0].sport",
+                "workouts[0].telemetry",
+                "workouts[1].exercises[0].sport",
+            ]
+        );
+
+        let yaml = serde_yaml::to_string(history).unwrap();
+        assert!(validate(&yaml).is_valid());
+    }
+
+    #[test]
+    fn test_downgrade_keeps_v1_fields() {
+        let yaml = r#"
+history_version: 2
+exported_at: "2025-01-15T10:30:00Z"
+workouts:
+  - date: "2025-01-15"
+    sport: cycling
+    devices:
+      - device_type: bike_computer
+        manufacturer: garmin
+    sport_segments:
+      - segment_id: ride
+        sport: cycling
+        segment_index: 0
+    telemetry:
+      heart_rate_avg: 130
+      power_metrics:
+        normalized_power: 200
+        w_prime_joules: 20000
+        matches_burned: 3
+      time_in_zones:
+        hr_zones_sec: [600, 1200]
+    exercises:
+      - name: Ride
+        modality: cycling
+        sport: cycling
+        sets:
+          - duration_sec: 1800
+            telemetry:
+              power_avg: 190
+              time_in_zones:
+                power_zones_sec: [1800]
+"#;
+        let migration = migrate(yaml, 1).unwrap();
+        let history = &migration.history;
+        let workout = &history.workouts[0];
+
+        assert_eq!(history.history_version, 1);
+        assert_eq!(workout.sport, Some(Sport::Cycling));
+        assert_eq!(workout.devices.len(), 1);
+        assert_eq!(workout.sport_segments.as_ref().unwrap().len(), 1);
+        assert_eq!(workout.exercises[0].sport, Some(Sport::Cycling));
+        let telemetry = workout.telemetry.as_ref().unwrap();
+        assert!(telemetry.time_in_zones.is_some());
+        let metrics = telemetry.power_metrics.as_ref().unwrap();
+        assert_eq!(metrics.normalized_power, Some(200));
+        assert!(metrics.w_prime_joules.is_none());
+        let set = workout.exercises[0].sets[0].telemetry.as_ref().unwrap();
+        assert_eq!(set.power_avg, Some(190));
+        assert!(set.time_in_zones.is_none());
+
+        let lost: Vec<&str> = migration
+            .report
+            .lost
+            .iter()
+            .map(|l| l.path.as_str())
+            .collect();
+        assert_eq!(
+            lost,
+            vec![
+                "workouts[0].telemetry.power_metrics.w_prime_joules",
+                "workouts[0].telemetry.power_metrics.matches_burned",
+                "workouts[0].exercises[0].sets[0].telemetry.time_in_zones",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        assert!(matches!(
+            migrate(V1_HISTORY, 0),
+            Err(MigrationError::UnsupportedVersion { from: 1, to: 0 })
+        ));
+    }
+}
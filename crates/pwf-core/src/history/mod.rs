@@ -2,11 +2,21 @@
 //!
 //! This module handles PWF history documents (completed workout exports).
 
-mod error_codes;
+pub(crate) mod error_codes;
+pub mod migrate;
 mod parser;
+mod plan_check;
+mod statistics;
+mod temporal;
 mod types;
 mod validator;
 
-pub use parser::parse;
+pub use migrate::{migrate, migrate_history, HistoryMigration, LATEST_HISTORY_VERSION};
+pub use parser::{parse, parse_json, to_json, to_yaml};
+pub use plan_check::{check_against_plan, validate_with_plan};
+pub use statistics::history_statistics;
+pub use temporal::check_temporal;
 pub use types::*;
-pub use validator::{validate, ValidationResult};
+pub use validator::{
+    validate, validate_json, validate_json_with_options, validate_with_options, ValidationResult,
+};
@@ -1,4 +1,4 @@
-//! YAML parsing for PWF history exports
+//! YAML and JSON parsing for PWF history exports
 
 use super::types::WpsHistory;
 use crate::error::ParseError;
@@ -9,6 +9,22 @@ pub fn parse(yaml: &str) -> Result<WpsHistory, ParseError> {
     Ok(history)
 }
 
+/// Parse a JSON string into a WpsHistory
+pub fn parse_json(json: &str) -> Result<WpsHistory, ParseError> {
+    let history: WpsHistory = serde_json::from_str(json)?;
+    Ok(history)
+}
+
+/// Serialize a WpsHistory to YAML
+pub fn to_yaml(history: &WpsHistory) -> Result<String, serde_yaml::Error> {
+    serde_yaml::to_string(history)
+}
+
+/// Serialize a WpsHistory to pretty-printed JSON
+pub fn to_json(history: &WpsHistory) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(history)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +86,124 @@ body_measurements:
         assert_eq!(history.personal_records.len(), 1);
         assert_eq!(history.body_measurements.len(), 1);
     }
+
+    #[test]
+    fn parse_json_history() {
+        let json = r#"{
+            "history_version": 1,
+            "exported_at": "2025-01-15T10:30:00Z",
+            "workouts": [{"date": "2025-01-15", "exercises": [
+                {"name": "Squat", "sets": [{"reps": 5, "weight_kg": 100}]}
+            ]}]
+        }"#;
+        let history = parse_json(json).unwrap();
+        assert_eq!(
+            history.workouts[0].exercises[0].sets[0].weight_kg,
+            Some(100.0)
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let yaml = r#"
+history_version: 1
+exported_at: "2025-01-15T10:30:00Z"
+workouts:
+  - date: "2025-01-15"
+    exercises:
+      - name: Squat
+        sets:
+          - reps: 5
+            weight_kg: 100
+"#;
+        let history = parse(yaml).unwrap();
+        let again = parse_json(&to_json(&history).unwrap()).unwrap();
+        assert_eq!(to_yaml(&again).unwrap(), to_yaml(&history).unwrap());
+    }
+
+    #[test]
+    fn empty_collections_are_not_serialized() {
+        let yaml = r#"
+history_version: 2
+exported_at: "2025-01-15T10:30:00Z"
+workouts:
+  - date: "2025-01-15"
+    exercises:
+      - name: Swim
+        sets:
+          - swimming:
+              stroke_type: freestyle
+    sport_segments:
+      - segment_id: s1
+        sport: swimming
+        segment_index: 0
+"#;
+        let history = parse(yaml).unwrap();
+        let json = to_json(&history).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let has = |v: &serde_json::Value, key: &str| v.as_object().unwrap().contains_key(key);
+        assert!(!has(&value, "personal_records"));
+        assert!(!has(&value, "body_measurements"));
+        let workout = &value["workouts"][0];
+        assert!(!has(workout, "devices"));
+        assert!(!has(&workout["sport_segments"][0], "exercise_ids"));
+        assert!(!has(
+            &workout["exercises"][0]["sets"][0]["swimming"],
+            "lengths"
+        ));
+        let again = parse_json(&json).unwrap();
+        assert_eq!(to_yaml(&again).unwrap(), to_yaml(&history).unwrap());
+    }
+
+    #[test]
+    fn populated_collections_round_trip() {
+        let yaml = r#"
+history_version: 2
+exported_at: "2025-01-15T10:30:00Z"
+workouts:
+  - date: "2025-01-15"
+    devices:
+      - device_type: watch
+        manufacturer: garmin
+    exercises:
+      - id: swim
+        name: Swim
+        sets:
+          - swimming:
+              lengths:
+                - length_number: 1
+                  stroke_type: freestyle
+                  duration_sec: 20
+    sport_segments:
+      - segment_id: s1
+        sport: swimming
+        segment_index: 0
+        exercise_ids: [swim]
+personal_records:
+  - exercise_name: Swim
+    record_type: max_reps
+    value: 1
+    achieved_at: "2025-01-15"
+body_measurements:
+  - date: "2025-01-15"
+    weight_kg: 70
+"#;
+        let history = parse(yaml).unwrap();
+        for again in [
+            parse_json(&to_json(&history).unwrap()).unwrap(),
+            parse(&to_yaml(&history).unwrap()).unwrap(),
+        ] {
+            let workout = &again.workouts[0];
+            assert_eq!(workout.devices.len(), 1);
+            let swimming = workout.exercises[0].sets[0].swimming.as_ref().unwrap();
+            assert_eq!(swimming.lengths.len(), 1);
+            assert_eq!(
+                workout.sport_segments.as_ref().unwrap()[0].exercise_ids,
+                vec!["swim"]
+            );
+            assert_eq!(again.personal_records.len(), 1);
+            assert_eq!(again.body_measurements.len(), 1);
+            assert_eq!(to_yaml(&again).unwrap(), to_yaml(&history).unwrap());
+        }
+    }
 }
@@ -0,0 +1,381 @@
+//! Cross-document checks of a history against the plan it was logged from
+//!
+//! Only workouts linked to the plan, through a matching `plan_id` or any
+//! `plan_day_id`, are checked. Workouts from a different plan get a single
+//! PWF-H901 warning and are otherwise left alone.
+
+use super::error_codes;
+use super::types::{CompletedExercise, CompletedSet, WpsHistory};
+use super::validator::ValidationResult;
+use crate::analytics::date_range::{parse_date, workout_date};
+use crate::error::{Severity, ValidationIssue};
+use crate::plan::{expand_plan, PlannedExercise, PlannedSession, WpsPlan};
+use crate::Modality;
+use chrono::{Duration, NaiveDate};
+
+impl ValidationResult {
+    /// Add the issues found by [`check_against_plan`], keeping `valid` in
+    /// step with the errors
+    ///
+    /// Does nothing when the history could not be parsed.
+    pub fn with_plan(mut self, plan: &WpsPlan) -> Self {
+        let Some(history) = &self.history else {
+            return self;
+        };
+        for issue in check_against_plan(history, plan) {
+            match issue.severity {
+                Severity::Error => self.errors.push(issue),
+                Severity::Warning => self.warnings.push(issue),
+            }
+        }
+        self.valid = self.errors.is_empty();
+        self
+    }
+}
+
+/// Validate a YAML history export, then check it against `plan`
+pub fn validate_with_plan(yaml: &str, plan: &WpsPlan) -> ValidationResult {
+    super::validate(yaml).with_plan(plan)
+}
+
+/// Check the plan references in a history against the plan itself
+///
+/// Reports `plan_id`s naming another plan (PWF-H901), `plan_day_id`s the
+/// plan does not define (PWF-H902), exercises missing from the referenced
+/// day (PWF-H903), linked workouts dated outside the plan's cycle
+/// (PWF-H904), exercises logged under a different modality than planned
+/// (PWF-H905) and sets whose metrics do not fit the planned modality
+/// (PWF-H906). Only unknown plan days are errors.
+pub fn check_against_plan(history: &WpsHistory, plan: &WpsPlan) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let plan_id = plan.meta.as_ref().and_then(|m| m.id.as_deref());
+    let sessions = expand_plan(plan);
+    let window = cycle_window(plan, &sessions);
+
+    for (workout_idx, workout) in history.workouts.iter().enumerate() {
+        let workout_path = format!("workouts[{}]", workout_idx);
+
+        if let (Some(linked), Some(plan_id)) = (workout.plan_id.as_deref(), plan_id) {
+            if linked != plan_id {
+                issues.push(ValidationIssue::warning_with_code(
+                    format!("{}.plan_id", workout_path),
+                    format!(
+                        "Workout references plan '{}' but is checked against plan '{}'",
+                        linked, plan_id
+                    ),
+                    error_codes::PLAN_ID_MISMATCH,
+                ));
+                continue;
+            }
+        }
+        let linked = workout.plan_day_id.is_some()
+            || (plan_id.is_some() && workout.plan_id.as_deref() == plan_id);
+        if !linked {
+            continue;
+        }
+
+        if let (Some(date), Some((start, end))) = (workout_date(workout), window) {
+            if date < start || end.is_some_and(|end| date > end) {
+                let cycle = match end {
+                    Some(end) => format!("{} to {}", start, end),
+                    None => format!("from {}", start),
+                };
+                issues.push(ValidationIssue::warning_with_code(
+                    format!("{}.date", workout_path),
+                    format!(
+                        "Workout date {} is outside the plan cycle ({})",
+                        date, cycle
+                    ),
+                    error_codes::WORKOUT_OUTSIDE_PLAN_CYCLE,
+                ));
+            }
+        }
+
+        let Some(day_id) = workout.plan_day_id.as_deref() else {
+            continue;
+        };
+        // Exercise names and modalities do not change between weeks
+        let Some(day) = sessions
+            .iter()
+            .find(|s| s.day_id.as_deref() == Some(day_id))
+        else {
+            issues.push(ValidationIssue::error_with_code(
+                format!("{}.plan_day_id", workout_path),
+                format!(
+                    "plan_day_id '{}' does not match any day in the plan",
+                    day_id
+                ),
+                error_codes::UNKNOWN_PLAN_DAY,
+            ));
+            continue;
+        };
+
+        for (ex_idx, exercise) in workout.exercises.iter().enumerate() {
+            let ex_path = format!("{}.exercises[{}]", workout_path, ex_idx);
+            match planned_exercise(day, exercise) {
+                Some(planned) => check_exercise(&ex_path, exercise, planned, &mut issues),
+                None => issues.push(ValidationIssue::warning_with_code(
+                    ex_path,
+                    format!(
+                        "Exercise '{}' is not part of plan day '{}'",
+                        exercise.name, day_id
+                    ),
+                    error_codes::EXERCISE_NOT_IN_PLAN_DAY,
+                )),
+            }
+        }
+    }
+
+    issues
+}
+
+/// First and (for plans with weeks) last date of the plan cycle
+///
+/// Starts at `cycle.start_date`, or the earliest scheduled day. Weekly plans
+/// run for as many weeks as they define.
+fn cycle_window(
+    plan: &WpsPlan,
+    sessions: &[PlannedSession],
+) -> Option<(NaiveDate, Option<NaiveDate>)> {
+    let scheduled = sessions.iter().filter_map(|s| s.scheduled_date);
+    let start = plan
+        .cycle
+        .start_date
+        .as_deref()
+        .and_then(parse_date)
+        .or_else(|| scheduled.clone().min())?;
+    let end = match &plan.cycle.weeks {
+        Some(weeks) if !weeks.is_empty() => {
+            let last_week = start + Duration::days(weeks.len() as i64 * 7 - 1);
+            Some(scheduled.max().map_or(last_week, |d| d.max(last_week)))
+        }
+        _ => None,
+    };
+    Some((start, end))
+}
+
+fn planned_exercise<'a>(
+    day: &'a PlannedSession,
+    exercise: &CompletedExercise,
+) -> Option<&'a PlannedExercise> {
+    day.exercises
+        .iter()
+        .find(|p| p.id.is_some() && p.id == exercise.id)
+        .or_else(|| {
+            day.exercises
+                .iter()
+                .find(|p| p.name.trim().eq_ignore_ascii_case(exercise.name.trim()))
+        })
+}
+
+fn check_exercise(
+    path: &str,
+    exercise: &CompletedExercise,
+    planned: &PlannedExercise,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let Some(modality) = planned.modality else {
+        return;
+    };
+    if let Some(logged) = exercise.modality {
+        if logged != modality {
+            issues.push(ValidationIssue::warning_with_code(
+                format!("{}.modality", path),
+                format!("Exercise logged as {} but planned as {}", logged, modality),
+                error_codes::PLAN_MODALITY_MISMATCH,
+            ));
+        }
+    }
+
+    for (set_idx, set) in exercise.sets.iter().enumerate() {
+        if let Some(problem) = contradiction(set, modality) {
+            issues.push(ValidationIssue::warning_with_code(
+                format!("{}.sets[{}]", path, set_idx),
+                format!("Set {} for a {} exercise", problem, modality),
+                error_codes::SET_CONTRADICTS_PLAN_MODALITY,
+            ));
+        }
+    }
+}
+
+/// Why a set's metrics cannot belong to the planned modality, if they can't
+///
+/// A strength set recording only time or distance contradicts it, as does a
+/// timed set with reps or weight but no duration, or an endurance set with
+/// reps or weight but neither duration nor distance. Interval sets may mix
+/// both.
+fn contradiction(set: &CompletedSet, modality: Modality) -> Option<&'static str> {
+    let lifting = set.reps.is_some() || set.weight_kg.is_some() || set.weight_lb.is_some();
+    let covering = set.duration_sec.is_some() || set.distance_meters.is_some();
+    match modality {
+        Modality::Strength if covering && !lifting => Some("records only time or distance"),
+        Modality::Countdown | Modality::Stopwatch if lifting && set.duration_sec.is_none() => {
+            Some("records reps or weight without a duration")
+        }
+        Modality::Cycling | Modality::Running | Modality::Rowing | Modality::Swimming
+            if lifting && !covering =>
+        {
+            Some("records reps or weight without a duration or distance")
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = r#"
+plan_version: 2
+meta:
+  id: base
+  title: Base
+cycle:
+  start_date: "2025-01-06"
+  weeks:
+    - week_number: 1
+    - week_number: 2
+  days:
+    - id: strength
+      exercises:
+        - name: Squat
+          modality: strength
+          target_sets: 3
+          target_reps: 5
+        - name: Plank
+          modality: countdown
+          target_duration_sec: 60
+    - id: run
+      exercises:
+        - name: Easy Run
+          modality: running
+          target_duration_sec: 1800
+"#;
+
+    fn check(history: &str) -> Vec<ValidationIssue> {
+        let plan = crate::plan::parse(PLAN).unwrap();
+        let history = crate::history::parse(history).unwrap();
+        check_against_plan(&history, &plan)
+    }
+
+    fn codes(issues: &[ValidationIssue]) -> Vec<&str> {
+        issues.iter().filter_map(|i| i.code.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_consistent_history_has_no_issues() {
+        let issues = check(
+            r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-06"
+    plan_id: base
+    plan_day_id: strength
+    exercises:
+      - name: squat
+        modality: strength
+        sets: [{ reps: 5, weight_kg: 100 }]
+      - name: Plank
+        sets: [{ duration_sec: 60 }]
+  - date: "2025-02-01"
+    exercises:
+      - name: Unrelated
+        sets: [{ reps: 5 }]
+"#,
+        );
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_dangling_references() {
+        let issues = check(
+            r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-07"
+    plan_id: other
+    plan_day_id: nowhere
+    exercises:
+      - name: Squat
+        sets: [{ reps: 5 }]
+  - date: "2025-01-08"
+    plan_day_id: nowhere
+    exercises:
+      - name: Squat
+        sets: [{ reps: 5 }]
+  - date: "2025-01-25"
+    plan_id: base
+    plan_day_id: run
+    exercises:
+      - name: Tempo Run
+        sets: [{ duration_sec: 1200 }]
+"#,
+        );
+        assert_eq!(
+            codes(&issues),
+            vec![
+                error_codes::PLAN_ID_MISMATCH,
+                error_codes::UNKNOWN_PLAN_DAY,
+                error_codes::WORKOUT_OUTSIDE_PLAN_CYCLE,
+                error_codes::EXERCISE_NOT_IN_PLAN_DAY,
+            ]
+        );
+        assert_eq!(issues[1].severity, Severity::Error);
+        assert_eq!(issues[2].path, "workouts[2].date");
+        assert!(issues[2].message.contains("2025-01-06 to 2025-01-19"));
+    }
+
+    #[test]
+    fn test_modality_contradictions() {
+        let issues = check(
+            r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-09"
+    plan_day_id: run
+    exercises:
+      - name: Easy Run
+        modality: cycling
+        sets:
+          - { reps: 10 }
+          - { duration_sec: 1800, distance_meters: 5000 }
+  - date: "2025-01-10"
+    plan_day_id: strength
+    exercises:
+      - name: Squat
+        sets: [{ distance_meters: 400 }]
+      - name: Plank
+        sets: [{ reps: 3 }]
+"#,
+        );
+        assert_eq!(
+            codes(&issues),
+            vec![
+                error_codes::PLAN_MODALITY_MISMATCH,
+                error_codes::SET_CONTRADICTS_PLAN_MODALITY,
+                error_codes::SET_CONTRADICTS_PLAN_MODALITY,
+                error_codes::SET_CONTRADICTS_PLAN_MODALITY,
+            ]
+        );
+        assert_eq!(issues[1].path, "workouts[0].exercises[0].sets[0]");
+        assert_eq!(issues[2].path, "workouts[1].exercises[0].sets[0]");
+    }
+
+    #[test]
+    fn test_with_plan_updates_validity() {
+        let plan = crate::plan::parse(PLAN).unwrap();
+        let result = crate::history::validate(
+            "history_version: 2\nexported_at: \"2025-01-20T00:00:00Z\"\nworkouts:\n  - date: \"2025-01-07\"\n    plan_day_id: legs\n    exercises:\n      - name: Squat\n        sets: [{ reps: 5 }]\n",
+        );
+        assert!(result.valid);
+        let result = result.with_plan(&plan);
+        assert!(!result.valid);
+        assert_eq!(
+            result.errors[0].code.as_deref(),
+            Some(error_codes::UNKNOWN_PLAN_DAY)
+        );
+    }
+}
@@ -0,0 +1,439 @@
+//! Summary statistics for history exports
+
+use super::types::{
+    ActivityTotals, CompletedExercise, CompletedSet, ConsistencyStatistics, HistoryStatistics,
+    ModalityTotals, PeriodTotals, SportTotals, StatisticsBreakdown, StreakStatistics, Workout,
+    WorkoutTelemetry, WpsHistory,
+};
+use crate::analytics::{workout_date, workout_duration_sec};
+use crate::{LB_TO_KG, METERS_PER_FOOT, METERS_PER_MILE};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// Totals, date range and the per-sport, per-modality and per-period
+/// breakdowns of a history
+pub fn history_statistics(history: &WpsHistory) -> HistoryStatistics {
+    HistoryStatistics {
+        breakdown: Some(breakdown(history)),
+        ..calculate_statistics(history)
+    }
+}
+
+/// Totals and date range, as reported by validation
+pub(super) fn calculate_statistics(history: &WpsHistory) -> HistoryStatistics {
+    let mut stats = HistoryStatistics {
+        total_workouts: history.workouts.len(),
+        personal_records_count: history.personal_records.len(),
+        body_measurements_count: history.body_measurements.len(),
+        ..Default::default()
+    };
+
+    let mut dates: Vec<&str> = Vec::new();
+
+    for workout in &history.workouts {
+        dates.push(&workout.date);
+        stats.total_exercises += workout.exercises.len();
+
+        for exercise in &workout.exercises {
+            stats.total_sets += exercise.sets.len();
+
+            stats.total_volume_kg += sum(exercise.sets.iter().map(set_volume_kg));
+        }
+    }
+
+    // Get date range
+    dates.sort();
+    if let Some(first) = dates.first() {
+        stats.date_range_start = Some(first.to_string());
+    }
+    if let Some(last) = dates.last() {
+        stats.date_range_end = Some(last.to_string());
+    }
+
+    stats
+}
+
+fn breakdown(history: &WpsHistory) -> StatisticsBreakdown {
+    let mut by_sport: Vec<SportTotals> = Vec::new();
+    let mut by_modality: Vec<ModalityTotals> = Vec::new();
+    let mut weekly: BTreeMap<NaiveDate, ActivityTotals> = BTreeMap::new();
+    let mut monthly: BTreeMap<(i32, u32), ActivityTotals> = BTreeMap::new();
+    let mut yearly: BTreeMap<i32, ActivityTotals> = BTreeMap::new();
+    let mut days: Vec<NaiveDate> = Vec::new();
+
+    for workout in &history.workouts {
+        for (sport, totals) in sport_totals(workout) {
+            match by_sport.iter_mut().find(|s| s.sport == sport) {
+                Some(entry) => add(&mut entry.totals, &totals),
+                None => by_sport.push(SportTotals { sport, totals }),
+            }
+        }
+        for exercise in &workout.exercises {
+            let Some(modality) = exercise.modality else {
+                continue;
+            };
+            let totals = exercise_totals(exercise);
+            match by_modality.iter_mut().find(|m| m.modality == modality) {
+                Some(entry) => add(&mut entry.totals, &totals),
+                None => by_modality.push(ModalityTotals { modality, totals }),
+            }
+        }
+
+        let Some(date) = workout_date(workout) else {
+            continue;
+        };
+        days.push(date);
+        let totals = workout_totals(workout);
+        add(weekly.entry(week_start(date)).or_default(), &totals);
+        add(
+            monthly.entry((date.year(), date.month())).or_default(),
+            &totals,
+        );
+        add(yearly.entry(date.year()).or_default(), &totals);
+    }
+    days.sort();
+    days.dedup();
+
+    by_sport.sort_by_key(|s| Reverse(s.totals.count));
+    by_modality.sort_by_key(|m| Reverse(m.totals.count));
+    let period = |period: String, totals: ActivityTotals| PeriodTotals { period, totals };
+    StatisticsBreakdown {
+        by_sport,
+        by_modality,
+        streaks: streaks(&days, &weekly.keys().copied().collect::<Vec<_>>()),
+        consistency: consistency(&days, weekly.len(), weekly.values().map(|w| w.count).sum()),
+        weekly: weekly
+            .into_iter()
+            .map(|(week, totals)| period(week.to_string(), totals))
+            .collect(),
+        monthly: monthly
+            .into_iter()
+            .map(|((year, month), totals)| period(format!("{}-{:02}", year, month), totals))
+            .collect(),
+        yearly: yearly
+            .into_iter()
+            .map(|(year, totals)| period(year.to_string(), totals))
+            .collect(),
+    }
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn add(total: &mut ActivityTotals, other: &ActivityTotals) {
+    total.count += other.count;
+    total.duration_sec += other.duration_sec;
+    total.distance_m += other.distance_m;
+    total.elevation_gain_m += other.elevation_gain_m;
+    total.calories += other.calories;
+    total.volume_kg += other.volume_kg;
+}
+
+/// Sum starting from +0.0, so that nothing adds up to 0 rather than -0
+fn sum(values: impl Iterator<Item = f64>) -> f64 {
+    values.fold(0.0, |total, v| total + v)
+}
+
+fn set_volume_kg(set: &CompletedSet) -> f64 {
+    let weight = set
+        .weight_kg
+        .or_else(|| set.weight_lb.map(|lb| lb * LB_TO_KG));
+    match (set.reps, weight) {
+        (Some(reps), Some(weight)) => reps as f64 * weight,
+        _ => 0.0,
+    }
+}
+
+fn exercise_totals(exercise: &CompletedExercise) -> ActivityTotals {
+    let sets = &exercise.sets;
+    let telemetry = || sets.iter().filter_map(|s| s.telemetry.as_ref());
+    ActivityTotals {
+        count: 1,
+        duration_sec: sets
+            .iter()
+            .filter_map(|s| s.duration_sec)
+            .map(u64::from)
+            .sum(),
+        distance_m: sum(sets.iter().filter_map(|s| s.distance_meters)),
+        elevation_gain_m: sum(telemetry().filter_map(|t| {
+            t.elevation_gain_m
+                .or_else(|| t.elevation_gain_ft.map(|ft| ft * METERS_PER_FOOT))
+        })),
+        calories: telemetry().filter_map(|t| t.calories).map(u64::from).sum(),
+        volume_kg: sum(sets.iter().map(set_volume_kg)),
+    }
+}
+
+/// Workout totals, preferring the workout's own telemetry over sums of its
+/// exercises
+fn workout_totals(workout: &Workout) -> ActivityTotals {
+    let mut exercises = ActivityTotals::default();
+    for exercise in &workout.exercises {
+        add(&mut exercises, &exercise_totals(exercise));
+    }
+    let telemetry = workout.telemetry.as_ref();
+    ActivityTotals {
+        count: 1,
+        duration_sec: workout_duration_sec(workout).map_or(0, u64::from),
+        distance_m: telemetry
+            .and_then(telemetry_distance_m)
+            .unwrap_or(exercises.distance_m),
+        elevation_gain_m: telemetry
+            .and_then(telemetry_elevation_m)
+            .unwrap_or(exercises.elevation_gain_m),
+        calories: telemetry
+            .and_then(|t| t.total_calories)
+            .map_or(exercises.calories, u64::from),
+        volume_kg: exercises.volume_kg,
+    }
+}
+
+/// Totals per sport: one entry per segment of a multi-sport workout,
+/// otherwise one for the whole workout
+fn sport_totals(workout: &Workout) -> Vec<(String, ActivityTotals)> {
+    let segments = workout.sport_segments.as_deref().unwrap_or_default();
+    if segments.is_empty() {
+        let sport = workout
+            .sport
+            .or_else(|| workout.exercises.iter().find_map(|e| e.sport))
+            .map_or_else(|| "other".to_string(), |s| s.to_string());
+        return vec![(sport, workout_totals(workout))];
+    }
+
+    segments
+        .iter()
+        .map(|segment| {
+            let exercises: Vec<&CompletedExercise> = workout
+                .exercises
+                .iter()
+                .filter(|e| {
+                    e.id.as_ref()
+                        .is_some_and(|id| segment.exercise_ids.contains(id))
+                })
+                .collect();
+            let mut sums = ActivityTotals::default();
+            for exercise in &exercises {
+                add(&mut sums, &exercise_totals(exercise));
+            }
+            let telemetry = segment.telemetry.as_ref();
+            let totals = ActivityTotals {
+                count: 1,
+                duration_sec: segment.duration_sec.map_or(sums.duration_sec, u64::from),
+                distance_m: segment
+                    .distance_m
+                    .or_else(|| telemetry.and_then(telemetry_distance_m))
+                    .unwrap_or(sums.distance_m),
+                elevation_gain_m: telemetry
+                    .and_then(telemetry_elevation_m)
+                    .unwrap_or(sums.elevation_gain_m),
+                calories: telemetry
+                    .and_then(|t| t.total_calories)
+                    .map_or(sums.calories, u64::from),
+                volume_kg: sums.volume_kg,
+            };
+            (segment.sport.to_string(), totals)
+        })
+        .collect()
+}
+
+fn telemetry_distance_m(telemetry: &WorkoutTelemetry) -> Option<f64> {
+    telemetry
+        .total_distance_m
+        .or_else(|| telemetry.total_distance_km.map(|km| km * 1000.0))
+        .or_else(|| telemetry.total_distance_mi.map(|mi| mi * METERS_PER_MILE))
+}
+
+fn telemetry_elevation_m(telemetry: &WorkoutTelemetry) -> Option<f64> {
+    telemetry.total_elevation_gain_m.or_else(|| {
+        telemetry
+            .total_elevation_gain_ft
+            .map(|ft| ft * METERS_PER_FOOT)
+    })
+}
+
+/// Longest and most recent runs of consecutive values `step` days apart,
+/// as `(current, longest, longest start)`
+fn runs(dates: &[NaiveDate], step: i64) -> (u32, u32, Option<NaiveDate>) {
+    let (mut current, mut longest, mut longest_start) = (0u32, 0u32, None);
+    let mut start = None;
+    for (i, &date) in dates.iter().enumerate() {
+        let continues = i > 0 && (date - dates[i - 1]).num_days() == step;
+        if continues {
+            current += 1;
+        } else {
+            current = 1;
+            start = Some(date);
+        }
+        if current > longest {
+            longest = current;
+            longest_start = start;
+        }
+    }
+    (current, longest, longest_start)
+}
+
+fn streaks(days: &[NaiveDate], weeks: &[NaiveDate]) -> StreakStatistics {
+    let (current_days, longest_days, day_start) = runs(days, 1);
+    let (current_weeks, longest_weeks, week_start) = runs(weeks, 7);
+    StreakStatistics {
+        current_days,
+        longest_days,
+        longest_days_start: day_start.map(|d| d.to_string()),
+        current_weeks,
+        longest_weeks,
+        longest_weeks_start: week_start.map(|d| d.to_string()),
+    }
+}
+
+fn consistency(
+    days: &[NaiveDate],
+    active_weeks: usize,
+    dated_workouts: usize,
+) -> ConsistencyStatistics {
+    let (Some(&first), Some(&last)) = (days.first(), days.last()) else {
+        return ConsistencyStatistics::default();
+    };
+    let total_days = (last - first).num_days() as u32 + 1;
+    let total_weeks = ((week_start(last) - week_start(first)).num_days() / 7) as u32 + 1;
+    let longest_gap_days = days
+        .windows(2)
+        .map(|w| (w[1] - w[0]).num_days() as u32 - 1)
+        .max()
+        .unwrap_or(0);
+    ConsistencyStatistics {
+        active_days: days.len() as u32,
+        total_days,
+        active_weeks: active_weeks as u32,
+        total_weeks,
+        active_weeks_percent: active_weeks as f64 / total_weeks as f64 * 100.0,
+        workouts_per_week: dated_workouts as f64 / total_weeks as f64,
+        longest_gap_days,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Modality;
+
+    const HISTORY: &str = r#"
+history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+workouts:
+  - date: "2024-12-30"
+    sport: running
+    duration_sec: 1800
+    exercises: []
+    telemetry:
+      total_distance_km: 5.0
+      total_elevation_gain_m: 40
+      total_calories: 350
+  - date: "2024-12-31"
+    duration_sec: 3600
+    exercises:
+      - name: Squat
+        modality: strength
+        sets:
+          - { reps: 5, weight_kg: 100 }
+          - { reps: 5, weight_lb: 220.462 }
+  - date: "2025-01-01"
+    sport: running
+    duration_sec: 2400
+    exercises: []
+    telemetry:
+      total_distance_mi: 5.0
+  - date: "2025-01-20"
+    duration_sec: 5400
+    exercises:
+      - id: swim
+        name: Swim
+        sets:
+          - { distance_meters: 1500 }
+      - id: bike
+        name: Bike
+        sets:
+          - { distance_meters: 40000 }
+    sport_segments:
+      - segment_id: s1
+        sport: swimming
+        segment_index: 0
+        duration_sec: 1800
+        exercise_ids: [swim]
+      - segment_id: s2
+        sport: cycling
+        segment_index: 1
+        duration_sec: 3600
+        exercise_ids: [bike]
+"#;
+
+    #[test]
+    fn test_sport_and_modality_totals() {
+        let history = crate::history::parse(HISTORY).unwrap();
+        let stats = history_statistics(&history);
+        assert_eq!(stats.total_workouts, 4);
+        let breakdown = stats.breakdown.unwrap();
+
+        let running = &breakdown.by_sport[0];
+        assert_eq!(running.sport, "running");
+        assert_eq!(running.totals.count, 2);
+        assert_eq!(running.totals.duration_sec, 4200);
+        assert!((running.totals.distance_m - (5000.0 + 5.0 * METERS_PER_MILE)).abs() < 1e-6);
+        assert_eq!(running.totals.elevation_gain_m, 40.0);
+        assert_eq!(running.totals.calories, 350);
+
+        let sports: Vec<&str> = breakdown
+            .by_sport
+            .iter()
+            .map(|s| s.sport.as_str())
+            .collect();
+        assert_eq!(sports, vec!["running", "other", "swimming", "cycling"]);
+        let swimming = &breakdown.by_sport[2].totals;
+        assert_eq!((swimming.duration_sec, swimming.distance_m), (1800, 1500.0));
+
+        assert_eq!(breakdown.by_modality.len(), 1);
+        let strength = &breakdown.by_modality[0];
+        assert_eq!(strength.modality, Modality::Strength);
+        assert!((strength.totals.volume_kg - 1000.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_rollups_streaks_and_consistency() {
+        let mut history = crate::history::parse(HISTORY).unwrap();
+        // Undated workouts count towards totals but not towards any period
+        let mut undated = history.workouts[1].clone();
+        undated.date = String::new();
+        history.workouts.push(undated);
+        let breakdown = history_statistics(&history).breakdown.unwrap();
+
+        let weeks: Vec<&str> = breakdown.weekly.iter().map(|p| p.period.as_str()).collect();
+        assert_eq!(weeks, vec!["2024-12-30", "2025-01-20"]);
+        assert_eq!(breakdown.weekly[0].totals.count, 3);
+        let months: Vec<&str> = breakdown
+            .monthly
+            .iter()
+            .map(|p| p.period.as_str())
+            .collect();
+        assert_eq!(months, vec!["2024-12", "2025-01"]);
+        assert_eq!(breakdown.yearly[1].period, "2025");
+        assert_eq!(breakdown.yearly[1].totals.count, 2);
+        assert_eq!(
+            breakdown.yearly[1].totals.distance_m,
+            5.0 * METERS_PER_MILE + 41500.0
+        );
+
+        let streaks = &breakdown.streaks;
+        assert_eq!((streaks.longest_days, streaks.current_days), (3, 1));
+        assert_eq!(streaks.longest_days_start.as_deref(), Some("2024-12-30"));
+        assert_eq!((streaks.longest_weeks, streaks.current_weeks), (1, 1));
+
+        let consistency = &breakdown.consistency;
+        assert_eq!(consistency.active_days, 4);
+        assert_eq!(consistency.total_days, 22);
+        assert_eq!((consistency.active_weeks, consistency.total_weeks), (2, 4));
+        assert_eq!(consistency.active_weeks_percent, 50.0);
+        assert_eq!(consistency.workouts_per_week, 1.0);
+        assert_eq!(consistency.longest_gap_days, 18);
+    }
+}
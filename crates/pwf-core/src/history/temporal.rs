@@ -0,0 +1,454 @@
+//! Temporal consistency of workouts, segments and telemetry streams
+//!
+//! Timestamps are compared as instants, so mixed UTC offsets are fine.
+//! Values that are not valid RFC 3339 timestamps are skipped here; the
+//! checks only look at times that can actually be placed on a clock.
+
+use super::error_codes;
+use super::types::{GpsRoute, SportSegment, TimeSeriesData, Workout, WpsHistory};
+use crate::analytics::series::parse_timestamp;
+use crate::error::ValidationIssue;
+
+/// Allowance for device clocks and rounding when comparing a time against
+/// the workout window or a recorded duration
+const CLOCK_SLACK_SEC: f64 = 60.0;
+
+/// Allowance when comparing consecutive sport segments
+const SEGMENT_SLACK_SEC: f64 = 1.0;
+
+/// Check the ordering of every timestamp in a history
+///
+/// Reports workouts ending before they start (PWF-H104), `duration_sec`
+/// disagreeing with `started_at`/`ended_at` (PWF-H105), sets completed
+/// outside the workout (PWF-H305), time series whose `timestamps` (PWF-H822)
+/// or `elapsed_sec` (PWF-H823) go backwards, overlapping sport segments
+/// (PWF-H843), segments extending past the workout (PWF-H844) and GPS
+/// positions out of order (PWF-H884). Workouts ending before they start and
+/// overlapping segments are errors; like other telemetry checks, the rest
+/// are warnings.
+pub fn check_temporal(history: &WpsHistory) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    for (workout_idx, workout) in history.workouts.iter().enumerate() {
+        let workout_path = format!("workouts[{}]", workout_idx);
+        let window = check_workout_window(&workout_path, workout, &mut issues);
+
+        for (ex_idx, exercise) in workout.exercises.iter().enumerate() {
+            for (set_idx, set) in exercise.sets.iter().enumerate() {
+                let set_path = format!("{}.exercises[{}].sets[{}]", workout_path, ex_idx, set_idx);
+
+                if let (Some(completed), Some((start, end))) = (
+                    set.completed_at.as_deref().and_then(parse_timestamp),
+                    window,
+                ) {
+                    let before = completed < start - CLOCK_SLACK_SEC;
+                    let after = end.is_some_and(|end| completed > end + CLOCK_SLACK_SEC);
+                    if before || after {
+                        issues.push(ValidationIssue::warning_with_code(
+                            format!("{}.completed_at", set_path),
+                            format!(
+                                "Set completed at {} is {} the workout",
+                                set.completed_at.as_deref().unwrap_or_default(),
+                                if before {
+                                    "before the start of"
+                                } else {
+                                    "after the end of"
+                                }
+                            ),
+                            error_codes::SET_COMPLETED_OUTSIDE_WORKOUT,
+                        ));
+                    }
+                }
+
+                if let Some(time_series) =
+                    set.telemetry.as_ref().and_then(|t| t.time_series.as_ref())
+                {
+                    check_time_series(
+                        &format!("{}.telemetry.time_series", set_path),
+                        time_series,
+                        &mut issues,
+                    );
+                }
+            }
+        }
+
+        if let Some(route) = workout
+            .telemetry
+            .as_ref()
+            .and_then(|t| t.gps_route.as_ref())
+        {
+            check_gps_route(
+                &format!("{}.telemetry.gps_route", workout_path),
+                route,
+                &mut issues,
+            );
+        }
+
+        if let Some(segments) = &workout.sport_segments {
+            for (idx, segment) in segments.iter().enumerate() {
+                if let Some(route) = segment
+                    .telemetry
+                    .as_ref()
+                    .and_then(|t| t.gps_route.as_ref())
+                {
+                    check_gps_route(
+                        &format!(
+                            "{}.sport_segments[{}].telemetry.gps_route",
+                            workout_path, idx
+                        ),
+                        route,
+                        &mut issues,
+                    );
+                }
+            }
+            check_segments(&workout_path, workout, segments, window, &mut issues);
+        }
+    }
+
+    issues
+}
+
+/// Check `started_at`, `ended_at` and `duration_sec` against each other and
+/// return the workout window: its start and, when known, its end
+fn check_workout_window(
+    path: &str,
+    workout: &Workout,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<(f64, Option<f64>)> {
+    let start = workout.started_at.as_deref().and_then(parse_timestamp)?;
+    let duration = workout.duration_sec.map(f64::from);
+
+    let Some(end) = workout.ended_at.as_deref().and_then(parse_timestamp) else {
+        return Some((start, duration.map(|d| start + d)));
+    };
+
+    if end < start {
+        issues.push(ValidationIssue::error_with_code(
+            format!("{}.ended_at", path),
+            format!(
+                "Workout ends at {} before it starts at {}",
+                workout.ended_at.as_deref().unwrap_or_default(),
+                workout.started_at.as_deref().unwrap_or_default()
+            ),
+            error_codes::WORKOUT_ENDS_BEFORE_START,
+        ));
+        return None;
+    }
+
+    if let Some(duration) = duration {
+        let span = end - start;
+        if (duration - span).abs() > CLOCK_SLACK_SEC {
+            issues.push(ValidationIssue::warning_with_code(
+                format!("{}.duration_sec", path),
+                format!(
+                    "duration_sec is {:.0} s but started_at to ended_at spans {:.0} s",
+                    duration, span
+                ),
+                error_codes::WORKOUT_DURATION_MISMATCH,
+            ));
+        }
+    }
+
+    Some((start, Some(end)))
+}
+
+/// Report the first place where a time series steps back in time
+fn check_time_series(path: &str, time_series: &TimeSeriesData, issues: &mut Vec<ValidationIssue>) {
+    let times: Vec<Option<f64>> = time_series
+        .timestamps
+        .iter()
+        .map(|t| parse_timestamp(t))
+        .collect();
+    if let Some(idx) = first_reversal(&times) {
+        issues.push(ValidationIssue::warning_with_code(
+            format!("{}.timestamps[{}]", path, idx),
+            format!(
+                "Timestamp {} is earlier than the one before it",
+                time_series.timestamps[idx]
+            ),
+            error_codes::TIMESTAMPS_NOT_MONOTONIC,
+        ));
+    }
+
+    if let Some(elapsed) = &time_series.elapsed_sec {
+        if let Some(idx) = elapsed.windows(2).position(|w| w[1] < w[0]) {
+            issues.push(ValidationIssue::warning_with_code(
+                format!("{}.elapsed_sec[{}]", path, idx + 1),
+                format!(
+                    "elapsed_sec goes back from {} to {}",
+                    elapsed[idx],
+                    elapsed[idx + 1]
+                ),
+                error_codes::ELAPSED_NOT_MONOTONIC,
+            ));
+        }
+    }
+}
+
+fn check_gps_route(path: &str, route: &GpsRoute, issues: &mut Vec<ValidationIssue>) {
+    let times: Vec<Option<f64>> = route
+        .positions
+        .iter()
+        .map(|p| parse_timestamp(&p.timestamp))
+        .collect();
+    if let Some(idx) = first_reversal(&times) {
+        issues.push(ValidationIssue::warning_with_code(
+            format!("{}.positions[{}].timestamp", path, idx),
+            format!(
+                "GPS position at {} is recorded before the position preceding it",
+                route.positions[idx].timestamp
+            ),
+            error_codes::GPS_POSITIONS_OUT_OF_ORDER,
+        ));
+    }
+}
+
+/// Check that segments follow each other and stay within the workout
+///
+/// Segments are ordered by `segment_index`. Timed segments are compared by
+/// their `started_at` and `duration_sec`; without start times, the summed
+/// segment and transition durations must still fit the workout duration.
+fn check_segments(
+    path: &str,
+    workout: &Workout,
+    segments: &[SportSegment],
+    window: Option<(f64, Option<f64>)>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let mut order: Vec<usize> = (0..segments.len()).collect();
+    order.sort_by_key(|&i| segments[i].segment_index);
+
+    let mut previous: Option<(usize, f64)> = None;
+    for &idx in &order {
+        let segment = &segments[idx];
+        let segment_path = format!("{}.sport_segments[{}]", path, idx);
+        let Some(start) = segment.started_at.as_deref().and_then(parse_timestamp) else {
+            continue;
+        };
+        let end = start + segment.duration_sec.map_or(0.0, f64::from);
+
+        if let Some((prev_idx, prev_end)) = previous {
+            if start < prev_end - SEGMENT_SLACK_SEC {
+                issues.push(ValidationIssue::error_with_code(
+                    format!("{}.started_at", segment_path),
+                    format!(
+                        "Segment '{}' starts {:.0} s before segment '{}' ends",
+                        segment.segment_id,
+                        prev_end - start,
+                        segments[prev_idx].segment_id
+                    ),
+                    error_codes::SEGMENT_OVERLAP,
+                ));
+            }
+        }
+        previous = Some((idx, end));
+
+        if let Some((workout_start, workout_end)) = window {
+            if start < workout_start - CLOCK_SLACK_SEC {
+                issues.push(ValidationIssue::warning_with_code(
+                    format!("{}.started_at", segment_path),
+                    format!("Segment '{}' starts before the workout", segment.segment_id),
+                    error_codes::SEGMENT_OUTSIDE_WORKOUT,
+                ));
+            } else if workout_end.is_some_and(|we| end > we + CLOCK_SLACK_SEC) {
+                issues.push(ValidationIssue::warning_with_code(
+                    format!("{}.duration_sec", segment_path),
+                    format!(
+                        "Segment '{}' runs past the end of the workout",
+                        segment.segment_id
+                    ),
+                    error_codes::SEGMENT_OUTSIDE_WORKOUT,
+                ));
+            }
+        }
+    }
+
+    let workout_duration = window
+        .and_then(|(start, end)| end.map(|end| end - start))
+        .or(workout.duration_sec.map(f64::from));
+    if let Some(workout_duration) = workout_duration {
+        let total: f64 = segments
+            .iter()
+            .map(|s| {
+                s.duration_sec.map_or(0.0, f64::from)
+                    + s.transition
+                        .as_ref()
+                        .and_then(|t| t.duration_sec)
+                        .map_or(0.0, f64::from)
+            })
+            .sum();
+        if total > workout_duration + CLOCK_SLACK_SEC {
+            issues.push(ValidationIssue::warning_with_code(
+                format!("{}.sport_segments", path),
+                format!(
+                    "Segments and transitions last {:.0} s, longer than the {:.0} s workout",
+                    total, workout_duration
+                ),
+                error_codes::SEGMENT_OUTSIDE_WORKOUT,
+            ));
+        }
+    }
+}
+
+/// Index of the first parsable time earlier than the latest parsable time
+/// before it
+fn first_reversal(times: &[Option<f64>]) -> Option<usize> {
+    let mut latest = f64::NEG_INFINITY;
+    for (idx, time) in times.iter().enumerate() {
+        let Some(time) = *time else { continue };
+        if time < latest {
+            return Some(idx);
+        }
+        latest = time;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(yaml: &str) -> Vec<ValidationIssue> {
+        check_temporal(&crate::history::parse(yaml).unwrap())
+    }
+
+    fn codes(issues: &[ValidationIssue]) -> Vec<&str> {
+        issues.iter().filter_map(|i| i.code.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_workout_window_checks() {
+        let issues = check(
+            r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    started_at: "2025-01-15T11:00:00Z"
+    ended_at: "2025-01-15T10:00:00Z"
+    exercises: [{ name: Squat, sets: [{ reps: 5 }] }]
+  - date: "2025-01-16"
+    started_at: "2025-01-16T10:00:00Z"
+    ended_at: "2025-01-16T11:00:00Z"
+    duration_sec: 1800
+    exercises:
+      - name: Squat
+        sets:
+          - { reps: 5, completed_at: "2025-01-16T10:10:00Z" }
+          - { reps: 5, completed_at: "2025-01-16T12:00:00Z" }
+  - date: "2025-01-17"
+    started_at: "2025-01-17T10:00:00+01:00"
+    ended_at: "2025-01-17T09:30:00Z"
+    duration_sec: 1800
+    exercises: [{ name: Squat, sets: [{ reps: 5 }] }]
+"#,
+        );
+        assert_eq!(
+            codes(&issues),
+            vec!["PWF-H104", "PWF-H105", "PWF-H305"],
+            "{:?}",
+            issues
+        );
+        assert_eq!(
+            issues[2].path,
+            "workouts[1].exercises[0].sets[1].completed_at"
+        );
+        assert!(issues[2].message.contains("after the end"));
+    }
+
+    #[test]
+    fn test_streams_going_backwards() {
+        let issues = check(
+            r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    telemetry:
+      gps_route:
+        route_id: r1
+        positions:
+          - { latitude_deg: 0.0, longitude_deg: 0.0, timestamp: "2025-01-15T10:00:05Z" }
+          - { latitude_deg: 0.0, longitude_deg: 0.0, timestamp: "2025-01-15T10:00:00Z" }
+    exercises:
+      - name: Ride
+        sets:
+          - duration_sec: 3
+            telemetry:
+              time_series:
+                timestamps: ["2025-01-15T10:00:00Z", "2025-01-15T10:00:02Z", "2025-01-15T10:00:01Z"]
+                elapsed_sec: [0, 2, 1]
+"#,
+        );
+        assert_eq!(codes(&issues), vec!["PWF-H822", "PWF-H823", "PWF-H884"]);
+        assert_eq!(
+            issues[0].path,
+            "workouts[0].exercises[0].sets[0].telemetry.time_series.timestamps[2]"
+        );
+        assert_eq!(
+            issues[1].path,
+            "workouts[0].exercises[0].sets[0].telemetry.time_series.elapsed_sec[2]"
+        );
+    }
+
+    #[test]
+    fn test_segments_overlapping_and_exceeding_workout() {
+        let issues = check(
+            r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    started_at: "2025-01-15T07:00:00Z"
+    ended_at: "2025-01-15T08:00:00Z"
+    exercises: []
+    sport_segments:
+      - segment_id: bike
+        sport: cycling
+        segment_index: 1
+        started_at: "2025-01-15T07:20:00Z"
+        duration_sec: 3000
+      - segment_id: swim
+        sport: swimming
+        segment_index: 0
+        started_at: "2025-01-15T07:00:00Z"
+        duration_sec: 1500
+"#,
+        );
+        assert_eq!(
+            codes(&issues),
+            vec!["PWF-H843", "PWF-H844", "PWF-H844"],
+            "{:?}",
+            issues
+        );
+        assert_eq!(issues[0].path, "workouts[0].sport_segments[0].started_at");
+        assert!(issues[0]
+            .message
+            .contains("'bike' starts 300 s before segment 'swim'"));
+    }
+
+    #[test]
+    fn test_consistent_times_pass() {
+        let issues = check(
+            r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    started_at: "2025-01-15T10:00:00Z"
+    ended_at: "2025-01-15T10:30:00Z"
+    duration_sec: 1790
+    exercises:
+      - name: Squat
+        sets:
+          - { reps: 5, completed_at: "2025-01-15T10:30:30Z" }
+          - duration_sec: 2
+            telemetry:
+              time_series:
+                timestamps: ["2025-01-15T10:00:00Z", "2025-01-15T10:00:00Z", "not a time", "2025-01-15T10:00:02Z"]
+                elapsed_sec: [0, 0, 1, 2]
+"#,
+        );
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+}
@@ -1,10 +1,11 @@
 //! History export type definitions
 
-use crate::{DistanceUnit, Modality, Sport, WeightUnit};
+use crate::{DistanceUnit, Extensions, Modality, Sport, WeightUnit, METERS_PER_YARD};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Root history export structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WpsHistory {
     pub history_version: u32,
     pub exported_at: String,
@@ -13,10 +14,13 @@ pub struct WpsHistory {
     #[serde(default)]
     pub units: Units,
     pub workouts: Vec<Workout>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub personal_records: Vec<PersonalRecord>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub body_measurements: Vec<BodyMeasurement>,
+    /// Vendor extension fields (`x-` prefixed keys)
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 impl WpsHistory {
@@ -27,7 +31,7 @@ impl WpsHistory {
 }
 
 /// Information about the exporting application
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ExportSource {
     #[serde(default)]
     pub app_name: Option<String>,
@@ -40,7 +44,7 @@ pub struct ExportSource {
 }
 
 /// Default units for the export
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Units {
     #[serde(default)]
     pub weight: WeightUnit,
@@ -58,7 +62,7 @@ impl Default for Units {
 }
 
 /// A completed workout session
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Workout {
     #[serde(default)]
     pub id: Option<String>,
@@ -80,7 +84,7 @@ pub struct Workout {
     pub exercises: Vec<CompletedExercise>,
     #[serde(default)]
     pub telemetry: Option<WorkoutTelemetry>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub devices: Vec<DeviceInfo>,
 
     // PWF v2.1: Sport tracking and multi-sport support
@@ -92,10 +96,13 @@ pub struct Workout {
     /// If present, this is a multi-sport session with distinct segments
     #[serde(default)]
     pub sport_segments: Option<Vec<SportSegment>>,
+    /// Vendor extension fields (`x-` prefixed keys)
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 /// Telemetry metrics for an entire workout session (PWF v2)
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct WorkoutTelemetry {
     // Heart Rate
     #[serde(default)]
@@ -188,7 +195,7 @@ pub struct WorkoutTelemetry {
 
 /// Advanced physiological and performance metrics
 /// Primarily from Garmin/Firstbeat algorithms
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct AdvancedMetrics {
     /// Training Effect score (0.0 - 5.0)
     /// Measures aerobic training stimulus
@@ -228,7 +235,7 @@ pub struct AdvancedMetrics {
 }
 
 /// Lactate threshold tracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LactateThreshold {
     /// Heart rate at lactate threshold (bpm)
     #[serde(default)]
@@ -248,7 +255,7 @@ pub struct LactateThreshold {
 }
 
 /// Training status classification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TrainingStatus {
     Detraining,
@@ -261,7 +268,7 @@ pub enum TrainingStatus {
 }
 
 /// Power-based cycling metrics (from power meter data)
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct PowerMetrics {
     /// Normalized Power (NP) - weighted average accounting for variability
     #[serde(default)]
@@ -306,10 +313,26 @@ pub struct PowerMetrics {
     /// Average right torque effectiveness (percentage)
     #[serde(default)]
     pub right_torque_effectiveness: Option<f64>,
+
+    /// Critical Power used for W' balance (watts)
+    #[serde(default)]
+    pub critical_power_watts: Option<u32>,
+
+    /// Anaerobic work capacity above CP (joules)
+    #[serde(default)]
+    pub w_prime_joules: Option<f64>,
+
+    /// Lowest W' balance reached during the workout (joules)
+    #[serde(default)]
+    pub min_w_prime_balance_joules: Option<f64>,
+
+    /// Number of distinct hard efforts that each drew down W' substantially
+    #[serde(default)]
+    pub matches_burned: Option<u32>,
 }
 
 /// Time spent in heart rate and power zones
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct TimeInZones {
     /// Time in each HR zone (seconds per zone)
     /// Zones typically: 1=easy, 2=moderate, 3=tempo, 4=threshold, 5=max
@@ -343,7 +366,7 @@ pub struct TimeInZones {
 // ============================================================================
 
 /// A segment within a multi-sport workout (e.g., swim/bike/run in triathlon)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SportSegment {
     /// Segment identifier
     pub segment_id: String,
@@ -367,7 +390,7 @@ pub struct SportSegment {
     pub distance_m: Option<f64>,
 
     /// Exercises/sets completed during this segment
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub exercise_ids: Vec<String>,
 
     /// Telemetry specific to this segment
@@ -384,7 +407,7 @@ pub struct SportSegment {
 }
 
 /// Transition between sports in a multi-sport event
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TransitionData {
     /// Transition identifier (e.g., "T1", "T2")
     pub transition_id: String,
@@ -417,7 +440,7 @@ pub struct TransitionData {
 // ============================================================================
 
 /// A single GPS position/waypoint with timestamp
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GpsPosition {
     /// Latitude in decimal degrees (WGS84)
     pub latitude_deg: f64,
@@ -462,7 +485,7 @@ pub struct GpsPosition {
 }
 
 /// A GPS route/track containing multiple positions
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GpsRoute {
     /// Unique identifier for this route
     pub route_id: String,
@@ -520,7 +543,7 @@ pub struct GpsRoute {
 }
 
 /// GPS fix quality indicator
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum GpsFix {
     /// No GPS fix
@@ -543,7 +566,7 @@ pub enum GpsFix {
 
 /// Columnar time-series data for second-by-second telemetry
 /// Uses columnar storage for better compression and flexibility
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct TimeSeriesData {
     /// Timestamps for each record (ISO 8601)
     /// All other arrays must match this length
@@ -713,6 +736,61 @@ impl TimeSeriesData {
         Ok(())
     }
 
+    /// Copy out the rows in `rows`
+    ///
+    /// Columns whose length does not cover the range are dropped.
+    /// `elapsed_sec` is rebased to start at zero; cumulative columns such as
+    /// `distance_m` keep their values.
+    pub fn slice(&self, rows: std::ops::Range<usize>) -> TimeSeriesData {
+        macro_rules! slice {
+            ($field:expr) => {
+                $field
+                    .as_ref()
+                    .and_then(|data| data.get(rows.clone()))
+                    .map(|data| data.to_vec())
+            };
+        }
+
+        let elapsed_sec = slice!(self.elapsed_sec).map(|elapsed: Vec<u32>| {
+            let start = elapsed.first().copied().unwrap_or(0);
+            elapsed.iter().map(|&e| e.saturating_sub(start)).collect()
+        });
+        TimeSeriesData {
+            timestamps: self
+                .timestamps
+                .get(rows.clone())
+                .unwrap_or_default()
+                .to_vec(),
+            elapsed_sec,
+            heart_rate: slice!(self.heart_rate),
+            power: slice!(self.power),
+            cadence: slice!(self.cadence),
+            speed_mps: slice!(self.speed_mps),
+            distance_m: slice!(self.distance_m),
+            elevation_m: slice!(self.elevation_m),
+            temperature_c: slice!(self.temperature_c),
+            latitude: slice!(self.latitude),
+            longitude: slice!(self.longitude),
+            grade_percent: slice!(self.grade_percent),
+            respiration_rate: slice!(self.respiration_rate),
+            core_temperature_c: slice!(self.core_temperature_c),
+            muscle_oxygen_percent: slice!(self.muscle_oxygen_percent),
+            power_balance: slice!(self.power_balance),
+            left_pedal_smoothness: slice!(self.left_pedal_smoothness),
+            right_pedal_smoothness: slice!(self.right_pedal_smoothness),
+            left_torque_effectiveness: slice!(self.left_torque_effectiveness),
+            right_torque_effectiveness: slice!(self.right_torque_effectiveness),
+            stride_length_m: slice!(self.stride_length_m),
+            vertical_oscillation_cm: slice!(self.vertical_oscillation_cm),
+            ground_contact_time_ms: slice!(self.ground_contact_time_ms),
+            ground_contact_balance: slice!(self.ground_contact_balance),
+            stroke_rate: slice!(self.stroke_rate),
+            stroke_count: slice!(self.stroke_count),
+            swolf: slice!(self.swolf),
+            stroke_type: slice!(self.stroke_type),
+        }
+    }
+
     /// Get the number of data points
     pub fn len(&self) -> usize {
         self.timestamps.len()
@@ -736,7 +814,7 @@ impl TimeSeriesData {
 }
 
 /// A completed exercise with recorded sets
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CompletedExercise {
     #[serde(default)]
     pub id: Option<String>,
@@ -754,10 +832,13 @@ pub struct CompletedExercise {
     // PWF v2.1: Sport classification (for multi-sport workouts)
     #[serde(default)]
     pub sport: Option<Sport>,
+    /// Vendor extension fields (`x-` prefixed keys)
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 /// A single completed set
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CompletedSet {
     #[serde(default)]
     pub set_number: Option<u32>,
@@ -789,10 +870,13 @@ pub struct CompletedSet {
     // PWF v2.1: Swimming-specific data
     #[serde(default)]
     pub swimming: Option<SwimmingSetData>,
+    /// Vendor extension fields (`x-` prefixed keys)
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 /// Telemetry metrics for a completed set (PWF v2)
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct SetTelemetry {
     // Heart Rate
     #[serde(default)]
@@ -869,10 +953,14 @@ pub struct SetTelemetry {
     // PWF v2.1: Second-by-second time-series data
     #[serde(default)]
     pub time_series: Option<TimeSeriesData>,
+
+    // Time in HR/power/pace zones for this set
+    #[serde(default)]
+    pub time_in_zones: Option<TimeInZones>,
 }
 
 /// Type of set (working, warmup, etc.)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum SetType {
     #[default]
@@ -888,7 +976,7 @@ pub enum SetType {
 // ============================================================================
 
 /// Swimming stroke type for pool and open water swimming
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum StrokeType {
     /// Freestyle/front crawl
@@ -909,7 +997,7 @@ pub enum StrokeType {
 }
 
 /// Unit for pool length measurement
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum PoolLengthUnit {
     #[default]
@@ -918,7 +1006,7 @@ pub enum PoolLengthUnit {
 }
 
 /// Pool configuration for swimming workouts
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PoolConfig {
     /// Length of the pool in the specified units
     pub pool_length: f64,
@@ -933,7 +1021,7 @@ impl PoolConfig {
     pub fn length_in_meters(&self) -> f64 {
         match self.pool_length_unit {
             PoolLengthUnit::Meters => self.pool_length,
-            PoolLengthUnit::Yards => self.pool_length * 0.9144, // 1 yard = 0.9144 meters
+            PoolLengthUnit::Yards => self.pool_length * METERS_PER_YARD,
         }
     }
 
@@ -963,7 +1051,7 @@ impl PoolConfig {
 }
 
 /// A single length (one pool length) within a swimming set/lap
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SwimmingLength {
     /// Length number within the set (1-indexed)
     pub length_number: u32,
@@ -1010,10 +1098,10 @@ impl SwimmingLength {
 }
 
 /// Swimming-specific data for a completed set
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
 pub struct SwimmingSetData {
     /// Individual lengths within this set/lap
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub lengths: Vec<SwimmingLength>,
 
     /// Primary stroke type for the set (if all lengths same stroke)
@@ -1068,7 +1156,7 @@ impl SwimmingSetData {
 }
 
 /// A personal record
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PersonalRecord {
     pub exercise_name: String,
     pub record_type: RecordType,
@@ -1083,7 +1171,7 @@ pub struct PersonalRecord {
 }
 
 /// Type of personal record
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum RecordType {
     #[serde(rename = "1rm")]
@@ -1105,7 +1193,7 @@ pub enum RecordType {
 }
 
 /// A body measurement entry
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BodyMeasurement {
     pub date: String,
     #[serde(default)]
@@ -1123,7 +1211,7 @@ pub struct BodyMeasurement {
 }
 
 /// Body dimension measurements
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct BodyDimensions {
     #[serde(default)]
     pub neck_cm: Option<f64>,
@@ -1164,10 +1252,90 @@ pub struct HistoryStatistics {
     pub date_range_end: Option<String>,
     pub personal_records_count: usize,
     pub body_measurements_count: usize,
+    /// Per-sport, per-modality and per-period breakdowns, filled in by
+    /// [`crate::history::history_statistics`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<StatisticsBreakdown>,
+}
+
+/// Detailed statistics over a whole history
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StatisticsBreakdown {
+    pub by_sport: Vec<SportTotals>,
+    pub by_modality: Vec<ModalityTotals>,
+    /// Monday-to-Sunday weeks with at least one workout
+    pub weekly: Vec<PeriodTotals>,
+    pub monthly: Vec<PeriodTotals>,
+    pub yearly: Vec<PeriodTotals>,
+    pub streaks: StreakStatistics,
+    pub consistency: ConsistencyStatistics,
+}
+
+/// Totals over a group of workouts (or exercises, for modalities)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ActivityTotals {
+    pub count: usize,
+    pub duration_sec: u64,
+    pub distance_m: f64,
+    pub elevation_gain_m: f64,
+    pub calories: u64,
+    pub volume_kg: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SportTotals {
+    /// Sport of the workout or multi-sport segment, `other` when unknown
+    pub sport: String,
+    #[serde(flatten)]
+    pub totals: ActivityTotals,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModalityTotals {
+    pub modality: Modality,
+    #[serde(flatten)]
+    pub totals: ActivityTotals,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeriodTotals {
+    /// Week start (`2025-03-03`), month (`2025-03`) or year (`2025`)
+    pub period: String,
+    #[serde(flatten)]
+    pub totals: ActivityTotals,
+}
+
+/// Runs of consecutive training days and weeks
+///
+/// The current streaks are those ending at the most recent workout.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StreakStatistics {
+    pub current_days: u32,
+    pub longest_days: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longest_days_start: Option<String>,
+    pub current_weeks: u32,
+    pub longest_weeks: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longest_weeks_start: Option<String>,
+}
+
+/// How regularly training happened between the first and last workout
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConsistencyStatistics {
+    pub active_days: u32,
+    /// Calendar days from the first to the last workout, inclusive
+    pub total_days: u32,
+    pub active_weeks: u32,
+    pub total_weeks: u32,
+    pub active_weeks_percent: f64,
+    pub workouts_per_week: f64,
+    /// Most days between two consecutive training days
+    pub longest_gap_days: u32,
 }
 
 /// Information about a device used during the workout
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DeviceInfo {
     /// Device index for multi-device workouts (e.g., 0=watch, 1=HRM, 2=power meter)
     #[serde(default)]
@@ -1203,7 +1371,7 @@ pub struct DeviceInfo {
 }
 
 /// Type of device
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum DeviceType {
     /// GPS sports watch
@@ -1233,7 +1401,7 @@ pub enum DeviceType {
 }
 
 /// Device manufacturer - supports both known manufacturers and custom strings
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Manufacturer {
     /// Known manufacturer from standard list
@@ -1243,7 +1411,7 @@ pub enum Manufacturer {
 }
 
 /// Well-known device manufacturers
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum KnownManufacturer {
     Garmin,
@@ -1267,7 +1435,7 @@ pub enum KnownManufacturer {
 }
 
 /// Battery information for a device
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BatteryInfo {
     /// Battery level at start of workout (percentage)
     #[serde(default)]
@@ -1284,7 +1452,7 @@ pub struct BatteryInfo {
 }
 
 /// Battery status indicator
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum BatteryStatus {
     /// Battery level is good
@@ -1300,7 +1468,7 @@ pub enum BatteryStatus {
 }
 
 /// Connection information for wireless sensors
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ConnectionInfo {
     /// Type of connection
     pub connection_type: ConnectionType,
@@ -1313,7 +1481,7 @@ pub struct ConnectionInfo {
 }
 
 /// Type of device connection
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ConnectionType {
     /// Local device (watch/bike computer itself)
@@ -1333,7 +1501,7 @@ pub enum ConnectionType {
 }
 
 /// Calibration information for sensors (e.g., power meters)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CalibrationInfo {
     /// Calibration factor or zero offset
     #[serde(default)]
@@ -1372,6 +1540,7 @@ mod tests {
             workouts: vec![],
             personal_records: vec![],
             body_measurements: vec![],
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string(&history).unwrap();
@@ -1396,6 +1565,7 @@ mod tests {
             workouts: vec![],
             personal_records: vec![],
             body_measurements: vec![],
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string(&history).unwrap();
@@ -1489,6 +1659,7 @@ mod tests {
             devices: vec![],
             sport: None,
             sport_segments: None,
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string(&workout).unwrap();
@@ -1517,6 +1688,7 @@ mod tests {
             devices: vec![],
             sport: None,
             sport_segments: None,
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string(&workout).unwrap();
@@ -1550,6 +1722,7 @@ mod tests {
                     completed_at: None,
                     telemetry: None,
                     swimming: None,
+                    extensions: Default::default(),
                 },
                 CompletedSet {
                     set_number: Some(2),
@@ -1566,10 +1739,12 @@ mod tests {
                     completed_at: Some("2025-01-15T10:30:00Z".to_string()),
                     telemetry: None,
                     swimming: None,
+                    extensions: Default::default(),
                 },
             ],
             pool_config: None,
             sport: None,
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string(&exercise).unwrap();
@@ -1600,6 +1775,7 @@ mod tests {
             completed_at: Some("2025-01-15T10:30:00Z".to_string()),
             telemetry: None,
             swimming: None,
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string(&set).unwrap();
@@ -1634,6 +1810,7 @@ mod tests {
             completed_at: None,
             telemetry: None,
             swimming: None,
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string(&set).unwrap();
@@ -1880,14 +2057,17 @@ mod tests {
                         completed_at: None,
                         telemetry: None,
                         swimming: None,
+                        extensions: Default::default(),
                     }],
                     pool_config: None,
                     sport: None,
+                    extensions: Default::default(),
                 }],
                 telemetry: None,
                 devices: vec![],
                 sport: None,
                 sport_segments: None,
+                extensions: Default::default(),
             }],
             personal_records: vec![PersonalRecord {
                 exercise_name: "Bench Press".to_string(),
@@ -1911,6 +2091,7 @@ mod tests {
                     ..Default::default()
                 }),
             }],
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string_pretty(&history).unwrap();
@@ -2237,6 +2418,25 @@ mod tests {
         assert_eq!(ts.duration_sec(), None);
     }
 
+    #[test]
+    fn test_time_series_slice() {
+        let ts = TimeSeriesData {
+            timestamps: vec!["a".into(), "b".into(), "c".into(), "d".into()],
+            elapsed_sec: Some(vec![0, 1, 2, 3]),
+            power: Some(vec![100, 200, 300, 400]),
+            distance_m: Some(vec![0.0, 5.0, 10.0, 15.0]),
+            heart_rate: Some(vec![120, 130]),
+            ..Default::default()
+        };
+
+        let slice = ts.slice(1..3);
+        assert_eq!(slice.timestamps, vec!["b", "c"]);
+        assert_eq!(slice.elapsed_sec, Some(vec![0, 1]));
+        assert_eq!(slice.power, Some(vec![200, 300]));
+        assert_eq!(slice.distance_m, Some(vec![5.0, 10.0]));
+        assert_eq!(slice.heart_rate, None);
+    }
+
     // ===== PWF v2.1 Advanced Metrics Tests =====
 
     #[test]
@@ -2309,14 +2509,17 @@ mod tests {
                         swolf_avg: Some(45),
                         drill_mode: Some(false),
                     }),
+                    extensions: Default::default(),
                 }],
                 pool_config: Some(PoolConfig::pool_25m()),
                 sport: Some(Sport::Swimming),
+                extensions: Default::default(),
             }],
             telemetry: None,
             devices: vec![],
             sport: Some(Sport::Swimming),
             sport_segments: None,
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string(&workout).unwrap();
@@ -2382,14 +2585,17 @@ mod tests {
                         ..Default::default()
                     }),
                     swimming: None,
+                    extensions: Default::default(),
                 }],
                 pool_config: None,
                 sport: Some(Sport::Cycling),
+                extensions: Default::default(),
             }],
             telemetry: None,
             devices: vec![],
             sport: Some(Sport::Cycling),
             sport_segments: None,
+            extensions: Default::default(),
         };
 
         let json = serde_json::to_string(&workout).unwrap();
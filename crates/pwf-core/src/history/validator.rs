@@ -1,10 +1,16 @@
 //! Validation rules for PWF history exports
 
 use super::error_codes;
-use super::parser::parse;
+use super::parser::{parse, parse_json};
+use super::statistics::calculate_statistics;
+use super::temporal::check_temporal;
 use super::types::{HistoryStatistics, RecordType, WpsHistory};
-use crate::error::ValidationIssue;
+use crate::analytics::{check_summaries, SummaryTolerances};
+use crate::error::{ParseError, Severity, ValidationIssue};
+use crate::fields::unknown_history_fields;
+use crate::schema::history_schema_issues;
 use crate::types::WeightUnit;
+use crate::ValidationOptions;
 
 /// Result of history validation
 #[derive(Debug, Clone, serde::Serialize)]
@@ -30,11 +36,39 @@ impl ValidationResult {
 
 /// Validate a YAML string as a PWF history export
 pub fn validate(yaml: &str) -> ValidationResult {
+    validate_with_options(yaml, &ValidationOptions::default())
+}
+
+/// Validate a YAML string as a PWF history export with custom options
+pub fn validate_with_options(yaml: &str, options: &ValidationOptions) -> ValidationResult {
+    let raw = serde_yaml::from_str::<serde_yaml::Value>(yaml).ok();
+    validate_parsed(parse(yaml), raw.as_ref(), options)
+}
+
+/// Validate a JSON string as a PWF history export
+pub fn validate_json(json: &str) -> ValidationResult {
+    validate_json_with_options(json, &ValidationOptions::default())
+}
+
+/// Validate a JSON string as a PWF history export with custom options
+pub fn validate_json_with_options(json: &str, options: &ValidationOptions) -> ValidationResult {
+    let raw = serde_json::from_str::<serde_json::Value>(json)
+        .ok()
+        .and_then(|value| serde_yaml::to_value(value).ok());
+    validate_parsed(parse_json(json), raw.as_ref(), options)
+}
+
+/// Shared validation for both input formats; `raw` is the untyped document
+/// used for unknown-field detection
+fn validate_parsed(
+    parsed: Result<WpsHistory, ParseError>,
+    raw: Option<&serde_yaml::Value>,
+    options: &ValidationOptions,
+) -> ValidationResult {
     let mut errors = Vec::new();
     let mut warnings = Vec::new();
 
-    // Parse YAML
-    let history = match parse(yaml) {
+    let history = match parsed {
         Ok(h) => h,
         Err(e) => {
             errors.push(ValidationIssue::error("", e.to_string()));
@@ -48,6 +82,33 @@ pub fn validate(yaml: &str) -> ValidationResult {
         }
     };
 
+    // Detect unknown and misspelled keys
+    if let Some(raw) = raw {
+        for unknown in unknown_history_fields(raw) {
+            if options.deny_unknown_fields {
+                errors.push(ValidationIssue::error_with_code(
+                    unknown.path.clone(),
+                    unknown.message(),
+                    error_codes::UNKNOWN_FIELD,
+                ));
+            } else {
+                warnings.push(ValidationIssue::warning_with_code(
+                    unknown.path.clone(),
+                    unknown.message(),
+                    error_codes::UNKNOWN_FIELD,
+                ));
+            }
+        }
+
+        // Schema violations count as errors. Documents that can't be
+        // expressed as JSON (non-string keys) are left to the checks below.
+        if options.check_schema {
+            if let Ok(document) = serde_json::to_value(raw) {
+                errors.extend(history_schema_issues(&document));
+            }
+        }
+    }
+
     // Validate history_version (support v1 and v2)
     if history.history_version != 1 && history.history_version != 2 {
         errors.push(ValidationIssue::error_with_code(
@@ -216,23 +277,21 @@ pub fn validate(yaml: &str) -> ValidationResult {
             | RecordType::MaxWeight5rm
             | RecordType::MaxWeight8rm
             | RecordType::MaxWeight10rm
-            | RecordType::MaxWeight => {
-                if pr.unit.is_none() {
-                    warnings.push(ValidationIssue::warning_with_code(
-                        format!("{}.unit", pr_path),
-                        "Weight-based personal records should specify a unit (kg or lb)",
-                        error_codes::PR_MISSING_UNIT,
-                    ));
-                }
+            | RecordType::MaxWeight
+                if pr.unit.is_none() =>
+            {
+                warnings.push(ValidationIssue::warning_with_code(
+                    format!("{}.unit", pr_path),
+                    "Weight-based personal records should specify a unit (kg or lb)",
+                    error_codes::PR_MISSING_UNIT,
+                ));
             }
-            RecordType::MaxDistance | RecordType::FastestTime => {
-                if pr.unit.is_none() {
-                    warnings.push(ValidationIssue::warning_with_code(
-                        format!("{}.unit", pr_path),
-                        "Distance/time personal records should specify appropriate units",
-                        error_codes::PR_MISSING_UNIT,
-                    ));
-                }
+            RecordType::MaxDistance | RecordType::FastestTime if pr.unit.is_none() => {
+                warnings.push(ValidationIssue::warning_with_code(
+                    format!("{}.unit", pr_path),
+                    "Distance/time personal records should specify appropriate units",
+                    error_codes::PR_MISSING_UNIT,
+                ));
             }
             _ => {}
         }
@@ -308,6 +367,23 @@ pub fn validate(yaml: &str) -> ValidationResult {
         }
     }
 
+    // Times that run backwards or fall outside their workout
+    for issue in check_temporal(&history) {
+        match issue.severity {
+            Severity::Error => errors.push(issue),
+            Severity::Warning => warnings.push(issue),
+        }
+    }
+
+    // Summaries that disagree with the time series or GPS route they came from
+    for discrepancy in check_summaries(&history, &SummaryTolerances::default()) {
+        warnings.push(ValidationIssue::warning_with_code(
+            discrepancy.path.clone(),
+            discrepancy.message(),
+            error_codes::TELEMETRY_SUMMARY_MISMATCH,
+        ));
+    }
+
     // Calculate statistics
     let statistics = if errors.is_empty() {
         Some(calculate_statistics(&history))
@@ -326,43 +402,6 @@ pub fn validate(yaml: &str) -> ValidationResult {
     }
 }
 
-fn calculate_statistics(history: &WpsHistory) -> HistoryStatistics {
-    let mut stats = HistoryStatistics {
-        total_workouts: history.workouts.len(),
-        personal_records_count: history.personal_records.len(),
-        body_measurements_count: history.body_measurements.len(),
-        ..Default::default()
-    };
-
-    let mut dates: Vec<&str> = Vec::new();
-
-    for workout in &history.workouts {
-        dates.push(&workout.date);
-        stats.total_exercises += workout.exercises.len();
-
-        for exercise in &workout.exercises {
-            stats.total_sets += exercise.sets.len();
-
-            for set in &exercise.sets {
-                if let (Some(reps), Some(weight)) = (set.reps, set.weight_kg) {
-                    stats.total_volume_kg += reps as f64 * weight;
-                }
-            }
-        }
-    }
-
-    // Get date range
-    dates.sort();
-    if let Some(first) = dates.first() {
-        stats.date_range_start = Some(first.to_string());
-    }
-    if let Some(last) = dates.last() {
-        stats.date_range_end = Some(last.to_string());
-    }
-
-    stats
-}
-
 fn validate_set_telemetry(
     path: &str,
     telemetry: &super::types::SetTelemetry,
@@ -1108,6 +1147,28 @@ workouts:
         assert_eq!(stats.total_volume_kg, 1000.0);
     }
 
+    #[test]
+    fn validate_statistics_counts_lb_volume() {
+        let yaml = r#"
+history_version: 1
+exported_at: "2025-01-15T10:30:00Z"
+units:
+  weight: lb
+workouts:
+  - date: "2025-01-15"
+    exercises:
+      - name: Squat
+        sets:
+          - reps: 5
+            weight_kg: 100
+          - reps: 5
+            weight_lb: 220.462
+"#;
+        let stats = validate(yaml).statistics.unwrap();
+        // Pound sets are converted to kg and counted alongside kg sets
+        assert!((stats.total_volume_kg - 1000.0).abs() < 1e-3);
+    }
+
     #[test]
     fn validate_rir_out_of_range() {
         let yaml = r#"
@@ -3035,4 +3096,50 @@ workouts:
             .iter()
             .any(|w| w.code == Some(error_codes::ZONE_ARRAY_LENGTH_MISMATCH.to_string())));
     }
+
+    #[test]
+    fn validate_temporal_issues() {
+        let yaml = r#"
+history_version: 2
+exported_at: "2025-01-20T00:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    started_at: "2025-01-15T11:00:00Z"
+    ended_at: "2025-01-15T10:00:00Z"
+    exercises:
+      - name: "Ride"
+        sets:
+          - duration_sec: 2
+            telemetry:
+              time_series:
+                timestamps: ["2025-01-15T10:00:01Z", "2025-01-15T10:00:00Z"]
+"#;
+        let result = validate(yaml);
+        assert!(!result.is_valid());
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.code == Some(error_codes::WORKOUT_ENDS_BEFORE_START.to_string())));
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.code == Some(error_codes::TIMESTAMPS_NOT_MONOTONIC.to_string())));
+    }
+
+    #[test]
+    fn validate_json_history_paths() {
+        let json = r#"{
+            "history_version": 1,
+            "exported_at": "2025-01-15T10:30:00Z",
+            "workouts": [{"date": "2025-01-15", "exercises": [
+                {"name": "Squat", "sets": [{"reps": 5, "weight_kg": 100, "rpe": 12}]}
+            ]}]
+        }"#;
+        let result = validate_json(json);
+        assert!(result.is_valid());
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.path == "workouts[0].exercises[0].sets[0].rpe"));
+    }
 }
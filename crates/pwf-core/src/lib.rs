@@ -48,9 +48,20 @@
 //! assert!(result.is_valid());
 //! ```
 
+pub mod analytics;
+pub mod compliance;
 pub mod error;
+pub mod extensions;
+mod fields;
 pub mod history;
+pub mod migration;
+pub mod options;
 pub mod plan;
+pub mod report;
+pub mod schema;
 mod types;
 
+pub use extensions::Extensions;
+pub use migration::{MigrationReport, MigrationStep};
+pub use options::ValidationOptions;
 pub use types::*;
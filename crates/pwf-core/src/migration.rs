@@ -0,0 +1,61 @@
+//! Shared report types for plan and history version migration
+//!
+//! The migrations themselves live in [`crate::plan::migrate`] and
+//! [`crate::history::migrate`].
+
+use serde::Serialize;
+
+/// A single transformation applied during migration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MigrationStep {
+    pub path: String,
+    pub message: String,
+}
+
+impl MigrationStep {
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Everything a migration did to a document
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Transformations that preserved the original data
+    pub changes: Vec<MigrationStep>,
+    /// Data that could not be represented in the target version and was dropped
+    pub lost: Vec<MigrationStep>,
+}
+
+impl MigrationReport {
+    pub(crate) fn new(from_version: u32, to_version: u32) -> Self {
+        Self {
+            from_version,
+            to_version,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn change(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.changes.push(MigrationStep::new(path, message));
+    }
+
+    pub(crate) fn lose(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.lost.push(MigrationStep::new(path, message));
+    }
+
+    /// Returns true if the document was already at the target version
+    pub fn is_noop(&self) -> bool {
+        self.changes.is_empty() && self.lost.is_empty()
+    }
+
+    /// Returns true if any data was dropped
+    pub fn is_lossy(&self) -> bool {
+        !self.lost.is_empty()
+    }
+}
@@ -0,0 +1,13 @@
+//! Options shared by plan and history validation
+
+/// Controls optional validation behaviour
+///
+/// The default matches [`crate::plan::validate`] and
+/// [`crate::history::validate`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ValidationOptions {
+    /// Report unknown or misspelled keys as errors instead of warnings
+    pub deny_unknown_fields: bool,
+    /// Also check the document against the bundled JSON Schema for its version
+    pub check_schema: bool,
+}
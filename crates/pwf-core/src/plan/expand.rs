@@ -0,0 +1,267 @@
+//! Expansion of a plan into the concrete sessions it prescribes
+//!
+//! A plan with `cycle.weeks` repeats its days once per week, applying each
+//! week's exercise overrides; a plan without weeks prescribes each day once.
+//! Templates and library references are resolved along the way.
+
+use super::resolver::{resolve_day, resolve_exercise};
+use super::types::{ExerciseOverride, PlanExercise, WpsPlan};
+use crate::{Modality, LB_TO_KG};
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+
+/// One exercise as prescribed for a specific session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedExercise {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modality: Option<Modality>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_sets: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_reps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_duration_sec: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_distance_meters: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_load: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_weight_percent: Option<f64>,
+}
+
+impl PlannedExercise {
+    /// The prescribed load in kg, when `target_load` is an absolute weight
+    /// such as `"100kg"` or `"225 lbs"`
+    pub fn target_load_kg(&self) -> Option<f64> {
+        parse_load_kg(self.target_load.as_deref()?)
+    }
+}
+
+/// One day of the plan in one week
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedSession {
+    /// 1-based week; always 1 for plans without `cycle.weeks`
+    pub week: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_id: Option<String>,
+    /// Position of the day in `cycle.days`
+    pub day_index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus: Option<String>,
+    /// From the day's `scheduled_date`, shifted by a week per week
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_date: Option<NaiveDate>,
+    pub exercises: Vec<PlannedExercise>,
+}
+
+/// Expand a plan into its sessions, week by week in day order
+pub fn expand_plan(plan: &WpsPlan) -> Vec<PlannedSession> {
+    let weeks: Vec<(u32, &[ExerciseOverride])> = match &plan.cycle.weeks {
+        Some(weeks) if !weeks.is_empty() => weeks
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                (
+                    w.week_number.unwrap_or(i as u32 + 1),
+                    w.overrides.as_slice(),
+                )
+            })
+            .collect(),
+        _ => vec![(1, &[][..])],
+    };
+
+    let mut sessions = Vec::new();
+    for (week_index, (week, overrides)) in weeks.iter().enumerate() {
+        for (day_index, day) in plan.cycle.days.iter().enumerate() {
+            let resolved = resolve_day(day, &plan.workout_templates);
+            let exercises = resolved
+                .exercises
+                .iter()
+                .map(|exercise| {
+                    let mut planned = planned_exercise(exercise, plan);
+                    for o in overrides.iter() {
+                        if override_applies(o, &planned, day.id.as_deref(), day.order) {
+                            apply_override(&mut planned, o);
+                        }
+                    }
+                    planned
+                })
+                .collect();
+            let scheduled_date = day
+                .scheduled_date
+                .as_deref()
+                .and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d").ok())
+                .map(|d| d + Duration::weeks(week_index as i64));
+            sessions.push(PlannedSession {
+                week: *week,
+                day_id: day.id.clone(),
+                day_index,
+                focus: resolved.focus,
+                scheduled_date,
+                exercises,
+            });
+        }
+    }
+    sessions
+}
+
+fn planned_exercise(exercise: &PlanExercise, plan: &WpsPlan) -> PlannedExercise {
+    match resolve_exercise(exercise, &plan.exercise_library) {
+        Some(resolved) => PlannedExercise {
+            id: resolved.id,
+            name: resolved.name,
+            modality: Some(resolved.modality),
+            target_sets: resolved.target_sets,
+            target_reps: resolved.target_reps,
+            target_duration_sec: resolved.target_duration_sec,
+            target_distance_meters: resolved.target_distance_meters,
+            target_load: resolved.target_load,
+            target_weight_percent: resolved.target_weight_percent,
+        },
+        // Unresolvable exercises (no modality, unknown reference) still
+        // count as prescribed work
+        None => PlannedExercise {
+            id: exercise.id.clone(),
+            name: exercise
+                .name
+                .clone()
+                .or_else(|| exercise.exercise_ref.clone())
+                .unwrap_or_else(|| "Unnamed Exercise".to_string()),
+            modality: exercise.modality,
+            target_sets: exercise.target_sets,
+            target_reps: exercise.target_reps,
+            target_duration_sec: exercise.target_duration_sec,
+            target_distance_meters: exercise.target_distance_meters,
+            target_load: exercise.target_load.clone(),
+            target_weight_percent: exercise.target_weight_percent,
+        },
+    }
+}
+
+fn override_applies(
+    o: &ExerciseOverride,
+    exercise: &PlannedExercise,
+    day_id: Option<&str>,
+    day_order: Option<u32>,
+) -> bool {
+    let exercise_matches = match (&o.exercise_id, &o.exercise_name) {
+        (Some(id), _) => exercise.id.as_deref() == Some(id.as_str()),
+        (None, Some(name)) => exercise.name.eq_ignore_ascii_case(name),
+        (None, None) => false,
+    };
+    let day_matches = o.day_id.as_deref().is_none_or(|id| day_id == Some(id))
+        && o.day_order.is_none_or(|order| day_order == Some(order));
+    exercise_matches && day_matches
+}
+
+fn apply_override(exercise: &mut PlannedExercise, o: &ExerciseOverride) {
+    exercise.target_sets = o.target_sets.or(exercise.target_sets);
+    exercise.target_reps = o.target_reps.or(exercise.target_reps);
+    exercise.target_duration_sec = o.target_duration_sec.or(exercise.target_duration_sec);
+    exercise.target_distance_meters = o.target_distance_meters.or(exercise.target_distance_meters);
+    // Load and percentage are alternatives, so setting one clears the other
+    if o.target_load.is_some() {
+        exercise.target_load = o.target_load.clone();
+        exercise.target_weight_percent = None;
+    } else if o.target_weight_percent.is_some() {
+        exercise.target_weight_percent = o.target_weight_percent;
+        exercise.target_load = None;
+    }
+}
+
+/// Parse an absolute load like `"100kg"`, `"100 kg"` or `"225 lbs"` into kg
+pub fn parse_load_kg(load: &str) -> Option<f64> {
+    let load = load.trim().to_ascii_lowercase();
+    let split = load
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(load.len());
+    let value: f64 = load[..split].parse().ok()?;
+    match load[split..].trim() {
+        "kg" | "kgs" => Some(value),
+        "lb" | "lbs" => Some(value * LB_TO_KG),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_load_kg() {
+        assert_eq!(parse_load_kg("100kg"), Some(100.0));
+        assert_eq!(parse_load_kg(" 62.5 KG "), Some(62.5));
+        assert!((parse_load_kg("225 lbs").unwrap() - 102.058).abs() < 0.001);
+        assert_eq!(parse_load_kg("RPE 8"), None);
+        assert_eq!(parse_load_kg("70% 1RM"), None);
+        assert_eq!(parse_load_kg("100"), None);
+    }
+
+    #[test]
+    fn test_expand_applies_weekly_overrides() {
+        let plan = crate::plan::parse(
+            r#"
+plan_version: 2
+exercise_library:
+  - id: squat-lib
+    name: Back Squat
+    modality: strength
+    default_sets: 3
+    default_reps: 5
+cycle:
+  weeks:
+    - week_number: 1
+    - week_number: 2
+      overrides:
+        - exercise_id: squat
+          target_sets: 5
+          target_load: "110kg"
+        - exercise_name: bench press
+          day_id: other-day
+          target_sets: 9
+  days:
+    - id: day-a
+      scheduled_date: "2025-01-06"
+      exercises:
+        - id: squat
+          exercise_ref: squat-lib
+          target_load: "100kg"
+        - name: Bench Press
+          modality: strength
+          target_sets: 3
+"#,
+        )
+        .unwrap();
+        let sessions = expand_plan(&plan);
+        assert_eq!(sessions.len(), 2);
+
+        let week1 = &sessions[0].exercises[0];
+        assert_eq!(week1.name, "Back Squat");
+        assert_eq!(week1.target_sets, Some(3));
+        assert_eq!(week1.target_load_kg(), Some(100.0));
+
+        let week2 = &sessions[1];
+        assert_eq!(week2.week, 2);
+        assert_eq!(week2.scheduled_date, NaiveDate::from_ymd_opt(2025, 1, 13));
+        assert_eq!(week2.exercises[0].target_sets, Some(5));
+        assert_eq!(week2.exercises[0].target_reps, Some(5));
+        assert_eq!(week2.exercises[0].target_load_kg(), Some(110.0));
+        // The bench override is for a different day
+        assert_eq!(week2.exercises[1].target_sets, Some(3));
+    }
+
+    #[test]
+    fn test_expand_without_weeks() {
+        let plan = crate::plan::parse(
+            "plan_version: 1\ncycle:\n  days:\n    - exercises:\n        - name: Run\n          modality: countdown\n    - exercises: []\n",
+        )
+        .unwrap();
+        let sessions = expand_plan(&plan);
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.iter().all(|s| s.week == 1));
+        assert_eq!(sessions[1].day_index, 1);
+    }
+}
@@ -0,0 +1,542 @@
+//! Version migration for PWF plans
+//!
+//! Upgrading from v1 lifts exercises that are defined inline more than once
+//! into `exercise_library`. Downgrading to v1 inlines library references and
+//! workout templates, and reports the v2-only data that had to be dropped.
+
+use super::parser::parse;
+use super::resolver::{resolve_day, resolve_exercise};
+use super::types::{Equipment, LibraryExercise, WpsPlan};
+use crate::error::MigrationError;
+use crate::migration::MigrationReport;
+use crate::Modality;
+use std::collections::{HashMap, HashSet};
+
+/// Newest `plan_version` produced by this crate
+pub const LATEST_PLAN_VERSION: u32 = 2;
+
+/// A migrated plan and the report of what was done to it
+#[derive(Debug, Clone)]
+pub struct PlanMigration {
+    pub plan: WpsPlan,
+    pub report: MigrationReport,
+}
+
+/// Parse a YAML plan and migrate it to `target_version`
+pub fn migrate(yaml: &str, target_version: u32) -> Result<PlanMigration, MigrationError> {
+    migrate_plan(parse(yaml)?, target_version)
+}
+
+/// Migrate an already parsed plan to `target_version`
+pub fn migrate_plan(
+    mut plan: WpsPlan,
+    target_version: u32,
+) -> Result<PlanMigration, MigrationError> {
+    let from = plan.plan_version;
+    let mut report = MigrationReport::new(from, target_version);
+
+    match (from, target_version) {
+        (1, 1) | (2, 2) => {}
+        (1, 2) => upgrade_v1_to_v2(&mut plan, &mut report),
+        (2, 1) => downgrade_v2_to_v1(&mut plan, &mut report),
+        _ => {
+            return Err(MigrationError::UnsupportedVersion {
+                from,
+                to: target_version,
+            })
+        }
+    }
+
+    Ok(PlanMigration { plan, report })
+}
+
+fn upgrade_v1_to_v2(plan: &mut WpsPlan, report: &mut MigrationReport) {
+    plan.plan_version = 2;
+    report.change("plan_version", "Set plan_version to 2");
+
+    if !plan.exercise_library.is_empty() {
+        report.change(
+            "exercise_library",
+            "exercise_library was ignored in plan_version 1 and is now active",
+        );
+    }
+    if !plan.workout_templates.is_empty() {
+        report.change(
+            "workout_templates",
+            "workout_templates was ignored in plan_version 1 and is now active",
+        );
+    }
+
+    lift_repeated_exercises(plan, report);
+}
+
+type ExerciseKey = (String, Modality);
+
+/// Move exercises defined inline on more than one day into `exercise_library`
+fn lift_repeated_exercises(plan: &mut WpsPlan, report: &mut MigrationReport) {
+    // Week overrides may match exercises by name, so those names must stay inline
+    let override_names: HashSet<String> = plan
+        .cycle
+        .weeks
+        .iter()
+        .flatten()
+        .flat_map(|week| &week.overrides)
+        .filter_map(|o| o.exercise_name.as_deref())
+        .map(normalize_name)
+        .collect();
+
+    // Exercises grouped by (normalized name, modality), as (day, exercise) indices
+    let mut groups: Vec<(ExerciseKey, Vec<(usize, usize)>)> = Vec::new();
+    let mut group_index: HashMap<ExerciseKey, usize> = HashMap::new();
+    for (day_idx, day) in plan.cycle.days.iter().enumerate() {
+        for (ex_idx, exercise) in day.exercises.iter().enumerate() {
+            if exercise.exercise_ref.is_some() {
+                continue;
+            }
+            let (Some(name), Some(modality)) = (&exercise.name, exercise.modality) else {
+                continue;
+            };
+            let key = (normalize_name(name), modality);
+            let idx = *group_index.entry(key.clone()).or_insert_with(|| {
+                groups.push((key, Vec::new()));
+                groups.len() - 1
+            });
+            groups[idx].1.push((day_idx, ex_idx));
+        }
+    }
+
+    let mut used_ids: HashSet<String> = plan
+        .exercise_library
+        .iter()
+        .map(|lib| lib.id.clone())
+        .collect();
+
+    for ((key, modality), occurrences) in groups {
+        if occurrences.len() < 2 {
+            continue;
+        }
+
+        let exercises: Vec<_> = occurrences
+            .iter()
+            .map(|&(d, e)| &plan.cycle.days[d].exercises[e])
+            .collect();
+        let name = exercises[0].name.clone().unwrap_or_default();
+        let cues = shared(exercises.iter().map(|ex| &ex.cues));
+        let link = shared(exercises.iter().map(|ex| &ex.link));
+        let image = shared(exercises.iter().map(|ex| &ex.image));
+        let equipment = shared(exercises.iter().map(|ex| &ex.equipment));
+
+        let id = unique_id(&slugify(&name), &mut used_ids);
+        let library_idx = plan.exercise_library.len();
+        plan.exercise_library.push(LibraryExercise {
+            id: id.clone(),
+            name: name.clone(),
+            description: None,
+            equipment: equipment.map(equipment_tag).into_iter().collect(),
+            muscle_groups: Vec::new(),
+            difficulty: None,
+            modality,
+            // Occurrences keep their own targets; the first one seeds the defaults
+            default_sets: exercises[0].target_sets,
+            default_reps: exercises[0].target_reps,
+            default_duration_sec: exercises[0].target_duration_sec,
+            default_distance_meters: exercises[0].target_distance_meters,
+            cues: cues.clone(),
+            link: link.clone(),
+            image: image.clone(),
+        });
+        report.change(
+            format!("exercise_library[{}]", library_idx),
+            format!(
+                "Added '{}' to exercise_library (defined inline {} times)",
+                id,
+                occurrences.len()
+            ),
+        );
+
+        for (day_idx, ex_idx) in occurrences {
+            let exercise = &mut plan.cycle.days[day_idx].exercises[ex_idx];
+            exercise.exercise_ref = Some(id.clone());
+            exercise.modality = None;
+            if exercise.name.as_deref() == Some(name.as_str()) && !override_names.contains(&key) {
+                exercise.name = None;
+            }
+            if cues.is_some() {
+                exercise.cues = None;
+            }
+            if link.is_some() {
+                exercise.link = None;
+            }
+            if image.is_some() {
+                exercise.image = None;
+            }
+            if equipment.is_some() {
+                exercise.equipment = None;
+            }
+            report.change(
+                format!("cycle.days[{}].exercises[{}]", day_idx, ex_idx),
+                format!("Replaced inline definition with exercise_ref '{}'", id),
+            );
+        }
+    }
+}
+
+fn downgrade_v2_to_v1(plan: &mut WpsPlan, report: &mut MigrationReport) {
+    plan.plan_version = 1;
+    report.change("plan_version", "Set plan_version to 1");
+
+    let templates = std::mem::take(&mut plan.workout_templates);
+    let mut used_templates = HashSet::new();
+    for (day_idx, day) in plan.cycle.days.iter_mut().enumerate() {
+        let Some(template_ref) = day.template_ref.clone() else {
+            continue;
+        };
+        let path = format!("cycle.days[{}]", day_idx);
+        if templates.iter().any(|t| t.id == template_ref) {
+            let resolved = resolve_day(day, &templates);
+            let inlined = resolved.exercises.len() - day.exercises.len();
+            day.exercises = resolved.exercises;
+            day.focus = resolved.focus;
+            day.target_session_length_min = resolved.target_session_length_min;
+            used_templates.insert(template_ref.clone());
+            report.change(
+                path,
+                format!(
+                    "Inlined {} exercise(s) from workout template '{}'",
+                    inlined, template_ref
+                ),
+            );
+        } else {
+            report.lose(
+                format!("{}.template_ref", path),
+                format!(
+                    "template_ref '{}' does not match any workout template",
+                    template_ref
+                ),
+            );
+        }
+        day.template_ref = None;
+    }
+    for (idx, template) in templates.iter().enumerate() {
+        let message = if used_templates.contains(&template.id) {
+            format!(
+                "Removed workout template '{}'; its name, description and tags have no v1 equivalent",
+                template.id
+            )
+        } else {
+            format!("Removed unused workout template '{}'", template.id)
+        };
+        report.lose(format!("workout_templates[{}]", idx), message);
+    }
+
+    let library = std::mem::take(&mut plan.exercise_library);
+    let mut used_library = HashSet::new();
+    for (day_idx, day) in plan.cycle.days.iter_mut().enumerate() {
+        for (ex_idx, exercise) in day.exercises.iter_mut().enumerate() {
+            let path = format!("cycle.days[{}].exercises[{}]", day_idx, ex_idx);
+
+            if let Some(exercise_ref) = exercise.exercise_ref.clone() {
+                match resolve_exercise(exercise, &library) {
+                    Some(resolved) => {
+                        exercise.name = Some(resolved.name);
+                        exercise.modality = Some(resolved.modality);
+                        exercise.target_sets = resolved.target_sets;
+                        exercise.target_reps = resolved.target_reps;
+                        exercise.target_duration_sec = resolved.target_duration_sec;
+                        exercise.target_distance_meters = resolved.target_distance_meters;
+                        exercise.cues = resolved.cues;
+                        exercise.link = resolved.link;
+                        exercise.image = resolved.image;
+                        exercise.equipment = resolved.equipment;
+                        used_library.insert(exercise_ref.clone());
+                        report.change(
+                            &path,
+                            format!("Inlined exercise_library entry '{}'", exercise_ref),
+                        );
+                    }
+                    None => report.lose(
+                        format!("{}.exercise_ref", path),
+                        format!(
+                            "exercise_ref '{}' does not match any exercise_library entry",
+                            exercise_ref
+                        ),
+                    ),
+                }
+                exercise.exercise_ref = None;
+            }
+
+            if exercise.progression_rules.take().is_some() {
+                report.lose(
+                    format!("{}.progression_rules", path),
+                    "progression_rules are not supported in plan_version 1",
+                );
+            }
+        }
+    }
+
+    for (idx, entry) in library.iter().enumerate() {
+        let path = format!("exercise_library[{}]", idx);
+        if !used_library.contains(&entry.id) {
+            report.lose(
+                path,
+                format!("Removed unused exercise_library entry '{}'", entry.id),
+            );
+            continue;
+        }
+
+        let mut dropped = Vec::new();
+        if entry.description.is_some() {
+            dropped.push("description");
+        }
+        if !entry.muscle_groups.is_empty() {
+            dropped.push("muscle_groups");
+        }
+        if entry.difficulty.is_some() {
+            dropped.push("difficulty");
+        }
+        if !dropped.is_empty() {
+            report.lose(
+                path,
+                format!(
+                    "Dropped {} of exercise_library entry '{}'",
+                    dropped.join(", "),
+                    entry.id
+                ),
+            );
+        }
+    }
+}
+
+fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Returns the common value if every item is the same `Some`
+fn shared<'a, T: PartialEq + Clone + 'a>(
+    mut values: impl Iterator<Item = &'a Option<T>>,
+) -> Option<T> {
+    let first = values.next()?.as_ref()?;
+    values
+        .all(|v| v.as_ref() == Some(first))
+        .then(|| first.clone())
+}
+
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "exercise".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+fn unique_id(base: &str, used: &mut HashSet<String>) -> String {
+    let mut id = base.to_string();
+    let mut n = 2;
+    while used.contains(&id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    used.insert(id.clone());
+    id
+}
+
+fn equipment_tag(equipment: Equipment) -> String {
+    match equipment {
+        Equipment::Barbell => "barbell",
+        Equipment::Dumbbell => "dumbbell",
+        Equipment::Kettlebell => "kettlebell",
+        Equipment::Bodyweight => "bodyweight",
+        Equipment::Cable => "cable",
+        Equipment::Machine => "machine",
+        Equipment::ResistanceBand => "resistance_band",
+        Equipment::Other => "other",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::validate;
+
+    const V1_PLAN: &str = r#"
+plan_version: 1
+meta:
+  title: Full body
+cycle:
+  days:
+    - focus: A
+      exercises:
+        - name: Squat
+          modality: strength
+          equipment: barbell
+          cues: Brace
+          target_sets: 5
+          target_reps: 5
+        - name: Plank
+          modality: countdown
+          target_duration_sec: 60
+    - focus: B
+      exercises:
+        - name: squat
+          modality: strength
+          equipment: barbell
+          cues: Brace
+          target_sets: 3
+          target_reps: 8
+"#;
+
+    #[test]
+    fn test_upgrade_lifts_repeated_exercises() {
+        let migration = migrate(V1_PLAN, 2).unwrap();
+        let plan = &migration.plan;
+
+        assert_eq!(plan.plan_version, 2);
+        assert_eq!(plan.exercise_library.len(), 1);
+        let lib = &plan.exercise_library[0];
+        assert_eq!(lib.id, "squat");
+        assert_eq!(lib.equipment, vec!["barbell".to_string()]);
+        assert_eq!(lib.cues.as_deref(), Some("Brace"));
+        assert_eq!(lib.default_sets, Some(5));
+
+        let first = &plan.cycle.days[0].exercises[0];
+        assert_eq!(first.exercise_ref.as_deref(), Some("squat"));
+        assert!(first.name.is_none() && first.modality.is_none());
+        assert_eq!(first.target_sets, Some(5));
+        // Different capitalisation keeps its own display name
+        assert_eq!(
+            plan.cycle.days[1].exercises[0].name.as_deref(),
+            Some("squat")
+        );
+        // Plank only appears once
+        assert!(plan.cycle.days[0].exercises[1].exercise_ref.is_none());
+
+        // plan_version + library entry + two references
+        assert_eq!(migration.report.changes.len(), 4);
+        assert!(!migration.report.is_lossy());
+
+        let yaml = serde_yaml::to_string(plan).unwrap();
+        let result = validate(&yaml);
+        assert!(result.is_valid(), "{:?}", result.errors);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    }
+
+    #[test]
+    fn test_upgrade_keeps_names_used_by_week_overrides() {
+        let yaml = format!(
+            "{}  weeks:\n    - week_number: 2\n      overrides:\n        - exercise_name: Squat\n          target_sets: 4\n",
+            V1_PLAN
+        );
+        let plan = migrate(&yaml, 2).unwrap().plan;
+        assert_eq!(
+            plan.cycle.days[0].exercises[0].name.as_deref(),
+            Some("Squat")
+        );
+    }
+
+    #[test]
+    fn test_same_version_is_noop() {
+        let migration = migrate(V1_PLAN, 1).unwrap();
+        assert!(migration.report.is_noop());
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        assert!(matches!(
+            migrate(V1_PLAN, 3),
+            Err(MigrationError::UnsupportedVersion { from: 1, to: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_downgrade_inlines_and_reports_losses() {
+        let yaml = r#"
+plan_version: 2
+meta:
+  title: Upper
+exercise_library:
+  - id: bench
+    name: Bench Press
+    modality: strength
+    description: Flat bench
+    default_sets: 3
+    default_reps: 10
+  - id: curl
+    name: Curl
+    modality: strength
+workout_templates:
+  - id: upper
+    name: Upper
+    focus: Upper body
+    exercises:
+      - exercise_ref: bench
+        target_reps: 8
+cycle:
+  days:
+    - template_ref: upper
+      exercises:
+        - name: Row
+          modality: strength
+          target_sets: 3
+          target_reps: 10
+          progression_rules:
+            type: linear
+            success_condition: all_sets_completed
+            weight_increment_kg: 2.5
+"#;
+        let migration = migrate(yaml, 1).unwrap();
+        let plan = &migration.plan;
+        let day = &plan.cycle.days[0];
+
+        assert_eq!(plan.plan_version, 1);
+        assert!(plan.exercise_library.is_empty());
+        assert!(plan.workout_templates.is_empty());
+        assert!(day.template_ref.is_none());
+        assert_eq!(day.focus.as_deref(), Some("Upper body"));
+        assert_eq!(day.exercises.len(), 2);
+
+        let bench = &day.exercises[0];
+        assert_eq!(bench.name.as_deref(), Some("Bench Press"));
+        assert_eq!(bench.modality, Some(Modality::Strength));
+        assert_eq!(bench.target_sets, Some(3));
+        assert_eq!(bench.target_reps, Some(8));
+        assert!(day.exercises[1].progression_rules.is_none());
+
+        let lost: Vec<&str> = migration
+            .report
+            .lost
+            .iter()
+            .map(|s| s.path.as_str())
+            .collect();
+        assert_eq!(
+            lost,
+            vec![
+                "workout_templates[0]",
+                "cycle.days[0].exercises[1].progression_rules",
+                "exercise_library[0]",
+                "exercise_library[1]",
+            ]
+        );
+
+        let out = serde_yaml::to_string(plan).unwrap();
+        let result = validate(&out);
+        assert!(result.is_valid(), "{:?}", result.errors);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Barbell Back Squat"), "barbell-back-squat");
+        assert_eq!(slugify("  Pull-up (weighted) "), "pull-up-weighted");
+        assert_eq!(slugify("!!"), "exercise");
+    }
+}
@@ -2,12 +2,18 @@
 //!
 //! This module handles PWF plan documents (workout templates).
 
+pub mod expand;
+pub mod migrate;
 mod parser;
 pub mod resolver;
 mod types;
 mod validator;
 
-pub use parser::parse;
+pub use expand::{expand_plan, parse_load_kg, PlannedExercise, PlannedSession};
+pub use migrate::{migrate, migrate_plan, PlanMigration, LATEST_PLAN_VERSION};
+pub use parser::{parse, parse_json, to_json, to_yaml};
 pub use resolver::{resolve_exercise, ResolvedExercise};
 pub use types::*;
-pub use validator::{validate, ValidationResult};
+pub use validator::{
+    validate, validate_json, validate_json_with_options, validate_with_options, ValidationResult,
+};
@@ -1,4 +1,4 @@
-//! YAML parsing for PWF plans
+//! YAML and JSON parsing for PWF plans
 
 use super::types::WpsPlan;
 use crate::error::ParseError;
@@ -9,6 +9,22 @@ pub fn parse(yaml: &str) -> Result<WpsPlan, ParseError> {
     Ok(plan)
 }
 
+/// Parse a JSON string into a WpsPlan
+pub fn parse_json(json: &str) -> Result<WpsPlan, ParseError> {
+    let plan: WpsPlan = serde_json::from_str(json)?;
+    Ok(plan)
+}
+
+/// Serialize a WpsPlan to YAML
+pub fn to_yaml(plan: &WpsPlan) -> Result<String, serde_yaml::Error> {
+    serde_yaml::to_string(plan)
+}
+
+/// Serialize a WpsPlan to pretty-printed JSON
+pub fn to_json(plan: &WpsPlan) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(plan)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,4 +73,121 @@ cycle:
         let yaml = "not: valid: yaml: [";
         assert!(parse(yaml).is_err());
     }
+
+    #[test]
+    fn parse_json_plan() {
+        let json = r#"{
+            "plan_version": 1,
+            "cycle": {"days": [{"exercises": [{"name": "Push-ups", "modality": "strength"}]}]}
+        }"#;
+        let plan = parse_json(json).unwrap();
+        assert_eq!(
+            plan.cycle.days[0].exercises[0].name.as_deref(),
+            Some("Push-ups")
+        );
+    }
+
+    #[test]
+    fn parse_json_invalid() {
+        let err = parse_json("{\"plan_version\": 1,").unwrap_err();
+        assert!(matches!(err, ParseError::JsonSyntax(_)));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let yaml = r#"
+plan_version: 1
+x-app-id: 7
+cycle:
+  days:
+    - exercises:
+        - name: Push-ups
+          modality: strength
+"#;
+        let plan = parse(yaml).unwrap();
+        let json = to_json(&plan).unwrap();
+        let again = parse_json(&json).unwrap();
+        assert_eq!(again.extensions, plan.extensions);
+        assert_eq!(to_yaml(&again).unwrap(), to_yaml(&plan).unwrap());
+    }
+
+    #[test]
+    fn empty_collections_are_not_serialized() {
+        let yaml = r#"
+plan_version: 1
+meta:
+  title: Bare
+exercise_library:
+  - id: squat
+    name: Squat
+    modality: strength
+cycle:
+  weeks:
+    - week: 1
+      days: []
+  days:
+    - exercises:
+        - exercise_ref: squat
+"#;
+        let plan = parse(yaml).unwrap();
+        let json = to_json(&plan).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let has = |v: &serde_json::Value, key: &str| v.as_object().unwrap().contains_key(key);
+        assert!(!has(&value, "glossary"));
+        assert!(!has(&value, "workout_templates"));
+        assert!(!has(&value["meta"], "equipment"));
+        assert!(!has(&value["meta"], "tags"));
+        assert!(!has(&value["exercise_library"][0], "equipment"));
+        assert!(!has(&value["exercise_library"][0], "muscle_groups"));
+        assert!(!has(&value["cycle"]["weeks"][0], "overrides"));
+        let again = parse_json(&json).unwrap();
+        assert_eq!(to_yaml(&again).unwrap(), to_yaml(&plan).unwrap());
+    }
+
+    #[test]
+    fn populated_collections_round_trip() {
+        let yaml = r#"
+plan_version: 2
+meta:
+  title: Full
+  equipment: [barbell]
+  tags: [strength]
+glossary:
+  RPE: Rate of perceived exertion
+exercise_library:
+  - id: squat
+    name: Squat
+    modality: strength
+    equipment: [barbell]
+    muscle_groups: [quads]
+workout_templates:
+  - id: lower
+    name: Lower
+    tags: [legs]
+    exercises:
+      - exercise_ref: squat
+cycle:
+  weeks:
+    - week: 1
+      overrides:
+        - exercise_ref: squat
+          target_sets: 3
+  days:
+    - template_ref: lower
+"#;
+        let plan = parse(yaml).unwrap();
+        for again in [
+            parse_json(&to_json(&plan).unwrap()).unwrap(),
+            parse(&to_yaml(&plan).unwrap()).unwrap(),
+        ] {
+            let meta = again.meta.as_ref().unwrap();
+            assert_eq!(meta.equipment, vec!["barbell"]);
+            assert_eq!(meta.tags, vec!["strength"]);
+            assert_eq!(again.glossary.len(), 1);
+            assert_eq!(again.exercise_library[0].muscle_groups, vec!["quads"]);
+            assert_eq!(again.workout_templates[0].tags, vec!["legs"]);
+            assert_eq!(again.cycle.weeks.as_ref().unwrap()[0].overrides.len(), 1);
+            assert_eq!(to_yaml(&again).unwrap(), to_yaml(&plan).unwrap());
+        }
+    }
 }
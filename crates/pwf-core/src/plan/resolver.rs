@@ -221,6 +221,7 @@ mod tests {
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let resolved = resolve_exercise(&exercise, &library).unwrap();
@@ -270,6 +271,7 @@ mod tests {
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let resolved = resolve_exercise(&exercise, &library).unwrap();
@@ -311,6 +313,7 @@ mod tests {
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let resolved = resolve_exercise(&exercise, &library);
@@ -363,6 +366,7 @@ mod tests {
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let resolved = resolve_exercise(&exercise, &library).unwrap();
@@ -416,6 +420,7 @@ mod tests {
                     ramp: None,
                     interval_phases: None,
                     progression_rules: None,
+                    extensions: Default::default(),
                 },
                 PlanExercise {
                     id: None,
@@ -443,6 +448,7 @@ mod tests {
                     ramp: None,
                     interval_phases: None,
                     progression_rules: None,
+                    extensions: Default::default(),
                 },
             ],
         }];
@@ -456,6 +462,7 @@ mod tests {
             target_session_length_min: None, // Will use template value
             template_ref: Some("push-day".to_string()),
             exercises: vec![],
+            extensions: Default::default(),
         };
 
         let resolved = resolve_day(&day, &templates);
@@ -507,6 +514,7 @@ mod tests {
                 ramp: None,
                 interval_phases: None,
                 progression_rules: None,
+                extensions: Default::default(),
             }],
         }];
 
@@ -544,7 +552,9 @@ mod tests {
                 ramp: None,
                 interval_phases: None,
                 progression_rules: None,
+                extensions: Default::default(),
             }],
+            extensions: Default::default(),
         };
 
         let resolved = resolve_day(&day, &templates);
@@ -594,7 +604,9 @@ mod tests {
                 ramp: None,
                 interval_phases: None,
                 progression_rules: None,
+                extensions: Default::default(),
             }],
+            extensions: Default::default(),
         };
 
         let resolved = resolve_day(&day, &templates);
@@ -618,6 +630,7 @@ mod tests {
             target_session_length_min: None,
             template_ref: Some("nonexistent".to_string()),
             exercises: vec![],
+            extensions: Default::default(),
         };
 
         let resolved = resolve_day(&day, &templates);
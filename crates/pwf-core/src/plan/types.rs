@@ -1,26 +1,30 @@
 //! Plan type definitions
 
-use crate::Modality;
+use crate::{Extensions, Modality};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Root plan structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WpsPlan {
     pub plan_version: u32,
     #[serde(default)]
     pub meta: Option<PlanMeta>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub glossary: HashMap<String, String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub exercise_library: Vec<LibraryExercise>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub workout_templates: Vec<WorkoutTemplate>,
     pub cycle: PlanCycle,
+    /// Vendor extension fields (`x-` prefixed keys)
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 /// Plan metadata
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct PlanMeta {
     #[serde(default)]
     pub id: Option<String>,
@@ -35,20 +39,23 @@ pub struct PlanMeta {
     pub activated_at: Option<String>,
     #[serde(default)]
     pub completed_at: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub equipment: Vec<String>,
     #[serde(default, rename = "daysPerWeek")]
     pub days_per_week: Option<u8>,
     #[serde(default, rename = "recommendedFirst")]
     pub recommended_first: bool,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
     #[serde(default)]
     pub athlete_profile: Option<AthleteProfile>,
+    /// Vendor extension fields (`x-` prefixed keys)
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 /// Plan status
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum PlanStatus {
     #[default]
@@ -59,7 +66,7 @@ pub enum PlanStatus {
 }
 
 /// Training cycle
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlanCycle {
     #[serde(default)]
     pub start_date: Option<String>,
@@ -71,7 +78,7 @@ pub struct PlanCycle {
 }
 
 /// Week definition for multi-week periodization (PWF v2.0)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlanWeek {
     #[serde(default)]
     pub week_number: Option<u32>,
@@ -79,12 +86,12 @@ pub struct PlanWeek {
     pub name: Option<String>,
     #[serde(default)]
     pub notes: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub overrides: Vec<ExerciseOverride>,
 }
 
 /// Exercise override for specific week(s) (PWF v2.0)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExerciseOverride {
     // Exercise identification (must match base exercise)
     #[serde(default)]
@@ -120,7 +127,7 @@ pub struct ExerciseOverride {
 }
 
 /// Single training day
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlanDay {
     #[serde(default)]
     pub id: Option<String>,
@@ -138,10 +145,13 @@ pub struct PlanDay {
     pub template_ref: Option<String>,
     #[serde(default)]
     pub exercises: Vec<PlanExercise>,
+    /// Vendor extension fields (`x-` prefixed keys)
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 /// Exercise grouping type for supersets and circuits
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum GroupType {
     Superset,
@@ -149,7 +159,7 @@ pub enum GroupType {
 }
 
 /// Equipment type for exercises
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Equipment {
     Barbell,
@@ -163,7 +173,7 @@ pub enum Equipment {
 }
 
 /// Difficulty level for exercises
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Difficulty {
     Beginner,
@@ -172,15 +182,15 @@ pub enum Difficulty {
 }
 
 /// Exercise library entry (PWF v2.0)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LibraryExercise {
     pub id: String,
     pub name: String,
     #[serde(default)]
     pub description: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub equipment: Vec<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub muscle_groups: Vec<String>,
     #[serde(default)]
     pub difficulty: Option<Difficulty>,
@@ -202,7 +212,7 @@ pub struct LibraryExercise {
 }
 
 /// Workout template - reusable group of exercises (PWF v2.0)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WorkoutTemplate {
     pub id: String,
     pub name: String,
@@ -212,13 +222,13 @@ pub struct WorkoutTemplate {
     pub focus: Option<String>,
     #[serde(default)]
     pub target_session_length_min: Option<u32>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
     pub exercises: Vec<PlanExercise>,
 }
 
 /// Training zone for endurance workouts
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TrainingZone {
     pub zone: u8,
     #[serde(default)]
@@ -232,7 +242,7 @@ pub struct TrainingZone {
 }
 
 /// Ramp configuration for gradual intensity changes
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RampConfig {
     pub start_power_watts: u32,
     pub end_power_watts: u32,
@@ -242,7 +252,7 @@ pub struct RampConfig {
 }
 
 /// Interval phase for complex structured workouts
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IntervalPhase {
     pub name: String,
     pub duration_sec: u32,
@@ -257,7 +267,7 @@ pub struct IntervalPhase {
 }
 
 /// Athlete profile with FTP, threshold HR, and other metrics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AthleteProfile {
     #[serde(default)]
     pub ftp_watts: Option<u32>,
@@ -272,7 +282,7 @@ pub struct AthleteProfile {
 }
 
 /// Progression type for automatic progressive overload
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ProgressionType {
     Linear,
@@ -280,7 +290,7 @@ pub enum ProgressionType {
 }
 
 /// Success condition for triggering progression
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SuccessCondition {
     AllSetsCompleted,
@@ -289,7 +299,7 @@ pub enum SuccessCondition {
 }
 
 /// Deload condition for regression
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum DeloadCondition {
     FailedOnceConsecutive,
@@ -299,7 +309,7 @@ pub enum DeloadCondition {
 }
 
 /// Progression rules for automatic progressive overload
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProgressionRules {
     #[serde(rename = "type")]
     pub progression_type: ProgressionType,
@@ -329,7 +339,7 @@ pub struct ProgressionRules {
 }
 
 /// Single exercise in a plan
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PlanExercise {
     #[serde(default)]
     pub id: Option<String>,
@@ -381,6 +391,9 @@ pub struct PlanExercise {
     pub interval_phases: Option<Vec<IntervalPhase>>,
     #[serde(default)]
     pub progression_rules: Option<ProgressionRules>,
+    /// Vendor extension fields (`x-` prefixed keys)
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 /// Statistics about a parsed plan
@@ -422,6 +435,7 @@ mod tests {
                 weeks: None,
                 days: vec![],
             },
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&plan).unwrap();
@@ -453,6 +467,7 @@ mod tests {
                 weeks: None,
                 days: vec![],
             },
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&plan).unwrap();
@@ -484,6 +499,7 @@ mod tests {
             recommended_first: true,
             tags: vec!["strength".to_string(), "hypertrophy".to_string()],
             athlete_profile: None,
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&meta).unwrap();
@@ -633,6 +649,7 @@ title: "Basic Plan"
                     target_session_length_min: Some(60),
                     template_ref: None,
                     exercises: vec![],
+                    extensions: Default::default(),
                 },
                 PlanDay {
                     id: Some("day-2".to_string()),
@@ -643,6 +660,7 @@ title: "Basic Plan"
                     target_session_length_min: Some(75),
                     template_ref: None,
                     exercises: vec![],
+                    extensions: Default::default(),
                 },
             ],
         };
@@ -674,6 +692,7 @@ title: "Basic Plan"
             target_session_length_min: Some(90),
             template_ref: None,
             exercises: vec![],
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&day).unwrap();
@@ -718,6 +737,7 @@ title: "Basic Plan"
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&exercise).unwrap();
@@ -771,6 +791,7 @@ title: "Basic Plan"
                 ramp: None,
                 interval_phases: None,
                 progression_rules: None,
+                extensions: Default::default(),
             };
 
             let yaml = serde_yaml::to_string(&exercise).unwrap();
@@ -812,6 +833,7 @@ title: "Basic Plan"
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&exercise).unwrap();
@@ -853,6 +875,7 @@ title: "Basic Plan"
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&exercise).unwrap();
@@ -894,6 +917,7 @@ title: "Basic Plan"
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&exercise).unwrap();
@@ -993,6 +1017,7 @@ title: "Basic Plan"
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&exercise).unwrap();
@@ -1024,6 +1049,7 @@ title: "Basic Plan"
                 recommended_first: false,
                 tags: vec!["strength".to_string()],
                 athlete_profile: None,
+                extensions: Default::default(),
             }),
             glossary,
             exercise_library: vec![],
@@ -1068,6 +1094,7 @@ title: "Basic Plan"
                             ramp: None,
                             interval_phases: None,
                             progression_rules: None,
+                            extensions: Default::default(),
                         },
                         PlanExercise {
                             id: Some("ex-2".to_string()),
@@ -1096,10 +1123,13 @@ title: "Basic Plan"
                             ramp: None,
                             interval_phases: None,
                             progression_rules: None,
+                            extensions: Default::default(),
                         },
                     ],
+                    extensions: Default::default(),
                 }],
             },
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&plan).unwrap();
@@ -1165,6 +1195,7 @@ recommendedFirst: false
             target_session_length_min: Some(45),
             template_ref: None,
             exercises: vec![],
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&day).unwrap();
@@ -1226,6 +1257,7 @@ recommendedFirst: false
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&exercise).unwrap();
@@ -1264,6 +1296,7 @@ recommendedFirst: false
             ramp: None,
             interval_phases: None,
             progression_rules: None,
+            extensions: Default::default(),
         };
 
         let yaml = serde_yaml::to_string(&exercise).unwrap();
@@ -1,9 +1,11 @@
 //! Validation rules for PWF plans
 
-use super::parser::parse;
+use super::parser::{parse, parse_json};
 use super::types::{PlanStatistics, WpsPlan};
-use crate::error::ValidationIssue;
-use crate::Modality;
+use crate::error::{ParseError, ValidationIssue};
+use crate::fields::unknown_plan_fields;
+use crate::schema::plan_schema_issues;
+use crate::{Modality, ValidationOptions};
 use std::collections::HashSet;
 
 /// Result of plan validation
@@ -30,11 +32,39 @@ impl ValidationResult {
 
 /// Validate a YAML string as a PWF plan
 pub fn validate(yaml: &str) -> ValidationResult {
+    validate_with_options(yaml, &ValidationOptions::default())
+}
+
+/// Validate a YAML string as a PWF plan with custom options
+pub fn validate_with_options(yaml: &str, options: &ValidationOptions) -> ValidationResult {
+    let raw = serde_yaml::from_str::<serde_yaml::Value>(yaml).ok();
+    validate_parsed(parse(yaml), raw.as_ref(), options)
+}
+
+/// Validate a JSON string as a PWF plan
+pub fn validate_json(json: &str) -> ValidationResult {
+    validate_json_with_options(json, &ValidationOptions::default())
+}
+
+/// Validate a JSON string as a PWF plan with custom options
+pub fn validate_json_with_options(json: &str, options: &ValidationOptions) -> ValidationResult {
+    let raw = serde_json::from_str::<serde_json::Value>(json)
+        .ok()
+        .and_then(|value| serde_yaml::to_value(value).ok());
+    validate_parsed(parse_json(json), raw.as_ref(), options)
+}
+
+/// Shared validation for both input formats; `raw` is the untyped document
+/// used for unknown-field detection
+fn validate_parsed(
+    parsed: Result<WpsPlan, ParseError>,
+    raw: Option<&serde_yaml::Value>,
+    options: &ValidationOptions,
+) -> ValidationResult {
     let mut errors = Vec::new();
     let mut warnings = Vec::new();
 
-    // Parse YAML
-    let plan = match parse(yaml) {
+    let plan = match parsed {
         Ok(p) => p,
         Err(e) => {
             errors.push(ValidationIssue::error("", e.to_string()));
@@ -48,6 +78,33 @@ pub fn validate(yaml: &str) -> ValidationResult {
         }
     };
 
+    // Detect unknown and misspelled keys
+    if let Some(raw) = raw {
+        for unknown in unknown_plan_fields(raw) {
+            if options.deny_unknown_fields {
+                errors.push(ValidationIssue::error_with_code(
+                    unknown.path.clone(),
+                    unknown.message(),
+                    "PWF-P063",
+                ));
+            } else {
+                warnings.push(ValidationIssue::warning_with_code(
+                    unknown.path.clone(),
+                    unknown.message(),
+                    "PWF-P063",
+                ));
+            }
+        }
+
+        // Check against the bundled JSON Schema; YAML documents with
+        // non-string keys cannot be represented as JSON and are skipped
+        if options.check_schema {
+            if let Ok(document) = serde_json::to_value(raw) {
+                errors.extend(plan_schema_issues(&document));
+            }
+        }
+    }
+
     // Validate plan_version
     if plan.plan_version != 1 && plan.plan_version != 2 {
         errors.push(ValidationIssue::error(
@@ -3019,4 +3076,37 @@ cycle:
             .iter()
             .any(|w| w.code == Some("PWF-P016".to_string())));
     }
+
+    #[test]
+    fn test_validate_json_matches_yaml() {
+        let json = r#"{
+            "plan_version": 1,
+            "meta": {"title": "JSON plan"},
+            "cycle": {"days": [{"exercises": [
+                {"name": "Squat", "modality": "strength", "target_sets": 3, "target_rep": 5}
+            ]}]}
+        }"#;
+        let result = validate_json(json);
+        assert!(result.is_valid());
+        assert!(result.warnings.iter().any(|w| {
+            w.path == "cycle.days[0].exercises[0].target_rep"
+                && w.code.as_deref() == Some("PWF-P063")
+        }));
+
+        let strict = validate_json_with_options(
+            json,
+            &ValidationOptions {
+                deny_unknown_fields: true,
+                ..Default::default()
+            },
+        );
+        assert!(!strict.is_valid());
+    }
+
+    #[test]
+    fn test_validate_json_syntax_error() {
+        let result = validate_json("{\"plan_version\": ");
+        assert!(!result.is_valid());
+        assert!(result.errors[0].message.starts_with("JSON syntax error"));
+    }
 }
@@ -0,0 +1,179 @@
+//! Self-contained HTML rendering of a [`TrainingReport`]
+
+use super::svg::{daily_minutes_chart, weight_chart};
+use super::{
+    describe_compliance, describe_record, describe_session, format_distance, format_duration,
+    TrainingReport,
+};
+use std::fmt::Write;
+
+const STYLE: &str = "\
+body{font-family:-apple-system,'Segoe UI',Helvetica,Arial,sans-serif;max-width:720px;margin:2em auto;padding:0 1em;color:#222}\
+h1{font-size:1.5em}h2{font-size:1.15em;margin-top:1.6em;border-bottom:1px solid #ddd}\
+table{border-collapse:collapse;width:100%}th,td{padding:4px 8px;border-bottom:1px solid #eee;text-align:right}\
+th:first-child,td:first-child{text-align:left}.muted{color:#888}.missed{color:#b00}\
+svg{max-width:100%;height:auto}.bar{fill:#3b82f6}.line{stroke:#3b82f6;stroke-width:2}.point{fill:#3b82f6}\
+.axis{font-size:11px;fill:#666}.baseline{stroke:#999}";
+
+/// Render a report as a standalone HTML page
+///
+/// Styles and charts are inline, so the page needs no other files.
+pub fn render_html(report: &TrainingReport) -> String {
+    let title = format!("Training report: {} to {}", report.from, report.to);
+    let totals = &report.totals;
+    let mut out = String::new();
+    // Writing to a String cannot fail
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+        title = escape(&title),
+        STYLE = STYLE
+    );
+
+    out.push_str("<h2>Totals</h2>\n<table>\n");
+    out.push_str(
+        "<tr><th>Workouts</th><th>Time</th><th>Distance</th><th>Elevation</th><th>Calories</th><th>Volume</th></tr>\n",
+    );
+    let _ = writeln!(
+        out,
+        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.0} m</td><td>{} kcal</td><td>{:.0} kg</td></tr>",
+        totals.count,
+        format_duration(totals.duration_sec),
+        format_distance(totals.distance_m),
+        totals.elevation_gain_m,
+        totals.calories,
+        totals.volume_kg
+    );
+    out.push_str("</table>\n");
+
+    if !report.by_sport.is_empty() {
+        out.push_str(
+            "<table>\n<tr><th>Sport</th><th>Workouts</th><th>Time</th><th>Distance</th></tr>\n",
+        );
+        for sport in &report.by_sport {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&sport.sport),
+                sport.totals.count,
+                format_duration(sport.totals.duration_sec),
+                format_distance(sport.totals.distance_m)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Training per day</h2>\n");
+    out.push_str(&daily_minutes_chart(&report.days));
+    out.push('\n');
+
+    if !report.key_sessions.is_empty() {
+        out.push_str("<h2>Key sessions</h2>\n<ul>\n");
+        for session in &report.key_sessions {
+            let _ = write!(
+                out,
+                "<li><strong>{}</strong> {}",
+                escape(&session.date),
+                escape(&session.title)
+            );
+            if let Some(sport) = &session.sport {
+                let _ = write!(out, " <span class=\"muted\">({})</span>", escape(sport));
+            }
+            let details = describe_session(session);
+            if !details.is_empty() {
+                let _ = write!(out, ": {}", escape(&details));
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str("<h2>New records</h2>\n");
+    if report.records.is_empty() {
+        out.push_str("<p class=\"muted\">No records this period.</p>\n");
+    } else {
+        out.push_str("<ul>\n");
+        for event in &report.records {
+            let _ = writeln!(
+                out,
+                "<li><strong>{}</strong> {}</li>",
+                escape(&event.achieved_at),
+                escape(&describe_record(event))
+            );
+        }
+        out.push_str("</ul>\n");
+    }
+
+    if let Some(body) = &report.body_weight {
+        let unit = body.unit.to_string();
+        out.push_str("<h2>Body weight</h2>\n");
+        let _ = writeln!(
+            out,
+            "<p>Trend {:.1} {unit} to {:.1} {unit} ({:+.1} {unit})</p>",
+            body.start,
+            body.end,
+            body.change,
+            unit = unit
+        );
+        if body.trend.len() > 1 {
+            out.push_str(&weight_chart(&body.trend, &unit));
+            out.push('\n');
+        }
+    }
+
+    if let Some(compliance) = &report.compliance {
+        out.push_str("<h2>Plan compliance</h2>\n");
+        let _ = writeln!(out, "<p>{}</p>", escape(&describe_compliance(compliance)));
+        if !compliance.missed_sessions.is_empty() {
+            out.push_str("<ul>\n");
+            for missed in &compliance.missed_sessions {
+                let _ = writeln!(out, "<li class=\"missed\">Missed: {}</li>", escape(missed));
+            }
+            out.push_str("</ul>\n");
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Escape text for use in element content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::report;
+    use super::*;
+
+    #[test]
+    fn test_html_document() {
+        let mut report = report();
+        report.key_sessions[0].title = "Hills <5x3'> & more".to_string();
+        let html = render_html(&report);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.ends_with("</html>\n"));
+        assert!(html.contains("<title>Training report: 2025-03-03 to 2025-03-09</title>"));
+        assert!(html.contains("<style>"));
+        // Day bars and the weight line, both inline
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("href="));
+        assert!(html.contains("Hills &lt;5x3&#39;&gt; &amp; more"));
+        assert!(!html.contains("Plan compliance"));
+    }
+}
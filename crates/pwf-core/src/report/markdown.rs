@@ -0,0 +1,148 @@
+//! Markdown rendering of a [`TrainingReport`]
+
+use super::{
+    describe_compliance, describe_record, describe_session, format_distance, format_duration,
+    TrainingReport,
+};
+use std::fmt::Write;
+
+/// Render a report as Markdown
+///
+/// Charts are left out; the day-by-day table carries the same numbers.
+pub fn render_markdown(report: &TrainingReport) -> String {
+    let mut out = String::new();
+    let totals = &report.totals;
+    // Writing to a String cannot fail
+    let _ = writeln!(out, "# Training report: {} to {}\n", report.from, report.to);
+
+    out.push_str("## Totals\n\n");
+    out.push_str("| Workouts | Time | Distance | Elevation | Calories | Volume |\n");
+    out.push_str("|---:|---:|---:|---:|---:|---:|\n");
+    let _ = writeln!(
+        out,
+        "| {} | {} | {} | {:.0} m | {} kcal | {:.0} kg |\n",
+        totals.count,
+        format_duration(totals.duration_sec),
+        format_distance(totals.distance_m),
+        totals.elevation_gain_m,
+        totals.calories,
+        totals.volume_kg
+    );
+
+    if !report.by_sport.is_empty() {
+        out.push_str("| Sport | Workouts | Time | Distance |\n");
+        out.push_str("|---|---:|---:|---:|\n");
+        for sport in &report.by_sport {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                sport.sport,
+                sport.totals.count,
+                format_duration(sport.totals.duration_sec),
+                format_distance(sport.totals.distance_m)
+            );
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Days\n\n");
+    out.push_str("| Date | Workouts | Time | Stress |\n");
+    out.push_str("|---|---:|---:|---:|\n");
+    for day in &report.days {
+        if day.workouts == 0 {
+            let _ = writeln!(out, "| {} | rest | | |", day.date);
+        } else {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {:.0} |",
+                day.date,
+                day.workouts,
+                format_duration(day.duration_sec),
+                day.stress
+            );
+        }
+    }
+    out.push('\n');
+
+    if !report.key_sessions.is_empty() {
+        out.push_str("## Key sessions\n\n");
+        for session in &report.key_sessions {
+            let _ = write!(out, "- **{}** {}", session.date, session.title);
+            if let Some(sport) = &session.sport {
+                let _ = write!(out, " ({})", sport);
+            }
+            let details = describe_session(session);
+            if !details.is_empty() {
+                let _ = write!(out, ": {}", details);
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## New records\n\n");
+    if report.records.is_empty() {
+        out.push_str("No records this period.\n\n");
+    } else {
+        for event in &report.records {
+            let _ = writeln!(
+                out,
+                "- **{}** {}",
+                event.achieved_at,
+                describe_record(event)
+            );
+        }
+        out.push('\n');
+    }
+
+    if let Some(body) = &report.body_weight {
+        out.push_str("## Body weight\n\n");
+        let _ = writeln!(
+            out,
+            "Trend {:.1} {unit} to {:.1} {unit} ({:+.1} {unit})\n",
+            body.start,
+            body.end,
+            body.change,
+            unit = body.unit
+        );
+    }
+
+    if let Some(compliance) = &report.compliance {
+        out.push_str("## Plan compliance\n\n");
+        let _ = writeln!(out, "{}\n", describe_compliance(compliance));
+        for missed in &compliance.missed_sessions {
+            let _ = writeln!(out, "- Missed: {}", missed);
+        }
+        if !compliance.missed_sessions.is_empty() {
+            out.push('\n');
+        }
+    }
+
+    // One trailing newline
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::report;
+    use super::*;
+
+    #[test]
+    fn test_markdown_sections() {
+        let markdown = render_markdown(&report());
+        assert!(markdown.starts_with("# Training report: 2025-03-03 to 2025-03-09\n"));
+        assert!(markdown.contains("| 3 | 1h 45m | 20.0 km |"));
+        assert!(markdown.contains("| 2025-03-04 | rest | | |"));
+        assert!(markdown.contains("| other | 1 | - | - |"));
+        assert!(markdown.contains(
+            "- **2025-03-05** Threshold intervals (running): 1h 00m, 12.0 km, stress 100"
+        ));
+        assert!(markdown.contains("- **2025-03-06** Squat: "));
+        assert!(markdown.contains("## Body weight"));
+        assert!(!markdown.contains("## Plan compliance"));
+        assert!(markdown.ends_with("kg)\n"));
+    }
+}
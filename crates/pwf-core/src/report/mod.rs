@@ -0,0 +1,577 @@
+//! Training reports for a date range, rendered as Markdown or HTML
+//!
+//! [`training_report`] gathers what a coach would put in a weekly or
+//! monthly summary: totals overall and per sport, day-by-day training time
+//! and stress, the hardest sessions, records broken in the period, the
+//! change in trend body weight and, given a plan, how many of its sessions
+//! were done. [`render_markdown`] and [`render_html`] turn the result into
+//! a document; the HTML is self-contained, with styles and SVG charts
+//! inline.
+
+mod html;
+mod markdown;
+mod svg;
+
+pub use html::render_html;
+pub use markdown::render_markdown;
+
+use crate::analytics::date_range::parse_date;
+use crate::analytics::{
+    analyze_records, body_trends, workout_date, workout_duration_sec, workout_stress, BodyOptions,
+    DateRange, LoadThresholds, PrEvent, RecordOptions, WorkoutStress,
+};
+use crate::compliance::{plan_compliance, ComplianceOptions, SessionStatus};
+use crate::history::{
+    history_statistics, ActivityTotals, RecordType, SportTotals, WorkoutTelemetry, WpsHistory,
+};
+use crate::plan::WpsPlan;
+use crate::{WeightUnit, METERS_PER_MILE};
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+
+/// Sessions listed under "key sessions" by default
+pub const DEFAULT_KEY_SESSIONS: usize = 3;
+
+/// Days covered when the range has no start
+pub const DEFAULT_REPORT_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportOptions {
+    /// Thresholds for stress scores; a plan's athlete profile fills the gaps
+    pub thresholds: LoadThresholds,
+    pub key_sessions: usize,
+    pub records: RecordOptions,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            thresholds: LoadThresholds::default(),
+            key_sessions: DEFAULT_KEY_SESSIONS,
+            records: RecordOptions::default(),
+        }
+    }
+}
+
+/// Training on one calendar day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayTotals {
+    pub date: NaiveDate,
+    pub workouts: usize,
+    pub duration_sec: u64,
+    pub stress: f64,
+}
+
+/// One of the period's hardest workouts
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeySession {
+    pub workout_index: usize,
+    pub date: String,
+    /// Title, else the exercise names
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sport: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_sec: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance_m: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stress: Option<WorkoutStress>,
+}
+
+/// A trend weight on one day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeightPoint {
+    pub date: NaiveDate,
+    pub weight: f64,
+}
+
+/// Trend body weight over the period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BodyWeightChange {
+    pub unit: WeightUnit,
+    pub start: f64,
+    pub end: f64,
+    pub change: f64,
+    pub trend: Vec<WeightPoint>,
+}
+
+/// Plan sessions scheduled in the period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeriodCompliance {
+    pub planned: u32,
+    pub completed: u32,
+    pub partial: u32,
+    pub missed: u32,
+    pub upcoming: u32,
+    /// Mean completion of the sessions due
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_percent: Option<f64>,
+    /// Focus or day id of each missed session, with its date
+    pub missed_sessions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrainingReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub totals: ActivityTotals,
+    pub by_sport: Vec<SportTotals>,
+    /// Every day of the period, including rest days
+    pub days: Vec<DayTotals>,
+    pub key_sessions: Vec<KeySession>,
+    /// Records broken in the period
+    pub records: Vec<PrEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_weight: Option<BodyWeightChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compliance: Option<PeriodCompliance>,
+}
+
+/// Build the report for `range`
+///
+/// An open end defaults to the latest workout, an open start to
+/// [`DEFAULT_REPORT_DAYS`] days before the end. Returns `None` when the end
+/// is open and no workout has a date, or when the start is after the end.
+pub fn training_report(
+    history: &WpsHistory,
+    plan: Option<&WpsPlan>,
+    range: &DateRange,
+    options: &ReportOptions,
+) -> Option<TrainingReport> {
+    let to = range
+        .to
+        .or_else(|| history.workouts.iter().filter_map(workout_date).max())?;
+    let from = range
+        .from
+        .unwrap_or(to - Duration::days(DEFAULT_REPORT_DAYS - 1));
+    if from > to {
+        return None;
+    }
+    let range = DateRange::new(Some(from), Some(to));
+
+    let thresholds = match plan
+        .and_then(|p| p.meta.as_ref())
+        .and_then(|m| m.athlete_profile.as_ref())
+    {
+        Some(profile) => {
+            let profile = LoadThresholds::from_profile(profile);
+            LoadThresholds {
+                ftp_watts: options.thresholds.ftp_watts.or(profile.ftp_watts),
+                threshold_hr_bpm: options
+                    .thresholds
+                    .threshold_hr_bpm
+                    .or(profile.threshold_hr_bpm),
+                threshold_pace_sec_per_km: options
+                    .thresholds
+                    .threshold_pace_sec_per_km
+                    .or(profile.threshold_pace_sec_per_km),
+            }
+        }
+        None => options.thresholds,
+    };
+
+    let period = WpsHistory {
+        history_version: history.history_version,
+        exported_at: history.exported_at.clone(),
+        export_source: None,
+        units: history.units.clone(),
+        workouts: range.workouts(history).cloned().collect(),
+        personal_records: Vec::new(),
+        body_measurements: Vec::new(),
+        extensions: Default::default(),
+    };
+    let breakdown = history_statistics(&period).breakdown.unwrap_or_default();
+    let mut totals = ActivityTotals::default();
+    for year in &breakdown.yearly {
+        totals.count += year.totals.count;
+        totals.duration_sec += year.totals.duration_sec;
+        totals.distance_m += year.totals.distance_m;
+        totals.elevation_gain_m += year.totals.elevation_gain_m;
+        totals.calories += year.totals.calories;
+        totals.volume_kg += year.totals.volume_kg;
+    }
+
+    let mut days: Vec<DayTotals> = from
+        .iter_days()
+        .take_while(|d| *d <= to)
+        .map(|date| DayTotals {
+            date,
+            workouts: 0,
+            duration_sec: 0,
+            stress: 0.0,
+        })
+        .collect();
+    let mut sessions: Vec<KeySession> = Vec::new();
+    for (index, workout) in history.workouts.iter().enumerate() {
+        let Some(date) = workout_date(workout).filter(|d| range.contains(*d)) else {
+            continue;
+        };
+        let stress = workout_stress(workout, &thresholds);
+        let duration_sec = workout_duration_sec(workout);
+        if let Some(day) = days.iter_mut().find(|d| d.date == date) {
+            day.workouts += 1;
+            day.duration_sec += duration_sec.map_or(0, u64::from);
+            day.stress += stress.as_ref().map_or(0.0, |s| s.score);
+        }
+        let title = workout.title.clone().unwrap_or_else(|| {
+            let names: Vec<&str> = workout.exercises.iter().map(|e| e.name.as_str()).collect();
+            if names.is_empty() {
+                "Workout".to_string()
+            } else {
+                names.join(", ")
+            }
+        });
+        sessions.push(KeySession {
+            workout_index: index,
+            date: workout.date.clone(),
+            title,
+            sport: workout
+                .sport
+                .or_else(|| workout.exercises.iter().find_map(|e| e.sport))
+                .map(|s| s.to_string()),
+            duration_sec,
+            distance_m: workout.telemetry.as_ref().and_then(distance_m).or_else(|| {
+                let total: f64 = workout
+                    .exercises
+                    .iter()
+                    .flat_map(|e| &e.sets)
+                    .filter_map(|s| s.distance_meters)
+                    .sum();
+                (total > 0.0).then_some(total)
+            }),
+            stress,
+        });
+    }
+    // Hardest first: stress, then duration for workouts without a score
+    sessions.sort_by(|a, b| {
+        let key = |s: &KeySession| {
+            (
+                s.stress.as_ref().map_or(-1.0, |s| s.score),
+                s.duration_sec.unwrap_or(0),
+            )
+        };
+        let (ka, kb) = (key(a), key(b));
+        kb.0.total_cmp(&ka.0).then(kb.1.cmp(&ka.1))
+    });
+    sessions.truncate(options.key_sessions);
+
+    let records = analyze_records(history, &options.records)
+        .events
+        .into_iter()
+        .filter(|e| parse_date(&e.achieved_at).is_some_and(|d| range.contains(d)))
+        .collect();
+
+    let body = body_trends(history, &range, &BodyOptions::default());
+    let trend: Vec<WeightPoint> = body
+        .entries
+        .iter()
+        .filter_map(|e| {
+            Some(WeightPoint {
+                date: e.date,
+                weight: e.trend_weight?,
+            })
+        })
+        .collect();
+    let body_weight = match (trend.first(), trend.last()) {
+        (Some(first), Some(last)) => Some(BodyWeightChange {
+            unit: body.unit,
+            start: first.weight,
+            end: last.weight,
+            change: last.weight - first.weight,
+            trend: trend.clone(),
+        }),
+        _ => None,
+    };
+
+    let compliance = plan.map(|plan| {
+        let report = plan_compliance(plan, history, &ComplianceOptions { as_of: Some(to) });
+        let in_period: Vec<_> = report
+            .sessions
+            .iter()
+            .filter(|s| {
+                s.scheduled_date
+                    .or_else(|| s.workout_date.as_deref().and_then(parse_date))
+                    .is_some_and(|d| range.contains(d))
+            })
+            .collect();
+        let count = |status| in_period.iter().filter(|s| s.status == status).count() as u32;
+        let due: Vec<f64> = in_period
+            .iter()
+            .filter_map(|s| s.completion_percent)
+            .collect();
+        PeriodCompliance {
+            planned: in_period.len() as u32,
+            completed: count(SessionStatus::Completed),
+            partial: count(SessionStatus::Partial),
+            missed: count(SessionStatus::Missed),
+            upcoming: count(SessionStatus::Upcoming),
+            completion_percent: (!due.is_empty())
+                .then(|| due.iter().sum::<f64>() / due.len() as f64),
+            missed_sessions: in_period
+                .iter()
+                .filter(|s| s.status == SessionStatus::Missed)
+                .map(|s| {
+                    let label = s
+                        .focus
+                        .clone()
+                        .or_else(|| s.day_id.clone())
+                        .unwrap_or_else(|| format!("Day {}", s.day_index + 1));
+                    match s.scheduled_date {
+                        Some(date) => format!("{} ({})", label, date),
+                        None => label,
+                    }
+                })
+                .collect(),
+        }
+    });
+
+    Some(TrainingReport {
+        from,
+        to,
+        totals,
+        by_sport: breakdown.by_sport,
+        days,
+        key_sessions: sessions,
+        records,
+        body_weight,
+        compliance,
+    })
+}
+
+fn distance_m(telemetry: &WorkoutTelemetry) -> Option<f64> {
+    telemetry
+        .total_distance_m
+        .or_else(|| telemetry.total_distance_km.map(|km| km * 1000.0))
+        .or_else(|| telemetry.total_distance_mi.map(|mi| mi * METERS_PER_MILE))
+}
+
+/// `1h 05m`, `45m` or `30s`; `-` for untimed training
+fn format_duration(seconds: u64) -> String {
+    let (h, m, s) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    match (h, m) {
+        (0, 0) if s == 0 => "-".to_string(),
+        (0, 0) => format!("{}s", s),
+        (0, m) => format!("{}m", m),
+        (h, m) => format!("{}h {:02}m", h, m),
+    }
+}
+
+fn format_distance(meters: f64) -> String {
+    if meters <= 0.0 {
+        "-".to_string()
+    } else if meters >= 1000.0 {
+        format!("{:.1} km", meters / 1000.0)
+    } else {
+        format!("{:.0} m", meters)
+    }
+}
+
+fn record_label(record_type: RecordType) -> &'static str {
+    match record_type {
+        RecordType::OneRepMax => "estimated 1RM",
+        RecordType::MaxWeight3rm => "3RM",
+        RecordType::MaxWeight5rm => "5RM",
+        RecordType::MaxWeight8rm => "8RM",
+        RecordType::MaxWeight10rm => "10RM",
+        RecordType::MaxWeight => "max weight",
+        RecordType::MaxReps => "max reps",
+        RecordType::MaxVolume => "session volume",
+        RecordType::MaxDuration => "longest",
+        RecordType::MaxDistance => "farthest",
+        RecordType::FastestTime => "fastest",
+    }
+}
+
+fn format_record_value(record_type: RecordType, value: f64) -> String {
+    match record_type {
+        RecordType::MaxReps => format!("{:.0} reps", value),
+        RecordType::MaxDuration | RecordType::FastestTime => format_duration(value.round() as u64),
+        RecordType::MaxDistance => format_distance(value),
+        _ => format!("{:.1} kg", value),
+    }
+}
+
+/// One line describing a record, e.g. `Squat: estimated 1RM 142.5 kg (was 140.0 kg)`
+fn describe_record(event: &PrEvent) -> String {
+    let mut line = format!(
+        "{}: {} {}",
+        event.exercise_name,
+        record_label(event.record_type),
+        format_record_value(event.record_type, event.value)
+    );
+    if event.previous > 0.0 {
+        line.push_str(&format!(
+            " (was {})",
+            format_record_value(event.record_type, event.previous)
+        ));
+    }
+    line
+}
+
+/// One line describing a key session, without its date and title
+fn describe_session(session: &KeySession) -> String {
+    let mut parts = Vec::new();
+    if let Some(duration) = session.duration_sec {
+        parts.push(format_duration(duration as u64));
+    }
+    if let Some(distance) = session.distance_m {
+        parts.push(format_distance(distance));
+    }
+    if let Some(stress) = &session.stress {
+        parts.push(format!("stress {:.0}", stress.score));
+    }
+    parts.join(", ")
+}
+
+fn describe_compliance(compliance: &PeriodCompliance) -> String {
+    let mut line = format!(
+        "{} of {} planned sessions completed, {} partial, {} missed",
+        compliance.completed, compliance.planned, compliance.partial, compliance.missed
+    );
+    if compliance.upcoming > 0 {
+        line.push_str(&format!(", {} upcoming", compliance.upcoming));
+    }
+    if let Some(percent) = compliance.completion_percent {
+        line.push_str(&format!(" ({:.0}% of targets)", percent));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) const HISTORY: &str = r#"
+history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+workouts:
+  - date: "2025-02-24"
+    exercises:
+      - name: Squat
+        sets:
+          - { reps: 5, weight_kg: 100 }
+  - date: "2025-03-03"
+    title: Easy run
+    sport: running
+    duration_sec: 2700
+    exercises: []
+    telemetry:
+      total_distance_km: 8.0
+  - date: "2025-03-05"
+    title: Threshold intervals
+    sport: running
+    duration_sec: 3600
+    exercises: []
+    telemetry:
+      heart_rate_avg: 170
+      total_distance_km: 12.0
+  - date: "2025-03-06"
+    exercises:
+      - name: Squat
+        sets:
+          - { reps: 5, weight_kg: 110, rpe: 8 }
+body_measurements:
+  - date: "2025-03-03"
+    weight_kg: 80.0
+  - date: "2025-03-09"
+    weight_kg: 79.0
+"#;
+
+    pub(super) fn report() -> TrainingReport {
+        let history = crate::history::parse(HISTORY).unwrap();
+        let options = ReportOptions {
+            thresholds: LoadThresholds {
+                threshold_hr_bpm: Some(170),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        training_report(
+            &history,
+            None,
+            &DateRange::parse(Some("2025-03-03"), Some("2025-03-09")).unwrap(),
+            &options,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_report_contents() {
+        let report = report();
+        assert_eq!(report.totals.count, 3);
+        assert_eq!(report.totals.distance_m, 20000.0);
+        assert_eq!(report.days.len(), 7);
+        assert_eq!(report.days[2].duration_sec, 3600);
+        assert_eq!(report.by_sport[0].sport, "running");
+
+        // The hour at threshold heart rate scores 100
+        assert_eq!(report.key_sessions[0].title, "Threshold intervals");
+        assert_eq!(report.key_sessions[0].stress.as_ref().unwrap().score, 100.0);
+        assert_eq!(report.key_sessions.len(), 3);
+
+        // Only records broken inside the period
+        assert!(!report.records.is_empty());
+        assert!(report.records.iter().all(|r| r.achieved_at == "2025-03-06"));
+
+        let body = report.body_weight.as_ref().unwrap();
+        assert_eq!(body.start, 80.0);
+        assert!(body.change < 0.0 && body.change > -1.0);
+        assert!(report.compliance.is_none());
+    }
+
+    #[test]
+    fn test_default_range_ends_at_latest_workout() {
+        let history = crate::history::parse(HISTORY).unwrap();
+        let report =
+            training_report(&history, None, &DateRange::all(), &ReportOptions::default()).unwrap();
+        assert_eq!(report.to.to_string(), "2025-03-06");
+        assert_eq!(report.from.to_string(), "2025-02-28");
+
+        let empty = crate::history::parse(
+            "history_version: 2\nexported_at: \"2025-03-20T00:00:00Z\"\nworkouts: []\n",
+        )
+        .unwrap();
+        assert!(
+            training_report(&empty, None, &DateRange::all(), &ReportOptions::default()).is_none()
+        );
+
+        // A start after the end is rejected rather than giving an empty report
+        let reversed = DateRange::parse(Some("2025-03-10"), Some("2025-03-01")).unwrap();
+        assert!(training_report(&history, None, &reversed, &ReportOptions::default()).is_none());
+        let after_latest = DateRange::parse(Some("2025-03-10"), None).unwrap();
+        assert!(
+            training_report(&history, None, &after_latest, &ReportOptions::default()).is_none()
+        );
+    }
+
+    #[test]
+    fn test_compliance_for_period() {
+        let history = crate::history::parse(HISTORY).unwrap();
+        let plan = crate::plan::parse(
+            r#"
+plan_version: 1
+cycle:
+  days:
+    - focus: Easy run
+      scheduled_date: "2025-03-03"
+      exercises:
+        - name: Run
+          modality: stopwatch
+    - focus: Long run
+      scheduled_date: "2025-03-08"
+      exercises:
+        - name: Run
+          modality: stopwatch
+"#,
+        )
+        .unwrap();
+        let range = DateRange::parse(Some("2025-03-03"), Some("2025-03-09")).unwrap();
+        let report =
+            training_report(&history, Some(&plan), &range, &ReportOptions::default()).unwrap();
+        let compliance = report.compliance.unwrap();
+        assert_eq!(compliance.planned, 2);
+        assert_eq!(compliance.missed, 1);
+        assert_eq!(compliance.missed_sessions, vec!["Long run (2025-03-08)"]);
+    }
+}
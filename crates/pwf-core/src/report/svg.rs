@@ -0,0 +1,176 @@
+//! Inline SVG charts for the HTML report
+//!
+//! The charts are plain `<svg>` elements with no scripts or external
+//! fonts, so a report keeps rendering when it is mailed or archived.
+
+use super::{DayTotals, WeightPoint};
+use std::fmt::Write;
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 200.0;
+/// Room for axis labels
+const MARGIN: f64 = 28.0;
+
+/// Bars of training minutes per day
+pub(super) fn daily_minutes_chart(days: &[DayTotals]) -> String {
+    let mut svg = open(&format!("Training minutes per day, {} days", days.len()));
+    if days.is_empty() {
+        svg.push_str("</svg>");
+        return svg;
+    }
+
+    let minutes: Vec<f64> = days.iter().map(|d| d.duration_sec as f64 / 60.0).collect();
+    let max = minutes.iter().copied().fold(0.0, f64::max).max(1.0);
+    let plot_height = HEIGHT - 2.0 * MARGIN;
+    let slot = (WIDTH - 2.0 * MARGIN) / days.len() as f64;
+    let bar = slot * 0.7;
+
+    let _ = write!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" class="axis">{:.0} min</text>"#,
+        MARGIN,
+        MARGIN - 8.0,
+        max
+    );
+    for (i, (day, value)) in days.iter().zip(&minutes).enumerate() {
+        let x = MARGIN + i as f64 * slot + (slot - bar) / 2.0;
+        let height = value / max * plot_height;
+        let y = HEIGHT - MARGIN - height;
+        let _ = write!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" class="bar"><title>{}: {:.0} min</title></rect>"#,
+            x, y, bar, height, day.date, value
+        );
+        // Label every day in a week, roughly weekly in longer reports
+        if days.len() <= 14 || i % 7 == 0 {
+            let _ = write!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" class="axis" text-anchor="middle">{}</text>"#,
+                x + bar / 2.0,
+                HEIGHT - MARGIN + 16.0,
+                day.date.format("%m-%d")
+            );
+        }
+    }
+    baseline(&mut svg);
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Line of trend body weight
+pub(super) fn weight_chart(points: &[WeightPoint], unit: &str) -> String {
+    let mut svg = open(&format!("Trend body weight in {}", unit));
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        svg.push_str("</svg>");
+        return svg;
+    };
+
+    let low = points
+        .iter()
+        .map(|p| p.weight)
+        .fold(f64::INFINITY, f64::min);
+    let high = points
+        .iter()
+        .map(|p| p.weight)
+        .fold(f64::NEG_INFINITY, f64::max);
+    // Keep a flat line off the axes
+    let (low, high) = if high - low < 1.0 {
+        let mid = (high + low) / 2.0;
+        (mid - 0.5, mid + 0.5)
+    } else {
+        (low, high)
+    };
+    let days = (last.date - first.date).num_days().max(1) as f64;
+    let x = |p: &WeightPoint| {
+        MARGIN + (p.date - first.date).num_days() as f64 / days * (WIDTH - 2.0 * MARGIN)
+    };
+    let y = |p: &WeightPoint| {
+        HEIGHT - MARGIN - (p.weight - low) / (high - low) * (HEIGHT - 2.0 * MARGIN)
+    };
+
+    let _ = write!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" class="axis">{:.1}–{:.1} {}</text>"#,
+        MARGIN,
+        MARGIN - 8.0,
+        low,
+        high,
+        unit
+    );
+    let path: Vec<String> = points
+        .iter()
+        .map(|p| format!("{:.1},{:.1}", x(p), y(p)))
+        .collect();
+    let _ = write!(
+        svg,
+        r#"<polyline points="{}" class="line" fill="none"/>"#,
+        path.join(" ")
+    );
+    for point in points {
+        let _ = write!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="3" class="point"><title>{}: {:.1} {}</title></circle>"#,
+            x(point),
+            y(point),
+            point.date,
+            point.weight,
+            unit
+        );
+    }
+    for point in [first, last] {
+        let _ = write!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" class="axis" text-anchor="middle">{}</text>"#,
+            x(point),
+            HEIGHT - MARGIN + 16.0,
+            point.date.format("%m-%d")
+        );
+    }
+    baseline(&mut svg);
+    svg.push_str("</svg>");
+    svg
+}
+
+fn open(label: &str) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="{w}" height="{h}" role="img" aria-label="{label}">"#,
+        w = WIDTH,
+        h = HEIGHT,
+        label = label
+    )
+}
+
+fn baseline(svg: &mut String) {
+    let y = HEIGHT - MARGIN;
+    let _ = write!(
+        svg,
+        r#"<line x1="{:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" class="baseline"/>"#,
+        MARGIN,
+        WIDTH - MARGIN,
+        y = y
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::report;
+    use super::*;
+
+    #[test]
+    fn test_charts() {
+        let report = report();
+        let bars = daily_minutes_chart(&report.days);
+        assert!(bars.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(bars.ends_with("</svg>"));
+        assert_eq!(bars.matches("<rect").count(), 7);
+        // The hour on Wednesday is the tallest bar
+        assert!(bars.contains(r#"height="144.0" class="bar"><title>2025-03-05: 60 min"#));
+
+        let body = report.body_weight.as_ref().unwrap();
+        let line = weight_chart(&body.trend, "kg");
+        assert_eq!(line.matches("<circle").count(), body.trend.len());
+        assert!(line.contains("<polyline points=\"28.0,"));
+
+        assert!(weight_chart(&[], "kg").ends_with("\"Trend body weight in kg\"></svg>"));
+    }
+}
@@ -0,0 +1,251 @@
+//! JSON Schema validation and generation
+//!
+//! The schemas under the repository's `schema/` directory are embedded at
+//! compile time. Setting [`crate::ValidationOptions::check_schema`] runs a
+//! document against the schema for its version and merges any violations
+//! into the regular [`ValidationIssue`] lists.
+//!
+//! [`plan_json_schema`] and [`history_json_schema`] derive schemas from the
+//! Rust types instead, which lets tests catch the bundled files drifting
+//! away from what the parser actually accepts.
+
+use crate::error::ValidationIssue;
+use crate::history::WpsHistory;
+use crate::plan::WpsPlan;
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::paths::LocationSegment;
+use jsonschema::{Draft, Resource, Validator};
+use schemars::generate::SchemaSettings;
+use schemars::JsonSchema;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+const PLAN_V1: &str = include_str!("../../../schema/pwf-v1.json");
+const PLAN_V2: &str = include_str!("../../../schema/pwf-v2.json");
+const HISTORY_V1: &str = include_str!("../../../schema/pwf-history-v1.json");
+const HISTORY_V2: &str = include_str!("../../../schema/pwf-history-v2.json");
+
+/// `pwf-v2.json` refers to `pwf-v1.json` relative to its own `$id`
+const PLAN_V1_RELATIVE_URI: &str = "https://pwf.dev/schema/v2/pwf-v1.json";
+
+/// The bundled plan schema for `plan_version`, if one exists
+pub fn bundled_plan_schema(version: u32) -> Option<Value> {
+    let text = match version {
+        1 => PLAN_V1,
+        2 => PLAN_V2,
+        _ => return None,
+    };
+    Some(serde_json::from_str(text).expect("bundled plan schema is valid JSON"))
+}
+
+/// The bundled history schema for `history_version`, if one exists
+pub fn bundled_history_schema(version: u32) -> Option<Value> {
+    let text = match version {
+        1 => HISTORY_V1,
+        2 => HISTORY_V2,
+        _ => return None,
+    };
+    Some(serde_json::from_str(text).expect("bundled history schema is valid JSON"))
+}
+
+/// Generate a draft-07 schema for [`WpsPlan`] from the Rust types
+pub fn plan_json_schema() -> Value {
+    generate::<WpsPlan>()
+}
+
+/// Generate a draft-07 schema for [`WpsHistory`] from the Rust types
+pub fn history_json_schema() -> Value {
+    generate::<WpsHistory>()
+}
+
+fn generate<T: JsonSchema>() -> Value {
+    let mut settings = SchemaSettings::draft07().for_deserialize();
+    settings.definitions_path = "/$defs".into();
+    settings
+        .into_generator()
+        .into_root_schema_for::<T>()
+        .to_value()
+}
+
+/// Check a plan against the bundled schema for its `plan_version`
+///
+/// Documents without a known version produce no issues; the regular
+/// validator already reports those.
+pub(crate) fn plan_schema_issues(document: &Value) -> Vec<ValidationIssue> {
+    static V1: OnceLock<Validator> = OnceLock::new();
+    static V2: OnceLock<Validator> = OnceLock::new();
+
+    let validator = match document.get("plan_version").and_then(Value::as_u64) {
+        Some(1) => V1.get_or_init(|| compile(PLAN_V1)),
+        Some(2) => V2.get_or_init(|| compile(PLAN_V2)),
+        _ => return Vec::new(),
+    };
+    schema_issues(validator, document, "PWF-P064")
+}
+
+/// Check a history export against the bundled schema for its `history_version`
+pub(crate) fn history_schema_issues(document: &Value) -> Vec<ValidationIssue> {
+    static V1: OnceLock<Validator> = OnceLock::new();
+    static V2: OnceLock<Validator> = OnceLock::new();
+
+    let validator = match document.get("history_version").and_then(Value::as_u64) {
+        Some(1) => V1.get_or_init(|| compile(HISTORY_V1)),
+        Some(2) => V2.get_or_init(|| compile(HISTORY_V2)),
+        _ => return Vec::new(),
+    };
+    schema_issues(
+        validator,
+        document,
+        crate::history::error_codes::SCHEMA_VIOLATION,
+    )
+}
+
+fn compile(text: &str) -> Validator {
+    let schema: Value = serde_json::from_str(text).expect("bundled schema is valid JSON");
+    let plan_v1: Value = serde_json::from_str(PLAN_V1).expect("bundled schema is valid JSON");
+    jsonschema::options()
+        .with_draft(Draft::Draft7)
+        .should_validate_formats(true)
+        .with_resource(PLAN_V1_RELATIVE_URI, Resource::from_contents(plan_v1))
+        .build(&schema)
+        .expect("bundled schema compiles")
+}
+
+fn schema_issues(validator: &Validator, document: &Value, code: &str) -> Vec<ValidationIssue> {
+    validator
+        .iter_errors(document)
+        // Unknown keys are already reported, with suggestions, by the
+        // unknown-field pass in both validators
+        .filter(|e| !matches!(e.kind(), ValidationErrorKind::AdditionalProperties { .. }))
+        .map(|e| {
+            let path = dotted_path(e.instance_path().iter());
+            ValidationIssue::error_with_code(path, format!("Schema violation: {}", e), code)
+        })
+        .collect()
+}
+
+/// Convert a JSON pointer into the `a.b[0].c` form used by validation issues
+fn dotted_path<'a>(segments: impl Iterator<Item = LocationSegment<'a>>) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        match segment {
+            LocationSegment::Property(name) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(&name);
+            }
+            LocationSegment::Index(index) => {
+                path.push_str(&format!("[{}]", index));
+            }
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_bundled_schemas_compile() {
+        for version in [1, 2] {
+            compile(&bundled_plan_schema(version).unwrap().to_string());
+            compile(&bundled_history_schema(version).unwrap().to_string());
+        }
+        assert!(bundled_plan_schema(3).is_none());
+        assert!(bundled_history_schema(0).is_none());
+    }
+
+    #[test]
+    fn test_plan_schema_issue_paths() {
+        let doc = yaml(
+            r#"
+plan_version: 1
+cycle:
+  days:
+    - exercises:
+        - name: Squat
+          modality: strength
+          target_sets: -1
+"#,
+        );
+        let issues = plan_schema_issues(&doc);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "cycle.days[0].exercises[0].target_sets");
+        assert_eq!(issues[0].code.as_deref(), Some("PWF-P064"));
+    }
+
+    #[test]
+    fn test_history_schema_format_violation() {
+        let doc = yaml(
+            r#"
+history_version: 1
+exported_at: "yesterday"
+workouts: []
+"#,
+        );
+        let issues = history_schema_issues(&doc);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "exported_at");
+        assert!(issues[0].message.contains("date-time"));
+    }
+
+    #[test]
+    fn test_additional_properties_left_to_unknown_field_pass() {
+        let doc = yaml(
+            r#"
+plan_version: 1
+cycle:
+  days:
+    - exercises:
+        - name: Squat
+          modality: strength
+          target_rep: 5
+"#,
+        );
+        assert!(plan_schema_issues(&doc).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_version_is_skipped() {
+        assert!(plan_schema_issues(&yaml("plan_version: 9\n")).is_empty());
+        assert!(history_schema_issues(&yaml("workouts: []\n")).is_empty());
+    }
+
+    #[test]
+    fn test_extension_keys_are_allowed() {
+        let doc = yaml(
+            r#"
+history_version: 2
+exported_at: "2025-01-15T10:30:00Z"
+x-app-export: 7
+workouts:
+  - date: "2025-01-15"
+    x-app-session: abc
+    exercises:
+      - name: Squat
+        x-app-exercise: 1
+        sets:
+          - reps: 5
+            x-app-set: true
+"#,
+        );
+        assert!(history_schema_issues(&doc).is_empty());
+    }
+
+    #[test]
+    fn test_generated_schemas_have_root_properties() {
+        let plan = plan_json_schema();
+        assert!(plan["properties"]["plan_version"].is_object());
+        assert!(plan["properties"]["cycle"].is_object());
+
+        let history = history_json_schema();
+        assert!(history["properties"]["history_version"].is_object());
+        assert!(history["properties"]["workouts"].is_object());
+    }
+}
@@ -1,10 +1,11 @@
 //! Common types shared between plan and history modules
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Supported exercise modalities
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Modality {
     Strength,
@@ -64,7 +65,7 @@ pub const EQUIPMENT_TAGS: &[&str] = &[
 ];
 
 /// Weight unit for history export
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum WeightUnit {
     #[default]
@@ -81,8 +82,26 @@ impl fmt::Display for WeightUnit {
     }
 }
 
+/// Kilograms per pound
+pub const LB_TO_KG: f64 = 0.453_592_37;
+
+/// Metres per foot
+pub const METERS_PER_FOOT: f64 = 0.3048;
+
+/// Metres per yard
+pub const METERS_PER_YARD: f64 = 0.9144;
+
+/// Metres in a statute mile
+pub const METERS_PER_MILE: f64 = 1609.344;
+
+/// Kilometres per hour in one metre per second
+pub const KPH_PER_MPS: f64 = 3.6;
+
+/// Metres per second in one mile per hour
+pub const MPS_PER_MPH: f64 = METERS_PER_MILE / 3600.0;
+
 /// Distance unit for history export
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum DistanceUnit {
     #[default]
@@ -106,7 +125,7 @@ impl fmt::Display for DistanceUnit {
 }
 
 /// Sport type for multi-sport activities (PWF v2)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum Sport {
     Swimming,
@@ -422,6 +422,76 @@ fn test_history_round_trip() {
     );
 }
 
+#[test]
+fn test_plan_round_trip_preserves_extensions() {
+    let yaml = r#"
+plan_version: 1
+x-ourapp-plan-id: p-1
+meta:
+  title: Test
+  x-ourapp-owner: coach
+cycle:
+  days:
+    - x-ourapp-day: { color: red }
+      exercises:
+        - name: Squat
+          modality: strength
+          target_sets: 3
+          target_reps: 5
+          x-ourapp-exercise-id: 17
+"#;
+
+    let result = plan::validate(yaml);
+    assert!(result.is_valid());
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    let plan1 = result.plan.unwrap();
+
+    let yaml2 = serde_yaml::to_string(&plan1).unwrap();
+    let plan2 = plan::parse(&yaml2).unwrap();
+
+    assert_eq!(plan1.extensions, plan2.extensions);
+    assert_eq!(plan2.meta.unwrap().extensions.len(), 1);
+    let day = &plan2.cycle.days[0];
+    assert_eq!(day.extensions, plan1.cycle.days[0].extensions);
+    assert_eq!(
+        day.exercises[0].extensions.get("x-ourapp-exercise-id"),
+        Some(&serde_yaml::Value::from(17))
+    );
+}
+
+#[test]
+fn test_history_round_trip_preserves_extensions() {
+    let yaml = r#"
+history_version: 1
+exported_at: "2025-01-15T10:30:00Z"
+x-ourapp-export: { build: 42 }
+workouts:
+  - date: "2025-01-15"
+    x-ourapp-session-id: abc
+    exercises:
+      - name: Squat
+        x-ourapp-exercise-id: 9
+        sets:
+          - reps: 5
+            weight_kg: 100
+            x-ourapp-bar-velocity: 0.61
+"#;
+
+    let history1 = history::parse(yaml).unwrap();
+    let yaml2 = serde_yaml::to_string(&history1).unwrap();
+    let history2 = history::parse(&yaml2).unwrap();
+
+    assert_eq!(history1.extensions, history2.extensions);
+    let workout = &history2.workouts[0];
+    assert_eq!(
+        workout.extensions.get("x-ourapp-session-id"),
+        Some(&serde_yaml::Value::from("abc"))
+    );
+    assert_eq!(workout.exercises[0].extensions.len(), 1);
+    assert_eq!(workout.exercises[0].sets[0].extensions.len(), 1);
+    assert!(yaml2.contains("x-ourapp-bar-velocity: 0.61"));
+}
+
 // ============================================================================
 // File Reading Error Tests
 // ============================================================================
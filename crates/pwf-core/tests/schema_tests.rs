@@ -0,0 +1,235 @@
+//! Tests keeping the bundled JSON Schemas in step with the Rust types
+//!
+//! The schemas generated from the Rust types are walked side by side with the
+//! bundled files, comparing the property names of every object reachable from
+//! the root. The latest schema of each document type must match the Rust
+//! types exactly; older schemas may only use properties the types know about.
+
+use pwf_core::schema::{
+    bundled_history_schema, bundled_plan_schema, history_json_schema, plan_json_schema,
+};
+use pwf_core::{history, plan, ValidationOptions};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// Helper function to get the path to the examples directory
+fn examples_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("examples")
+}
+
+/// A schema node together with the document its `$ref`s resolve against
+#[derive(Clone, Copy)]
+struct Node<'a> {
+    doc: &'a Value,
+    value: &'a Value,
+}
+
+struct Walker<'a> {
+    /// Target of `pwf-v1.json#...` references from the v2 plan schema
+    plan_v1: &'a Value,
+}
+
+impl<'a> Walker<'a> {
+    fn resolve(&self, node: Node<'a>, reference: &str) -> Option<Node<'a>> {
+        let (doc, pointer) = match reference.split_once('#') {
+            Some(("", pointer)) => (node.doc, pointer),
+            Some(("pwf-v1.json", pointer)) => (self.plan_v1, pointer),
+            _ => return None,
+        };
+        doc.pointer(pointer).map(|value| Node { doc, value })
+    }
+
+    /// Find the object schema (one with `properties`) a node describes,
+    /// looking through references, arrays and `Option` wrappers
+    fn object(&self, node: Node<'a>) -> Option<Node<'a>> {
+        if node.value.get("properties").is_some() {
+            return Some(node);
+        }
+        if let Some(reference) = node.value.get("$ref").and_then(Value::as_str) {
+            return self.object(self.resolve(node, reference)?);
+        }
+        if let Some(items) = node.value.get("items") {
+            return self.object(Node {
+                value: items,
+                ..node
+            });
+        }
+        ["anyOf", "oneOf", "allOf"]
+            .iter()
+            .filter_map(|key| node.value.get(*key).and_then(Value::as_array))
+            .flatten()
+            .find_map(|sub| self.object(Node { value: sub, ..node }))
+    }
+
+    fn properties(node: Node<'a>) -> BTreeSet<&'a str> {
+        node.value["properties"]
+            .as_object()
+            .map(|props| props.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Collect `(path, property)` pairs present on only one side
+    fn compare(
+        &self,
+        path: &str,
+        generated: Node<'a>,
+        bundled: Node<'a>,
+        only_generated: &mut Vec<String>,
+        only_bundled: &mut Vec<String>,
+    ) {
+        let (Some(generated), Some(bundled)) = (self.object(generated), self.object(bundled))
+        else {
+            return;
+        };
+        let generated_props = Self::properties(generated);
+        let bundled_props = Self::properties(bundled);
+
+        for prop in generated_props.difference(&bundled_props) {
+            only_generated.push(format!("{}{}", path, prop));
+        }
+        for prop in bundled_props.difference(&generated_props) {
+            only_bundled.push(format!("{}{}", path, prop));
+        }
+        for prop in generated_props.intersection(&bundled_props) {
+            self.compare(
+                &format!("{}{}.", path, prop),
+                Node {
+                    value: &generated.value["properties"][*prop],
+                    ..generated
+                },
+                Node {
+                    value: &bundled.value["properties"][*prop],
+                    ..bundled
+                },
+                only_generated,
+                only_bundled,
+            );
+        }
+    }
+}
+
+fn drift(generated: &Value, bundled: &Value) -> (Vec<String>, Vec<String>) {
+    let plan_v1 = bundled_plan_schema(1).unwrap();
+    let walker = Walker { plan_v1: &plan_v1 };
+    let mut only_generated = Vec::new();
+    let mut only_bundled = Vec::new();
+    walker.compare(
+        "",
+        Node {
+            doc: generated,
+            value: generated,
+        },
+        Node {
+            doc: bundled,
+            value: bundled,
+        },
+        &mut only_generated,
+        &mut only_bundled,
+    );
+    (only_generated, only_bundled)
+}
+
+#[test]
+fn test_latest_plan_schema_matches_types() {
+    let (only_generated, only_bundled) = drift(
+        &plan_json_schema(),
+        &bundled_plan_schema(plan::LATEST_PLAN_VERSION).unwrap(),
+    );
+    assert!(
+        only_generated.is_empty(),
+        "fields missing from the bundled plan schema: {:?}",
+        only_generated
+    );
+    assert!(
+        only_bundled.is_empty(),
+        "bundled plan schema fields unknown to the Rust types: {:?}",
+        only_bundled
+    );
+}
+
+#[test]
+fn test_latest_history_schema_matches_types() {
+    let (only_generated, only_bundled) = drift(
+        &history_json_schema(),
+        &bundled_history_schema(history::LATEST_HISTORY_VERSION).unwrap(),
+    );
+    assert!(
+        only_generated.is_empty(),
+        "fields missing from the bundled history schema: {:?}",
+        only_generated
+    );
+    assert!(
+        only_bundled.is_empty(),
+        "bundled history schema fields unknown to the Rust types: {:?}",
+        only_bundled
+    );
+}
+
+#[test]
+fn test_older_schemas_are_subsets_of_types() {
+    let (_, only_bundled) = drift(&plan_json_schema(), &bundled_plan_schema(1).unwrap());
+    assert!(only_bundled.is_empty(), "plan v1: {:?}", only_bundled);
+
+    let (_, only_bundled) = drift(&history_json_schema(), &bundled_history_schema(1).unwrap());
+    assert!(only_bundled.is_empty(), "history v1: {:?}", only_bundled);
+}
+
+#[test]
+fn test_examples_pass_schema_validation() {
+    let options = ValidationOptions {
+        check_schema: true,
+        ..Default::default()
+    };
+
+    for entry in fs::read_dir(examples_dir()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("yaml") {
+            continue;
+        }
+        let yaml = fs::read_to_string(&path).unwrap();
+        let result_errors = if yaml.contains("history_version:") {
+            history::validate_with_options(&yaml, &options).errors
+        } else {
+            plan::validate_with_options(&yaml, &options).errors
+        };
+        assert!(
+            result_errors.is_empty(),
+            "{} failed schema validation: {:?}",
+            path.display(),
+            result_errors
+        );
+    }
+}
+
+#[test]
+fn test_schema_violations_are_merged_into_result() {
+    let yaml = r#"
+history_version: 1
+exported_at: "last tuesday"
+workouts:
+  - date: "2025-01-15"
+    exercises:
+      - name: Squat
+        sets:
+          - reps: 5
+            weight_kg: 100
+"#;
+    assert!(history::validate(yaml).is_valid());
+
+    let options = ValidationOptions {
+        check_schema: true,
+        ..Default::default()
+    };
+    let result = history::validate_with_options(yaml, &options);
+    assert!(!result.is_valid());
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].path, "exported_at");
+    assert_eq!(result.errors[0].code.as_deref(), Some("PWF-H004"));
+}
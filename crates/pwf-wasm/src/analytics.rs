@@ -0,0 +1,191 @@
+//! Training analytics bindings for PWF history exports
+
+use pwf_core::analytics::{
+    self, DateRange, IntensityOptions, IntensityStream, LoadParams, LoadThresholds, ZoneBoundaries,
+};
+use pwf_core::plan::AthleteProfile;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+/// Compute the mean-maximal power curve for a PWF history export.
+///
+/// # Parameters
+/// - `yaml`: The PWF history YAML content as a string
+/// - `from`: Optional first workout date to include (`YYYY-MM-DD`)
+/// - `to`: Optional last workout date to include (`YYYY-MM-DD`)
+///
+/// # Returns
+/// JSON object with:
+/// ```json
+/// {
+///   "curve": { "points": [{ "duration_sec": 1, "watts": 850.0, "date": "2025-01-15" }] },
+///   "critical_power": { "cp_watts": 280.0, "w_prime_joules": 20000.0, "r_squared": 0.99 },
+///   "estimated_ftp": { "watts": 280, "source": "critical_power" }
+/// }
+/// ```
+#[wasm_bindgen]
+pub fn power_curve(yaml: &str, from: Option<String>, to: Option<String>) -> JsValue {
+    let range = match DateRange::parse(from.as_deref(), to.as_deref()) {
+        Ok(range) => range,
+        Err(err) => return error_value(format!("Invalid date: {}", err)),
+    };
+    let history = match pwf_core::history::parse(yaml) {
+        Ok(history) => history,
+        Err(err) => return error_value(format!("Failed to parse PWF YAML: {}", err)),
+    };
+
+    let analysis = analytics::analyze_power_curve(analytics::history_power_curve(&history, &range));
+    serde_wasm_bindgen::to_value(&analysis).unwrap_or_else(|err| {
+        JsValue::from_str(&format!("Failed to serialize power curve: {}", err))
+    })
+}
+
+/// Options accepted by [`training_load`]; every field is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct LoadOptions {
+    from: Option<String>,
+    to: Option<String>,
+    ftp_watts: Option<u32>,
+    threshold_hr_bpm: Option<u32>,
+    threshold_pace_sec_per_km: Option<u32>,
+    ctl_days: Option<f64>,
+    atl_days: Option<f64>,
+}
+
+/// Compute daily training stress, CTL, ATL and TSB for a PWF history export.
+///
+/// # Parameters
+/// - `yaml`: The PWF history YAML content as a string
+/// - `options`: Optional object with `from`, `to` (`YYYY-MM-DD`),
+///   `ftp_watts`, `threshold_hr_bpm`, `threshold_pace_sec_per_km`,
+///   `ctl_days` (default 42) and `atl_days` (default 7)
+///
+/// # Returns
+/// JSON array of days:
+/// ```json
+/// [{ "date": "2025-01-15", "stress": 85.0, "ctl": 62.4, "atl": 71.9, "tsb": -8.3 }]
+/// ```
+#[wasm_bindgen]
+pub fn training_load(yaml: &str, options: JsValue) -> JsValue {
+    let options: LoadOptions = if options.is_undefined() || options.is_null() {
+        LoadOptions::default()
+    } else {
+        match serde_wasm_bindgen::from_value(options) {
+            Ok(options) => options,
+            Err(err) => return error_value(format!("Invalid options: {}", err)),
+        }
+    };
+    let range = match DateRange::parse(options.from.as_deref(), options.to.as_deref()) {
+        Ok(range) => range,
+        Err(err) => return error_value(format!("Invalid date: {}", err)),
+    };
+    let history = match pwf_core::history::parse(yaml) {
+        Ok(history) => history,
+        Err(err) => return error_value(format!("Failed to parse PWF YAML: {}", err)),
+    };
+
+    let thresholds = LoadThresholds {
+        ftp_watts: options.ftp_watts,
+        threshold_hr_bpm: options.threshold_hr_bpm,
+        threshold_pace_sec_per_km: options.threshold_pace_sec_per_km,
+    };
+    let defaults = LoadParams::default();
+    let params = LoadParams {
+        ctl_days: options.ctl_days.unwrap_or(defaults.ctl_days),
+        atl_days: options.atl_days.unwrap_or(defaults.atl_days),
+        ..defaults
+    };
+    let days = analytics::training_load(&history, &thresholds, &params, &range);
+    serde_wasm_bindgen::to_value(&days).unwrap_or_else(|err| {
+        JsValue::from_str(&format!("Failed to serialize training load: {}", err))
+    })
+}
+
+/// Options accepted by [`intensity_distribution`]; every field is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct DistributionOptions {
+    from: Option<String>,
+    to: Option<String>,
+    stream: Option<String>,
+    ftp_watts: Option<u32>,
+    threshold_hr_bpm: Option<u32>,
+    max_hr_bpm: Option<u32>,
+    threshold_pace_sec_per_km: Option<u32>,
+    low_zones: Option<usize>,
+    moderate_zones: Option<usize>,
+}
+
+/// Weekly low/moderate/high intensity distribution of a PWF history export,
+/// overall and per sport, with the polarization index and its trend.
+///
+/// # Parameters
+/// - `yaml`: The PWF history YAML content as a string
+/// - `options`: Optional object with `from`, `to` (`YYYY-MM-DD`), `stream`
+///   (`power`, `pace` or `heart_rate`; default the first one available),
+///   the thresholds `ftp_watts`, `threshold_hr_bpm`, `max_hr_bpm` and
+///   `threshold_pace_sec_per_km` for workouts without stored time in zones,
+///   and `low_zones`/`moderate_zones` to override how zones are grouped
+///
+/// # Returns
+/// ```json
+/// {
+///   "overall": { "low_percent": 78.0, "moderate_percent": 7.0, "high_percent": 15.0,
+///                "polarization_index": 2.22, "model": "polarized", ... },
+///   "weeks": [{ "week_start": "2025-03-03", "sessions": 5, "low_sec": 14400.0, ... }],
+///   "trend": { "low_percent_per_week": -1.5, ... },
+///   "by_sport": [{ "sport": "running", "overall": { ... }, "weeks": [...] }]
+/// }
+/// ```
+#[wasm_bindgen]
+pub fn intensity_distribution(yaml: &str, options: JsValue) -> JsValue {
+    let options: DistributionOptions = if options.is_undefined() || options.is_null() {
+        DistributionOptions::default()
+    } else {
+        match serde_wasm_bindgen::from_value(options) {
+            Ok(options) => options,
+            Err(err) => return error_value(format!("Invalid options: {}", err)),
+        }
+    };
+    let stream = match options.stream.as_deref() {
+        None => None,
+        Some("power") => Some(IntensityStream::Power),
+        Some("pace") => Some(IntensityStream::Pace),
+        Some("heart_rate") => Some(IntensityStream::HeartRate),
+        Some(other) => return error_value(format!("Invalid stream: {}", other)),
+    };
+    let range = match DateRange::parse(options.from.as_deref(), options.to.as_deref()) {
+        Ok(range) => range,
+        Err(err) => return error_value(format!("Invalid date: {}", err)),
+    };
+    let history = match pwf_core::history::parse(yaml) {
+        Ok(history) => history,
+        Err(err) => return error_value(format!("Failed to parse PWF YAML: {}", err)),
+    };
+
+    let profile = AthleteProfile {
+        ftp_watts: options.ftp_watts,
+        threshold_hr_bpm: options.threshold_hr_bpm,
+        max_hr_bpm: options.max_hr_bpm,
+        threshold_pace_sec_per_km: options.threshold_pace_sec_per_km,
+        weight_kg: None,
+    };
+    let options = IntensityOptions {
+        stream,
+        boundaries: ZoneBoundaries::from_profile(&profile),
+        low_zones: options.low_zones,
+        moderate_zones: options.moderate_zones,
+    };
+    let report = analytics::intensity_distribution(&history, &range, &options);
+    serde_wasm_bindgen::to_value(&report).unwrap_or_else(|err| {
+        JsValue::from_str(&format!(
+            "Failed to serialize intensity distribution: {}",
+            err
+        ))
+    })
+}
+
+fn error_value(message: String) -> JsValue {
+    serde_wasm_bindgen::to_value(&serde_json::json!({ "error": message })).unwrap()
+}
@@ -3,10 +3,12 @@
 //! This crate provides WASM bindings for the PWF validation and conversion functionality,
 //! allowing PWF to be used in web browsers without a backend server.
 
+mod analytics;
 mod conversion;
 mod utils;
 mod validation;
 
+pub use analytics::*;
 pub use conversion::*;
 pub use utils::*;
 pub use validation::*;
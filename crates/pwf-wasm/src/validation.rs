@@ -1,10 +1,22 @@
 //! Validation bindings for PWF plans and history files
 
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
 use wasm_bindgen::prelude::*;
 
+/// Serialize maps as plain JS objects.
+///
+/// Vendor `x-` extension fields are flattened into their parent objects, so
+/// the default `Map` output would hide every plan and history object behind
+/// `Map.get()`.
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, serde_wasm_bindgen::Error> {
+    value.serialize(&Serializer::new().serialize_maps_as_objects(true))
+}
+
 /// Validate a PWF plan from YAML string.
 ///
-/// Returns a JSON object with the validation result:
+/// Returns a JSON object with the validation result. Vendor extension
+/// fields (`x-` keys) appear as ordinary properties on the plan objects:
 /// ```json
 /// {
 ///   "valid": true,
@@ -34,7 +46,7 @@ use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
 pub fn validate_plan(yaml: &str) -> JsValue {
     let result = pwf_core::plan::validate(yaml);
-    serde_wasm_bindgen::to_value(&result).unwrap_or_else(|err| {
+    to_js(&result).unwrap_or_else(|err| {
         JsValue::from_str(&format!("Failed to serialize validation result: {}", err))
     })
 }
@@ -72,7 +84,56 @@ pub fn validate_plan(yaml: &str) -> JsValue {
 #[wasm_bindgen]
 pub fn validate_history(yaml: &str) -> JsValue {
     let result = pwf_core::history::validate(yaml);
-    serde_wasm_bindgen::to_value(&result).unwrap_or_else(|err| {
+    to_js(&result).unwrap_or_else(|err| {
+        JsValue::from_str(&format!("Failed to serialize validation result: {}", err))
+    })
+}
+
+/// Validate a PWF plan from a JSON string.
+///
+/// Returns the same result shape as [`validate_plan`].
+#[wasm_bindgen]
+pub fn validate_plan_json(json: &str) -> JsValue {
+    let result = pwf_core::plan::validate_json(json);
+    to_js(&result).unwrap_or_else(|err| {
+        JsValue::from_str(&format!("Failed to serialize validation result: {}", err))
+    })
+}
+
+/// Validate a PWF history file from a JSON string.
+///
+/// Returns the same result shape as [`validate_history`].
+#[wasm_bindgen]
+pub fn validate_history_json(json: &str) -> JsValue {
+    let result = pwf_core::history::validate_json(json);
+    to_js(&result).unwrap_or_else(|err| {
+        JsValue::from_str(&format!("Failed to serialize validation result: {}", err))
+    })
+}
+
+/// Validate a PWF history file from YAML string against the plan it was
+/// logged from.
+///
+/// Returns the same result shape as [`validate_history`], with cross-document
+/// issues (PWF-H901 to PWF-H906) added for dangling `plan_id` and
+/// `plan_day_id` references, exercises missing from the plan day, workouts
+/// outside the plan cycle and modality contradictions. An unparseable plan
+/// is reported as an error.
+#[wasm_bindgen]
+pub fn validate_history_with_plan(history_yaml: &str, plan_yaml: &str) -> JsValue {
+    let result = match pwf_core::plan::parse(plan_yaml) {
+        Ok(plan) => pwf_core::history::validate_with_plan(history_yaml, &plan),
+        Err(e) => {
+            let mut result = pwf_core::history::validate(history_yaml);
+            result.valid = false;
+            result.errors.push(pwf_core::error::ValidationIssue::error(
+                "plan",
+                format!("Invalid plan: {}", e),
+            ));
+            result
+        }
+    };
+    to_js(&result).unwrap_or_else(|err| {
         JsValue::from_str(&format!("Failed to serialize validation result: {}", err))
     })
 }
@@ -0,0 +1,110 @@
+//! Tests for the analytics WASM bindings
+
+#![cfg(target_arch = "wasm32")]
+
+use pwf_wasm::*;
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const RIDE: &str = r#"
+history_version: 2
+exported_at: "2025-01-15T12:00:00Z"
+workouts:
+  - date: "2025-01-15"
+    exercises:
+      - name: Ride
+        sets:
+          - telemetry:
+              time_series:
+                timestamps: ["", "", "", "", ""]
+                power: [200, 300, 400, 300, 200]
+"#;
+
+#[wasm_bindgen_test]
+fn test_power_curve_basic() {
+    let json: Value = serde_wasm_bindgen::from_value(power_curve(RIDE, None, None)).unwrap();
+    let points = json["curve"]["points"].as_array().unwrap();
+    assert_eq!(points[0]["duration_sec"], 1);
+    assert_eq!(points[0]["watts"], 400.0);
+    assert_eq!(points[0]["date"], "2025-01-15");
+    assert!(json["critical_power"].is_null());
+}
+
+#[wasm_bindgen_test]
+fn test_power_curve_date_range_excludes_workouts() {
+    let result = power_curve(RIDE, Some("2025-02-01".to_string()), None);
+    let json: Value = serde_wasm_bindgen::from_value(result).unwrap();
+    assert!(json["curve"]["points"].as_array().unwrap().is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_power_curve_invalid_input() {
+    let json: Value =
+        serde_wasm_bindgen::from_value(power_curve("not: [valid", None, None)).unwrap();
+    assert!(json["error"].as_str().unwrap().contains("parse"));
+
+    let json: Value =
+        serde_wasm_bindgen::from_value(power_curve(RIDE, Some("Jan".to_string()), None)).unwrap();
+    assert!(json["error"].as_str().unwrap().contains("Invalid date"));
+}
+
+#[wasm_bindgen_test]
+fn test_training_load_with_threshold_hr() {
+    let yaml = r#"
+history_version: 2
+exported_at: "2025-01-15T12:00:00Z"
+workouts:
+  - date: "2025-01-14"
+    duration_sec: 3600
+    exercises: []
+    telemetry:
+      heart_rate_avg: 160
+"#;
+    let options = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "threshold_hr_bpm": 160,
+        "to": "2025-01-15"
+    }))
+    .unwrap();
+    let json: Value = serde_wasm_bindgen::from_value(training_load(yaml, options)).unwrap();
+    let days = json.as_array().unwrap();
+    assert_eq!(days.len(), 2);
+    assert_eq!(days[0]["stress"], 100.0);
+    assert_eq!(days[1]["stress"], 0.0);
+
+    let json: Value =
+        serde_wasm_bindgen::from_value(training_load(yaml, JsValue::UNDEFINED)).unwrap();
+    assert!(json.as_array().unwrap().is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_intensity_distribution() {
+    let yaml = r#"
+history_version: 2
+exported_at: "2025-03-20T00:00:00Z"
+workouts:
+  - date: "2025-03-03"
+    sport: running
+    exercises: []
+    telemetry:
+      time_in_zones:
+        hr_zones_sec: [1000, 2600, 100, 50, 250]
+"#;
+    let options = serde_wasm_bindgen::to_value(&serde_json::json!({
+        "stream": "heart_rate"
+    }))
+    .unwrap();
+    let json: Value =
+        serde_wasm_bindgen::from_value(intensity_distribution(yaml, options)).unwrap();
+    assert_eq!(json["overall"]["low_sec"], 3600.0);
+    assert_eq!(json["overall"]["model"], "polarized");
+    assert_eq!(json["by_sport"][0]["sport"], "running");
+
+    let options =
+        serde_wasm_bindgen::to_value(&serde_json::json!({ "stream": "cadence" })).unwrap();
+    let json: Value =
+        serde_wasm_bindgen::from_value(intensity_distribution(yaml, options)).unwrap();
+    assert!(json["error"].as_str().unwrap().contains("Invalid stream"));
+}
@@ -451,3 +451,92 @@ workouts: [
 
     assert_eq!(json["valid"], false);
 }
+
+#[wasm_bindgen_test]
+fn test_validate_history_preserves_extensions() {
+    let yaml = r#"
+history_version: 1
+exported_at: "2025-12-24T12:00:00Z"
+x-ourapp-export-id: abc123
+workouts:
+  - date: "2025-12-24"
+    x-ourapp-session-id: 42
+    exercises:
+      - name: Squat
+        sets:
+          - reps: 5
+            weight_kg: 100
+"#;
+
+    let result = validate_history(yaml);
+    let json: Value = serde_wasm_bindgen::from_value(result).unwrap();
+
+    assert_eq!(json["valid"], true);
+    assert_eq!(json["history"]["x-ourapp-export-id"], "abc123");
+    assert_eq!(json["history"]["workouts"][0]["x-ourapp-session-id"], 42);
+}
+
+#[wasm_bindgen_test]
+fn test_validate_plan_json() {
+    let input = r#"{
+  "plan_version": 1,
+  "cycle": {
+    "days": [
+      { "exercises": [{ "name": "Squat", "modality": "strength", "target_sets": 3, "target_reps": 5 }] }
+    ]
+  }
+}"#;
+
+    let result = validate_plan_json(input);
+    let json: Value = serde_wasm_bindgen::from_value(result).unwrap();
+
+    assert_eq!(json["valid"], true);
+    assert_eq!(
+        json["plan"]["cycle"]["days"][0]["exercises"][0]["name"],
+        "Squat"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_validate_history_json_syntax_error() {
+    let result = validate_history_json(r#"{ "history_version": 1, "#);
+    let json: Value = serde_wasm_bindgen::from_value(result).unwrap();
+
+    assert_eq!(json["valid"], false);
+    assert!(json["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("JSON syntax error"));
+}
+
+#[wasm_bindgen_test]
+fn test_validate_history_with_plan_unknown_day() {
+    let plan = r#"
+plan_version: 1
+cycle:
+  days:
+    - id: lower
+      exercises:
+        - name: Squat
+          modality: strength
+          target_sets: 3
+          target_reps: 5
+"#;
+    let history = r#"
+history_version: 1
+exported_at: "2025-12-24T12:00:00Z"
+workouts:
+  - date: "2025-12-24"
+    plan_day_id: upper
+    exercises:
+      - name: Squat
+        sets:
+          - reps: 5
+"#;
+
+    let result = validate_history_with_plan(history, plan);
+    let json: Value = serde_wasm_bindgen::from_value(result).unwrap();
+
+    assert_eq!(json["valid"], false);
+    assert_eq!(json["errors"][0]["code"], "PWF-H902");
+}